@@ -0,0 +1,82 @@
+#ifndef LAME_RS_H
+#define LAME_RS_H
+
+/* 由 cbindgen 生成，请勿手动修改 */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// 不透明的编码器句柄
+typedef struct LameRsEncoder LameRsEncoder;
+
+// 编码器配置
+//
+// 可选字段取负值（`bitrate` 取 0）时使用 LAME 默认值。
+typedef struct LameRsConfig {
+  // 输入采样率（Hz）
+  int32_t sample_rate;
+  // 声道数（1 或 2）
+  int32_t channels;
+  // 比特率（kbps），0 表示默认
+  int32_t bitrate;
+  // 编码质量：0、2、4、5、7、9，负值表示默认
+  int32_t quality;
+  // VBR 模式：0 = 关闭，3 = ABR，4 = VBR，负值表示默认
+  int32_t vbr_mode;
+  // VBR 质量（0-9），负值表示默认
+  int32_t vbr_quality;
+} LameRsConfig;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// 按配置创建编码器
+//
+// 失败时返回 `NULL`，错误信息见 `lame_rs_last_error_message`。
+struct LameRsEncoder *lame_rs_encoder_new(const struct LameRsConfig *config);
+
+// 释放编码器，传入 `NULL` 时不做任何事
+void lame_rs_encoder_free(struct LameRsEncoder *encoder);
+
+// 编码 `samples_per_channel` 个样本所需的最坏情况输出缓冲区大小
+size_t lame_rs_mp3_buffer_size(size_t samples_per_channel);
+
+// 编码分离声道的 16-bit PCM
+//
+// 单声道编码器传入 `right = NULL`。返回写入 `out` 的字节数，失败返回 -1。
+int64_t lame_rs_encode_i16(struct LameRsEncoder *encoder,
+                           const int16_t *left,
+                           const int16_t *right,
+                           size_t samples_per_channel,
+                           uint8_t *out,
+                           size_t out_len);
+
+// 编码交错立体声 16-bit PCM（L, R, L, R, ...）
+//
+// `pcm` 中共有 `samples_per_channel * 2` 个样本。返回写入 `out` 的字节数，失败返回 -1。
+int64_t lame_rs_encode_interleaved_i16(struct LameRsEncoder *encoder,
+                                       const int16_t *pcm,
+                                       size_t samples_per_channel,
+                                       uint8_t *out,
+                                       size_t out_len);
+
+// 刷新编码器，输出剩余的 MP3 帧
+//
+// `out` 至少需要 `lame_rs_mp3_buffer_size(0)` 字节。返回写入的字节数，失败返回 -1。
+int64_t lame_rs_flush(struct LameRsEncoder *encoder, uint8_t *out, size_t out_len);
+
+// 当前线程最后一次失败的错误描述，没有错误时返回 `NULL`
+const char *lame_rs_last_error_message(void);
+
+// 链接的 LAME 版本字符串
+const char *lame_rs_version(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* LAME_RS_H */
@@ -0,0 +1,292 @@
+//! lame-sys 的稳定 C ABI
+//!
+//! 为 Go、C# 等非 Rust 调用方提供与 `lame-sys` 相同的安全封装行为：
+//! 参数校验、最坏情况缓冲区大小计算、输入长度检查。头文件 `include/lame_rs.h`
+//! 由 cbindgen 在构建时生成。
+//!
+//! # 所有权约定
+//!
+//! - `lame_rs_encoder_new` 返回的编码器归调用方所有，必须且只能用
+//!   `lame_rs_encoder_free` 释放一次。
+//! - 所有输入、输出缓冲区都由调用方分配，函数不会保留这些指针。
+//! - `lame_rs_last_error_message` 和 `lame_rs_version` 返回的字符串由本库持有，
+//!   调用方不能释放。
+//!
+//! # 错误约定
+//!
+//! - 返回指针的函数失败时返回 `NULL`。
+//! - 返回字节数的函数失败时返回 `-1`。
+//! - 失败后可以在同一线程调用 `lame_rs_last_error_message` 获取错误描述，
+//!   该字符串在同一线程下一次调用本库函数前有效。
+//!
+//! # 指针约定
+//!
+//! 所有 `unsafe` 函数都要求：编码器指针为 `NULL` 或来自 `lame_rs_encoder_new` 且尚未释放；
+//! 非空的缓冲区指针至少包含参数声明的元素个数。`NULL` 会被检测并作为错误返回。
+//!
+//! # 线程安全
+//!
+//! 单个编码器不能被多个线程同时使用；不同的编码器可以在不同线程中并行使用。
+
+#![allow(clippy::missing_safety_doc)]
+#![deny(unsafe_op_in_unsafe_fn)]
+#![warn(clippy::undocumented_unsafe_blocks)]
+
+use lame_sys::{EncoderConfig, LameEncoder, LameError, Quality, VbrMode};
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::ptr;
+use std::sync::OnceLock;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// 记录当前线程的最后一个错误
+fn set_last_error(message: impl Into<String>) {
+    let message = CString::new(message.into())
+        .unwrap_or_else(|_| CString::new("error message contains a null byte").unwrap());
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// 把编码结果转换为 C 约定的返回值
+fn bytes_or_error(result: Result<usize, LameError>) -> i64 {
+    match result {
+        Ok(bytes) => {
+            clear_last_error();
+            bytes as i64
+        }
+        Err(err) => {
+            set_last_error(err.to_string());
+            -1
+        }
+    }
+}
+
+/// 编码器配置
+///
+/// 可选字段取负值（`bitrate` 取 0）时使用 LAME 默认值。
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LameRsConfig {
+    /// 输入采样率（Hz）
+    pub sample_rate: i32,
+    /// 声道数（1 或 2）
+    pub channels: i32,
+    /// 比特率（kbps），0 表示默认
+    pub bitrate: i32,
+    /// 编码质量：0、2、4、5、7、9，负值表示默认
+    pub quality: i32,
+    /// VBR 模式：0 = 关闭，3 = ABR，4 = VBR，负值表示默认
+    pub vbr_mode: i32,
+    /// VBR 质量（0-9），负值表示默认
+    pub vbr_quality: i32,
+}
+
+impl LameRsConfig {
+    fn to_encoder_config(self) -> Result<EncoderConfig, LameError> {
+        let quality = match self.quality {
+            q if q < 0 => None,
+            0 => Some(Quality::Best),
+            2 => Some(Quality::High),
+            4 => Some(Quality::Good),
+            5 => Some(Quality::Standard),
+            7 => Some(Quality::Fast),
+            9 => Some(Quality::Fastest),
+            _ => return Err(LameError::InvalidParameter("quality".to_string())),
+        };
+
+        let vbr_mode = match self.vbr_mode {
+            m if m < 0 => None,
+            0 => Some(VbrMode::Off),
+            3 => Some(VbrMode::Abr),
+            4 => Some(VbrMode::Vbr),
+            _ => return Err(LameError::InvalidParameter("vbr_mode".to_string())),
+        };
+
+        Ok(EncoderConfig {
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            bitrate: (self.bitrate > 0).then_some(self.bitrate),
+            quality,
+            vbr_mode,
+            vbr_quality: (self.vbr_quality >= 0).then_some(self.vbr_quality),
+        })
+    }
+}
+
+/// 不透明的编码器句柄
+pub struct LameRsEncoder {
+    inner: LameEncoder,
+}
+
+/// 按配置创建编码器
+///
+/// 失败时返回 `NULL`，错误信息见 `lame_rs_last_error_message`。
+#[no_mangle]
+pub unsafe extern "C" fn lame_rs_encoder_new(config: *const LameRsConfig) -> *mut LameRsEncoder {
+    // SAFETY: 按指针约定，config 为 NULL 或指向有效的 LameRsConfig
+    let Some(config) = (unsafe { config.as_ref() }) else {
+        set_last_error("config is NULL");
+        return ptr::null_mut();
+    };
+
+    match config.to_encoder_config().and_then(|config| config.build()) {
+        Ok(inner) => {
+            clear_last_error();
+            Box::into_raw(Box::new(LameRsEncoder { inner }))
+        }
+        Err(err) => {
+            set_last_error(err.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// 释放编码器，传入 `NULL` 时不做任何事
+#[no_mangle]
+pub unsafe extern "C" fn lame_rs_encoder_free(encoder: *mut LameRsEncoder) {
+    if !encoder.is_null() {
+        // SAFETY: 非空的 encoder 来自 lame_rs_encoder_new 的 Box::into_raw，且只释放一次
+        drop(unsafe { Box::from_raw(encoder) });
+    }
+}
+
+/// 编码 `samples_per_channel` 个样本所需的最坏情况输出缓冲区大小
+#[no_mangle]
+pub extern "C" fn lame_rs_mp3_buffer_size(samples_per_channel: usize) -> usize {
+    LameEncoder::mp3_buffer_size(samples_per_channel)
+}
+
+/// 编码分离声道的 16-bit PCM
+///
+/// 单声道编码器传入 `right = NULL`。返回写入 `out` 的字节数，失败返回 -1。
+#[no_mangle]
+pub unsafe extern "C" fn lame_rs_encode_i16(
+    encoder: *mut LameRsEncoder,
+    left: *const i16,
+    right: *const i16,
+    samples_per_channel: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> i64 {
+    // SAFETY: 按指针约定，encoder 为 NULL 或尚未释放的编码器，且没有其他线程在使用
+    let Some(encoder) = (unsafe { encoder.as_mut() }) else {
+        set_last_error("encoder is NULL");
+        return -1;
+    };
+    if (left.is_null() && samples_per_channel > 0) || out.is_null() {
+        set_last_error("buffer is NULL");
+        return -1;
+    }
+
+    // SAFETY: 按指针约定，非空的 left/right 至少有 samples_per_channel 个样本，
+    // out 至少有 out_len 字节；空指针已在上面排除
+    let left = unsafe { slice_or_empty(left, samples_per_channel) };
+    // SAFETY: 同上
+    let out = unsafe { std::slice::from_raw_parts_mut(out, out_len) };
+
+    let result = if right.is_null() {
+        encoder.inner.encode_mono(left, out)
+    } else {
+        // SAFETY: 同上
+        let right = unsafe { std::slice::from_raw_parts(right, samples_per_channel) };
+        encoder.inner.encode(left, right, out)
+    };
+    bytes_or_error(result)
+}
+
+/// 编码交错立体声 16-bit PCM（L, R, L, R, ...）
+///
+/// `pcm` 中共有 `samples_per_channel * 2` 个样本。返回写入 `out` 的字节数，失败返回 -1。
+#[no_mangle]
+pub unsafe extern "C" fn lame_rs_encode_interleaved_i16(
+    encoder: *mut LameRsEncoder,
+    pcm: *const i16,
+    samples_per_channel: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> i64 {
+    // SAFETY: 按指针约定，encoder 为 NULL 或尚未释放的编码器，且没有其他线程在使用
+    let Some(encoder) = (unsafe { encoder.as_mut() }) else {
+        set_last_error("encoder is NULL");
+        return -1;
+    };
+    if (pcm.is_null() && samples_per_channel > 0) || out.is_null() {
+        set_last_error("buffer is NULL");
+        return -1;
+    }
+
+    let Some(samples) = samples_per_channel.checked_mul(2) else {
+        set_last_error("samples_per_channel is too large");
+        return -1;
+    };
+
+    // SAFETY: 按指针约定，非空的 pcm 至少有 samples_per_channel * 2 个样本，
+    // out 至少有 out_len 字节；空指针已在上面排除
+    let pcm = unsafe { slice_or_empty(pcm, samples) };
+    // SAFETY: 同上
+    let out = unsafe { std::slice::from_raw_parts_mut(out, out_len) };
+    bytes_or_error(encoder.inner.encode_interleaved(pcm, out))
+}
+
+/// 刷新编码器，输出剩余的 MP3 帧
+///
+/// `out` 至少需要 `lame_rs_mp3_buffer_size(0)` 字节。返回写入的字节数，失败返回 -1。
+#[no_mangle]
+pub unsafe extern "C" fn lame_rs_flush(
+    encoder: *mut LameRsEncoder,
+    out: *mut u8,
+    out_len: usize,
+) -> i64 {
+    // SAFETY: 按指针约定，encoder 为 NULL 或尚未释放的编码器，且没有其他线程在使用
+    let Some(encoder) = (unsafe { encoder.as_mut() }) else {
+        set_last_error("encoder is NULL");
+        return -1;
+    };
+    if out.is_null() {
+        set_last_error("buffer is NULL");
+        return -1;
+    }
+
+    // SAFETY: 按指针约定，out 非空且至少有 out_len 字节
+    let out = unsafe { std::slice::from_raw_parts_mut(out, out_len) };
+    bytes_or_error(encoder.inner.flush(out))
+}
+
+/// 当前线程最后一次失败的错误描述，没有错误时返回 `NULL`
+#[no_mangle]
+pub extern "C" fn lame_rs_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// 链接的 LAME 版本字符串
+#[no_mangle]
+pub extern "C" fn lame_rs_version() -> *const c_char {
+    static VERSION: OnceLock<CString> = OnceLock::new();
+    VERSION
+        .get_or_init(|| CString::new(lame_sys::get_lame_version()).unwrap_or_default())
+        .as_ptr()
+}
+
+/// 长度为 0 时允许空指针
+///
+/// # 安全性
+///
+/// `len` 不为 0 时，`data` 必须非空并指向至少 `len` 个在 `'a` 期间有效的元素。
+unsafe fn slice_or_empty<'a, T>(data: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        // SAFETY: 由调用者保证
+        unsafe { std::slice::from_raw_parts(data, len) }
+    }
+}
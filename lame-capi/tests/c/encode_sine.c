@@ -0,0 +1,83 @@
+/*
+ * 通过 C API 编码 1 秒 440 Hz 立体声正弦波
+ *
+ * 用法：encode_sine <output.mp3>
+ * 成功时退出码为 0；同时检查错误路径（非法配置、NULL 参数）。
+ */
+#include <math.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "lame_rs.h"
+
+#define SAMPLE_RATE 44100
+#define FRAME_SAMPLES 1152
+
+static int fail(const char *what) {
+    const char *message = lame_rs_last_error_message();
+    fprintf(stderr, "%s: %s\n", what, message ? message : "(no message)");
+    return 1;
+}
+
+int main(int argc, char **argv) {
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s <output.mp3>\n", argv[0]);
+        return 2;
+    }
+
+    /* 非法配置必须返回 NULL 并设置错误信息 */
+    LameRsConfig bad = {SAMPLE_RATE, 2, 0, 3, -1, -1};
+    if (lame_rs_encoder_new(&bad) != NULL || lame_rs_last_error_message() == NULL) {
+        fprintf(stderr, "invalid quality was accepted\n");
+        return 1;
+    }
+
+    LameRsConfig config = {SAMPLE_RATE, 2, 128, 5, -1, -1};
+    LameRsEncoder *encoder = lame_rs_encoder_new(&config);
+    if (encoder == NULL) {
+        return fail("lame_rs_encoder_new");
+    }
+
+    if (lame_rs_encode_i16(NULL, NULL, NULL, 0, NULL, 0) != -1) {
+        fprintf(stderr, "NULL encoder was accepted\n");
+        return 1;
+    }
+
+    size_t out_len = lame_rs_mp3_buffer_size(FRAME_SAMPLES);
+    uint8_t *out = malloc(out_len);
+    int16_t pcm[FRAME_SAMPLES * 2];
+
+    FILE *file = fopen(argv[1], "wb");
+    if (file == NULL || out == NULL) {
+        perror("setup");
+        return 1;
+    }
+
+    size_t t = 0;
+    for (int frame = 0; frame < SAMPLE_RATE / FRAME_SAMPLES; frame++) {
+        for (size_t i = 0; i < FRAME_SAMPLES; i++, t++) {
+            int16_t value = (int16_t)(sin(2.0 * M_PI * 440.0 * t / SAMPLE_RATE) * 16384.0);
+            pcm[i * 2] = value;
+            pcm[i * 2 + 1] = value;
+        }
+
+        int64_t written = lame_rs_encode_interleaved_i16(encoder, pcm, FRAME_SAMPLES, out, out_len);
+        if (written < 0) {
+            return fail("lame_rs_encode_interleaved_i16");
+        }
+        fwrite(out, 1, (size_t)written, file);
+    }
+
+    int64_t written = lame_rs_flush(encoder, out, out_len);
+    if (written < 0) {
+        return fail("lame_rs_flush");
+    }
+    fwrite(out, 1, (size_t)written, file);
+
+    fclose(file);
+    free(out);
+    lame_rs_encoder_free(encoder);
+
+    printf("LAME %s\n", lame_rs_version());
+    return 0;
+}
@@ -0,0 +1,118 @@
+//! 编译 tests/c/encode_sine.c 并通过 C API 编码，再用 Rust 解析输出的 MP3 帧
+
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// MPEG-1 Layer III 比特率表（kbps），下标为头部中的比特率索引
+const BITRATES: [u32; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+const SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+
+#[derive(Debug)]
+struct FrameHeader {
+    bitrate: u32,
+    sample_rate: u32,
+    length: usize,
+}
+
+/// 解析 MPEG-1 Layer III 帧头
+fn parse_frame_header(bytes: &[u8]) -> Option<FrameHeader> {
+    let header = u32::from_be_bytes(bytes.get(..4)?.try_into().ok()?);
+
+    let sync = header >> 21 == 0x7FF;
+    let mpeg1 = (header >> 19) & 0b11 == 0b11;
+    let layer3 = (header >> 17) & 0b11 == 0b01;
+    if !(sync && mpeg1 && layer3) {
+        return None;
+    }
+
+    let bitrate = *BITRATES.get(((header >> 12) & 0xF) as usize)?;
+    let sample_rate = *SAMPLE_RATES.get(((header >> 10) & 0b11) as usize)?;
+    let padding = (header >> 9) & 1;
+    if bitrate == 0 {
+        return None;
+    }
+
+    Some(FrameHeader {
+        bitrate,
+        sample_rate,
+        length: (144 * bitrate * 1000 / sample_rate + padding) as usize,
+    })
+}
+
+/// 测试二进制位于 target/<profile>/deps，cdylib 位于 target/<profile>
+fn library_dir() -> PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    exe.parent().unwrap().parent().unwrap().to_path_buf()
+}
+
+fn compile_c_program(output: &Path) -> bool {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib_dir = library_dir();
+
+    let status = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(crate_dir.join("tests/c/encode_sine.c"))
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .args(["-llame_rs", "-lm", "-o"])
+        .arg(output)
+        .status();
+
+    match status {
+        Ok(status) => status.success(),
+        Err(err) => {
+            // 没有 C 编译器的环境跳过
+            eprintln!("skipping C API test, C compiler unavailable: {}", err);
+            false
+        }
+    }
+}
+
+#[test]
+fn test_c_program_encodes_valid_frames() {
+    let out_dir = std::env::temp_dir().join(format!("lame-capi-test-{}", std::process::id()));
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let program = out_dir.join("encode_sine");
+    let mp3_path = out_dir.join("sine.mp3");
+
+    if !compile_c_program(&program) {
+        assert!(
+            Command::new("cc").arg("--version").output().is_err(),
+            "failed to compile tests/c/encode_sine.c"
+        );
+        return;
+    }
+
+    let output = Command::new(&program).arg(&mp3_path).output().unwrap();
+    assert!(
+        output.status.success(),
+        "C program failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let mp3 = std::fs::read(&mp3_path).unwrap();
+    let mut offset = 0;
+    let mut frames = 0;
+
+    while offset < mp3.len() {
+        let header = parse_frame_header(&mp3[offset..])
+            .unwrap_or_else(|| panic!("invalid frame header at offset {}", offset));
+        assert_eq!(header.sample_rate, 44100);
+        assert_eq!(header.bitrate, 128);
+
+        offset += header.length;
+        frames += 1;
+    }
+
+    assert_eq!(offset, mp3.len(), "trailing partial frame");
+    // 1 秒音频约 38 帧，另有 LAME 信息帧和编码延迟
+    assert!(frames >= 38, "only {} frames", frames);
+
+    std::fs::remove_dir_all(&out_dir).ok();
+}
@@ -1,22 +1,10 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use lame_sys::signal;
 use std::mem::MaybeUninit;
 
 // 生成测试 PCM 数据（单声道 440 Hz 正弦波）
 fn generate_pcm_data(num_samples: usize) -> Vec<i16> {
-    let sample_rate = 44100.0;
-    let frequency = 440.0;
-
-    let mut pcm = Vec::with_capacity(num_samples);
-
-    for i in 0..num_samples {
-        let t = i as f32 / sample_rate;
-        let sample = (2.0 * std::f32::consts::PI * frequency * t).sin();
-        let value = (sample * 16384.0) as i16;
-
-        pcm.push(value);
-    }
-
-    pcm
+    signal::sine(440.0, 44100, signal::duration_of(num_samples, 44100))
 }
 
 // ============================================================================
@@ -96,6 +84,52 @@ fn bench_lame_sys_complete(c: &mut Criterion) {
     });
 }
 
+// ============================================================================
+// 场景 3: 通过 Mp3Writer 逐帧写入（1000 frames）- 比较每帧调用与批量调用
+// ============================================================================
+
+fn bench_lame_sys_writer(c: &mut Criterion) {
+    let frame_size = 1152;
+    let num_frames = 1000;
+    let pcm = generate_pcm_data(frame_size * num_frames);
+
+    // 1152 = 每帧调用一次 LAME；16384 = 默认批量
+    for batch_samples in [frame_size, lame_sys::writer::DEFAULT_BATCH_SAMPLES] {
+        let name = format!(
+            "lame-sys/writer_1000_frames_mono_q4_batch_{}",
+            batch_samples
+        );
+
+        c.bench_function(&name, |b| {
+            b.iter(|| {
+                let encoder = lame_sys::LameEncoder::builder()
+                    .expect("Failed to create builder")
+                    .sample_rate(44100)
+                    .expect("Failed to set sample rate")
+                    .channels(1)
+                    .expect("Failed to set channels")
+                    .bitrate(128)
+                    .expect("Failed to set bitrate")
+                    .quality(lame_sys::Quality::Standard)
+                    .expect("Failed to set quality")
+                    .build()
+                    .expect("Failed to build encoder");
+
+                let mut writer = lame_sys::Mp3Writer::new(encoder, Vec::with_capacity(512 * 1024));
+                writer
+                    .set_batch_samples(batch_samples)
+                    .expect("Failed to set batch size");
+
+                for frame in pcm.chunks(frame_size) {
+                    writer.write_pcm(black_box(frame)).expect("Failed to write");
+                }
+
+                writer.finish().expect("Failed to finish").len()
+            });
+        });
+    }
+}
+
 // ============================================================================
 // 竞品对比：mp3lame-encoder（相同测试场景）
 // ============================================================================
@@ -175,6 +209,7 @@ criterion_group!(
     benches,
     bench_lame_sys_single_frame,
     bench_lame_sys_complete,
+    bench_lame_sys_writer,
     bench_competitor_single_frame,
     bench_competitor_complete,
 );
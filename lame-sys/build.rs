@@ -1,5 +1,68 @@
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 按版本出现的 LAME 符号：(cfg 名称, 头文件中的探测符号)
+///
+/// 绑定的头文件中存在该符号时输出对应的 cfg，Rust 侧据此启用或降级相应 API。
+/// 新增条目时需同步更新 `src/features.rs` 中的 `Feature`。
+const FEATURE_PROBES: &[(&str, &str)] = &[
+    ("lame_has_fractional_vbr_quality", "lame_set_VBR_quality"),
+    ("lame_has_utf16_tags", "id3tag_set_textinfo_utf16"),
+    (
+        "lame_has_max_samples_getter",
+        "lame_get_maximum_number_of_samples",
+    ),
+    ("lame_has_lametag_frame", "lame_get_lametag_frame"),
+    ("lame_has_id3v2_tag_getter", "lame_get_id3v2_tag"),
+];
+
+/// 探测头文件中的可选符号，输出 cfg 标志
+fn probe_features(header: &Path) {
+    let contents = fs::read_to_string(header)
+        .unwrap_or_else(|e| panic!("Couldn't read {}: {}", header.display(), e));
+
+    for &(cfg, symbol) in FEATURE_PROBES {
+        // 让未启用的 cfg 也不会触发 unexpected_cfgs 警告
+        println!("cargo:rustc-check-cfg=cfg({})", cfg);
+
+        let declared = contents.match_indices(symbol).any(|(index, _)| {
+            let rest = &contents[index + symbol.len()..];
+            let boundary = rest
+                .chars()
+                .next()
+                .is_none_or(|c| !(c.is_ascii_alphanumeric() || c == '_'));
+            boundary && rest.trim_start().starts_with('(')
+        });
+
+        if declared {
+            println!("cargo:rustc-cfg={}", cfg);
+        }
+    }
+}
+
+/// 从源码树的 `version.h` 读取头文件对应的 LAME 版本
+///
+/// 系统安装的 LAME 不包含 `version.h`，此时返回 `None`，运行时版本以
+/// `get_lame_version()` 为准。
+fn header_version(lame_dir: &Path) -> Option<String> {
+    let contents = fs::read_to_string(lame_dir.join("libmp3lame").join("version.h")).ok()?;
+    let define = |name: &str| {
+        contents.lines().find_map(|line| {
+            let mut parts = line.trim_start_matches(['#', ' ']).split_whitespace();
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("define"), Some(n), Some(value)) if n == name => Some(value.to_string()),
+                _ => None,
+            }
+        })
+    };
+
+    Some(format!(
+        "{}.{}",
+        define("LAME_MAJOR_VERSION")?,
+        define("LAME_MINOR_VERSION")?
+    ))
+}
 
 fn main() {
     // 获取 LAME 源代码路径
@@ -28,6 +91,13 @@ fn main() {
         .fast_build(true)
         .build();
 
+    // 消息回调的 C 垫片引用 libmp3lame，需要先于它链接
+    println!("cargo:rerun-if-changed=csrc/report.c");
+    cc::Build::new()
+        .file("csrc/report.c")
+        .include(&include_dir)
+        .compile("lame_sys_report");
+
     // 链接生成的静态库
     println!("cargo:rustc-link-search=native={}/lib", dst.display());
     println!("cargo:rustc-link-lib=static=mp3lame");
@@ -35,10 +105,18 @@ fn main() {
     // 链接数学库
     println!("cargo:rustc-link-lib=m");
 
-    // 2. 使用 bindgen 生成 Rust FFI 绑定
+    // 2. 探测头文件中按版本出现的 API
+    let header = include_dir.join("lame.h");
+    println!("cargo:rerun-if-changed={}", header.display());
+    probe_features(&header);
+
+    let version = header_version(&lame_dir).unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=LAME_HEADER_VERSION={}", version);
+
+    // 3. 使用 bindgen 生成 Rust FFI 绑定
     let bindings = bindgen::Builder::default()
         // 输入头文件
-        .header(include_dir.join("lame.h").to_str().unwrap())
+        .header(header.to_str().unwrap())
         // 添加 clang 参数（include 路径）
         .clang_arg(format!("-I{}", lame_dir.display()))
         .clang_arg(format!("-I{}", include_dir.display()))
@@ -0,0 +1,89 @@
+/*
+ * LAME 消息回调的 C 垫片
+ *
+ * lame_set_msgf / debugf / errorf 的回调是 printf 风格的 (format, va_list)，
+ * 稳定版 Rust 无法直接接收 va_list，这里用 vsnprintf 格式化成字符串后交给 Rust 侧的收集函数。
+ */
+
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "lame.h"
+
+/* 与 src/report.rs 中的 Kind 对应 */
+enum {
+    REPORT_MSG = 0,
+    REPORT_DEBUG = 1,
+    REPORT_ERROR = 2
+};
+
+/* 处理一条消息，返回 0 时由垫片输出到 stderr */
+typedef int (*lame_sys_sink)(int kind, const char *message, size_t len);
+
+static lame_sys_sink sink = NULL;
+
+static void
+report(int kind, const char *format, va_list args)
+{
+    char    stack[512];
+    char   *message = stack;
+    va_list copy;
+    int     len;
+
+    va_copy(copy, args);
+    len = vsnprintf(stack, sizeof(stack), format, args);
+    if (len >= 0 && (size_t) len >= sizeof(stack)) {
+        message = malloc((size_t) len + 1);
+        if (message != NULL) {
+            vsnprintf(message, (size_t) len + 1, format, copy);
+        }
+    }
+    va_end(copy);
+    if (len < 0 || message == NULL) {
+        return;
+    }
+
+    /* 与 LAME 的默认回调相同，输出到 stderr */
+    if (sink == NULL || !sink(kind, message, (size_t) len)) {
+        fputs(message, stderr);
+        fflush(stderr);
+    }
+    if (message != stack) {
+        free(message);
+    }
+}
+
+static void
+report_msg(const char *format, va_list args)
+{
+    report(REPORT_MSG, format, args);
+}
+
+static void
+report_debug(const char *format, va_list args)
+{
+    report(REPORT_DEBUG, format, args);
+}
+
+static void
+report_error(const char *format, va_list args)
+{
+    report(REPORT_ERROR, format, args);
+}
+
+/* 设置收集函数，只在安装回调之前调用一次 */
+void
+lame_sys_set_report_sink(lame_sys_sink collect)
+{
+    sink = collect;
+}
+
+/* 让句柄的三种消息都经过本垫片，必须在 lame_init_params 之前调用 */
+int
+lame_sys_install_report(lame_global_flags * gfp)
+{
+    return lame_set_msgf(gfp, report_msg)
+        | lame_set_debugf(gfp, report_debug)
+        | lame_set_errorf(gfp, report_error);
+}
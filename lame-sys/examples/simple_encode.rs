@@ -9,11 +9,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 1. 创建编码器
     println!("Creating encoder...");
-    let mut encoder = LameEncoder::builder()
-        .sample_rate(44100)         // 44.1 kHz 标准 CD 音质
-        .channels(2)                // 立体声
-        .quality(Quality::Standard) // 标准质量
-        .bitrate(192)               // 192 kbps
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?         // 44.1 kHz 标准 CD 音质
+        .channels(2)?                // 立体声
+        .quality(Quality::Standard)? // 标准质量
+        .bitrate(192)?               // 192 kbps
         .build()?;
 
     println!("✓ Encoder created successfully");
@@ -26,7 +26,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .album("Example Album")?
         .year("2024")?
         .comment("Generated by lame-sys example")?
-        .track(1)
+        .track(1, Some(10))
         .genre("Electronic")?
         .apply()?;
 
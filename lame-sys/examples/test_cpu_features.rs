@@ -1,57 +1,37 @@
-use lame_sys::ffi;
-use std::ffi::CStr;
+use lame_sys::prelude::*;
 
 fn main() {
     println!("Testing LAME CPU feature detection...\n");
 
-    unsafe {
-        let gfp = ffi::lame_init();
-        if gfp.is_null() {
-            eprintln!("Failed to initialize LAME");
+    println!("Initializing LAME parameters...");
+    println!("(This should print CPU features detected)\n");
+
+    // Building the encoder calls lame_init_params, which triggers CPU detection
+    let mut encoder = match LameEncoder::builder()
+        .and_then(|builder| builder.sample_rate(44100))
+        .and_then(|builder| builder.channels(1))
+        .and_then(|builder| builder.bitrate(192))
+        .and_then(|builder| builder.quality(Quality::Good))
+        .and_then(|builder| builder.build())
+    {
+        Ok(encoder) => encoder,
+        Err(err) => {
+            eprintln!("Failed to initialize LAME: {}", err);
             return;
         }
+    };
 
-        // Set basic parameters
-        ffi::lame_set_in_samplerate(gfp, 44100);
-        ffi::lame_set_num_channels(gfp, 1);
-        ffi::lame_set_brate(gfp, 192);
-        ffi::lame_set_quality(gfp, 4);
+    println!("\n===========================================");
+    println!("LAME Version: {}", lame_sys::get_lame_version());
+    println!("===========================================");
 
-        println!("Initializing LAME parameters...");
-        println!("(This should print CPU features detected)\n");
+    // Test encode to ensure everything works
+    let pcm: Vec<i16> = vec![0; 1152];
+    let mut mp3_buffer: Vec<u8> = vec![0; 8192];
 
-        // Initialize - this triggers CPU detection
-        let result = ffi::lame_init_params(gfp);
-        if result < 0 {
-            eprintln!("Failed to initialize parameters");
-            ffi::lame_close(gfp);
-            return;
-        }
-
-        println!("\n===========================================");
-        println!("LAME Version: {}", CStr::from_ptr(ffi::get_lame_version()).to_str().unwrap());
-        println!("===========================================");
-
-        // Test encode to ensure everything works
-        let pcm: Vec<i16> = vec![0; 1152];
-        let mut mp3_buffer: Vec<u8> = vec![0; 8192];
-
-        let bytes = ffi::lame_encode_buffer(
-            gfp,
-            pcm.as_ptr(),
-            std::ptr::null(),
-            1152,
-            mp3_buffer.as_mut_ptr(),
-            8192,
-        );
-
-        if bytes >= 0 {
-            println!("Test encode successful: {} bytes", bytes);
-        } else {
-            println!("Test encode failed: {}", bytes);
-        }
-
-        ffi::lame_close(gfp);
+    match encoder.encode_mono(&pcm, &mut mp3_buffer) {
+        Ok(bytes) => println!("Test encode successful: {} bytes", bytes),
+        Err(err) => println!("Test encode failed: {}", err),
     }
 
     println!("\nCheck the output above for 'CPU features:' line");
@@ -0,0 +1,331 @@
+use crate::encoder::{ChannelMode, EncoderBuilder, LameEncoder, Quality, VbrMode};
+use crate::error::{LameError, Result};
+use crate::ffi;
+use crate::writer::FrameHeader;
+use std::fmt::Debug;
+
+/// 编码器配置
+///
+/// 与 [`EncoderBuilder`] 不同，`EncoderConfig` 是不持有 LAME 指针的普通值类型，
+/// 可以克隆、比较，并多次用于创建编码器。值为 `None` 的字段保持 LAME 默认值。
+///
+/// 启用 `serde` feature 后可以序列化，用于把编码设置保存在 JSON、TOML 等配置文件中。
+/// 反序列化时缺少的字段取 [`Default`] 中的值。
+///
+/// # 示例
+///
+/// ```no_run
+/// use lame_sys::prelude::*;
+///
+/// # fn main() -> Result<()> {
+/// let config = EncoderConfig {
+///     bitrate: Some(192),
+///     quality: Some(Quality::High),
+///     ..EncoderConfig::default()
+/// };
+///
+/// let first = config.build()?;
+/// let second = config.build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct EncoderConfig {
+    /// 采样率（Hz）
+    pub sample_rate: i32,
+    /// 声道数（1 = 单声道, 2 = 立体声）
+    pub channels: i32,
+    /// 比特率（kbps）
+    pub bitrate: Option<i32>,
+    /// 编码质量
+    pub quality: Option<Quality>,
+    /// VBR 模式
+    pub vbr_mode: Option<VbrMode>,
+    /// VBR 质量（0-9，0 = 最高质量）
+    pub vbr_quality: Option<i32>,
+    /// VBR/ABR 的最低比特率（kbps）
+    pub vbr_min_bitrate: Option<i32>,
+    /// VBR/ABR 的最高比特率（kbps）
+    pub vbr_max_bitrate: Option<i32>,
+    /// MPEG 声道模式
+    pub mode: Option<ChannelMode>,
+    /// 低通滤波器的截止频率（Hz），`-1` 表示关闭
+    pub lowpass: Option<i32>,
+    /// 高通滤波器的截止频率（Hz），`-1` 表示关闭
+    pub highpass: Option<i32>,
+}
+
+impl Default for EncoderConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100,
+            channels: 2,
+            bitrate: None,
+            quality: None,
+            vbr_mode: None,
+            vbr_quality: None,
+            vbr_min_bitrate: None,
+            vbr_max_bitrate: None,
+            mode: None,
+            lowpass: None,
+            highpass: None,
+        }
+    }
+}
+
+/// 两份配置中取值不同的一项，由 [`EncoderConfig::diff`] 返回
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// 字段名，与 [`EncoderConfig`] 的字段同名
+    pub field: &'static str,
+    /// 第一份配置中的取值，`None` 显示为 `"default"`
+    pub a: String,
+    /// 第二份配置中的取值
+    pub b: String,
+}
+
+impl EncoderConfig {
+    /// 根据配置创建编码器
+    pub fn build(&self) -> Result<LameEncoder> {
+        EncoderBuilder::from_config(self)?.build()
+    }
+
+    /// 列出与 `other` 取值不同的字段，顺序与字段定义相同
+    ///
+    /// 用于解释两个编码器（或编码器与已有文件）的输出为什么不同，
+    /// 通常与 [`LameEncoder::config`] 和 [`from_lametag`](Self::from_lametag) 配合使用。
+    pub fn diff(&self, other: &EncoderConfig) -> Vec<ConfigDiff> {
+        let mut diffs = Vec::new();
+        push_diff(
+            &mut diffs,
+            "sample_rate",
+            &Some(self.sample_rate),
+            &Some(other.sample_rate),
+        );
+        push_diff(
+            &mut diffs,
+            "channels",
+            &Some(self.channels),
+            &Some(other.channels),
+        );
+        push_diff(&mut diffs, "bitrate", &self.bitrate, &other.bitrate);
+        push_diff(&mut diffs, "quality", &self.quality, &other.quality);
+        push_diff(&mut diffs, "vbr_mode", &self.vbr_mode, &other.vbr_mode);
+        push_diff(
+            &mut diffs,
+            "vbr_quality",
+            &self.vbr_quality,
+            &other.vbr_quality,
+        );
+        push_diff(
+            &mut diffs,
+            "vbr_min_bitrate",
+            &self.vbr_min_bitrate,
+            &other.vbr_min_bitrate,
+        );
+        push_diff(
+            &mut diffs,
+            "vbr_max_bitrate",
+            &self.vbr_max_bitrate,
+            &other.vbr_max_bitrate,
+        );
+        push_diff(&mut diffs, "mode", &self.mode, &other.mode);
+        push_diff(&mut diffs, "lowpass", &self.lowpass, &other.lowpass);
+        push_diff(&mut diffs, "highpass", &self.highpass, &other.highpass);
+        diffs
+    }
+
+    /// 从 LAME 信息帧（Xing/Info 帧中的 LAME 标签）推断编码时的配置
+    ///
+    /// `data` 以信息帧开头，或以 ID3v2 标签加信息帧开头（即 MP3 文件的开头），
+    /// 例如 [`LameEncoder::lametag_frame`] 的返回值。各字段的来源：
+    ///
+    /// - `sample_rate`：帧头中的输出采样率；编码时重采样过则与输入采样率不同
+    /// - `channels`：帧头中的声道模式，单声道为 1，其他为 2
+    /// - `vbr_mode`：LAME 标签中的 VBR 方法
+    /// - `bitrate`：CBR 取帧头的比特率；ABR 取标签中的目标比特率，超过 255 kbps 时记为 255；VBR 为 `None`
+    /// - `quality`、`vbr_quality`：Xing 质量字段（`100 - 10 × VBR 质量 - 质量级别`），
+    ///   `vbr_quality` 只在 VBR 时给出；质量级别没有对应的 [`Quality`] 变体时为 `None`
+    ///
+    /// 其余字段为 `None`。不是带 LAME 标签的信息帧时返回 [`LameError::InvalidInput`]。
+    pub fn from_lametag(data: &[u8]) -> Result<Self> {
+        let frame = skip_id3v2(data);
+        let invalid = |reason: &str| LameError::InvalidInput(format!("not a LAME tag: {}", reason));
+
+        let header = frame
+            .get(..4)
+            .and_then(FrameHeader::parse)
+            .ok_or_else(|| invalid("no MPEG Layer III frame header"))?;
+        let frame = &frame[..header.len.min(frame.len())];
+
+        let marker = 4 + header.side_info_len();
+        match frame.get(marker..marker + 4) {
+            Some(b"Xing") | Some(b"Info") => {}
+            _ => return Err(invalid("missing Xing/Info marker")),
+        }
+        let flags = read_u32(frame, marker + 4).ok_or_else(|| invalid("truncated Xing header"))?;
+
+        // 依次跳过帧数、字节数和 TOC，最后是质量字段
+        let mut cursor = marker + 8;
+        for (flag, len) in [(0x1, 4), (0x2, 4), (0x4, 100)] {
+            if flags & flag != 0 {
+                cursor += len;
+            }
+        }
+        let scale = if flags & 0x8 != 0 {
+            let scale = read_u32(frame, cursor);
+            cursor += 4;
+            scale
+        } else {
+            None
+        };
+
+        // LAME 标签：9 字节版本字符串、版本/VBR 方法、低通……比特率在第 20 字节
+        let tag = frame
+            .get(cursor..cursor + 36)
+            .filter(|tag| tag[0] != 0)
+            .ok_or_else(|| invalid("no LAME extension after the Xing header"))?;
+        let vbr_mode = match tag[9] & 0x0F {
+            1 | 8 => Some(VbrMode::Off),
+            2 | 9 => Some(VbrMode::Abr),
+            3..=6 => Some(VbrMode::Vbr),
+            _ => None,
+        };
+        let bitrate = match vbr_mode {
+            Some(VbrMode::Off) => Some(header.kbps as i32),
+            Some(VbrMode::Abr) => Some(tag[20] as i32),
+            _ => None,
+        };
+
+        let level = scale
+            .and_then(|scale| 100i32.checked_sub(scale.try_into().ok()?))
+            .filter(|level| (0..100).contains(level));
+        let quality = level.and_then(|level| Quality::from_level(level % 10));
+        let vbr_quality = level
+            .filter(|_| vbr_mode == Some(VbrMode::Vbr))
+            .map(|level| level / 10);
+
+        Ok(Self {
+            sample_rate: header.sample_rate as i32,
+            channels: if header.mono { 1 } else { 2 },
+            bitrate,
+            quality,
+            vbr_mode,
+            vbr_quality,
+            ..Self::default()
+        })
+    }
+}
+
+impl LameEncoder {
+    /// 读取编码器当前的参数，生成配置快照
+    ///
+    /// 各字段经过 `lame_init_params` 调整，可能与构建时传入的值不同（例如未设置质量时为 LAME 的默认级别）。
+    /// 为了能与 [`EncoderConfig::from_lametag`] 的结果比较：`bitrate` 在 CBR 时为比特率，
+    /// ABR 时为目标比特率，VBR 时为 `None`；`vbr_quality` 只在 VBR 时给出；
+    /// 质量级别没有对应的 [`Quality`] 变体时 `quality` 为 `None`。
+    /// 声道模式、滤波器和 VBR 比特率范围由 LAME 按其他参数选择，快照中为 `None`。
+    pub fn config(&self) -> EncoderConfig {
+        let vbr_mode = self.vbr_mode();
+        let bitrate = match vbr_mode {
+            VbrMode::Off => Some(self.bitrate()),
+            VbrMode::Abr => Some(self.get(ffi::lame_get_VBR_mean_bitrate_kbps)),
+            VbrMode::Vbr => None,
+        };
+
+        EncoderConfig {
+            sample_rate: self.input_sample_rate(),
+            channels: self.channels(),
+            bitrate,
+            quality: self.quality(),
+            vbr_mode: Some(vbr_mode),
+            vbr_quality: (vbr_mode == VbrMode::Vbr).then(|| self.get(ffi::lame_get_VBR_q)),
+            ..EncoderConfig::default()
+        }
+    }
+}
+
+/// 两个取值不同时记录一项差异
+fn push_diff<T: PartialEq + Debug>(
+    diffs: &mut Vec<ConfigDiff>,
+    field: &'static str,
+    a: &Option<T>,
+    b: &Option<T>,
+) {
+    if a != b {
+        diffs.push(ConfigDiff {
+            field,
+            a: show(a),
+            b: show(b),
+        });
+    }
+}
+
+fn show<T: Debug>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => format!("{:?}", value),
+        None => "default".to_string(),
+    }
+}
+
+/// 跳过开头的 ID3v2 标签（如果有）
+fn skip_id3v2(data: &[u8]) -> &[u8] {
+    if data.len() < 10 || &data[..3] != b"ID3" {
+        return data;
+    }
+    // 标签大小为 synchsafe 整数，不含 10 字节的标签头
+    let size = data[6..10]
+        .iter()
+        .fold(0usize, |size, &b| (size << 7) | (b & 0x7F) as usize);
+    data.get(10 + size..).unwrap_or(&[])
+}
+
+/// 读取大端序的 u32
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    let bytes = data.get(at..at + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+impl EncoderBuilder {
+    /// 从 [`EncoderConfig`] 创建构建器
+    ///
+    /// 返回的构建器仍可继续调用其他配置方法。
+    pub fn from_config(config: &EncoderConfig) -> Result<Self> {
+        let mut builder = Self::new()?;
+        builder
+            .set_sample_rate(config.sample_rate)?
+            .set_channels(config.channels)?;
+
+        if let Some(bitrate) = config.bitrate {
+            builder.set_bitrate(bitrate)?;
+        }
+        if let Some(quality) = config.quality {
+            builder.set_quality(quality)?;
+        }
+        if let Some(mode) = config.vbr_mode {
+            builder.set_vbr_mode(mode)?;
+        }
+        if let Some(quality) = config.vbr_quality {
+            builder.set_vbr_quality(quality)?;
+        }
+        if let Some(kbps) = config.vbr_min_bitrate {
+            builder.set_vbr_min_bitrate(kbps)?;
+        }
+        if let Some(kbps) = config.vbr_max_bitrate {
+            builder.set_vbr_max_bitrate(kbps)?;
+        }
+        if let Some(mode) = config.mode {
+            builder.set_mode(mode)?;
+        }
+        if let Some(freq_hz) = config.lowpass {
+            builder.set_lowpass(freq_hz)?;
+        }
+        if let Some(freq_hz) = config.highpass {
+            builder.set_highpass(freq_hz)?;
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,136 @@
+//! 编码前的简单 PCM 处理
+//!
+//! 剪辑的开头和结尾如果不是从静音开始/结束，解码后会听到“咔哒”声。
+//! 这里的淡入淡出函数在编码前原地修改 16-bit PCM：淡入让第一个样本为 0，
+//! 淡出让最后一个样本为 0，增益在淡变区间内单调变化。
+//!
+//! # 示例
+//!
+//! ```
+//! use lame_sys::dsp::{self, FadeShape};
+//!
+//! let mut mono = vec![1000i16; 4410];
+//! dsp::fade_in(&mut mono, 441);
+//! dsp::fade_out(&mut mono, 441);
+//! assert_eq!(mono[0], 0);
+//! assert_eq!(mono[4409], 0);
+//!
+//! // 交错立体声按帧淡变，左右声道使用相同的增益
+//! let mut stereo = vec![1000i16; 4410 * 2];
+//! dsp::fade_out_interleaved(&mut stereo, 2, 441, FadeShape::RaisedCosine)?;
+//! # Ok::<(), lame_sys::LameError>(())
+//! ```
+
+use crate::error::{LameError, Result};
+
+/// 淡变曲线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FadeShape {
+    /// 线性增益
+    #[default]
+    Linear,
+    /// 升余弦（半个余弦周期），两端变化平缓
+    RaisedCosine,
+}
+
+impl FadeShape {
+    /// 长度为 `length` 的淡入中，第 `position` 个样本（帧）的增益
+    ///
+    /// `position` 为 0 时增益为 0，随 `position` 单调递增，到 `length` 时为 1。
+    /// 淡出的增益为从末尾倒数的位置对应的淡入增益。
+    pub fn gain(self, position: usize, length: usize) -> f32 {
+        if length == 0 || position >= length {
+            return 1.0;
+        }
+        let t = position as f32 / length as f32;
+        match self {
+            FadeShape::Linear => t,
+            FadeShape::RaisedCosine => 0.5 - 0.5 * (std::f32::consts::PI * t).cos(),
+        }
+    }
+}
+
+/// 对单声道 PCM 的前 `samples` 个样本线性淡入
+///
+/// `samples` 超过 `pcm` 长度时整段淡入。
+pub fn fade_in(pcm: &mut [i16], samples: usize) {
+    fade_in_shaped(pcm, samples, FadeShape::Linear);
+}
+
+/// 对单声道 PCM 的最后 `samples` 个样本线性淡出
+///
+/// `samples` 超过 `pcm` 长度时整段淡出。
+pub fn fade_out(pcm: &mut [i16], samples: usize) {
+    fade_out_shaped(pcm, samples, FadeShape::Linear);
+}
+
+/// 按指定曲线对单声道 PCM 的前 `samples` 个样本淡入
+pub fn fade_in_shaped(pcm: &mut [i16], samples: usize, shape: FadeShape) {
+    apply_fade_in(pcm, 1, samples, shape);
+}
+
+/// 按指定曲线对单声道 PCM 的最后 `samples` 个样本淡出
+pub fn fade_out_shaped(pcm: &mut [i16], samples: usize, shape: FadeShape) {
+    apply_fade_out(pcm, 1, samples, shape);
+}
+
+/// 对交错 PCM 的前 `frames` 帧淡入，同一帧的所有声道使用相同的增益
+///
+/// `pcm` 的长度必须是 `channels` 的整数倍，否则返回 [`LameError::InvalidInput`]。
+pub fn fade_in_interleaved(
+    pcm: &mut [i16],
+    channels: usize,
+    frames: usize,
+    shape: FadeShape,
+) -> Result<()> {
+    check_interleaved(pcm, channels)?;
+    apply_fade_in(pcm, channels, frames, shape);
+    Ok(())
+}
+
+/// 对交错 PCM 的最后 `frames` 帧淡出，同一帧的所有声道使用相同的增益
+///
+/// `pcm` 的长度必须是 `channels` 的整数倍，否则返回 [`LameError::InvalidInput`]。
+pub fn fade_out_interleaved(
+    pcm: &mut [i16],
+    channels: usize,
+    frames: usize,
+    shape: FadeShape,
+) -> Result<()> {
+    check_interleaved(pcm, channels)?;
+    apply_fade_out(pcm, channels, frames, shape);
+    Ok(())
+}
+
+fn check_interleaved(pcm: &[i16], channels: usize) -> Result<()> {
+    if channels == 0 || pcm.len() % channels != 0 {
+        return Err(LameError::InvalidInput(format!(
+            "Interleaved data length {} is not a multiple of {} channels",
+            pcm.len(),
+            channels
+        )));
+    }
+    Ok(())
+}
+
+/// 对一帧的所有样本乘以增益
+fn scale(frame: &mut [i16], gain: f32) {
+    for sample in frame {
+        *sample = (f32::from(*sample) * gain).round() as i16;
+    }
+}
+
+fn apply_fade_in(pcm: &mut [i16], channels: usize, frames: usize, shape: FadeShape) {
+    let frames = frames.min(pcm.len() / channels);
+    for (position, frame) in pcm.chunks_exact_mut(channels).take(frames).enumerate() {
+        scale(frame, shape.gain(position, frames));
+    }
+}
+
+fn apply_fade_out(pcm: &mut [i16], channels: usize, frames: usize, shape: FadeShape) {
+    let frames = frames.min(pcm.len() / channels);
+    // 从末尾倒数：最后一帧的位置为 0
+    for (position, frame) in pcm.rchunks_exact_mut(channels).take(frames).enumerate() {
+        scale(frame, shape.gain(position, frames));
+    }
+}
@@ -1,9 +1,18 @@
 use crate::error::{LameError, Result};
 use crate::ffi;
-use std::ptr::{self, NonNull};
+use crate::param::{Accessor, RawParam, RawValue};
+use crate::pcm::sealed::{ChunkControl, InputDispatch};
+use crate::pcm::{
+    short_range, DualPcm, FloatScale, InterleavedPcm, MonoPcm, PcmInput, MAX_SAMPLES_PER_CALL,
+};
+use crate::watchdog::{CallProgress, CancelToken, ProgressHook};
+use crate::writer::StreamingEncoder;
+use std::ffi::c_ulong;
+use std::ptr::NonNull;
 
 /// LAME 编码质量级别
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Quality {
     /// 最高质量（最慢）
     Best = 0,
@@ -19,17 +28,229 @@ pub enum Quality {
     Fastest = 9,
 }
 
+impl Quality {
+    /// 与 LAME 质量级别（`-q`）完全对应的变体，没有对应变体的级别返回 `None`
+    pub(crate) fn from_level(level: i32) -> Option<Self> {
+        match level {
+            0 => Some(Quality::Best),
+            2 => Some(Quality::High),
+            4 => Some(Quality::Good),
+            5 => Some(Quality::Standard),
+            7 => Some(Quality::Fast),
+            9 => Some(Quality::Fastest),
+            _ => None,
+        }
+    }
+}
+
+/// MPEG 声道模式
+///
+/// 与输入声道数无关：`channels(2)` 配合 [`ChannelMode::Mono`] 会把立体声输入下混为单声道输出。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelMode {
+    /// 独立编码左右声道
+    Stereo = 0,
+    /// 联合立体声，LAME 逐帧选择 L/R 或 M/S 编码（立体声默认值）
+    JointStereo = 1,
+    /// 双声道（两个独立的单声道节目），LAME 按 [`Stereo`](Self::Stereo) 编码
+    DualChannel = 2,
+    /// 单声道
+    Mono = 3,
+}
+
+impl ChannelMode {
+    /// 从 `lame_get_mode` 的返回值转换；`build()` 之后 LAME 总会选定一种模式
+    pub(crate) fn from_raw(mode: u32) -> Self {
+        match mode {
+            0 => ChannelMode::Stereo,
+            2 => ChannelMode::DualChannel,
+            3 => ChannelMode::Mono,
+            _ => ChannelMode::JointStereo,
+        }
+    }
+}
+
+/// 短块（short block）的使用方式
+///
+/// 短块把一帧拆成更短的变换窗口，能减少鼓点等瞬态前的预回声，但编码效率较低。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortBlockMode {
+    /// 由心理声学模型按需切换（默认）
+    Allowed,
+    /// 只用长块，对应命令行的 `--no-short-blocks`
+    Dispensed,
+    /// 全部使用短块，对应命令行的 `--allshort`
+    Forced,
+}
+
+/// 帧头中的去加重（emphasis）标记
+///
+/// 只是告诉解码器输入经过了哪种预加重，LAME 不会处理音频本身。
+/// 帧头中的保留值 2 不在此列出。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emphasis {
+    /// 无（默认）
+    None = 0,
+    /// 50/15 µs
+    E5015 = 1,
+    /// CCITT J.17
+    Ccitt = 3,
+}
+
 /// VBR（可变比特率）模式
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VbrMode {
     /// 关闭 VBR（使用 CBR）
     Off = 0,
     /// VBR 模式
     Vbr = 4,
-    /// ABR（平均比特率）模式
+    /// ABR（平均比特率）模式，目标比特率由 [`EncoderBuilder::abr_bitrate`] 设置
     Abr = 3,
 }
 
+impl VbrMode {
+    /// 从 `lame_get_VBR` 的返回值转换，LAME 内部的各种 VBR 算法都归为 [`VbrMode::Vbr`]
+    pub(crate) fn from_raw(mode: u32) -> Self {
+        match mode {
+            0 => VbrMode::Off,
+            3 => VbrMode::Abr,
+            _ => VbrMode::Vbr,
+        }
+    }
+}
+
+/// LAME 预设，对应命令行工具的 `-V n` 和 `--preset`
+///
+/// 通过 [`EncoderBuilder::preset`] 应用。预设会一次性设置 VBR 模式、比特率、质量和心理声学参数，
+/// 因此应当最先调用；之后显式调用的 [`bitrate`](EncoderBuilder::bitrate)、
+/// [`vbr_mode`](EncoderBuilder::vbr_mode) 等设置会覆盖预设中的对应值，而在预设之前的设置会被预设覆盖。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// VBR 质量 0（最高，约 245 kbps）
+    V0,
+    /// VBR 质量 1（约 225 kbps）
+    V1,
+    /// VBR 质量 2（约 190 kbps）
+    V2,
+    /// VBR 质量 3（约 175 kbps）
+    V3,
+    /// VBR 质量 4（约 165 kbps）
+    V4,
+    /// VBR 质量 5（约 130 kbps）
+    V5,
+    /// VBR 质量 6（约 115 kbps）
+    V6,
+    /// VBR 质量 7（约 100 kbps）
+    V7,
+    /// VBR 质量 8（约 85 kbps）
+    V8,
+    /// VBR 质量 9（最低，约 65 kbps）
+    V9,
+    /// `--preset standard`，等同于 [`V2`](Self::V2)
+    Standard,
+    /// `--preset extreme`，等同于 [`V0`](Self::V0)
+    Extreme,
+    /// `--preset insane`，320 kbps CBR
+    Insane,
+    /// 指定平均比特率（8-320 kbps）的 ABR
+    Abr(i32),
+}
+
+impl Preset {
+    /// 传给 `lame_set_preset` 的值（`preset_mode`）
+    fn lame_value(self) -> i32 {
+        match self {
+            Preset::V0 => 500,
+            Preset::V1 => 490,
+            Preset::V2 => 480,
+            Preset::V3 => 470,
+            Preset::V4 => 460,
+            Preset::V5 => 450,
+            Preset::V6 => 440,
+            Preset::V7 => 430,
+            Preset::V8 => 420,
+            Preset::V9 => 410,
+            Preset::Standard => 1001,
+            Preset::Extreme => 1002,
+            Preset::Insane => 1003,
+            // ABR 预设直接使用目标比特率
+            Preset::Abr(kbps) => kbps,
+        }
+    }
+
+    /// 是否为 `-V n` 预设
+    fn is_vbr_quality(self) -> bool {
+        matches!(
+            self,
+            Preset::V0
+                | Preset::V1
+                | Preset::V2
+                | Preset::V3
+                | Preset::V4
+                | Preset::V5
+                | Preset::V6
+                | Preset::V7
+                | Preset::V8
+                | Preset::V9
+        )
+    }
+}
+
+/// MPEG 支持的输出采样率（Hz）
+const MPEG_SAMPLE_RATES: [i32; 9] = [8000, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000];
+
+/// 把值列表格式化为 `a, b, c`，用于错误信息
+fn list_values<T: std::fmt::Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `lame_set_*` / `lame_get_*` 整数参数函数
+type IntSetter = unsafe extern "C" fn(*mut ffi::lame_global_flags, i32) -> i32;
+type IntGetter = unsafe extern "C" fn(*const ffi::lame_global_flags) -> i32;
+
+/// 暂存缓冲区归还时保留的最大容量（元素个数）
+const SCRATCH_RETAIN_LEN: usize = 1152 * 2 * 64;
+
+/// 把暂存缓冲区放回编码器，超出 [`SCRATCH_RETAIN_LEN`] 的容量会被释放，
+/// 一次超大的调用不会让长期运行的编码器一直持有对应的内存
+fn recycle_scratch<T>(slot: &mut Vec<T>, mut scratch: Vec<T>) {
+    if scratch.capacity() > SCRATCH_RETAIN_LEN {
+        scratch.clear();
+        scratch.shrink_to(SCRATCH_RETAIN_LEN);
+    }
+    *slot = scratch;
+}
+
+/// 编码每个声道 `num_samples` 个样本时 MP3 输出的最坏情况大小（字节）
+///
+/// 使用 LAME 文档推荐的公式 `1.25 * num_samples + 7200`（向上取整），
+/// 按这个大小分配的缓冲区不会因为空间不足而编码失败。
+#[inline(always)]
+pub const fn max_mp3_buffer_size(num_samples: usize) -> usize {
+    (num_samples * 5 + 3) / 4 + 7200
+}
+
+/// [`LameEncoder::finalize`] 的结果：流末尾的数据和回写用的信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinalizedStream {
+    /// 刷新编码器得到的最后几帧（以及 ID3v1 标签），追加到输出末尾
+    pub trailing: Vec<u8>,
+    /// 完整的 LAME 信息帧（Xing/Info），用来覆盖输出的第一帧
+    ///
+    /// 没有写入信息帧，或链接的 LAME 不提供 `lame_get_lametag_frame` 时为 `None`。
+    pub lametag: Option<Vec<u8>>,
+    /// 编码器延迟（每个声道的样本数），见 [`LameEncoder::encoder_delay`]
+    pub encoder_delay: i32,
+    /// 末尾填充（每个声道的样本数），见 [`LameEncoder::end_padding`]
+    pub encoder_padding: i32,
+}
+
 /// LAME MP3 编码器
 ///
 /// 这是对 LAME C API 的安全封装，使用 RAII 模式自动管理资源。
@@ -37,7 +258,7 @@ pub enum VbrMode {
 /// # 示例
 ///
 /// ```no_run
-/// use lame_sys::{LameEncoder, Quality};
+/// use lame_sys::prelude::*;
 ///
 /// let mut encoder = LameEncoder::builder()?
 ///     .sample_rate(44100)?
@@ -52,17 +273,52 @@ pub enum VbrMode {
 /// let mut mp3_buffer = vec![0u8; 8192];
 ///
 /// let bytes_written = encoder.encode(&pcm_left, &pcm_right, &mut mp3_buffer)?;
-/// # Ok::<(), lame_sys::LameError>(())
+/// # Ok::<(), LameError>(())
 /// ```
 pub struct LameEncoder {
     /// 指向 LAME global flags 的非空指针（优化友好）
     gfp: NonNull<ffi::lame_global_flags>,
+    /// 构建时 LAME 接受的设置，[`reset`](Self::reset) 时重放；`from_raw` 取回的句柄为 `None`
+    settings: Option<Vec<Setting>>,
+    /// 8-bit 输入转换为 16-bit 的暂存缓冲区，重复调用时复用
+    scratch: Vec<i16>,
+    /// 24-bit 输入解包为 32-bit 的暂存缓冲区
+    scratch_i32: Vec<i32>,
+    /// 流式编码时复用的 MP3 输出缓冲区
+    scratch_mp3: Vec<u8>,
+    /// 单次 LAME 调用的样本数上限（每个声道），`None` 表示不限制
+    max_samples_per_call: Option<usize>,
+    /// 分段调用之间检查的取消令牌
+    cancel_token: Option<CancelToken>,
+    /// 分段调用之间的进度回调
+    progress_hook: Option<ProgressHook>,
+    /// 最近一次 flush 是否已经算出 ReplayGain 结果
+    gain_analyzed: bool,
+    /// LAME 最近一次输出的错误消息
+    last_error: Option<String>,
+    /// 是否已经开始编码、是否已经调用过 flush
+    state: EncoderState,
+}
+
+/// [`LameEncoder`] 所处的阶段
+///
+/// LAME 没有规定 `lame_encode_flush` 之后再编码或再次刷新的行为，实际会输出错误的帧，
+/// 所以由封装层拒绝这些调用。ID3 标签同样只能在当前段开始编码之前设置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EncoderState {
+    /// 当前段还没有编码过，可以设置 ID3 标签；新建、`reset` 和 `flush_nogap` 之后处于这一阶段
+    Ready,
+    /// 当前段已经开始编码
+    Encoding,
+    /// 已经调用过 `flush`，只能读取统计信息和信息帧
+    Flushed,
 }
 
 impl std::fmt::Debug for LameEncoder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("LameEncoder")
             .field("gfp", &self.gfp.as_ptr())
+            .field("max_samples_per_call", &self.max_samples_per_call)
             .finish()
     }
 }
@@ -75,6 +331,15 @@ impl LameEncoder {
         EncoderBuilder::new()
     }
 
+    /// 计算编码 `samples_per_channel` 个样本所需的最坏情况输出缓冲区大小
+    ///
+    /// 与 [`max_mp3_buffer_size`] 相同。交错输入应传入每个声道的样本数，而不是切片总长度。
+    /// `samples_per_channel` 为 0 时返回 7200，即 [`flush`](Self::flush) 所需的大小。
+    #[inline(always)]
+    pub const fn mp3_buffer_size(samples_per_channel: usize) -> usize {
+        max_mp3_buffer_size(samples_per_channel)
+    }
+
     /// 编码立体声 PCM 数据到 MP3
     ///
     /// # 参数
@@ -93,29 +358,168 @@ impl LameEncoder {
         pcm_right: &[i16],
         mp3_buffer: &mut [u8],
     ) -> Result<usize> {
-        if pcm_left.len() != pcm_right.len() {
-            return Err(LameError::InvalidInput(
-                "Left and right channel lengths must match".to_string(),
-            ));
+        self.encode_pcm(DualPcm::new(pcm_left, pcm_right), mp3_buffer)
+    }
+
+    /// 编码任意布局和样本类型的 PCM 数据到 MP3
+    ///
+    /// 根据输入类型分派到对应的 `lame_encode_buffer_*` 调用，参见 [`crate::pcm`]。
+    /// 所有 `encode*` 方法最终都经过这里：每个声道超过
+    /// [`MAX_SAMPLES_PER_CALL`](crate::pcm::MAX_SAMPLES_PER_CALL) 个样本时会拆分为多次调用，
+    /// 输出缓冲区放不下时返回 [`LameError::BufferTooSmall`]。
+    /// 设置了 [`set_max_samples_per_call`](Self::set_max_samples_per_call) 时按更小的上限拆分，
+    /// 并在拆分的调用之间执行进度回调和取消检查，参见 [`crate::watchdog`]。
+    ///
+    /// # 参数
+    ///
+    /// * `input` - [`MonoPcm`](crate::pcm::MonoPcm)、[`DualPcm`](crate::pcm::DualPcm)
+    ///   或 [`InterleavedPcm`](crate::pcm::InterleavedPcm)
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    #[inline(always)]
+    pub fn encode_pcm<P: PcmInput>(&mut self, input: P, mp3_buffer: &mut [u8]) -> Result<usize> {
+        self.ensure_encoding("encode called after flush")?;
+        let cancel_token = self.cancel_token.as_ref();
+        let progress_hook = &mut self.progress_hook;
+        let mut between_chunks = |samples_done, samples_total| {
+            if let Some(hook) = progress_hook {
+                hook(CallProgress {
+                    samples_done,
+                    samples_total,
+                });
+            }
+            !cancel_token.is_some_and(CancelToken::is_cancelled)
+        };
+
+        let mut control = ChunkControl {
+            max_samples: self.max_samples_per_call.unwrap_or(MAX_SAMPLES_PER_CALL),
+            between_chunks: &mut between_chunks,
+            called_lame: false,
+        };
+        let gfp = self.gfp.as_ptr();
+        let (result, lame_output) = crate::report::collect_errors(|| {
+            // SAFETY: gfp 在编码器存活期间有效，`&mut self` 保证调用期间没有其他访问
+            unsafe { input.encode_raw(gfp, mp3_buffer, &mut control) }
+        });
+        // 校验失败的输入没有交给 LAME，编码器仍处于原来的状态
+        if control.called_lame {
+            self.state = EncoderState::Encoding;
         }
+        self.record_lame_output(result, lame_output)
+    }
 
-        let num_samples = pcm_left.len();
+    /// 当前段是否已经开始编码
+    ///
+    /// 开始编码之后 LAME 不会再写入 ID3v2 标签，[`Id3Tag::apply`](crate::Id3Tag::apply)
+    /// 返回 [`LameError::InvalidState`]。[`flush_nogap`](Self::flush_nogap) 开始新的一段，
+    /// [`reset`](Self::reset) 重新开始，之后都可以再设置标签。
+    pub fn has_started(&self) -> bool {
+        self.state != EncoderState::Ready
+    }
 
-        unsafe {
-            let result = ffi::lame_encode_buffer(
-                self.gfp.as_ptr(),
-                pcm_left.as_ptr(),
-                pcm_right.as_ptr(),
-                num_samples as i32,
-                mp3_buffer.as_mut_ptr(),
-                mp3_buffer.len() as i32,
-            );
-
-            if result < 0 {
-                Err(LameError::EncodingFailed(result))
-            } else {
-                Ok(result as usize)
+    /// 已经 flush 时返回 [`LameError::InvalidState`]，`misuse` 说明被拒绝的调用
+    fn ensure_encoding(&self, misuse: &'static str) -> Result<()> {
+        match self.state {
+            EncoderState::Ready | EncoderState::Encoding => Ok(()),
+            EncoderState::Flushed => Err(LameError::InvalidState(misuse)),
+        }
+    }
+
+    /// 记录调用期间 LAME 输出的错误消息，编码失败时用它作为错误信息
+    ///
+    /// 调用成功时 LAME 输出的消息（例如警告）照常写到 stderr 或 `log`。
+    fn record_lame_output<T>(
+        &mut self,
+        result: Result<T>,
+        lame_output: Option<String>,
+    ) -> Result<T> {
+        let Some(text) = lame_output else {
+            return result;
+        };
+        let result = match result {
+            Err(LameError::EncodingFailed { code, .. }) => Err(LameError::EncodingFailed {
+                code,
+                message: text.clone(),
+            }),
+            result => {
+                crate::report::emit_error(&text);
+                result
             }
+        };
+        self.last_error = Some(text);
+        result
+    }
+
+    /// LAME 最近一次输出的错误消息
+    ///
+    /// LAME 在编码出错（以及少数警告）时通过错误回调输出文字说明，
+    /// 同样的内容也会放进 [`LameError::EncodingFailed`] 的 `message` 中。没有输出过时返回 `None`。
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// 限制单次 LAME 调用处理的样本数（每个声道）
+    ///
+    /// 超过上限的输入会拆成多次调用，调用之间检查取消令牌并执行进度回调。
+    /// `None` 表示不限制（默认）。上限超过
+    /// [`MAX_SAMPLES_PER_CALL`](crate::pcm::MAX_SAMPLES_PER_CALL) 时按后者处理。
+    ///
+    /// # 错误
+    ///
+    /// `Some(0)` 返回 [`LameError::InvalidParameter`]。
+    pub fn set_max_samples_per_call(&mut self, max_samples: Option<usize>) -> Result<()> {
+        if max_samples == Some(0) {
+            return Err(LameError::InvalidParameter(
+                "max_samples_per_call must be greater than 0".to_string(),
+            ));
+        }
+
+        self.max_samples_per_call = max_samples;
+        Ok(())
+    }
+
+    /// 当前的单次调用样本数上限，`None` 表示不限制
+    pub fn max_samples_per_call(&self) -> Option<usize> {
+        self.max_samples_per_call
+    }
+
+    /// 设置取消令牌
+    ///
+    /// 令牌被取消后，正在进行的 `encode*` 调用会在下一次 LAME 调用之前返回
+    /// [`LameError::Cancelled`]。未拆分的调用不会被中断。
+    pub fn set_cancel_token(&mut self, token: Option<CancelToken>) {
+        self.cancel_token = token;
+    }
+
+    /// 设置进度回调，在每两次拆分的 LAME 调用之间执行
+    pub fn set_progress_hook<F>(&mut self, hook: Option<F>)
+    where
+        F: FnMut(CallProgress) + Send + 'static,
+    {
+        self.progress_hook = hook.map(|hook| Box::new(hook) as ProgressHook);
+    }
+
+    /// 预先分配编码时使用的暂存缓冲区
+    ///
+    /// 8-bit、24-bit、按步长和迭代器输入要先转换到编码器内部的暂存缓冲区，默认在第一次
+    /// 调用时分配，之后复用。实时场景可以在构建后调用此方法，按单次调用最多
+    /// `samples_per_channel` 个样本（每个声道）预留容量，第一次调用也不再分配内存。
+    /// 预留的容量最多为 `1152 * 2 * 64` 个样本，更大的调用仍会临时分配。
+    ///
+    /// 其余 `encode_*` 方法（返回 `Vec` 的除外）直接把输入交给 LAME，不分配内存。
+    pub fn reserve_scratch(&mut self, samples_per_channel: usize) {
+        let len = (samples_per_channel.max(1152) * 2).min(SCRATCH_RETAIN_LEN);
+        self.scratch.clear();
+        self.scratch.reserve(len);
+        self.scratch_i32.clear();
+        self.scratch_i32.reserve(len);
+
+        let mp3_len = Self::mp3_buffer_size(1152);
+        if self.scratch_mp3.len() < mp3_len {
+            self.scratch_mp3.resize(mp3_len, 0);
         }
     }
 
@@ -129,230 +533,2639 @@ impl LameEncoder {
     /// # 返回
     ///
     /// 返回写入 `mp3_buffer` 的字节数
+    ///
+    /// # 错误
+    ///
+    /// 编码器是单声道，或 `pcm_interleaved` 长度为奇数时返回 [`LameError::InvalidInput`]
     #[inline(always)]
     pub fn encode_interleaved(
         &mut self,
         pcm_interleaved: &[i16],
         mp3_buffer: &mut [u8],
     ) -> Result<usize> {
-        let num_samples = pcm_interleaved.len() / 2;
-
-        unsafe {
-            let result = ffi::lame_encode_buffer_interleaved(
-                self.gfp.as_ptr(),
-                pcm_interleaved.as_ptr() as *mut i16,
-                num_samples as i32,
-                mp3_buffer.as_mut_ptr(),
-                mp3_buffer.len() as i32,
-            );
-
-            if result < 0 {
-                Err(LameError::EncodingFailed(result))
-            } else {
-                Ok(result as usize)
-            }
-        }
+        self.encode_pcm(InterleavedPcm(pcm_interleaved), mp3_buffer)
     }
 
-    /// 编码单声道 PCM 数据到 MP3
+    /// 编码立体声帧数组到 MP3
+    ///
+    /// `[i16; 2]` 的内存布局与交错立体声数据相同，因此直接复用交错编码调用，不会复制数据。
     ///
     /// # 参数
     ///
-    /// * `pcm` - 单声道 PCM 样本（16-bit）
+    /// * `frames` - 立体声帧（每个元素为 `[左, 右]`）
     /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
     ///
     /// # 返回
     ///
     /// 返回写入 `mp3_buffer` 的字节数
+    #[inline(always)]
+    pub fn encode_frames(&mut self, frames: &[[i16; 2]], mp3_buffer: &mut [u8]) -> Result<usize> {
+        // SAFETY: [i16; 2] 与两个连续的 i16 布局相同，切片长度不会越界
+        let pcm =
+            unsafe { std::slice::from_raw_parts(frames.as_ptr() as *const i16, frames.len() * 2) };
+        self.encode_pcm(InterleavedPcm(pcm), mp3_buffer)
+    }
+
+    /// 编码按步长存放的立体声 PCM 数据到 MP3
     ///
-    /// # 示例
+    /// 第 `i` 帧的左右声道样本分别位于 `data[left_offset + i * stride]` 和
+    /// `data[right_offset + i * stride]`，可以直接描述交错、平面或带额外声道的缓冲区。
+    /// 样本每次收集一个 LAME 帧（1152 个）到编码器内部的暂存缓冲区后再编码，
+    /// 不会复制整段输入。
     ///
-    /// ```no_run
-    /// use lame_sys::LameEncoder;
+    /// # 参数
     ///
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let mut encoder = LameEncoder::builder()?
-    ///     .sample_rate(44100)?
-    ///     .channels(1)?  // 单声道
-    ///     .bitrate(128)?
-    ///     .build()?;
+    /// * `data` - 包含两个声道样本的缓冲区
+    /// * `left_offset` - 第一帧左声道样本的下标
+    /// * `right_offset` - 第一帧右声道样本的下标
+    /// * `stride` - 相邻两帧之间的下标间隔
+    /// * `frames` - 帧数（每个声道的样本数）
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
     ///
-    /// let pcm = vec![0i16; 1152];
-    /// let mut mp3_buffer = vec![0u8; 8192];
+    /// # 返回
     ///
-    /// let bytes_written = encoder.encode_mono(&pcm, &mut mp3_buffer)?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    #[inline(always)]
-    pub fn encode_mono(&mut self, pcm: &[i16], mp3_buffer: &mut [u8]) -> Result<usize> {
-        unsafe {
-            let result = ffi::lame_encode_buffer(
-                self.gfp.as_ptr(),
-                pcm.as_ptr(),
-                ptr::null(), // 单声道传递 null 指针
-                pcm.len() as i32,
-                mp3_buffer.as_mut_ptr(),
-                mp3_buffer.len() as i32,
-            );
-
-            if result < 0 {
-                Err(LameError::EncodingFailed(result))
-            } else {
-                Ok(result as usize)
+    /// 返回写入 `mp3_buffer` 的字节数
+    ///
+    /// # 错误
+    ///
+    /// `stride` 为 0 或最后一帧的样本越出 `data` 时返回 [`LameError::InvalidInput`]，
+    /// 此时不会调用 LAME
+    pub fn encode_strided(
+        &mut self,
+        data: &[i16],
+        left_offset: usize,
+        right_offset: usize,
+        stride: usize,
+        frames: usize,
+        mp3_buffer: &mut [u8],
+    ) -> Result<usize> {
+        if stride == 0 {
+            return Err(LameError::InvalidInput(
+                "Strided input stride must be at least 1".to_string(),
+            ));
+        }
+        if frames > 0 {
+            let last = (frames - 1)
+                .checked_mul(stride)
+                .and_then(|base| base.checked_add(left_offset.max(right_offset)));
+            if last.is_none_or(|last| last >= data.len()) {
+                return Err(LameError::InvalidInput(format!(
+                    "Strided input out of bounds: {} frames with stride {} and offsets ({}, {}) \
+                     need more than the {} samples provided",
+                    frames,
+                    stride,
+                    left_offset,
+                    right_offset,
+                    data.len()
+                )));
             }
         }
+
+        const CHUNK: usize = 1152;
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.clear();
+        scratch.resize(CHUNK * 2, 0);
+
+        let mut written = 0;
+        let mut start = 0;
+        let result = loop {
+            let len = (frames - start).min(CHUNK);
+            let (left, right) = scratch.split_at_mut(CHUNK);
+            let frames_in_chunk = left[..len].iter_mut().zip(&mut right[..len]);
+            for (i, (l, r)) in frames_in_chunk.enumerate() {
+                let base = (start + i) * stride;
+                *l = data[base + left_offset];
+                *r = data[base + right_offset];
+            }
+
+            let pcm = DualPcm::new(&left[..len], &right[..len]);
+            match self.encode_pcm(pcm, &mut mp3_buffer[written..]) {
+                Ok(bytes) => written += bytes,
+                Err(LameError::BufferTooSmall { .. }) => {
+                    break Err(LameError::BufferTooSmall {
+                        required: written + Self::mp3_buffer_size(frames - start),
+                        provided: mp3_buffer.len(),
+                    })
+                }
+                Err(err) => break Err(err),
+            }
+
+            start += len;
+            if start >= frames {
+                break Ok(written);
+            }
+        };
+
+        recycle_scratch(&mut self.scratch, scratch);
+        result
     }
 
-    /// 刷新编码器缓冲区
+    /// 编码交错立体声浮点 PCM 数据到 MP3
     ///
-    /// 在编码完所有数据后调用此方法，获取最后的 MP3 帧。
+    /// 样本范围为 [-1.0, 1.0]。满量程为 ±32768 的样本使用
+    /// [`encode_interleaved_float_scaled`](Self::encode_interleaved_float_scaled)。
     ///
     /// # 参数
     ///
-    /// * `mp3_buffer` - 输出缓冲区
+    /// * `pcm` - 交错的立体声浮点样本（L, R, L, R, ...）
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
     ///
     /// # 返回
     ///
-    /// 返回写入的字节数
+    /// 返回写入 `mp3_buffer` 的字节数
     #[inline(always)]
-    pub fn flush(&mut self, mp3_buffer: &mut [u8]) -> Result<usize> {
-        unsafe {
-            let result = ffi::lame_encode_flush(
-                self.gfp.as_ptr(),
-                mp3_buffer.as_mut_ptr(),
-                mp3_buffer.len() as i32,
-            );
-
-            if result < 0 {
-                Err(LameError::EncodingFailed(result))
-            } else {
-                Ok(result as usize)
-            }
-        }
+    pub fn encode_interleaved_float(
+        &mut self,
+        pcm: &[f32],
+        mp3_buffer: &mut [u8],
+    ) -> Result<usize> {
+        self.encode_pcm(InterleavedPcm(pcm), mp3_buffer)
     }
 
-    /// 获取原始的 LAME global flags 指针（用于高级操作）
+    /// 编码立体声浮点 PCM 数据到 MP3，显式指定满量程约定
     ///
-    /// # 安全性
+    /// [`FloatScale::Normalized`] 调用 `lame_encode_buffer_ieee_float`（满量程 ±1.0），
+    /// [`FloatScale::ShortRange`] 调用 `lame_encode_buffer_float`（满量程 ±32768）。
     ///
-    /// 调用者必须确保不会释放返回的指针，也不能在编码器销毁后使用。
-    pub unsafe fn as_ptr(&self) -> *mut ffi::lame_global_flags {
-        self.gfp.as_ptr()
-    }
-}
-
-impl Drop for LameEncoder {
-    fn drop(&mut self) {
-        unsafe {
-            ffi::lame_close(self.gfp.as_ptr());
-        }
-    }
-}
-
-// LameEncoder 不是 Send/Sync，因为 LAME C 库不是线程安全的
-// 如果需要多线程编码，应该为每个线程创建独立的编码器
-
-/// 编码器构建器
-///
-/// 使用 Builder 模式配置并创建 LAME 编码器。
-///
-/// 注意：Builder 在创建时就初始化 LAME C 结构体，每个配置方法立即调用底层 FFI。
-/// 这种设计消除了额外的内存分配和分支判断，提供更好的性能。
-pub struct EncoderBuilder {
-    /// 指向 LAME global flags 的非空指针
-    inner: NonNull<ffi::lame_global_flags>,
-}
-
-impl EncoderBuilder {
-    /// 创建新的构建器
+    /// # 参数
     ///
-    /// 立即初始化 LAME C 结构体。如果初始化失败，返回错误。
-    pub fn new() -> Result<Self> {
-        unsafe {
-            let gfp = ffi::lame_init();
-            if gfp.is_null() {
-                return Err(LameError::InitializationFailed);
+    /// * `pcm_left` - 左声道浮点样本
+    /// * `pcm_right` - 右声道浮点样本
+    /// * `scale` - 样本的满量程约定
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    pub fn encode_float(
+        &mut self,
+        pcm_left: &[f32],
+        pcm_right: &[f32],
+        scale: FloatScale,
+        mp3_buffer: &mut [u8],
+    ) -> Result<usize> {
+        match scale {
+            FloatScale::Normalized => {
+                self.encode_pcm(DualPcm::new(pcm_left, pcm_right), mp3_buffer)
             }
-            Ok(Self {
-                inner: NonNull::new_unchecked(gfp),
-            })
+            FloatScale::ShortRange => self.encode_pcm(
+                DualPcm::new(short_range(pcm_left), short_range(pcm_right)),
+                mp3_buffer,
+            ),
         }
     }
 
-    /// 获取内部指针（私有辅助方法）
-    #[inline(always)]
-    fn ptr(&self) -> *mut ffi::lame_global_flags {
-        self.inner.as_ptr()
-    }
-
-    /// 设置采样率（Hz）
+    /// 编码交错立体声浮点 PCM 数据到 MP3，显式指定满量程约定
     ///
-    /// 常见值：8000, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000
-    #[inline(always)]
-    pub fn sample_rate(self, rate: i32) -> Result<Self> {
-        unsafe {
-            if ffi::lame_set_in_samplerate(self.ptr(), rate) < 0 {
-                return Err(LameError::InvalidParameter("sample_rate".to_string()));
-            }
-            ffi::lame_set_out_samplerate(self.ptr(), rate);
+    /// 参见 [`encode_float`](Self::encode_float)。LAME 没有交错的 `lame_encode_buffer_float`，
+    /// [`FloatScale::ShortRange`] 的输入会先拆分为左右声道。
+    ///
+    /// # 错误
+    ///
+    /// `pcm` 长度为奇数时返回 [`LameError::InvalidInput`]
+    pub fn encode_interleaved_float_scaled(
+        &mut self,
+        pcm: &[f32],
+        scale: FloatScale,
+        mp3_buffer: &mut [u8],
+    ) -> Result<usize> {
+        match scale {
+            FloatScale::Normalized => self.encode_pcm(InterleavedPcm(pcm), mp3_buffer),
+            FloatScale::ShortRange => self.encode_pcm(InterleavedPcm(short_range(pcm)), mp3_buffer),
         }
-        Ok(self)
     }
 
-    /// 设置声道数（1 = 单声道, 2 = 立体声）
+    /// 编码立体声双精度浮点 PCM 数据到 MP3
+    ///
+    /// 样本范围为 [-1.0, 1.0]。
+    ///
+    /// # 参数
+    ///
+    /// * `pcm_left` - 左声道浮点样本
+    /// * `pcm_right` - 右声道浮点样本
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
     #[inline(always)]
-    pub fn channels(self, channels: i32) -> Result<Self> {
-        unsafe {
-            if ffi::lame_set_num_channels(self.ptr(), channels) < 0 {
-                return Err(LameError::InvalidParameter("channels".to_string()));
-            }
-        }
-        Ok(self)
+    pub fn encode_double(
+        &mut self,
+        pcm_left: &[f64],
+        pcm_right: &[f64],
+        mp3_buffer: &mut [u8],
+    ) -> Result<usize> {
+        self.encode_pcm(DualPcm::new(pcm_left, pcm_right), mp3_buffer)
     }
 
-    /// 设置比特率（kbps）
+    /// 编码单声道双精度浮点 PCM 数据到 MP3
+    ///
+    /// 样本范围为 [-1.0, 1.0]。满量程为 ±32768 的样本使用
+    /// [`encode_mono_float_scaled`](Self::encode_mono_float_scaled)。
+    ///
+    /// # 参数
+    ///
+    /// * `pcm` - 单声道浮点样本
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
     ///
-    /// 常见值：32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320
+    /// 返回写入 `mp3_buffer` 的字节数
     #[inline(always)]
-    pub fn bitrate(self, bitrate: i32) -> Result<Self> {
-        unsafe {
-            if ffi::lame_set_brate(self.ptr(), bitrate) < 0 {
-                return Err(LameError::InvalidParameter("bitrate".to_string()));
-            }
-        }
-        Ok(self)
+    pub fn encode_double_mono(&mut self, pcm: &[f64], mp3_buffer: &mut [u8]) -> Result<usize> {
+        self.encode_pcm(MonoPcm(pcm), mp3_buffer)
+    }
+
+    /// 编码交错立体声双精度浮点 PCM 数据到 MP3
+    ///
+    /// 样本范围为 [-1.0, 1.0]。
+    ///
+    /// # 参数
+    ///
+    /// * `pcm` - 交错的立体声浮点样本（L, R, L, R, ...）
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    #[inline(always)]
+    pub fn encode_interleaved_double(
+        &mut self,
+        pcm: &[f64],
+        mp3_buffer: &mut [u8],
+    ) -> Result<usize> {
+        self.encode_pcm(InterleavedPcm(pcm), mp3_buffer)
+    }
+
+    /// 编码立体声 32-bit 整数 PCM 数据到 MP3
+    ///
+    /// 与 16-bit 接口不同，LAME 要求样本覆盖完整的 `i32` 范围（±2^31），
+    /// 即 16-bit 样本需要左移 16 位才能得到相同的响度。
+    ///
+    /// # 参数
+    ///
+    /// * `pcm_left` - 左声道 PCM 样本（32-bit 满量程）
+    /// * `pcm_right` - 右声道 PCM 样本（32-bit 满量程）
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    #[inline(always)]
+    pub fn encode_int(
+        &mut self,
+        pcm_left: &[i32],
+        pcm_right: &[i32],
+        mp3_buffer: &mut [u8],
+    ) -> Result<usize> {
+        self.encode_pcm(DualPcm::new(pcm_left, pcm_right), mp3_buffer)
+    }
+
+    /// 编码单声道 32-bit 整数 PCM 数据到 MP3
+    ///
+    /// 样本缩放约定与 [`encode_int`](Self::encode_int) 相同（±2^31 满量程）。
+    ///
+    /// # 参数
+    ///
+    /// * `pcm` - 单声道 PCM 样本（32-bit 满量程）
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    #[inline(always)]
+    pub fn encode_int_mono(&mut self, pcm: &[i32], mp3_buffer: &mut [u8]) -> Result<usize> {
+        self.encode_pcm(MonoPcm(pcm), mp3_buffer)
+    }
+
+    /// 编码交错立体声 32-bit 整数 PCM 数据到 MP3
+    ///
+    /// 样本缩放约定与 [`encode_int`](Self::encode_int) 相同（±2^31 满量程）。
+    ///
+    /// # 参数
+    ///
+    /// * `pcm` - 交错的立体声 PCM 样本（L, R, L, R, ...）
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    #[inline(always)]
+    pub fn encode_interleaved_int(&mut self, pcm: &[i32], mp3_buffer: &mut [u8]) -> Result<usize> {
+        self.encode_pcm(InterleavedPcm(pcm), mp3_buffer)
+    }
+
+    /// 编码单声道 PCM 数据到 MP3
+    ///
+    /// # 参数
+    ///
+    /// * `pcm` - 单声道 PCM 样本（16-bit）
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use lame_sys::prelude::*;
+    ///
+    /// # fn main() -> Result<()> {
+    /// let mut encoder = LameEncoder::builder()?
+    ///     .sample_rate(44100)?
+    ///     .channels(1)?  // 单声道
+    ///     .bitrate(128)?
+    ///     .build()?;
+    ///
+    /// let pcm = vec![0i16; 1152];
+    /// let mut mp3_buffer = vec![0u8; 8192];
+    ///
+    /// let bytes_written = encoder.encode_mono(&pcm, &mut mp3_buffer)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn encode_mono(&mut self, pcm: &[i16], mp3_buffer: &mut [u8]) -> Result<usize> {
+        self.encode_pcm(MonoPcm(pcm), mp3_buffer)
+    }
+
+    /// 编码单声道浮点 PCM 数据到 MP3
+    ///
+    /// 样本范围为 [-1.0, 1.0]。
+    ///
+    /// # 参数
+    ///
+    /// * `pcm` - 单声道浮点样本
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    #[inline(always)]
+    pub fn encode_mono_float(&mut self, pcm: &[f32], mp3_buffer: &mut [u8]) -> Result<usize> {
+        self.encode_pcm(MonoPcm(pcm), mp3_buffer)
+    }
+
+    /// 编码单声道浮点 PCM 数据到 MP3，显式指定满量程约定
+    ///
+    /// 参见 [`encode_float`](Self::encode_float)。
+    pub fn encode_mono_float_scaled(
+        &mut self,
+        pcm: &[f32],
+        scale: FloatScale,
+        mp3_buffer: &mut [u8],
+    ) -> Result<usize> {
+        match scale {
+            FloatScale::Normalized => self.encode_pcm(MonoPcm(pcm), mp3_buffer),
+            FloatScale::ShortRange => self.encode_pcm(MonoPcm(short_range(pcm)), mp3_buffer),
+        }
+    }
+
+    /// 编码单声道 8-bit 无符号 PCM 数据到 MP3
+    ///
+    /// 样本以 128 为零点，按 `(s - 128) << 8` 转换为 16-bit 后编码。
+    /// 转换使用编码器内部的暂存缓冲区，重复调用不会重新分配。
+    ///
+    /// # 参数
+    ///
+    /// * `pcm` - 单声道 8-bit 无符号样本
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    pub fn encode_mono_u8(&mut self, pcm: &[u8], mp3_buffer: &mut [u8]) -> Result<usize> {
+        let scratch = self.fill_scratch_u8(pcm);
+        let result = self.encode_pcm(MonoPcm(&scratch), mp3_buffer);
+        recycle_scratch(&mut self.scratch, scratch);
+        result
+    }
+
+    /// 编码交错立体声 8-bit 无符号 PCM 数据到 MP3
+    ///
+    /// 转换规则同 [`encode_mono_u8`](Self::encode_mono_u8)。
+    ///
+    /// # 参数
+    ///
+    /// * `pcm` - 交错的立体声 8-bit 无符号样本（L, R, L, R, ...）
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    ///
+    /// # 错误
+    ///
+    /// `pcm` 长度为奇数时返回 [`LameError::InvalidInput`]
+    pub fn encode_interleaved_u8(&mut self, pcm: &[u8], mp3_buffer: &mut [u8]) -> Result<usize> {
+        let scratch = self.fill_scratch_u8(pcm);
+        let result = self.encode_pcm(InterleavedPcm(&scratch), mp3_buffer);
+        recycle_scratch(&mut self.scratch, scratch);
+        result
+    }
+
+    /// 编码打包的 24-bit PCM 数据到 MP3
+    ///
+    /// 每个样本为 3 字节小端序（WAV 常见格式），解包为满量程 32-bit 整数后通过
+    /// `lame_encode_buffer_interleaved_int` 编码（单声道使用 `lame_encode_buffer_int`）。
+    ///
+    /// # 参数
+    ///
+    /// * `bytes` - 打包的 24-bit 样本，多声道时交错排列
+    /// * `channels` - 声道数，必须与编码器配置一致
+    /// * `mp3_buffer` - 输出 MP3 数据的缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `mp3_buffer` 的字节数
+    ///
+    /// # 错误
+    ///
+    /// `channels` 与编码器不一致，或 `bytes.len()` 不是 `3 * channels` 的整数倍时返回
+    /// [`LameError::InvalidInput`]
+    pub fn encode_interleaved_i24(
+        &mut self,
+        bytes: &[u8],
+        channels: usize,
+        mp3_buffer: &mut [u8],
+    ) -> Result<usize> {
+        let encoder_channels = self.get(ffi::lame_get_num_channels) as usize;
+        if channels != encoder_channels {
+            return Err(LameError::InvalidInput(format!(
+                "24-bit input has {} channels but the encoder expects {}",
+                channels, encoder_channels
+            )));
+        }
+        if bytes.len() % (3 * channels) != 0 {
+            return Err(LameError::InvalidInput(format!(
+                "24-bit input length must be a multiple of {}, got {}",
+                3 * channels,
+                bytes.len()
+            )));
+        }
+
+        let mut scratch = std::mem::take(&mut self.scratch_i32);
+        scratch.clear();
+        // 放在高 24 位，得到满量程 ±2^31 的样本
+        scratch.extend(
+            bytes
+                .chunks_exact(3)
+                .map(|b| i32::from_le_bytes([0, b[0], b[1], b[2]])),
+        );
+
+        let result = if channels == 1 {
+            self.encode_pcm(MonoPcm(&scratch), mp3_buffer)
+        } else {
+            self.encode_pcm(InterleavedPcm(&scratch), mp3_buffer)
+        };
+        recycle_scratch(&mut self.scratch_i32, scratch);
+        result
+    }
+
+    /// 编码立体声 PCM 数据，返回只包含输出字节的 `Vec`
+    ///
+    /// 输出缓冲区按 [`max_mp3_buffer_size`] 分配，编码后截断到实际写入的长度。
+    pub fn encode_to_vec(&mut self, left: &[i16], right: &[i16]) -> Result<Vec<u8>> {
+        let mut mp3_buffer = vec![0u8; max_mp3_buffer_size(left.len())];
+        let bytes_written = self.encode(left, right, &mut mp3_buffer)?;
+        mp3_buffer.truncate(bytes_written);
+        Ok(mp3_buffer)
+    }
+
+    /// 编码单声道 PCM 数据，返回只包含输出字节的 `Vec`
+    ///
+    /// 参见 [`encode_to_vec`](Self::encode_to_vec)。
+    pub fn encode_mono_to_vec(&mut self, pcm: &[i16]) -> Result<Vec<u8>> {
+        let mut mp3_buffer = vec![0u8; max_mp3_buffer_size(pcm.len())];
+        let bytes_written = self.encode_mono(pcm, &mut mp3_buffer)?;
+        mp3_buffer.truncate(bytes_written);
+        Ok(mp3_buffer)
+    }
+
+    /// 编码交错立体声 PCM 数据，返回只包含输出字节的 `Vec`
+    ///
+    /// 参见 [`encode_to_vec`](Self::encode_to_vec)。
+    ///
+    /// # 错误
+    ///
+    /// `pcm_interleaved` 长度为奇数时返回 [`LameError::InvalidInput`]
+    pub fn encode_interleaved_to_vec(&mut self, pcm_interleaved: &[i16]) -> Result<Vec<u8>> {
+        let mut mp3_buffer = vec![0u8; max_mp3_buffer_size(pcm_interleaved.len() / 2)];
+        let bytes_written = self.encode_interleaved(pcm_interleaved, &mut mp3_buffer)?;
+        mp3_buffer.truncate(bytes_written);
+        Ok(mp3_buffer)
+    }
+
+    /// 从迭代器拉取样本流式编码，MP3 数据写入 `sink`
+    ///
+    /// 每次取一帧（`1152 * channels` 个交错样本）编码，最后不足一帧的部分同样送入编码器。
+    /// 样本和输出都使用编码器内部的暂存缓冲区，内存占用与输入长度无关。
+    /// 不会调用 [`flush`](Self::flush)，结束时由调用方刷新。
+    ///
+    /// # 参数
+    ///
+    /// * `samples` - 样本迭代器，立体声时按 L, R, L, R, ... 交错
+    /// * `sink` - MP3 数据的下游写入器
+    ///
+    /// # 返回
+    ///
+    /// 返回写入 `sink` 的总字节数
+    ///
+    /// # 错误
+    ///
+    /// 立体声样本总数为奇数时返回 [`LameError::InvalidInput`]，此前的完整帧已经写入 `sink`
+    pub fn encode_from_iter<I: Iterator<Item = i16>>(
+        &mut self,
+        samples: I,
+        sink: &mut impl std::io::Write,
+    ) -> Result<u64> {
+        let channels = self.get(ffi::lame_get_num_channels).clamp(1, 2) as usize;
+        let chunk_len = 1152 * channels;
+
+        let mut pcm = std::mem::take(&mut self.scratch);
+        let mut mp3_buffer = std::mem::take(&mut self.scratch_mp3);
+        let required_size = Self::mp3_buffer_size(1152);
+        if mp3_buffer.len() < required_size {
+            mp3_buffer.resize(required_size, 0);
+        }
+
+        let mut samples = samples.fuse();
+        let mut total = 0u64;
+        let result = loop {
+            pcm.clear();
+            pcm.extend(samples.by_ref().take(chunk_len));
+            if pcm.is_empty() {
+                break Ok(total);
+            }
+
+            let encoded = if channels == 1 {
+                self.encode_pcm(MonoPcm(&pcm), &mut mp3_buffer)
+            } else {
+                self.encode_pcm(InterleavedPcm(&pcm), &mut mp3_buffer)
+            };
+            let written = match encoded {
+                Ok(written) => written,
+                Err(err) => break Err(err),
+            };
+            if let Err(err) = sink.write_all(&mp3_buffer[..written]) {
+                break Err(err.into());
+            }
+            total += written as u64;
+
+            if pcm.len() < chunk_len {
+                break Ok(total);
+            }
+        };
+
+        recycle_scratch(&mut self.scratch, pcm);
+        recycle_scratch(&mut self.scratch_mp3, mp3_buffer);
+        result
+    }
+
+    /// 把 8-bit 样本转换到暂存缓冲区，调用方用完后需放回 `self.scratch`
+    fn fill_scratch_u8(&mut self, pcm: &[u8]) -> Vec<i16> {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.clear();
+        scratch.extend(pcm.iter().map(|&s| (s as i16 - 128) << 8));
+        scratch
+    }
+
+    /// 刷新编码器缓冲区
+    ///
+    /// 在编码完所有数据后调用此方法，获取最后的 MP3 帧。之后再调用 `encode*`、`flush`
+    /// 或 [`flush_nogap`](Self::flush_nogap) 返回 [`LameError::InvalidState`]，
+    /// [`reset`](Self::reset) 后可以重新编码。不再需要编码器时可以用 [`finalize`](Self::finalize)，
+    /// 它同时取出信息帧，并消耗编码器。
+    ///
+    /// # 参数
+    ///
+    /// * `mp3_buffer` - 输出缓冲区
+    ///
+    /// # 返回
+    ///
+    /// 返回写入的字节数
+    #[inline(always)]
+    pub fn flush(&mut self, mp3_buffer: &mut [u8]) -> Result<usize> {
+        self.ensure_encoding("flush called twice")?;
+        let (result, lame_output) = crate::report::collect_errors(|| {
+            // SAFETY: 句柄有效且被独占借用；LAME 最多写入传入的缓冲区大小
+            unsafe {
+                ffi::lame_encode_flush(
+                    self.gfp.as_ptr(),
+                    mp3_buffer.as_mut_ptr(),
+                    mp3_buffer.len().min(i32::MAX as usize) as i32,
+                )
+            }
+        });
+
+        let result = if result < 0 {
+            Err(LameError::encoding_failed(result, None))
+        } else {
+            self.gain_analyzed = true;
+            self.state = EncoderState::Flushed;
+            Ok(result as usize)
+        };
+        self.record_lame_output(result, lame_output)
+    }
+
+    /// 结束编码：刷新编码器，取出回写文件开头所需的信息，并消耗编码器
+    ///
+    /// 相当于依次调用 [`flush`](Self::flush)、[`lametag_frame`](Self::lametag_frame)、
+    /// [`encoder_delay`](Self::encoder_delay) 和 [`end_padding`](Self::end_padding)。
+    /// 编码器随之释放，不会出现忘记刷新或刷新后继续编码的情况。
+    ///
+    /// ```no_run
+    /// # use lame_sys::LameEncoder;
+    /// # fn main() -> lame_sys::Result<()> {
+    /// # let pcm = vec![0i16; 44100 * 2];
+    /// # let mut encoder = LameEncoder::builder()?.sample_rate(44100)?.channels(2)?.build()?;
+    /// let mut mp3 = encoder.encode_interleaved_to_vec(&pcm)?;
+    /// let stream = encoder.finalize()?;
+    /// mp3.extend_from_slice(&stream.trailing);
+    /// if let Some(lametag) = &stream.lametag {
+    ///     // 信息帧是输出的第一帧，没有 ID3v2 标签时从偏移 0 开始
+    ///     mp3[..lametag.len()].copy_from_slice(lametag);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn finalize(mut self) -> Result<FinalizedStream> {
+        let mut trailing = vec![0u8; Self::mp3_buffer_size(0)];
+        let len = self.flush(&mut trailing)?;
+        trailing.truncate(len);
+
+        let lametag =
+            if self.writes_vbr_tag() && crate::features::Feature::LametagFrame.is_available() {
+                Some(self.lametag_frame()?).filter(|frame| !frame.is_empty())
+            } else {
+                None
+            };
+
+        Ok(FinalizedStream {
+            trailing,
+            lametag,
+            encoder_delay: self.get(ffi::lame_get_encoder_delay),
+            encoder_padding: self.get(ffi::lame_get_encoder_padding),
+        })
+    }
+
+    /// 无缝刷新：输出缓存的 MP3 数据，但保留尚未编码的 PCM
+    ///
+    /// 调用后已输出的数据构成完整的 MP3 流，之后继续编码的数据可以写到新文件，
+    /// 两个文件拼接后播放没有间隙。帧计数和比特率统计不会被重置。
+    /// 不会写入 ID3v1 标签。`mp3_buffer` 至少需要 [`mp3_buffer_size(0)`](Self::mp3_buffer_size) 字节。
+    ///
+    /// 把连续的音频（例如 DJ 混音）切成多个文件时的调用顺序：
+    ///
+    /// 1. 构建时用 [`nogap_total`](EncoderBuilder::nogap_total) 设置总段数；
+    /// 2. 编码第一段，调用 `flush_nogap`，把输出写入第一个文件；
+    /// 3. 用 [`set_nogap_current_index`](Self::set_nogap_current_index) 标记下一段，
+    ///    继续编码，输出写入新文件；中间的每一段都以 `flush_nogap` 结束；
+    /// 4. 最后一段用 [`flush`](Self::flush) 结束。
+    ///
+    /// ```no_run
+    /// # use lame_sys::LameEncoder;
+    /// # fn main() -> lame_sys::Result<()> {
+    /// # let tracks: Vec<Vec<i16>> = Vec::new();
+    /// let mut encoder = LameEncoder::builder()?
+    ///     .sample_rate(44100)?
+    ///     .channels(2)?
+    ///     .bitrate(192)?
+    ///     .nogap_total(tracks.len() as i32)?
+    ///     .build()?;
+    /// let mut tail = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+    ///
+    /// for (index, pcm) in tracks.iter().enumerate() {
+    ///     encoder.set_nogap_current_index(index as i32)?;
+    ///     let mut mp3 = encoder.encode_interleaved_to_vec(pcm)?;
+    ///     let len = if index + 1 < tracks.len() {
+    ///         encoder.flush_nogap(&mut tail)?
+    ///     } else {
+    ///         encoder.flush(&mut tail)?
+    ///     };
+    ///     mp3.extend_from_slice(&tail[..len]);
+    ///     std::fs::write(format!("track{}.mp3", index + 1), &mp3)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// LAME 信息帧（Xing/Info）只写在第一段开头；段数和序号只影响其中的无缝标志位。
+    ///
+    /// # 返回
+    ///
+    /// 返回写入的字节数，可能为 0
+    pub fn flush_nogap(&mut self, mp3_buffer: &mut [u8]) -> Result<usize> {
+        self.ensure_encoding("flush_nogap called after flush")?;
+        let (result, lame_output) = crate::report::collect_errors(|| {
+            // SAFETY: 同 flush
+            unsafe {
+                ffi::lame_encode_flush_nogap(
+                    self.gfp.as_ptr(),
+                    mp3_buffer.as_mut_ptr(),
+                    mp3_buffer.len().min(i32::MAX as usize) as i32,
+                )
+            }
+        });
+
+        let result = if result < 0 {
+            Err(LameError::encoding_failed(result, None))
+        } else {
+            self.gain_analyzed = true;
+            self.state = EncoderState::Ready;
+            Ok(result as usize)
+        };
+        self.record_lame_output(result, lame_output)
+    }
+
+    /// 设置当前段在无缝分段中的序号（从 0 开始）
+    ///
+    /// 在开始编码某一段之前调用，参见 [`flush_nogap`](Self::flush_nogap)。
+    /// 需要先用 [`EncoderBuilder::nogap_total`] 设置总段数。
+    ///
+    /// # 错误
+    ///
+    /// `index` 为负数或不小于总段数时返回 [`LameError::InvalidParameter`]
+    pub fn set_nogap_current_index(&mut self, index: i32) -> Result<()> {
+        let total = self.nogap_total();
+        if total < 1 {
+            return Err(LameError::InvalidParameter(
+                "nogap_current_index requires nogap_total to be set".to_string(),
+            ));
+        }
+        if !(0..total).contains(&index) {
+            return Err(LameError::InvalidParameter(format!(
+                "nogap_current_index must be 0-{}, got {}",
+                total - 1,
+                index
+            )));
+        }
+        // SAFETY: 句柄有效且被独占借用；序号只用于生成 LAME 信息帧
+        unsafe { ffi::lame_set_nogap_currentindex(self.gfp.as_ptr(), index) };
+        Ok(())
+    }
+
+    /// 无缝分段的总段数，未设置时为 0
+    pub fn nogap_total(&self) -> i32 {
+        self.get(ffi::lame_get_nogap_total)
+    }
+
+    /// 当前段在无缝分段中的序号
+    pub fn nogap_current_index(&self) -> i32 {
+        self.get(ffi::lame_get_nogap_currentindex)
+    }
+
+    /// 在分段边界上修改 CBR 比特率（kbps）
+    ///
+    /// LAME 在 `lame_init_params()` 之后不会再读取比特率，因此这里按当前句柄的参数
+    /// 创建一个新句柄，只替换比特率，然后接替旧句柄继续编码。MP3 允许逐帧改变比特率，
+    /// 前后两段拼接起来是合法的码流，播放器按新帧头解码。
+    ///
+    /// 限制：
+    ///
+    /// - 只能在开始编码之前，或 [`flush_nogap`](Self::flush_nogap) 之后调用，
+    ///   否则旧句柄中缓存的 PCM 会丢失，返回 [`LameError::InvalidInput`]
+    /// - 只支持 CBR，且不能使用 [`free_format`](EncoderBuilder::free_format)
+    /// - 需要在构建时关闭 [`write_vbr_tag`](EncoderBuilder::write_vbr_tag)，
+    ///   流开头的信息帧无法描述多种比特率
+    /// - 输出采样率不变，因此 MPEG 版本也不变，`kbps` 必须是该版本的标准比特率
+    ///
+    /// 不满足时返回 [`LameError::InvalidParameter`]，编码器保持不变。
+    ///
+    /// 新的一段像 [`flush_nogap`](Self::flush_nogap) 之后的下一段一样从编码器延迟开始。
+    /// 低通、高通等由 LAME 按初始比特率选出的参数保持不变，听感不会在切换时跳变。
+    /// [`frame_count`](Self::frame_count) 和 [`bitrate_histogram`](Self::bitrate_histogram)
+    /// 从新句柄重新计数。ID3 标签属于旧句柄，切换后 [`flush`](Self::flush) 不会再写入 ID3v1 标签。
+    pub fn set_bitrate(&mut self, kbps: i32) -> Result<()> {
+        if self.get(ffi::lame_get_VBR) != VbrMode::Off as u32
+            || self.get(ffi::lame_get_free_format) != 0
+        {
+            return Err(LameError::InvalidParameter(
+                "set_bitrate requires CBR without free_format".to_string(),
+            ));
+        }
+        if self.writes_vbr_tag() {
+            return Err(LameError::InvalidParameter(
+                "set_bitrate requires write_vbr_tag(false)".to_string(),
+            ));
+        }
+
+        // 构建、reset 和 flush_nogap 之后旧句柄中没有缓存的 PCM
+        if self.state != EncoderState::Ready {
+            return Err(LameError::InvalidInput(
+                "set_bitrate must be called before encoding or right after flush_nogap()"
+                    .to_string(),
+            ));
+        }
+        if kbps == self.get(ffi::lame_get_brate) {
+            return Ok(());
+        }
+
+        // 交换句柄后 successor 持有旧句柄，drop 时关闭
+        let mut successor = self.successor(kbps)?;
+        std::mem::swap(&mut self.gfp, &mut successor.gfp);
+        std::mem::swap(&mut self.settings, &mut successor.settings);
+        // 新句柄的分析从头开始
+        self.gain_analyzed = false;
+        Ok(())
+    }
+
+    /// 回到刚构建完成的状态，开始一个新的、独立的 MP3 流
+    ///
+    /// 用同一套设置连续编码多条音轨时，可以代替每条音轨重新配置构建器。
+    /// LAME 无法在 `lame_init_params()` 之后重新初始化同一个句柄，因此这里新建一个句柄，
+    /// 按顺序重放构建时 LAME 接受的设置，再替换旧句柄。封装层的状态原样保留：
+    /// 暂存缓冲区不会重新分配，[`set_max_samples_per_call`](Self::set_max_samples_per_call)、
+    /// 取消令牌和进度回调继续生效。
+    ///
+    /// 新的流与同样设置新建的编码器输出逐字节相同。旧流中未刷新的数据被丢弃，
+    /// 帧计数、统计和 ReplayGain 从头开始。ID3 标签属于旧句柄，需要为新音轨重新设置。
+    /// [`set_bitrate`](Self::set_bitrate) 之后重置使用切换后的比特率。
+    ///
+    /// ```no_run
+    /// # use lame_sys::LameEncoder;
+    /// # fn main() -> lame_sys::Result<()> {
+    /// # let tracks: Vec<Vec<i16>> = Vec::new();
+    /// let mut encoder = LameEncoder::builder()?
+    ///     .sample_rate(44100)?
+    ///     .channels(2)?
+    ///     .bitrate(192)?
+    ///     .build()?;
+    /// let mut tail = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+    ///
+    /// for (index, pcm) in tracks.iter().enumerate() {
+    ///     let mut mp3 = encoder.encode_interleaved_to_vec(pcm)?;
+    ///     let len = encoder.flush(&mut tail)?;
+    ///     mp3.extend_from_slice(&tail[..len]);
+    ///     std::fs::write(format!("track{}.mp3", index + 1), &mp3)?;
+    ///     encoder.reset()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # 错误
+    ///
+    /// 通过 `from_raw` 取回的编码器没有记录设置，返回 [`LameError::InvalidInput`]。
+    /// 创建新句柄失败时返回相应的错误，编码器保持不变。
+    pub fn reset(&mut self) -> Result<()> {
+        let Some(settings) = &self.settings else {
+            return Err(LameError::InvalidInput(
+                "reset requires an encoder created by EncoderBuilder".to_string(),
+            ));
+        };
+
+        let mut builder = EncoderBuilder::new()?;
+        for setting in settings {
+            // SAFETY: 构建器的句柄有效且尚未初始化参数
+            if !unsafe { setting.apply(builder.ptr()) } {
+                return Err(LameError::InternalError(
+                    "failed to replay encoder settings".to_string(),
+                ));
+            }
+        }
+        builder.settings = settings.clone();
+
+        // 交换句柄后 fresh 持有旧句柄，drop 时关闭
+        let mut fresh = builder.build()?;
+        std::mem::swap(&mut self.gfp, &mut fresh.gfp);
+        self.gain_analyzed = false;
+        self.last_error = None;
+        self.state = EncoderState::Ready;
+        Ok(())
+    }
+
+    /// 按当前句柄的参数创建一个比特率为 `kbps` 的编码器
+    fn successor(&self, kbps: i32) -> Result<LameEncoder> {
+        let mut builder = EncoderBuilder::new()?
+            .input_sample_rate(self.input_sample_rate())?
+            .output_sample_rate(self.output_sample_rate())?
+            .channels(self.get(ffi::lame_get_num_channels))?
+            .bitrate(kbps)?
+            .ms_fix(self.ms_fix())?
+            .write_vbr_tag(false)?;
+
+        // 经过 lame_init_params 调整后的值，LAME 在新句柄上会得到相同的结果
+        let settings: [(IntSetter, IntGetter); 12] = [
+            (ffi::lame_set_quality, ffi::lame_get_quality),
+            (ffi::lame_set_lowpassfreq, ffi::lame_get_lowpassfreq),
+            (ffi::lame_set_highpassfreq, ffi::lame_get_highpassfreq),
+            (ffi::lame_set_copyright, ffi::lame_get_copyright),
+            (ffi::lame_set_original, ffi::lame_get_original),
+            (ffi::lame_set_extension, ffi::lame_get_extension),
+            (ffi::lame_set_emphasis, ffi::lame_get_emphasis),
+            (ffi::lame_set_force_ms, ffi::lame_get_force_ms),
+            (ffi::lame_set_useTemporal, ffi::lame_get_useTemporal),
+            (ffi::lame_set_findReplayGain, ffi::lame_get_findReplayGain),
+            (ffi::lame_set_nogap_total, ffi::lame_get_nogap_total),
+            (
+                ffi::lame_set_nogap_currentindex,
+                ffi::lame_get_nogap_currentindex,
+            ),
+        ];
+        let copied = builder.set(ffi::lame_set_mode, self.get(ffi::lame_get_mode))
+            && settings
+                .into_iter()
+                .all(|(setter, getter)| builder.set(setter, self.get(getter)));
+        if !copied {
+            return Err(LameError::InternalError(
+                "failed to copy encoder settings".to_string(),
+            ));
+        }
+
+        // 未设置时为 LAME 的默认值（耦合），只复制明确的选择
+        if self.get(ffi::lame_get_no_short_blocks) == 1 {
+            builder.set(ffi::lame_set_no_short_blocks, 1);
+        }
+        if self.get(ffi::lame_get_force_short_blocks) == 1 {
+            builder.set(ffi::lame_set_force_short_blocks, 1);
+        }
+        // 没有内置解码器的 LAME 连关闭也会拒绝
+        if self.get(ffi::lame_get_decode_on_the_fly) == 1 {
+            builder.set(ffi::lame_set_decode_on_the_fly, 1);
+        }
+
+        for param in RawParam::ALL {
+            // 由比特率推导，新句柄重新计算
+            if param != RawParam::CompressionRatio {
+                builder = builder.set_raw(param, self.get_raw(param))?;
+            }
+        }
+
+        builder.build()
+    }
+
+    /// 已编码的帧数
+    ///
+    /// 不包含 LAME 信息帧（Xing/Info），[`flush_nogap`](Self::flush_nogap) 不会重置计数。
+    pub fn frame_count(&self) -> usize {
+        self.frames_encoded() as usize
+    }
+
+    /// 已编码的帧数（`lame_get_frameNum`），与 [`frame_count`](Self::frame_count) 相同
+    ///
+    /// 类型与 [`estimated_total_frames`](Self::estimated_total_frames) 一致，便于计算进度。
+    pub fn frames_encoded(&self) -> u32 {
+        self.get(ffi::lame_get_frameNum).max(0) as u32
+    }
+
+    /// 预计的总帧数，用于显示进度
+    ///
+    /// 由 [`EncoderBuilder::total_samples`] 推算，包括末尾补齐的帧；未设置时返回 `None`。
+    pub fn estimated_total_frames(&self) -> Option<u32> {
+        match self.get(ffi::lame_get_totalframes) {
+            frames if frames > 0 => Some(frames as u32),
+            _ => None,
+        }
+    }
+
+    /// 每帧的样本数（每个声道）：MPEG-1 为 1152，MPEG-2/2.5 为 576
+    ///
+    /// 由输出采样率决定，按帧对齐输入块时使用。
+    pub fn frame_size(&self) -> usize {
+        self.get(ffi::lame_get_framesize).max(0) as usize
+    }
+
+    /// 输出缓冲区为 `mp3_buffer_len` 字节时，一次编码调用最多传入的样本数（每个声道）
+    ///
+    /// 与 [`mp3_buffer_size`](Self::mp3_buffer_size) 相反，由 LAME 按当前的比特率估算
+    /// （VBR 按该 MPEG 版本的最大比特率）；重采样时按输入采样率计数。估算不包括
+    /// LAME 内部尚未输出的数据，缓冲区应当再留出 [`mp3_buffer_size(0)`](Self::mp3_buffer_size) 的余量。
+    #[cfg(lame_has_max_samples_getter)]
+    pub fn max_samples_for_buffer(&self, mp3_buffer_len: usize) -> usize {
+        // SAFETY: 句柄有效；LAME 只读取编码参数
+        let samples =
+            unsafe { ffi::lame_get_maximum_number_of_samples(self.gfp.as_ptr(), mp3_buffer_len) };
+        samples.max(0) as usize
+    }
+
+    /// 输出缓冲区为 `mp3_buffer_len` 字节时，一次编码调用最多传入的样本数（每个声道）
+    ///
+    /// 链接的 LAME 不提供 `lame_get_maximum_number_of_samples`，按
+    /// [`mp3_buffer_size`](Self::mp3_buffer_size) 的最坏情况公式反算，结果偏保守。
+    #[cfg(not(lame_has_max_samples_getter))]
+    pub fn max_samples_for_buffer(&self, mp3_buffer_len: usize) -> usize {
+        mp3_buffer_len.saturating_sub(max_mp3_buffer_size(0)) * 4 / 5
+    }
+
+    /// 编码器延迟：解码输出开头需要丢弃的样本数（每个声道）
+    pub fn encoder_delay(&self) -> usize {
+        self.get(ffi::lame_get_encoder_delay).max(0) as usize
+    }
+
+    /// 末尾为凑满最后一帧补入的样本数（每个声道）
+    ///
+    /// 在 [`flush`](Self::flush) 中计算，之前为 0。
+    pub fn end_padding(&self) -> usize {
+        self.get(ffi::lame_get_encoder_padding).max(0) as usize
+    }
+
+    /// 去掉延迟和填充后可播放的样本数（每个声道）
+    ///
+    /// 即 `frame_count * frame_size - encoder_delay - end_padding`，[`flush`](Self::flush)
+    /// 之后等于输入的样本数，可用于 CMAF/HLS 的 edit list 等无缝播放元数据。
+    /// 重采样时按输出采样率计数。
+    pub fn gapless_samples(&self) -> usize {
+        (self.frame_count() * self.frame_size())
+            .saturating_sub(self.encoder_delay() + self.end_padding())
+    }
+
+    /// 整段音频的 ReplayGain（dB，精确到 0.1）
+    ///
+    /// 需要用 [`EncoderBuilder::find_replay_gain`] 开启分析。结果在 [`flush`](Self::flush)
+    /// 或 [`flush_nogap`](Self::flush_nogap) 中计算，之前返回 `None`；`flush_nogap`
+    /// 之后的值只覆盖上一段。响亮的音频得到负值，表示播放时需要降低的音量。
+    /// 音频太短（不足 50 ms）时 LAME 报告 0.0。
+    pub fn replay_gain(&self) -> Option<f32> {
+        if !self.gain_analyzed || self.get(ffi::lame_get_findReplayGain) == 0 {
+            return None;
+        }
+        Some(self.get(ffi::lame_get_RadioGain) as f32 / 10.0)
+    }
+
+    /// 解码输出中绝对值最大的样本，以 16-bit 满幅（32767）为单位
+    ///
+    /// 需要用 [`EncoderBuilder::decode_on_the_fly`] 开启边编码边解码。
+    /// 与 [`replay_gain`](Self::replay_gain) 一样，在 flush 之前返回 `None`。
+    pub fn peak_sample(&self) -> Option<f32> {
+        if !self.gain_analyzed || self.get(ffi::lame_get_decode_on_the_fly) == 0 {
+            return None;
+        }
+        Some(self.get(ffi::lame_get_PeakSample))
+    }
+
+    /// 比特率直方图：`(kbps, 帧数)`，与 `lame` 命令行结束时的统计相同
+    ///
+    /// 共 14 项，依次对应 MPEG 比特率索引 1-14，比特率取决于输出的 MPEG 版本。
+    /// 没有使用的比特率计数为 0，所有计数之和等于 [`frame_count`](Self::frame_count)。
+    pub fn bitrate_histogram(&self) -> Vec<(i32, i32)> {
+        let mut kbps = [0i32; 14];
+        let mut counts = [0i32; 14];
+        // SAFETY: lame_bitrate_kbps 和 lame_bitrate_hist 各自正好写入 14 项
+        unsafe {
+            ffi::lame_bitrate_kbps(self.gfp.as_ptr(), kbps.as_mut_ptr());
+            ffi::lame_bitrate_hist(self.gfp.as_ptr(), counts.as_mut_ptr());
+        }
+        kbps.into_iter().zip(counts).collect()
+    }
+
+    /// 立体声模式直方图：`[LR, LR-I, MS, MS-I]` 各自的帧数
+    ///
+    /// 只对立体声输出有意义，单声道输出全为 0。LAME 不支持强度立体声，
+    /// 下标 1 和 3 总是 0；联合立体声时可以看出 M/S 编码的帧所占的比例。
+    pub fn stereo_mode_histogram(&self) -> [usize; 4] {
+        let mut counts = [0i32; 4];
+        // SAFETY: lame_stereo_mode_hist 正好写入 4 个计数
+        unsafe {
+            ffi::lame_stereo_mode_hist(self.gfp.as_ptr(), counts.as_mut_ptr());
+        }
+        counts.map(|count| count.max(0) as usize)
+    }
+
+    /// 输入 PCM 的采样率（Hz）
+    pub fn input_sample_rate(&self) -> i32 {
+        self.get(ffi::lame_get_in_samplerate)
+    }
+
+    /// MP3 的输出采样率（Hz），包括 LAME 自动选择的值
+    pub fn output_sample_rate(&self) -> i32 {
+        self.get(ffi::lame_get_out_samplerate)
+    }
+
+    /// 同 [`input_sample_rate`](Self::input_sample_rate)，对应 `lame_get_in_samplerate`
+    pub fn sample_rate_in(&self) -> i32 {
+        self.input_sample_rate()
+    }
+
+    /// 同 [`output_sample_rate`](Self::output_sample_rate)，对应 `lame_get_out_samplerate`
+    pub fn sample_rate_out(&self) -> i32 {
+        self.output_sample_rate()
+    }
+
+    /// 输入声道数
+    pub fn channels(&self) -> i32 {
+        self.get(ffi::lame_get_num_channels)
+    }
+
+    /// 生效的 CBR 比特率（kbps），包括 LAME 按输出采样率调整后的值
+    ///
+    /// VBR 和 ABR 模式下没有意义，ABR 的目标比特率见 [`config`](Self::config)。
+    pub fn bitrate(&self) -> i32 {
+        self.get(ffi::lame_get_brate)
+    }
+
+    /// 生效的声道模式
+    ///
+    /// 未设置时 LAME 按声道数和比特率选择，例如立体声默认为 [`ChannelMode::JointStereo`]，
+    /// 单声道输入总是 [`ChannelMode::Mono`]。
+    pub fn mode(&self) -> ChannelMode {
+        ChannelMode::from_raw(self.get(ffi::lame_get_mode))
+    }
+
+    /// 生效的 LAME 质量级别（0-9，0 最好）
+    pub fn quality_level(&self) -> i32 {
+        self.get(ffi::lame_get_quality)
+    }
+
+    /// 生效的质量级别对应的 [`Quality`]，没有对应变体时返回 `None`
+    pub fn quality(&self) -> Option<Quality> {
+        Quality::from_level(self.quality_level())
+    }
+
+    /// 生效的 VBR 模式，LAME 内部的各种 VBR 算法都归为 [`VbrMode::Vbr`]
+    pub fn vbr_mode(&self) -> VbrMode {
+        VbrMode::from_raw(self.get(ffi::lame_get_VBR))
+    }
+
+    /// LAME 命令行工具打印的配置信息
+    ///
+    /// 依次包括 `lame_print_config` 的输出、命令行工具的 "Encoding as" 一行
+    /// （采样率、声道模式、MPEG 版本、比特率和质量级别）和 `lame_print_internals` 的详细参数，
+    /// 用于排查音质问题。LAME 输出的内容随版本变化，不要解析其中的格式。
+    ///
+    /// 输出通过构建器安装的消息回调收集，不会写到 stderr。
+    /// [`from_raw`](Self::from_raw) 取回的外部句柄没有安装回调，LAME 的部分仍输出到原来的位置。
+    pub fn config_summary(&self) -> String {
+        let mut summary = crate::report::capture(|| {
+            // SAFETY: 句柄有效且已初始化参数；两个函数只读取参数并通过消息回调输出
+            unsafe { ffi::lame_print_config(self.gfp.as_ptr()) }
+        });
+
+        let mode = match self.mode() {
+            ChannelMode::JointStereo if self.forces_ms() => "force-ms",
+            ChannelMode::JointStereo => "j-stereo",
+            ChannelMode::Stereo => "stereo",
+            ChannelMode::DualChannel => "dual-ch",
+            ChannelMode::Mono => "single-ch",
+        };
+        let mpeg = match self.output_sample_rate() {
+            rate if rate >= 32000 => "1",
+            rate if rate >= 16000 => "2",
+            _ => "2.5",
+        };
+        let rate = match self.vbr_mode() {
+            VbrMode::Off => format!("{} kbps", self.bitrate()),
+            VbrMode::Abr => format!(
+                "average {} kbps",
+                self.get(ffi::lame_get_VBR_mean_bitrate_kbps)
+            ),
+            VbrMode::Vbr => format!("VBR(q={})", self.get(ffi::lame_get_VBR_q)),
+        };
+        summary.push_str(&format!(
+            "Encoding as {} kHz {} MPEG-{} Layer III {} qval={}\n",
+            self.output_sample_rate() as f64 / 1000.0,
+            mode,
+            mpeg,
+            rate,
+            self.quality_level()
+        ));
+
+        summary.push_str(&crate::report::capture(|| {
+            // SAFETY: 同上
+            unsafe { ffi::lame_print_internals(self.gfp.as_ptr()) }
+        }));
+        summary
+    }
+
+    /// 是否在流的开头写入 LAME 信息帧（Xing/Info）
+    pub fn writes_vbr_tag(&self) -> bool {
+        self.get(ffi::lame_get_bWriteVbrTag) != 0
+    }
+
+    /// 完整的 LAME 信息帧（Xing/Info）
+    ///
+    /// 编码时流开头写入的信息帧只是全 0 的占位，[`flush`](Self::flush) 之后用这里的内容
+    /// 覆盖它，播放器才能读到准确的时长、TOC 和编码延迟。占位帧是输出的第一帧，
+    /// 有 ID3v2 标签时紧跟在标签之后。直接写到 socket 等无法回写的下游时，
+    /// 可以把它单独保存，或在之后的请求中替换文件开头的那一帧。
+    ///
+    /// 必须在 `flush` 之后调用，之前的内容不完整。没有写入信息帧时返回空数组。
+    /// 链接的 LAME 不提供 `lame_get_lametag_frame` 时返回 [`LameError::InternalError`]。
+    #[cfg(lame_has_lametag_frame)]
+    pub fn lametag_frame(&self) -> Result<Vec<u8>> {
+        // SAFETY: 缓冲区大小为 0 时 LAME 只返回所需的字节数，不写入
+        let size =
+            unsafe { ffi::lame_get_lametag_frame(self.gfp.as_ptr(), std::ptr::null_mut(), 0) };
+        let mut frame = vec![0u8; size];
+        if size > 0 {
+            // SAFETY: 缓冲区正好是 LAME 要求的大小
+            let written = unsafe {
+                ffi::lame_get_lametag_frame(self.gfp.as_ptr(), frame.as_mut_ptr(), frame.len())
+            };
+            frame.truncate(written);
+        }
+        Ok(frame)
+    }
+
+    /// 完整的 LAME 信息帧（Xing/Info）
+    ///
+    /// 链接的 LAME 不提供 `lame_get_lametag_frame`，总是返回 [`LameError::InternalError`]。
+    #[cfg(not(lame_has_lametag_frame))]
+    pub fn lametag_frame(&self) -> Result<Vec<u8>> {
+        Err(crate::features::Feature::LametagFrame.unsupported())
+    }
+
+    /// 把 LAME 信息帧写入 `buf`，返回写入的字节数
+    ///
+    /// 与 [`lametag_frame`](Self::lametag_frame) 相同，但不分配内存。信息帧是一个 MP3 帧，
+    /// `buf` 有 [`mp3_buffer_size(0)`](Self::mp3_buffer_size) 字节总是足够。
+    /// 没有写入信息帧时返回 0。
+    ///
+    /// # 错误
+    ///
+    /// `buf` 放不下时返回 [`LameError::InvalidInput`]，信息中包含所需的字节数
+    #[cfg(lame_has_lametag_frame)]
+    pub fn lametag_frame_into(&self, buf: &mut [u8]) -> Result<usize> {
+        // SAFETY: LAME 最多写入 buf.len() 字节；放不下时只返回所需的大小，不写入
+        let size =
+            unsafe { ffi::lame_get_lametag_frame(self.gfp.as_ptr(), buf.as_mut_ptr(), buf.len()) };
+        if size > buf.len() {
+            return Err(LameError::InvalidInput(format!(
+                "lametag frame needs {} bytes, buffer has {}",
+                size,
+                buf.len()
+            )));
+        }
+        Ok(size)
+    }
+
+    /// 把 LAME 信息帧写入 `buf`，返回写入的字节数
+    ///
+    /// 链接的 LAME 不提供 `lame_get_lametag_frame`，总是返回 [`LameError::InternalError`]。
+    #[cfg(not(lame_has_lametag_frame))]
+    pub fn lametag_frame_into(&self, _buf: &mut [u8]) -> Result<usize> {
+        Err(crate::features::Feature::LametagFrame.unsupported())
+    }
+
+    /// 一次设置 [`Id3Metadata`](crate::Id3Metadata) 中的所有 ID3 字段
+    ///
+    /// 相当于用 [`Id3Tag`](crate::Id3Tag) 逐个设置非 `None` 的字段后调用
+    /// [`apply`](crate::Id3Tag::apply)，同样替换之前设置的标签，并且必须在开始编码之前调用。
+    /// 有字段无效时不会在第一个错误处停止：返回的 [`LameError::InvalidInput`]
+    /// 列出所有出错的字段名和原因，整个标签被丢弃。
+    pub fn set_id3(&mut self, meta: &crate::Id3Metadata) -> Result<()> {
+        meta.apply(self).map(|_| ())
+    }
+
+    /// LAME 是否自动把 ID3 标签写入输出，见 [`EncoderBuilder::write_id3_automatic`]
+    pub fn writes_id3_automatic(&self) -> bool {
+        self.get(ffi::lame_get_write_id3tag_automatic) != 0
+    }
+
+    /// 编码时写在流开头的 ID3v2 标签
+    ///
+    /// 内容由 [`Id3Tag`](crate::Id3Tag) 设置的字段决定，可以在编码前检查。
+    /// 没有 ID3v2 标签（未设置标签，或字段都能放进 ID3v1）时返回空数组。
+    /// 自行写入标签时用 [`write_id3_automatic(false)`](EncoderBuilder::write_id3_automatic)
+    /// 关闭 LAME 的自动写入。
+    /// 链接的 LAME 不提供 `lame_get_id3v2_tag` 时返回 [`LameError::InternalError`]。
+    #[cfg(lame_has_id3v2_tag_getter)]
+    pub fn id3v2_tag(&self) -> Result<Vec<u8>> {
+        // SAFETY: 缓冲区大小为 0 时 LAME 只返回所需的字节数，不写入
+        let size = unsafe { ffi::lame_get_id3v2_tag(self.gfp.as_ptr(), std::ptr::null_mut(), 0) };
+        let mut tag = vec![0u8; size];
+        if size > 0 {
+            // SAFETY: 缓冲区正好是 LAME 要求的大小
+            let written =
+                unsafe { ffi::lame_get_id3v2_tag(self.gfp.as_ptr(), tag.as_mut_ptr(), tag.len()) };
+            tag.truncate(written);
+        }
+        Ok(tag)
+    }
+
+    /// 编码时写在流开头的 ID3v2 标签
+    ///
+    /// 链接的 LAME 不提供 `lame_get_id3v2_tag`，总是返回 [`LameError::InternalError`]。
+    #[cfg(not(lame_has_id3v2_tag_getter))]
+    pub fn id3v2_tag(&self) -> Result<Vec<u8>> {
+        Err(crate::features::Feature::Id3v2TagGetter.unsupported())
+    }
+
+    /// 编码时写在流开头的 ID3v2 标签的字节数
+    ///
+    /// 等于 10 字节的标签头、所有帧和 [`Id3Tag::pad_v2`](crate::Id3Tag::pad_v2) 预留的填充之和，
+    /// 即 [`id3v2_tag`](Self::id3v2_tag) 的长度，但不生成标签内容。流媒体中可以在发送音频前
+    /// 确认之后原地更新元数据的空间。没有 ID3v2 标签时返回 0。
+    #[cfg(lame_has_id3v2_tag_getter)]
+    pub fn id3v2_tag_size(&self) -> Result<usize> {
+        // SAFETY: 缓冲区大小为 0 时 LAME 只返回所需的字节数，不写入
+        Ok(unsafe { ffi::lame_get_id3v2_tag(self.gfp.as_ptr(), std::ptr::null_mut(), 0) })
+    }
+
+    /// 编码时写在流开头的 ID3v2 标签的字节数
+    ///
+    /// 链接的 LAME 不提供 `lame_get_id3v2_tag`，总是返回 [`LameError::InternalError`]。
+    #[cfg(not(lame_has_id3v2_tag_getter))]
+    pub fn id3v2_tag_size(&self) -> Result<usize> {
+        Err(crate::features::Feature::Id3v2TagGetter.unsupported())
+    }
+
+    /// 刷新时追加在流末尾的 128 字节 ID3v1 标签
+    ///
+    /// 未设置标签或只写 ID3v2（[`Id3Version::V2Only`](crate::Id3Version::V2Only)）时返回空数组。
+    /// `lame_get_id3v1_tag` 与 `lame_get_id3v2_tag` 在同一版本加入，
+    /// 链接的 LAME 不提供时返回 [`LameError::InternalError`]。
+    #[cfg(lame_has_id3v2_tag_getter)]
+    pub fn id3v1_tag(&self) -> Result<Vec<u8>> {
+        // SAFETY: 缓冲区大小为 0 时 LAME 只返回所需的字节数，不写入
+        let size = unsafe { ffi::lame_get_id3v1_tag(self.gfp.as_ptr(), std::ptr::null_mut(), 0) };
+        let mut tag = vec![0u8; size];
+        if size > 0 {
+            // SAFETY: 缓冲区正好是 LAME 要求的大小
+            let written =
+                unsafe { ffi::lame_get_id3v1_tag(self.gfp.as_ptr(), tag.as_mut_ptr(), tag.len()) };
+            tag.truncate(written);
+        }
+        Ok(tag)
+    }
+
+    /// 刷新时追加在流末尾的 ID3v1 标签
+    ///
+    /// 链接的 LAME 不提供 `lame_get_id3v1_tag`，总是返回 [`LameError::InternalError`]。
+    #[cfg(not(lame_has_id3v2_tag_getter))]
+    pub fn id3v1_tag(&self) -> Result<Vec<u8>> {
+        Err(crate::features::Feature::Id3v2TagGetter.unsupported())
+    }
+
+    /// 帧是否带有 CRC 校验（帧头的 protection 位）
+    pub fn is_error_protected(&self) -> bool {
+        self.get(ffi::lame_get_error_protection) != 0
+    }
+
+    /// 帧头的 copyright 位是否置位
+    pub fn is_copyright(&self) -> bool {
+        self.get(ffi::lame_get_copyright) != 0
+    }
+
+    /// 帧头的 original 位是否置位
+    pub fn is_original(&self) -> bool {
+        self.get(ffi::lame_get_original) != 0
+    }
+
+    /// 帧头的 private 位是否置位
+    pub fn is_private(&self) -> bool {
+        self.get(ffi::lame_get_extension) != 0
+    }
+
+    /// 帧头中的去加重标记；保留值 2 按 [`Emphasis::None`] 返回
+    pub fn emphasis(&self) -> Emphasis {
+        match self.get(ffi::lame_get_emphasis) {
+            1 => Emphasis::E5015,
+            3 => Emphasis::Ccitt,
+            _ => Emphasis::None,
+        }
+    }
+
+    /// 是否强制所有帧使用 M/S 立体声，参见 [`EncoderBuilder::force_ms`]
+    pub fn forces_ms(&self) -> bool {
+        self.get(ffi::lame_get_force_ms) != 0
+    }
+
+    /// 生效的 M/S 掩蔽调整值，参见 [`EncoderBuilder::ms_fix`]
+    pub fn ms_fix(&self) -> f32 {
+        self.get(ffi::lame_get_msfix)
+    }
+
+    /// 生效的声道间掩蔽比，参见 [`EncoderBuilder::inter_channel_ratio`]
+    pub fn inter_channel_ratio(&self) -> f32 {
+        self.get(ffi::lame_get_interChRatio)
+    }
+
+    /// 读取未封装的 LAME 参数，见 [`param`](crate::param) 模块
+    ///
+    /// 返回 `build()` 之后生效的值，类型与参数一致。
+    pub fn get_raw(&self, param: RawParam) -> RawValue {
+        match param.accessor() {
+            Accessor::Int(_, getter) => RawValue::Int(self.get(getter)),
+            Accessor::Float(_, getter) => RawValue::Float(self.get(getter)),
+        }
+    }
+
+    /// 以编码器的句柄调用只读的 `lame_get_*` 函数
+    ///
+    /// `getter` 必须是只读取句柄参数的 `lame_get_*` 系列函数。
+    #[inline(always)]
+    pub(crate) fn get<T>(
+        &self,
+        getter: unsafe extern "C" fn(*const ffi::lame_global_flags) -> T,
+    ) -> T {
+        // SAFETY: gfp 在编码器存活期间有效；lame_get_* 不修改句柄，也不保留指针
+        unsafe { getter(self.gfp.as_ptr()) }
+    }
+
+    /// 获取原始的 LAME global flags 指针（用于高级操作）
+    ///
+    /// # 安全性
+    ///
+    /// 调用者必须确保不会释放返回的指针，也不能在编码器销毁后使用。
+    pub unsafe fn as_ptr(&self) -> *mut ffi::lame_global_flags {
+        self.gfp.as_ptr()
+    }
+
+    /// 交出 LAME 句柄的所有权，不调用 `lame_close`
+    ///
+    /// 与 [`Box::into_raw`] 类似：调用者负责之后用 [`from_raw`](Self::from_raw) 取回，
+    /// 或自行调用 `lame_close` 释放，否则句柄会泄漏。
+    ///
+    /// 只有 LAME 句柄内的状态会被保留（参数、已缓存的 PCM 和比特流、
+    /// [`frame_count`](Self::frame_count)、[`bitrate_histogram`](Self::bitrate_histogram)、ID3 标签）。
+    /// 封装层的状态会丢失：暂存缓冲区、
+    /// [`set_max_samples_per_call`](Self::set_max_samples_per_call)、
+    /// [`set_cancel_token`](Self::set_cancel_token) 和
+    /// [`set_progress_hook`](Self::set_progress_hook) 的设置，`from_raw` 之后恢复为默认值。
+    #[cfg(feature = "raw")]
+    pub fn into_raw(self) -> *mut ffi::lame_global_flags {
+        // 不运行 Drop，但仍然释放封装层持有的资源。
+        // 模式列出全部字段，新增字段时编译器会要求在这里决定如何处理
+        let mut this = std::mem::ManuallyDrop::new(self);
+        let LameEncoder {
+            gfp,
+            settings,
+            scratch,
+            scratch_i32,
+            scratch_mp3,
+            max_samples_per_call: _,
+            cancel_token,
+            progress_hook,
+            gain_analyzed: _,
+            last_error,
+            state: _,
+        } = &mut *this;
+        drop(settings.take());
+        drop(std::mem::take(scratch));
+        drop(std::mem::take(scratch_i32));
+        drop(std::mem::take(scratch_mp3));
+        drop(cancel_token.take());
+        drop(progress_hook.take());
+        drop(last_error.take());
+        crate::lifecycle::handle_closed();
+        gfp.as_ptr()
+    }
+
+    /// 从 [`into_raw`](Self::into_raw) 交出的句柄重建编码器
+    ///
+    /// 封装层的设置恢复为默认值，参见 [`into_raw`](Self::into_raw)。
+    ///
+    /// # 安全性
+    ///
+    /// - `ptr` 必须来自 [`into_raw`](Self::into_raw)，或是已经成功调用过
+    ///   `lame_init_params` 的 LAME 句柄
+    /// - 同一个指针只能调用一次 `from_raw`，之后编码器拥有句柄，Drop 时调用 `lame_close`
+    /// - `ptr` 不能为空
+    /// - 调用后不能再通过其他途径使用或释放 `ptr`
+    #[cfg(feature = "raw")]
+    pub unsafe fn from_raw(ptr: *mut ffi::lame_global_flags) -> LameEncoder {
+        debug_assert!(
+            !ptr.is_null(),
+            "LameEncoder::from_raw called with a null pointer"
+        );
+        crate::lifecycle::handle_opened();
+        LameEncoder {
+            // SAFETY: 调用者保证 ptr 非空
+            gfp: unsafe { NonNull::new_unchecked(ptr) },
+            settings: None,
+            scratch: Vec::new(),
+            scratch_i32: Vec::new(),
+            scratch_mp3: Vec::new(),
+            max_samples_per_call: None,
+            cancel_token: None,
+            progress_hook: None,
+            gain_analyzed: false,
+            last_error: None,
+            state: EncoderState::Ready,
+        }
+    }
+}
+
+impl Drop for LameEncoder {
+    fn drop(&mut self) {
+        // SAFETY: 编码器独占句柄（into_raw 不会运行 Drop），这里是唯一一次释放
+        unsafe {
+            ffi::lame_close(self.gfp.as_ptr());
+        }
+        crate::lifecycle::handle_closed();
+    }
+}
+
+// SAFETY: LameEncoder 独占其 lame_global_flags，LAME 不使用线程局部状态，
+// 因此可以把编码器整体移动到其他线程。
+// LameEncoder 不是 Sync：同一个编码器不能被多个线程同时访问，
+// 如果需要并行编码，应该为每个线程使用独立的编码器（参见 `pool::EncoderPool`）。
+unsafe impl Send for LameEncoder {}
+
+/// 编码器构建器
+///
+/// 使用 Builder 模式配置并创建 LAME 编码器。
+///
+/// 注意：Builder 在创建时就初始化 LAME C 结构体，每个配置方法立即调用底层 FFI。
+/// 这种设计消除了额外的内存分配和分支判断，提供更好的性能。
+pub struct EncoderBuilder {
+    /// 指向 LAME global flags 的非空指针
+    inner: NonNull<ffi::lame_global_flags>,
+    /// 是否显式设置了 ABR 平均比特率，`build()` 时检查 VBR 模式
+    abr_bitrate_set: bool,
+    /// LAME 接受的设置，按调用顺序记录，供 [`LameEncoder::reset`] 在新句柄上重放
+    settings: Vec<Setting>,
+}
+
+/// 一次被 LAME 接受的 `lame_set_*` 调用
+#[derive(Debug, Clone, Copy)]
+enum Setting {
+    Int(
+        unsafe extern "C" fn(*mut ffi::lame_global_flags, i32) -> i32,
+        i32,
+    ),
+    UInt(
+        unsafe extern "C" fn(*mut ffi::lame_global_flags, u32) -> i32,
+        u32,
+    ),
+    ULong(
+        unsafe extern "C" fn(*mut ffi::lame_global_flags, u64) -> i32,
+        u64,
+    ),
+    Float(
+        unsafe extern "C" fn(*mut ffi::lame_global_flags, f32) -> i32,
+        f32,
+    ),
+    /// `lame_set_msfix` 没有返回值，单独处理
+    MsFix(f64),
+    /// `lame_set_write_id3tag_automatic` 同样没有返回值
+    WriteId3Automatic(bool),
+}
+
+impl Setting {
+    /// 在 `gfp` 上重放，返回 LAME 是否接受
+    ///
+    /// # Safety
+    ///
+    /// `gfp` 是有效的、尚未初始化参数的句柄
+    unsafe fn apply(self, gfp: *mut ffi::lame_global_flags) -> bool {
+        // SAFETY: 调用方保证 gfp 有效；lame_set_* 只检查并保存参数
+        unsafe {
+            match self {
+                Setting::Int(setter, value) => setter(gfp, value) >= 0,
+                Setting::UInt(setter, value) => setter(gfp, value) >= 0,
+                Setting::ULong(setter, value) => setter(gfp, value) >= 0,
+                Setting::Float(setter, value) => setter(gfp, value) >= 0,
+                Setting::MsFix(value) => {
+                    ffi::lame_set_msfix(gfp, value);
+                    true
+                }
+                Setting::WriteId3Automatic(enabled) => {
+                    ffi::lame_set_write_id3tag_automatic(gfp, enabled as i32);
+                    true
+                }
+            }
+        }
+    }
+}
+
+/// `lame_set_*` 接受的参数类型
+trait SettingValue: Copy {
+    fn record(
+        setter: unsafe extern "C" fn(*mut ffi::lame_global_flags, Self) -> i32,
+        value: Self,
+    ) -> Setting;
+}
+
+impl SettingValue for i32 {
+    fn record(
+        setter: unsafe extern "C" fn(*mut ffi::lame_global_flags, i32) -> i32,
+        value: i32,
+    ) -> Setting {
+        Setting::Int(setter, value)
+    }
+}
+
+impl SettingValue for u32 {
+    fn record(
+        setter: unsafe extern "C" fn(*mut ffi::lame_global_flags, u32) -> i32,
+        value: u32,
+    ) -> Setting {
+        Setting::UInt(setter, value)
+    }
+}
+
+// c_ulong 在 Windows 上是 u32，其他平台是 u64
+impl SettingValue for u64 {
+    fn record(
+        setter: unsafe extern "C" fn(*mut ffi::lame_global_flags, u64) -> i32,
+        value: u64,
+    ) -> Setting {
+        Setting::ULong(setter, value)
+    }
+}
+
+impl SettingValue for f32 {
+    fn record(
+        setter: unsafe extern "C" fn(*mut ffi::lame_global_flags, f32) -> i32,
+        value: f32,
+    ) -> Setting {
+        Setting::Float(setter, value)
+    }
+}
+
+impl EncoderBuilder {
+    /// 创建新的构建器
+    ///
+    /// 立即初始化 LAME C 结构体。如果初始化失败，返回错误。
+    pub fn new() -> Result<Self> {
+        // SAFETY: lame_init 没有前置条件，失败时返回空指针
+        let gfp = unsafe { ffi::lame_init() };
+        let inner = NonNull::new(gfp).ok_or_else(|| {
+            LameError::InitializationFailed("lame_init() returned NULL".to_string())
+        })?;
+        crate::lifecycle::handle_opened();
+        // 消息回调只能在 lame_init_params 之前设置，供 LameEncoder::config_summary 收集
+        crate::report::install(gfp);
+        Ok(Self {
+            inner,
+            abr_bitrate_set: false,
+            settings: Vec::new(),
+        })
+    }
+
+    /// 获取内部指针（私有辅助方法）
+    #[inline(always)]
+    fn ptr(&self) -> *mut ffi::lame_global_flags {
+        self.inner.as_ptr()
+    }
+
+    /// 以构建器的句柄调用 `lame_set_*` 函数，返回 LAME 是否接受该值
+    ///
+    /// `setter` 必须是 `lame_set_*` 系列函数：第一个参数是句柄，失败时返回负数。
+    /// 接受的设置会被记录，供 [`LameEncoder::reset`] 重放。
+    #[inline(always)]
+    fn set<T: SettingValue>(
+        &mut self,
+        setter: unsafe extern "C" fn(*mut ffi::lame_global_flags, T) -> i32,
+        value: T,
+    ) -> bool {
+        // SAFETY: inner 来自 lame_init，在构建器 drop 或交给编码器之前一直有效；
+        // lame_set_* 只检查并保存参数，不保留句柄指针
+        let accepted = unsafe { setter(self.ptr(), value) >= 0 };
+        if accepted {
+            self.settings.push(T::record(setter, value));
+        }
+        accepted
+    }
+
+    /// 同时设置输入和输出采样率（Hz）
+    ///
+    /// 常见值：8000, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000
+    ///
+    /// `rate` 不是 MPEG 支持的输出采样率时（如 96000），输出采样率仍由 LAME 自动选择。
+    /// 需要重采样时分别使用 [`input_sample_rate`](Self::input_sample_rate) 和
+    /// [`output_sample_rate`](Self::output_sample_rate)。
+    #[inline(always)]
+    pub fn sample_rate(mut self, rate: i32) -> Result<Self> {
+        self.set_sample_rate(rate)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`sample_rate`](Self::sample_rate)
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, rate: i32) -> Result<&mut Self> {
+        self.set_input_sample_rate(rate)?;
+        // 不是 MPEG 输出采样率时忽略，由 LAME 自动选择
+        self.set(ffi::lame_set_out_samplerate, rate);
+        Ok(self)
+    }
+
+    /// 设置输入 PCM 的采样率（Hz）
+    ///
+    /// 未设置输出采样率时，LAME 在 `build()` 时根据输入采样率和比特率选择输出采样率。
+    #[inline(always)]
+    pub fn input_sample_rate(mut self, rate: i32) -> Result<Self> {
+        self.set_input_sample_rate(rate)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`input_sample_rate`](Self::input_sample_rate)
+    #[inline(always)]
+    pub fn set_input_sample_rate(&mut self, rate: i32) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_in_samplerate, rate) {
+            return Err(LameError::InvalidParameter("input_sample_rate".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置 MP3 的输出采样率（Hz），与输入不同时由 LAME 内置的重采样器转换
+    ///
+    /// 只接受 MPEG 支持的采样率：8000, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000。
+    /// `0` 表示由 LAME 自动选择（默认）。
+    #[inline(always)]
+    pub fn output_sample_rate(mut self, rate: i32) -> Result<Self> {
+        self.set_output_sample_rate(rate)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`output_sample_rate`](Self::output_sample_rate)
+    #[inline(always)]
+    pub fn set_output_sample_rate(&mut self, rate: i32) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_out_samplerate, rate) {
+            return Err(LameError::InvalidParameter(format!(
+                "output_sample_rate: {} Hz is not an MPEG sample rate",
+                rate
+            )));
+        }
+        Ok(self)
+    }
+
+    /// 设置声道数（1 = 单声道, 2 = 立体声）
+    #[inline(always)]
+    pub fn channels(mut self, channels: i32) -> Result<Self> {
+        self.set_channels(channels)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`channels`](Self::channels)
+    #[inline(always)]
+    pub fn set_channels(&mut self, channels: i32) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_num_channels, channels) {
+            return Err(LameError::InvalidParameter("channels".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置比特率（kbps）
+    ///
+    /// CBR 时必须是输出采样率对应的标准比特率，否则 `build()` 返回
+    /// [`LameError::InvalidParameter`] 并列出允许的值：
+    ///
+    /// - MPEG-1（32000-48000 Hz）：32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320
+    /// - MPEG-2 / MPEG-2.5（8000-24000 Hz）：8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160
+    ///
+    /// 打开 [`free_format`](Self::free_format) 后可以使用 8-640 kbps 之间的任意值。
+    #[inline(always)]
+    pub fn bitrate(mut self, bitrate: i32) -> Result<Self> {
+        self.set_bitrate(bitrate)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`bitrate`](Self::bitrate)
+    #[inline(always)]
+    pub fn set_bitrate(&mut self, bitrate: i32) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_brate, bitrate) {
+            return Err(LameError::InvalidParameter("bitrate".to_string()));
+        }
+        Ok(self)
     }
 
     /// 设置编码质量
     #[inline(always)]
-    pub fn quality(self, quality: Quality) -> Result<Self> {
-        unsafe {
-            if ffi::lame_set_quality(self.ptr(), quality as i32) < 0 {
-                return Err(LameError::InvalidParameter("quality".to_string()));
-            }
+    pub fn quality(mut self, quality: Quality) -> Result<Self> {
+        self.set_quality(quality)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`quality`](Self::quality)
+    #[inline(always)]
+    pub fn set_quality(&mut self, quality: Quality) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_quality, quality as i32) {
+            return Err(LameError::InvalidParameter("quality".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 以 0-9 的整数设置编码质量（0 = 最高质量，9 = 最快）
+    ///
+    /// [`Quality`] 只列出了其中 6 个级别，需要 1、3、6、8 时使用这个方法。
+    #[inline(always)]
+    pub fn quality_level(mut self, quality: i32) -> Result<Self> {
+        self.set_quality_level(quality)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`quality_level`](Self::quality_level)
+    #[inline(always)]
+    pub fn set_quality_level(&mut self, quality: i32) -> Result<&mut Self> {
+        if !(0..=9).contains(&quality) || !self.set(ffi::lame_set_quality, quality) {
+            return Err(LameError::InvalidParameter("quality_level".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置 MPEG 声道模式（默认由 LAME 按声道数和比特率选择）
+    #[inline(always)]
+    pub fn mode(mut self, mode: ChannelMode) -> Result<Self> {
+        self.set_mode(mode)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`mode`](Self::mode)
+    #[inline(always)]
+    pub fn set_mode(&mut self, mode: ChannelMode) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_mode, mode as u32) {
+            return Err(LameError::InvalidParameter("mode".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 所有帧都强制使用 M/S（中/侧）立体声编码（默认否）
+    ///
+    /// 只在 [`ChannelMode::JointStereo`] 下起作用。低比特率下左右声道相近的素材
+    /// （如播客人声）可以省下码率。
+    #[inline(always)]
+    pub fn force_ms(mut self, enabled: bool) -> Result<Self> {
+        self.set_force_ms(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`force_ms`](Self::force_ms)
+    #[inline(always)]
+    pub fn set_force_ms(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_force_ms, enabled as i32) {
+            return Err(LameError::InvalidParameter("force_ms".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// M/S 掩蔽调整（对应命令行的 `--ns-msfix`），必须是非负的有限值
+    ///
+    /// 未设置时由 LAME 按比特率选择。
+    #[inline(always)]
+    pub fn ms_fix(mut self, msfix: f32) -> Result<Self> {
+        self.set_ms_fix(msfix)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`ms_fix`](Self::ms_fix)
+    #[inline(always)]
+    pub fn set_ms_fix(&mut self, msfix: f32) -> Result<&mut Self> {
+        if !msfix.is_finite() || msfix < 0.0 {
+            return Err(LameError::InvalidParameter("ms_fix".to_string()));
+        }
+        let setting = Setting::MsFix(msfix as f64);
+        // SAFETY: 句柄在构建器存活期间有效；lame_set_msfix 只保存参数，没有返回值
+        unsafe { setting.apply(self.ptr()) };
+        self.settings.push(setting);
+        Ok(self)
+    }
+
+    /// 设置声道间的掩蔽比（0.0-1.0，不设置时由 LAME 决定）
+    ///
+    /// 一个声道的掩蔽阈值会参考另一个声道，比例越大，立体声两个声道的量化噪声越接近。
+    /// 对应 `lame_set_interChRatio`，单声道编码时没有作用。
+    #[inline(always)]
+    pub fn inter_channel_ratio(mut self, ratio: f32) -> Result<Self> {
+        self.set_inter_channel_ratio(ratio)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`inter_channel_ratio`](Self::inter_channel_ratio)
+    #[inline(always)]
+    pub fn set_inter_channel_ratio(&mut self, ratio: f32) -> Result<&mut Self> {
+        if !ratio.is_finite() || !self.set(ffi::lame_set_interChRatio, ratio) {
+            return Err(LameError::InvalidParameter(format!(
+                "inter_channel_ratio must be 0.0-1.0, got {}",
+                ratio
+            )));
+        }
+        Ok(self)
+    }
+
+    /// 设置 VBR 模式
+    #[inline(always)]
+    pub fn vbr_mode(mut self, mode: VbrMode) -> Result<Self> {
+        self.set_vbr_mode(mode)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`vbr_mode`](Self::vbr_mode)
+    #[inline(always)]
+    pub fn set_vbr_mode(&mut self, mode: VbrMode) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_VBR, mode as u32) {
+            return Err(LameError::InvalidParameter("vbr_mode".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 应用 LAME 预设
+    ///
+    /// 应当在其他编码参数之前调用，与显式设置的交互见 [`Preset`]。
+    /// `-V n` 预设本身不会打开 VBR，这里与命令行工具一样同时切换到 [`VbrMode::Vbr`]。
+    /// [`Preset::Abr`] 的比特率不在 8-320 kbps 范围内时返回错误。
+    #[inline(always)]
+    pub fn preset(mut self, preset: Preset) -> Result<Self> {
+        self.set_preset(preset)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`preset`](Self::preset)
+    #[inline(always)]
+    pub fn set_preset(&mut self, preset: Preset) -> Result<&mut Self> {
+        if let Preset::Abr(kbps) = preset {
+            if !(8..=320).contains(&kbps) {
+                return Err(LameError::InvalidParameter(format!(
+                    "preset: ABR bitrate must be 8-320 kbps, got {}",
+                    kbps
+                )));
+            }
+        }
+        if preset.is_vbr_quality() && !self.set(ffi::lame_set_VBR, VbrMode::Vbr as u32) {
+            return Err(LameError::InvalidParameter("preset".to_string()));
+        }
+        if !self.set(ffi::lame_set_preset, preset.lame_value()) {
+            return Err(LameError::InvalidParameter("preset".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置 ABR 模式的平均比特率（8-320 kbps）
+    ///
+    /// 只在 [`vbr_mode(VbrMode::Abr)`](Self::vbr_mode) 下生效；其他模式下设置会让
+    /// [`build`](Self::build) 返回 [`LameError::InvalidParameter`]。
+    #[inline(always)]
+    pub fn abr_bitrate(mut self, kbps: i32) -> Result<Self> {
+        self.set_abr_bitrate(kbps)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`abr_bitrate`](Self::abr_bitrate)
+    #[inline(always)]
+    pub fn set_abr_bitrate(&mut self, kbps: i32) -> Result<&mut Self> {
+        if !(8..=320).contains(&kbps) {
+            return Err(LameError::InvalidParameter(format!(
+                "abr_bitrate must be 8-320 kbps, got {}",
+                kbps
+            )));
+        }
+        if !self.set(ffi::lame_set_VBR_mean_bitrate_kbps, kbps) {
+            return Err(LameError::InvalidParameter("abr_bitrate".to_string()));
+        }
+        self.abr_bitrate_set = true;
+        Ok(self)
+    }
+
+    /// 设置 VBR 模式的最低比特率（8-320 kbps）
+    ///
+    /// 静音等简单段落默认仍可能低于该值，需要严格保证时配合
+    /// [`vbr_enforce_min(true)`](Self::vbr_enforce_min)。
+    /// 与 [`vbr_max_bitrate`](Self::vbr_max_bitrate) 同时设置时，最低值大于最高值会让
+    /// [`build`](Self::build) 返回 [`LameError::InvalidParameter`]。
+    #[inline(always)]
+    pub fn vbr_min_bitrate(mut self, kbps: i32) -> Result<Self> {
+        self.set_vbr_min_bitrate(kbps)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`vbr_min_bitrate`](Self::vbr_min_bitrate)
+    #[inline(always)]
+    pub fn set_vbr_min_bitrate(&mut self, kbps: i32) -> Result<&mut Self> {
+        if !(8..=320).contains(&kbps) {
+            return Err(LameError::InvalidParameter(format!(
+                "vbr_min_bitrate must be 8-320 kbps, got {}",
+                kbps
+            )));
+        }
+        if !self.set(ffi::lame_set_VBR_min_bitrate_kbps, kbps) {
+            return Err(LameError::InvalidParameter("vbr_min_bitrate".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置 VBR 模式的最高比特率（8-320 kbps）
+    #[inline(always)]
+    pub fn vbr_max_bitrate(mut self, kbps: i32) -> Result<Self> {
+        self.set_vbr_max_bitrate(kbps)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`vbr_max_bitrate`](Self::vbr_max_bitrate)
+    #[inline(always)]
+    pub fn set_vbr_max_bitrate(&mut self, kbps: i32) -> Result<&mut Self> {
+        if !(8..=320).contains(&kbps) {
+            return Err(LameError::InvalidParameter(format!(
+                "vbr_max_bitrate must be 8-320 kbps, got {}",
+                kbps
+            )));
+        }
+        if !self.set(ffi::lame_set_VBR_max_bitrate_kbps, kbps) {
+            return Err(LameError::InvalidParameter("vbr_max_bitrate".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 是否严格遵守 VBR 最低比特率（默认否）
+    ///
+    /// 默认情况下 LAME 会在数字静音的帧上低于
+    /// [`vbr_min_bitrate`](Self::vbr_min_bitrate)；打开后所有帧都不低于该值。
+    #[inline(always)]
+    pub fn vbr_enforce_min(mut self, enforce: bool) -> Result<Self> {
+        self.set_vbr_enforce_min(enforce)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`vbr_enforce_min`](Self::vbr_enforce_min)
+    #[inline(always)]
+    pub fn set_vbr_enforce_min(&mut self, enforce: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_VBR_hard_min, enforce as i32) {
+            return Err(LameError::InvalidParameter("vbr_enforce_min".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 预先告知输入的总样本数（每个声道）
+    ///
+    /// 编码已知长度的文件时设置，LAME 会在信息帧（Xing/Info）中写入准确的总帧数，
+    /// 并据此改善流末尾的比特分配。LAME 使用 `unsigned long`，在 32 位平台和 Windows 上
+    /// 超过 `u32::MAX` 的值返回 [`LameError::InvalidParameter`]。
+    #[inline(always)]
+    pub fn total_samples(mut self, n: u64) -> Result<Self> {
+        self.set_total_samples(n)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`total_samples`](Self::total_samples)
+    #[inline(always)]
+    pub fn set_total_samples(&mut self, n: u64) -> Result<&mut Self> {
+        if n > c_ulong::MAX as u64 {
+            return Err(LameError::InvalidParameter(format!(
+                "total_samples {} does not fit in unsigned long on this platform",
+                n
+            )));
+        }
+        if !self.set(ffi::lame_set_num_samples, n as c_ulong) {
+            return Err(LameError::InvalidParameter("total_samples".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 编码时计算 ReplayGain（默认关闭），与 `lame --replaygain-fast` 相同
+    ///
+    /// 分析的是输入的 PCM，结果在 flush 之后由 [`LameEncoder::replay_gain`] 读取。
+    /// 分析会额外占用 CPU。
+    #[inline(always)]
+    pub fn find_replay_gain(mut self, enabled: bool) -> Result<Self> {
+        self.set_find_replay_gain(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`find_replay_gain`](Self::find_replay_gain)
+    #[inline(always)]
+    pub fn set_find_replay_gain(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_findReplayGain, enabled as i32) {
+            return Err(LameError::InvalidParameter("find_replay_gain".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 边编码边解码（默认关闭），用于找出峰值样本
+    ///
+    /// 开启后 [`LameEncoder::peak_sample`] 报告解码输出的峰值；同时开启
+    /// [`find_replay_gain`](Self::find_replay_gain) 时 ReplayGain 基于解码结果计算，
+    /// 与 `lame --replaygain-accurate` 相同。
+    ///
+    /// 需要 LAME 带有内置解码器（mpglib）。本 crate 自带的 LAME 编译时关闭了解码器，
+    /// 此时开启会返回 [`LameError::InvalidParameter`]；关闭总是成功。
+    #[inline(always)]
+    pub fn decode_on_the_fly(mut self, enabled: bool) -> Result<Self> {
+        self.set_decode_on_the_fly(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`decode_on_the_fly`](Self::decode_on_the_fly)
+    #[inline(always)]
+    pub fn set_decode_on_the_fly(&mut self, enabled: bool) -> Result<&mut Self> {
+        // 没有解码器的 LAME 对 0 也返回错误，但这时本来就是关闭的
+        if !self.set(ffi::lame_set_decode_on_the_fly, enabled as i32) && enabled {
+            return Err(LameError::InvalidParameter(
+                "decode_on_the_fly requires LAME built with its decoder".to_string(),
+            ));
+        }
+        Ok(self)
+    }
+
+    /// 设置无缝分段的总段数，与 `lame --nogap` 相同
+    ///
+    /// 用于把连续的音频分成多个无缝衔接的文件，调用顺序见
+    /// [`LameEncoder::flush_nogap`]。LAME 用它在信息帧中标记前后是否还有衔接的段。
+    #[inline(always)]
+    pub fn nogap_total(mut self, total: i32) -> Result<Self> {
+        self.set_nogap_total(total)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`nogap_total`](Self::nogap_total)
+    #[inline(always)]
+    pub fn set_nogap_total(&mut self, total: i32) -> Result<&mut Self> {
+        if total < 1 || !self.set(ffi::lame_set_nogap_total, total) {
+            return Err(LameError::InvalidParameter(format!(
+                "nogap_total must be at least 1, got {}",
+                total
+            )));
+        }
+        Ok(self)
+    }
+
+    /// 设置第一段的序号（默认 0），之后用 [`LameEncoder::set_nogap_current_index`] 切换
+    ///
+    /// 必须小于 [`nogap_total`](Self::nogap_total)，在 `build()` 时检查。
+    #[inline(always)]
+    pub fn nogap_current_index(mut self, index: i32) -> Result<Self> {
+        self.set_nogap_current_index(index)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`nogap_current_index`](Self::nogap_current_index)
+    #[inline(always)]
+    pub fn set_nogap_current_index(&mut self, index: i32) -> Result<&mut Self> {
+        if index < 0 || !self.set(ffi::lame_set_nogap_currentindex, index) {
+            return Err(LameError::InvalidParameter(format!(
+                "nogap_current_index must not be negative, got {}",
+                index
+            )));
+        }
+        Ok(self)
+    }
+
+    /// 设置 VBR 质量（0-9，0 = 最高质量）
+    #[inline(always)]
+    pub fn vbr_quality(mut self, quality: i32) -> Result<Self> {
+        self.set_vbr_quality(quality)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`vbr_quality`](Self::vbr_quality)
+    #[inline(always)]
+    pub fn set_vbr_quality(&mut self, quality: i32) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_VBR_q, quality) {
+            return Err(LameError::InvalidParameter("vbr_quality".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置低通滤波器的截止频率（Hz）
+    ///
+    /// `0` 表示由 LAME 按比特率自动选择（默认），`-1` 表示关闭。
+    /// 语音可以设为 8000 左右，把码率留给有用的频段。
+    #[inline(always)]
+    pub fn lowpass(mut self, freq_hz: i32) -> Result<Self> {
+        self.set_lowpass(freq_hz)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`lowpass`](Self::lowpass)
+    #[inline(always)]
+    pub fn set_lowpass(&mut self, freq_hz: i32) -> Result<&mut Self> {
+        if freq_hz < -1 || !self.set(ffi::lame_set_lowpassfreq, freq_hz) {
+            return Err(LameError::InvalidParameter("lowpass".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置低通滤波器的过渡带宽度（Hz）
+    ///
+    /// `-1` 表示使用 LAME 的默认宽度（截止频率的 15%）。
+    #[inline(always)]
+    pub fn lowpass_width(mut self, width_hz: i32) -> Result<Self> {
+        self.set_lowpass_width(width_hz)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`lowpass_width`](Self::lowpass_width)
+    #[inline(always)]
+    pub fn set_lowpass_width(&mut self, width_hz: i32) -> Result<&mut Self> {
+        if width_hz < -1 || !self.set(ffi::lame_set_lowpasswidth, width_hz) {
+            return Err(LameError::InvalidParameter("lowpass_width".to_string()));
         }
         Ok(self)
     }
 
-    /// 设置 VBR 模式
+    /// 设置高通滤波器的截止频率（Hz）
+    ///
+    /// `0` 表示由 LAME 自动选择（默认），`-1` 表示关闭。
     #[inline(always)]
-    pub fn vbr_mode(self, mode: VbrMode) -> Result<Self> {
-        unsafe {
-            if ffi::lame_set_VBR(self.ptr(), mode as u32) < 0 {
-                return Err(LameError::InvalidParameter("vbr_mode".to_string()));
+    pub fn highpass(mut self, freq_hz: i32) -> Result<Self> {
+        self.set_highpass(freq_hz)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`highpass`](Self::highpass)
+    #[inline(always)]
+    pub fn set_highpass(&mut self, freq_hz: i32) -> Result<&mut Self> {
+        if freq_hz < -1 || !self.set(ffi::lame_set_highpassfreq, freq_hz) {
+            return Err(LameError::InvalidParameter("highpass".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置高通滤波器的过渡带宽度（Hz）
+    ///
+    /// `-1` 表示使用 LAME 的默认宽度。
+    #[inline(always)]
+    pub fn highpass_width(mut self, width_hz: i32) -> Result<Self> {
+        self.set_highpass_width(width_hz)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`highpass_width`](Self::highpass_width)
+    #[inline(always)]
+    pub fn set_highpass_width(&mut self, width_hz: i32) -> Result<&mut Self> {
+        if width_hz < -1 || !self.set(ffi::lame_set_highpasswidth, width_hz) {
+            return Err(LameError::InvalidParameter("highpass_width".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 编码前把输入乘以 `scale`（默认 1.0）
+    ///
+    /// 可用于提升过轻的录音。放大后超出满量程的部分在解码时会削波。
+    #[inline(always)]
+    pub fn scale(mut self, scale: f32) -> Result<Self> {
+        self.set_scale(scale)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`scale`](Self::scale)
+    #[inline(always)]
+    pub fn set_scale(&mut self, scale: f32) -> Result<&mut Self> {
+        if !scale.is_finite() || !self.set(ffi::lame_set_scale, scale) {
+            return Err(LameError::InvalidParameter("scale".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 编码前把左声道乘以 `scale`，与 [`scale`](Self::scale) 叠加
+    #[inline(always)]
+    pub fn scale_left(mut self, scale: f32) -> Result<Self> {
+        self.set_scale_left(scale)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`scale_left`](Self::scale_left)
+    #[inline(always)]
+    pub fn set_scale_left(&mut self, scale: f32) -> Result<&mut Self> {
+        if !scale.is_finite() || !self.set(ffi::lame_set_scale_left, scale) {
+            return Err(LameError::InvalidParameter("scale_left".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 编码前把右声道乘以 `scale`，与 [`scale`](Self::scale) 叠加
+    #[inline(always)]
+    pub fn scale_right(mut self, scale: f32) -> Result<Self> {
+        self.set_scale_right(scale)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`scale_right`](Self::scale_right)
+    #[inline(always)]
+    pub fn set_scale_right(&mut self, scale: f32) -> Result<&mut Self> {
+        if !scale.is_finite() || !self.set(ffi::lame_set_scale_right, scale) {
+            return Err(LameError::InvalidParameter("scale_right".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 只用绝对听阈（ATH）决定量化噪声，不使用心理声学模型（默认否）
+    #[inline(always)]
+    pub fn ath_only(mut self, enabled: bool) -> Result<Self> {
+        self.set_ath_only(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`ath_only`](Self::ath_only)
+    #[inline(always)]
+    pub fn set_ath_only(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_ATHonly, enabled as i32) {
+            return Err(LameError::InvalidParameter("ath_only".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 完全不使用绝对听阈（默认否）
+    #[inline(always)]
+    pub fn no_ath(mut self, enabled: bool) -> Result<Self> {
+        self.set_no_ath(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`no_ath`](Self::no_ath)
+    #[inline(always)]
+    pub fn set_no_ath(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_noATH, enabled as i32) {
+            return Err(LameError::InvalidParameter("no_ath".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 选择绝对听阈曲线的类型，对应命令行的 `--athtype`
+    #[inline(always)]
+    pub fn ath_type(mut self, ath_type: i32) -> Result<Self> {
+        self.set_ath_type(ath_type)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`ath_type`](Self::ath_type)
+    #[inline(always)]
+    pub fn set_ath_type(&mut self, ath_type: i32) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_ATHtype, ath_type) {
+            return Err(LameError::InvalidParameter("ath_type".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 把绝对听阈整体降低 `db` 分贝，对应命令行的 `--athlower`
+    ///
+    /// 正值让更安静的细节也被编码，会多占用码率。
+    #[inline(always)]
+    pub fn ath_lower(mut self, db: f32) -> Result<Self> {
+        self.set_ath_lower(db)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`ath_lower`](Self::ath_lower)
+    #[inline(always)]
+    pub fn set_ath_lower(&mut self, db: f32) -> Result<&mut Self> {
+        if !db.is_finite() || !self.set(ffi::lame_set_ATHlower, db) {
+            return Err(LameError::InvalidParameter("ath_lower".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 自适应 ATH 的灵敏度（dB），对应命令行的 `--athaa-sensitivity`
+    #[inline(always)]
+    pub fn athaa_sensitivity(mut self, db: f32) -> Result<Self> {
+        self.set_athaa_sensitivity(db)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`athaa_sensitivity`](Self::athaa_sensitivity)
+    #[inline(always)]
+    pub fn set_athaa_sensitivity(&mut self, db: f32) -> Result<&mut Self> {
+        if !db.is_finite() || !self.set(ffi::lame_set_athaa_sensitivity, db) {
+            return Err(LameError::InvalidParameter("athaa_sensitivity".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 是否使用时域掩蔽效应（默认使用），关闭对应命令行的 `--notemp`
+    #[inline(always)]
+    pub fn use_temporal_masking(mut self, enabled: bool) -> Result<Self> {
+        self.set_use_temporal_masking(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`use_temporal_masking`](Self::use_temporal_masking)
+    #[inline(always)]
+    pub fn set_use_temporal_masking(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_useTemporal, enabled as i32) {
+            return Err(LameError::InvalidParameter(
+                "use_temporal_masking".to_string(),
+            ));
+        }
+        Ok(self)
+    }
+
+    /// 设置短块的使用方式，参见 [`ShortBlockMode`]
+    #[inline(always)]
+    pub fn short_blocks(mut self, mode: ShortBlockMode) -> Result<Self> {
+        self.set_short_blocks(mode)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`short_blocks`](Self::short_blocks)
+    #[inline(always)]
+    pub fn set_short_blocks(&mut self, mode: ShortBlockMode) -> Result<&mut Self> {
+        // 两个标志都要设置，否则切换模式时会留下之前的强制或禁用
+        let ok = match mode {
+            ShortBlockMode::Allowed => {
+                self.set(ffi::lame_set_force_short_blocks, 0)
+                    && self.set(ffi::lame_set_no_short_blocks, 0)
+            }
+            ShortBlockMode::Dispensed => {
+                self.set(ffi::lame_set_force_short_blocks, 0)
+                    && self.set(ffi::lame_set_no_short_blocks, 1)
             }
+            ShortBlockMode::Forced => {
+                self.set(ffi::lame_set_no_short_blocks, 0)
+                    && self.set(ffi::lame_set_force_short_blocks, 1)
+            }
+        };
+        if !ok {
+            return Err(LameError::InvalidParameter("short_blocks".to_string()));
         }
         Ok(self)
     }
 
-    /// 设置 VBR 质量（0-9，0 = 最高质量）
+    /// 是否在流的开头写入 LAME 信息帧（Xing/Info，默认写入）
+    ///
+    /// 信息帧是一个占位帧，播放器用它获取时长和编码延迟。
+    /// 分段输出时只出现在第一段，参见 [`Mp3Writer::rotate`](crate::Mp3Writer::rotate)。
     #[inline(always)]
-    pub fn vbr_quality(self, quality: i32) -> Result<Self> {
-        unsafe {
-            if ffi::lame_set_VBR_q(self.ptr(), quality) < 0 {
-                return Err(LameError::InvalidParameter("vbr_quality".to_string()));
+    pub fn write_vbr_tag(mut self, enabled: bool) -> Result<Self> {
+        self.set_write_vbr_tag(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`write_vbr_tag`](Self::write_vbr_tag)
+    #[inline(always)]
+    pub fn set_write_vbr_tag(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_bWriteVbrTag, enabled as i32) {
+            return Err(LameError::InvalidParameter("write_vbr_tag".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 是否由 LAME 把 ID3 标签写入输出（默认写入）
+    ///
+    /// 关闭后 LAME 不在流的开头写 ID3v2 标签，也不在 [`flush`](LameEncoder::flush) 时
+    /// 追加 ID3v1 标签。标签要写到容器的其他位置时，先关闭这一项，再用
+    /// [`LameEncoder::id3v2_tag`] 和 [`LameEncoder::id3v1_tag`] 取出标签自行写入，
+    /// 否则同一份标签会出现两次。
+    #[inline(always)]
+    pub fn write_id3_automatic(mut self, enabled: bool) -> Result<Self> {
+        self.set_write_id3_automatic(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`write_id3_automatic`](Self::write_id3_automatic)
+    #[inline(always)]
+    pub fn set_write_id3_automatic(&mut self, enabled: bool) -> Result<&mut Self> {
+        let setting = Setting::WriteId3Automatic(enabled);
+        // SAFETY: 句柄在构建器存活期间有效；lame_set_write_id3tag_automatic 只保存参数
+        unsafe { setting.apply(self.ptr()) };
+        self.settings.push(setting);
+        Ok(self)
+    }
+
+    /// 是否为每帧加入 CRC 校验（默认否）
+    ///
+    /// 每帧多占用 2 字节，广播等需要检测传输错误的场合使用。
+    #[inline(always)]
+    pub fn error_protection(mut self, enabled: bool) -> Result<Self> {
+        self.set_error_protection(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`error_protection`](Self::error_protection)
+    #[inline(always)]
+    pub fn set_error_protection(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_error_protection, enabled as i32) {
+            return Err(LameError::InvalidParameter("error_protection".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置帧头的 copyright 位（默认否）
+    #[inline(always)]
+    pub fn copyright(mut self, enabled: bool) -> Result<Self> {
+        self.set_copyright(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`copyright`](Self::copyright)
+    #[inline(always)]
+    pub fn set_copyright(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_copyright, enabled as i32) {
+            return Err(LameError::InvalidParameter("copyright".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置帧头的 original 位（默认是）
+    #[inline(always)]
+    pub fn original(mut self, enabled: bool) -> Result<Self> {
+        self.set_original(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`original`](Self::original)
+    #[inline(always)]
+    pub fn set_original(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_original, enabled as i32) {
+            return Err(LameError::InvalidParameter("original".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置帧头的去加重标记（默认 [`Emphasis::None`]）
+    #[inline(always)]
+    pub fn emphasis(mut self, emphasis: Emphasis) -> Result<Self> {
+        self.set_emphasis(emphasis)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`emphasis`](Self::emphasis)
+    #[inline(always)]
+    pub fn set_emphasis(&mut self, emphasis: Emphasis) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_emphasis, emphasis as i32) {
+            return Err(LameError::InvalidParameter("emphasis".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 设置帧头的 private 位（默认否），对应 `lame_set_extension`
+    #[inline(always)]
+    pub fn private_bit(mut self, enabled: bool) -> Result<Self> {
+        self.set_private_bit(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`private_bit`](Self::private_bit)
+    #[inline(always)]
+    pub fn set_private_bit(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_extension, enabled as i32) {
+            return Err(LameError::InvalidParameter("private_bit".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 是否使用自由格式（free format）比特率（默认否）
+    ///
+    /// 自由格式的帧头不记录比特率，[`bitrate`](Self::bitrate) 可以是 8-640 kbps 之间的任意值，
+    /// 超出范围时 [`build`](Self::build) 返回 [`LameError::InvalidParameter`]。
+    /// 只在 CBR 下生效。
+    ///
+    /// **大多数解码器和播放器无法播放自由格式的文件**，只适合实验和测试用途。
+    #[inline(always)]
+    pub fn free_format(mut self, enabled: bool) -> Result<Self> {
+        self.set_free_format(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`free_format`](Self::free_format)
+    #[inline(always)]
+    pub fn set_free_format(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_free_format, enabled as i32) {
+            return Err(LameError::InvalidParameter("free_format".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 是否严格遵守 ISO 标准（默认否）
+    ///
+    /// 打开后 LAME 不使用标准之外的扩展，例如超出 ISO 限制的帧大小，
+    /// 用于兼容实现较严格的硬件解码器。
+    #[inline(always)]
+    pub fn strict_iso(mut self, enabled: bool) -> Result<Self> {
+        self.set_strict_iso(enabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`strict_iso`](Self::strict_iso)
+    #[inline(always)]
+    pub fn set_strict_iso(&mut self, enabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_strict_ISO, enabled as i32) {
+            return Err(LameError::InvalidParameter("strict_iso".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// 是否关闭比特池（bit reservoir，默认开启）
+    ///
+    /// 关闭后每帧的数据都放在本帧内，不借用前面帧的空间，解码器不需要缓存之前的帧。
+    /// 代价是相同比特率下音质略差。
+    #[inline(always)]
+    pub fn disable_bit_reservoir(mut self, disabled: bool) -> Result<Self> {
+        self.set_disable_bit_reservoir(disabled)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`disable_bit_reservoir`](Self::disable_bit_reservoir)
+    #[inline(always)]
+    pub fn set_disable_bit_reservoir(&mut self, disabled: bool) -> Result<&mut Self> {
+        if !self.set(ffi::lame_set_disable_reservoir, disabled as i32) {
+            return Err(LameError::InvalidParameter(
+                "disable_bit_reservoir".to_string(),
+            ));
+        }
+        Ok(self)
+    }
+
+    /// 设置未封装的 LAME 参数，见 [`param`](crate::param) 模块
+    ///
+    /// 浮点参数也接受 [`RawValue::Int`]；整数参数传入 [`RawValue::Float`]、
+    /// 浮点值不是有限数或 LAME 拒绝该值时返回 [`LameError::InvalidParameter`]，
+    /// 信息中包含参数名。
+    #[inline(always)]
+    pub fn set_raw(mut self, param: RawParam, value: RawValue) -> Result<Self> {
+        self.set_raw_param(param, value)?;
+        Ok(self)
+    }
+
+    /// 以 `&mut self` 方式调用 [`set_raw`](Self::set_raw)
+    #[inline(always)]
+    pub fn set_raw_param(&mut self, param: RawParam, value: RawValue) -> Result<&mut Self> {
+        let accepted = match (param.accessor(), value) {
+            (Accessor::Int(setter, _), RawValue::Int(value)) => self.set(setter, value),
+            (Accessor::Int(..), RawValue::Float(_)) => {
+                return Err(LameError::InvalidParameter(format!(
+                    "{} expects an integer value",
+                    param.name()
+                )));
+            }
+            (Accessor::Float(setter, _), value) => {
+                let value = value.as_float();
+                value.is_finite() && self.set(setter, value)
             }
+        };
+
+        if !accepted {
+            return Err(LameError::InvalidParameter(param.name().to_string()));
         }
         Ok(self)
     }
@@ -361,27 +3174,207 @@ impl EncoderBuilder {
     ///
     /// 完成配置并创建可用的编码器。此方法会调用 `lame_init_params()` 来最终确定所有设置。
     #[inline(always)]
-    pub fn build(self) -> Result<LameEncoder> {
-        unsafe {
-            // 初始化参数（所有配置都已在 setter 中设置完成）
-            if ffi::lame_init_params(self.ptr()) < 0 {
-                return Err(LameError::InitializationFailed);
+    pub fn build(mut self) -> Result<LameEncoder> {
+        // SAFETY: 句柄在构建器存活期间有效，lame_get_VBR 只读取参数
+        let vbr_mode = unsafe { ffi::lame_get_VBR(self.ptr()) };
+        if self.abr_bitrate_set && vbr_mode != VbrMode::Abr as u32 {
+            return Err(LameError::InvalidParameter(
+                "abr_bitrate requires vbr_mode(VbrMode::Abr)".to_string(),
+            ));
+        }
+
+        // SAFETY: 同上，只读取参数；未设置时为 0
+        let (vbr_min, vbr_max) = unsafe {
+            (
+                ffi::lame_get_VBR_min_bitrate_kbps(self.ptr()),
+                ffi::lame_get_VBR_max_bitrate_kbps(self.ptr()),
+            )
+        };
+        if vbr_min > 0 && vbr_max > 0 && vbr_min > vbr_max {
+            return Err(LameError::InvalidParameter(format!(
+                "vbr_min_bitrate ({} kbps) exceeds vbr_max_bitrate ({} kbps)",
+                vbr_min, vbr_max
+            )));
+        }
+
+        // SAFETY: 同上，只读取参数
+        let (free_format, brate) = unsafe {
+            (
+                ffi::lame_get_free_format(self.ptr()),
+                ffi::lame_get_brate(self.ptr()),
+            )
+        };
+        if free_format != 0 && vbr_mode == VbrMode::Off as u32 && !(8..=640).contains(&brate) {
+            return Err(LameError::InvalidParameter(format!(
+                "free_format bitrate must be 8-640 kbps, got {}",
+                brate
+            )));
+        }
+
+        self.check_format(vbr_mode == VbrMode::Off as u32 && free_format == 0, brate)?;
+
+        // 以上检查只覆盖常见的错误组合，lame_init_params 仍然是最后一道检查
+        self.init_params()?;
+
+        // 转移所有权给 LameEncoder，防止 Drop 释放
+        let inner = self.inner;
+        let settings = std::mem::take(&mut self.settings);
+        std::mem::forget(self);
+
+        Ok(LameEncoder {
+            gfp: inner,
+            settings: Some(settings),
+            scratch: Vec::new(),
+            scratch_i32: Vec::new(),
+            scratch_mp3: Vec::new(),
+            max_samples_per_call: None,
+            cancel_token: None,
+            progress_hook: None,
+            gain_analyzed: false,
+            last_error: None,
+            state: EncoderState::Ready,
+        })
+    }
+
+    /// 构建编码器，并和下游写入器组合为 [`StreamingEncoder`]
+    ///
+    /// 它在 drop 时自动刷新编码器，提前返回也不会截断输出。
+    pub fn sink<W: std::io::Write>(self, inner: W) -> Result<StreamingEncoder<W>> {
+        Ok(StreamingEncoder::new(self.build()?, inner))
+    }
+}
+
+impl EncoderBuilder {
+    /// 调用 `lame_init_params`，失败时的错误信息包含 LAME 输出的错误
+    ///
+    /// LAME 3.100 在参数无效时直接返回 -1，不输出任何消息，此时错误信息列出它检查的参数。
+    pub(crate) fn init_params(&self) -> Result<()> {
+        let (status, lame_output) = crate::report::collect_errors(|| {
+            // SAFETY: 句柄有效，且尚未调用过 lame_init_params
+            unsafe { ffi::lame_init_params(self.ptr()) }
+        });
+        if status >= 0 {
+            if let Some(text) = lame_output {
+                crate::report::emit_error(&text);
             }
+            return Ok(());
+        }
+
+        let message = lame_output.unwrap_or_else(|| {
+            // SAFETY: 句柄有效，lame_get_* 只读取参数
+            let (in_rate, out_rate, channels) = unsafe {
+                (
+                    ffi::lame_get_in_samplerate(self.ptr()),
+                    ffi::lame_get_out_samplerate(self.ptr()),
+                    ffi::lame_get_num_channels(self.ptr()),
+                )
+            };
+            format!(
+                "lame_init_params() returned {} (input_sample_rate {} Hz, \
+                 output_sample_rate {} Hz, channels {})",
+                status, in_rate, out_rate, channels
+            )
+        });
+        Err(LameError::InitializationFailed(message))
+    }
+
+    /// 检查采样率、声道数、比特率和 VBR 质量的组合，错误信息列出允许的值
+    ///
+    /// `cbr` 为 `true` 时比特率必须是输出采样率对应的 MPEG 版本的标准比特率；
+    /// 未指定输出采样率时，只要求是某个 MPEG 版本的标准比特率，由 LAME 选择匹配的采样率。
+    fn check_format(&self, cbr: bool, brate: i32) -> Result<()> {
+        // SAFETY: 句柄在构建器存活期间有效，lame_get_* 只读取参数
+        let (in_rate, out_rate, channels, vbr_q) = unsafe {
+            (
+                ffi::lame_get_in_samplerate(self.ptr()),
+                ffi::lame_get_out_samplerate(self.ptr()),
+                ffi::lame_get_num_channels(self.ptr()),
+                ffi::lame_get_VBR_q(self.ptr()),
+            )
+        };
 
-            // 转移所有权给 LameEncoder，防止 Drop 释放
-            let inner = self.inner;
-            std::mem::forget(self);
+        if in_rate <= 0 {
+            return Err(LameError::InvalidParameter(format!(
+                "input_sample_rate must be positive, got {} Hz",
+                in_rate
+            )));
+        }
+        if out_rate != 0 && !MPEG_SAMPLE_RATES.contains(&out_rate) {
+            return Err(LameError::InvalidParameter(format!(
+                "output_sample_rate {} Hz is not an MPEG sample rate; allowed: {}",
+                out_rate,
+                list_values(&MPEG_SAMPLE_RATES)
+            )));
+        }
+        if !(1..=2).contains(&channels) {
+            return Err(LameError::InvalidParameter(format!(
+                "channels must be 1 or 2, got {}",
+                channels
+            )));
+        }
+        if !(0..=9).contains(&vbr_q) {
+            return Err(LameError::InvalidParameter(format!(
+                "vbr_quality must be 0-9, got {}",
+                vbr_q
+            )));
+        }
+        // SAFETY: 同上
+        let (nogap_total, nogap_index) = unsafe {
+            (
+                ffi::lame_get_nogap_total(self.ptr()),
+                ffi::lame_get_nogap_currentindex(self.ptr()),
+            )
+        };
+        if nogap_index > 0 && nogap_index >= nogap_total {
+            return Err(LameError::InvalidParameter(format!(
+                "nogap_current_index {} must be less than nogap_total {}",
+                nogap_index, nogap_total
+            )));
+        }
 
-            Ok(LameEncoder { gfp: inner })
+        // 0 表示未设置，由 LAME 选择默认比特率
+        if !cbr || brate == 0 {
+            return Ok(());
+        }
+        let brate = brate as usize;
+        let mpeg1 = &crate::writer::MPEG1_BITRATES[1..];
+        let mpeg2 = &crate::writer::MPEG2_BITRATES[1..];
+        let (version, allowed) = match out_rate {
+            0 if mpeg1.contains(&brate) || mpeg2.contains(&brate) => return Ok(()),
+            0 => {
+                let mut allowed: Vec<usize> = mpeg2.iter().chain(mpeg1).copied().collect();
+                allowed.sort_unstable();
+                allowed.dedup();
+                return Err(LameError::InvalidParameter(format!(
+                    "bitrate {} kbps is not a standard MP3 bitrate; allowed: {}",
+                    brate,
+                    list_values(&allowed)
+                )));
+            }
+            32000.. => ("MPEG-1", mpeg1),
+            16000..=24000 => ("MPEG-2", mpeg2),
+            _ => ("MPEG-2.5", mpeg2),
+        };
+        if !allowed.contains(&brate) {
+            return Err(LameError::InvalidParameter(format!(
+                "bitrate {} kbps is not valid for {} at {} Hz; allowed: {}",
+                brate,
+                version,
+                out_rate,
+                list_values(allowed)
+            )));
         }
+        Ok(())
     }
 }
 
 impl Drop for EncoderBuilder {
     fn drop(&mut self) {
         // 清理 LAME C 结构体（如果 build() 未被调用）
+        // SAFETY: build() 会 forget 构建器，走到这里时句柄仍归构建器所有且只释放一次
         unsafe {
             ffi::lame_close(self.ptr());
         }
+        crate::lifecycle::handle_closed();
     }
 }
@@ -1,17 +1,22 @@
-use std::fmt;
 use std::error::Error;
+use std::fmt;
 
 /// LAME 编码器错误类型
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LameError {
-    /// 初始化失败
-    InitializationFailed,
+    /// 初始化失败，信息为 LAME 输出的错误，LAME 没有输出时为失败的调用和相关参数
+    InitializationFailed(String),
 
     /// 参数设置失败
     InvalidParameter(String),
 
     /// 编码失败
-    EncodingFailed(i32),
+    EncodingFailed {
+        /// `lame_encode_*` 或 `lame_encode_flush*` 返回的负数
+        code: i32,
+        /// LAME 在这次调用中输出的错误，没有输出时为 `code` 的含义
+        message: String,
+    },
 
     /// 缓冲区太小
     BufferTooSmall { required: usize, provided: usize },
@@ -25,24 +30,43 @@ pub enum LameError {
     /// 内部错误
     InternalError(String),
 
+    /// 编码器当前的状态不允许这个调用，例如 `flush` 之后继续编码
+    InvalidState(&'static str),
+
     /// UTF-8 字符串转换错误
     Utf8Error(std::str::Utf8Error),
 
     /// 空指针错误
     NullPointer,
+
+    /// 写入下游时的 I/O 错误
+    Io {
+        /// 原始错误的 [`std::io::ErrorKind`]
+        kind: std::io::ErrorKind,
+        /// 原始错误的描述
+        message: String,
+    },
+
+    /// 分段编码被取消，`bytes_written` 为取消前已写入输出缓冲区的字节数
+    Cancelled { bytes_written: usize },
+
+    /// 下游暂时无法写入（`WouldBlock`），`buffered` 字节的输出暂存在写入器中
+    ///
+    /// 输入已经处理完毕，下游可写后调用 [`Mp3Writer::resume`](crate::Mp3Writer::resume) 继续。
+    Pending { buffered: usize },
 }
 
 impl fmt::Display for LameError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            LameError::InitializationFailed => {
-                write!(f, "Failed to initialize LAME encoder")
+            LameError::InitializationFailed(msg) => {
+                write!(f, "Failed to initialize LAME encoder: {}", msg)
             }
             LameError::InvalidParameter(msg) => {
                 write!(f, "Invalid parameter: {}", msg)
             }
-            LameError::EncodingFailed(code) => {
-                write!(f, "Encoding failed with code: {}", code)
+            LameError::EncodingFailed { code, message } => {
+                write!(f, "Encoding failed with code {}: {}", code, message)
             }
             LameError::BufferTooSmall { required, provided } => {
                 write!(
@@ -60,12 +84,28 @@ impl fmt::Display for LameError {
             LameError::InternalError(msg) => {
                 write!(f, "Internal error: {}", msg)
             }
+            LameError::InvalidState(msg) => {
+                write!(f, "Invalid encoder state: {}", msg)
+            }
             LameError::Utf8Error(err) => {
                 write!(f, "UTF-8 conversion error: {}", err)
             }
             LameError::NullPointer => {
                 write!(f, "Unexpected null pointer")
             }
+            LameError::Io { message, .. } => {
+                write!(f, "I/O error: {}", message)
+            }
+            LameError::Cancelled { bytes_written } => {
+                write!(f, "Encoding cancelled after {} bytes", bytes_written)
+            }
+            LameError::Pending { buffered } => {
+                write!(
+                    f,
+                    "Output pending: {} bytes buffered until the sink is writable",
+                    buffered
+                )
+            }
         }
     }
 }
@@ -85,11 +125,39 @@ impl From<std::str::Utf8Error> for LameError {
     }
 }
 
+impl From<std::io::Error> for LameError {
+    fn from(err: std::io::Error) -> Self {
+        LameError::Io {
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
 impl From<std::ffi::NulError> for LameError {
     fn from(_: std::ffi::NulError) -> Self {
         LameError::InvalidInput("String contains null byte".to_string())
     }
 }
 
+impl LameError {
+    /// 按 `lame_encode_*` 的返回值构造 [`LameError::EncodingFailed`]
+    ///
+    /// `lame_output` 为调用期间 LAME 输出的错误，没有时使用 `lame.h` 中对返回值的说明。
+    pub(crate) fn encoding_failed(code: i32, lame_output: Option<String>) -> Self {
+        let message = lame_output.unwrap_or_else(|| {
+            match code {
+                -1 => "mp3 buffer too small",
+                -2 => "memory allocation failed",
+                -3 => "lame_init_params() not called",
+                -4 => "psychoacoustic model problem",
+                _ => "unknown error",
+            }
+            .to_string()
+        });
+        LameError::EncodingFailed { code, message }
+    }
+}
+
 /// Result 类型别名
 pub type Result<T> = std::result::Result<T, LameError>;
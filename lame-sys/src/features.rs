@@ -0,0 +1,185 @@
+//! 链接的 LAME 版本能力
+//!
+//! 部分 API 只存在于较新的 LAME 中（例如 3.99 的小数 VBR 质量、3.100 的 UTF-16 标签）。
+//! 构建脚本会探测绑定的 `lame.h`，为存在的符号输出 `lame_has_*` cfg 标志；
+//! 依赖这些符号的封装在标志缺失时返回 [`LameError::InternalError`]，而不是链接失败。
+//!
+//! # 示例
+//!
+//! ```no_run
+//! use lame_sys::features::{build_info, Feature};
+//!
+//! let info = build_info();
+//! println!("LAME {} (header {})", info.lame_version, info.header_version);
+//!
+//! if Feature::Utf16Tags.is_available() {
+//!     // 可以使用 UTF-16 ID3v2 标签
+//! }
+//! ```
+
+use crate::error::{LameError, Result};
+
+/// 与 LAME 版本相关的可选能力
+///
+/// 每个变体对应构建脚本输出的一个 `lame_has_*` cfg 标志。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// 小数 VBR 质量（`lame_set_VBR_quality`，LAME 3.99+）
+    FractionalVbrQuality,
+    /// UTF-16 ID3v2 文本帧（`id3tag_set_textinfo_utf16`，LAME 3.100+）
+    Utf16Tags,
+    /// 按缓冲区大小查询最大样本数（`lame_get_maximum_number_of_samples`）
+    MaxSamplesGetter,
+    /// 读取 LAME/Xing 信息帧（`lame_get_lametag_frame`）
+    LametagFrame,
+    /// 读取 ID3v2 标签字节（`lame_get_id3v2_tag`）
+    Id3v2TagGetter,
+}
+
+impl Feature {
+    /// 所有可选能力
+    pub const ALL: [Feature; 5] = [
+        Feature::FractionalVbrQuality,
+        Feature::Utf16Tags,
+        Feature::MaxSamplesGetter,
+        Feature::LametagFrame,
+        Feature::Id3v2TagGetter,
+    ];
+
+    /// 对应的 cfg 标志名
+    pub fn cfg_name(self) -> &'static str {
+        match self {
+            Feature::FractionalVbrQuality => "lame_has_fractional_vbr_quality",
+            Feature::Utf16Tags => "lame_has_utf16_tags",
+            Feature::MaxSamplesGetter => "lame_has_max_samples_getter",
+            Feature::LametagFrame => "lame_has_lametag_frame",
+            Feature::Id3v2TagGetter => "lame_has_id3v2_tag_getter",
+        }
+    }
+
+    /// 链接的 LAME 是否提供该能力
+    pub fn is_available(self) -> bool {
+        match self {
+            Feature::FractionalVbrQuality => cfg!(lame_has_fractional_vbr_quality),
+            Feature::Utf16Tags => cfg!(lame_has_utf16_tags),
+            Feature::MaxSamplesGetter => cfg!(lame_has_max_samples_getter),
+            Feature::LametagFrame => cfg!(lame_has_lametag_frame),
+            Feature::Id3v2TagGetter => cfg!(lame_has_id3v2_tag_getter),
+        }
+    }
+
+    /// 能力不可用时返回错误
+    ///
+    /// 错误信息形如 `"lame_has_utf16_tags not supported by linked LAME 3.98"`。
+    pub fn require(self) -> Result<()> {
+        if self.is_available() {
+            Ok(())
+        } else {
+            Err(self.unsupported())
+        }
+    }
+
+    /// 构造"不支持"错误，供 cfg 关闭时的降级分支使用
+    pub(crate) fn unsupported(self) -> LameError {
+        LameError::InternalError(format!(
+            "{} not supported by linked LAME {}",
+            self.cfg_name(),
+            crate::get_lame_version()
+        ))
+    }
+}
+
+/// 构建信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// 运行时链接的 LAME 版本（`get_lame_version()`）
+    pub lame_version: String,
+    /// 生成绑定时头文件对应的版本，无法确定时为 `"unknown"`
+    pub header_version: &'static str,
+    /// 链接的 LAME 提供的可选能力
+    pub features: Vec<Feature>,
+}
+
+impl BuildInfo {
+    /// 是否提供指定能力
+    pub fn has(&self, feature: Feature) -> bool {
+        self.features.contains(&feature)
+    }
+}
+
+/// 获取构建信息
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        lame_version: crate::get_lame_version(),
+        header_version: env!("LAME_HEADER_VERSION"),
+        features: Feature::ALL
+            .iter()
+            .copied()
+            .filter(|feature| feature.is_available())
+            .collect(),
+    }
+}
+
+/// 数字形式的 LAME 版本（`lame_version_t`）
+///
+/// 需要按版本号分支时使用；显示用的字符串见 [`get_lame_version`](crate::get_lame_version)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LameVersion {
+    /// 主版本号
+    pub major: i32,
+    /// 次版本号
+    pub minor: i32,
+    /// alpha 版本号，不是 alpha 版本时为 0
+    pub alpha: i32,
+    /// beta 版本号，不是 beta 版本时为 0
+    pub beta: i32,
+    /// 心理声学模型的主版本号
+    pub psy_major: i32,
+    /// 心理声学模型的次版本号
+    pub psy_minor: i32,
+    /// 编译期特性描述。LAME 不保证其内容，当前版本总是空字符串
+    pub features: String,
+}
+
+impl LameVersion {
+    /// 版本不低于 `major.minor` 时返回 `true`
+    pub fn is_at_least(&self, major: i32, minor: i32) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+}
+
+/// 获取运行时链接的 LAME 版本号
+///
+/// # 示例
+///
+/// ```no_run
+/// let version = lame_sys::version();
+/// if version.is_at_least(3, 100) {
+///     // 3.100 及以后的行为
+/// }
+/// ```
+pub fn version() -> LameVersion {
+    let mut raw = std::mem::MaybeUninit::<crate::ffi::lame_version_t>::zeroed();
+    // SAFETY: get_lame_version_numerical 只写入 raw 的各个字段；
+    // features 指向静态字符串或为空指针，空指针已检查
+    unsafe {
+        crate::ffi::get_lame_version_numerical(raw.as_mut_ptr());
+        let raw = raw.assume_init();
+        let features = if raw.features.is_null() {
+            String::new()
+        } else {
+            std::ffi::CStr::from_ptr(raw.features)
+                .to_string_lossy()
+                .into_owned()
+        };
+        LameVersion {
+            major: raw.major,
+            minor: raw.minor,
+            alpha: raw.alpha,
+            beta: raw.beta,
+            psy_major: raw.psy_major,
+            psy_minor: raw.psy_minor,
+            features,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! 无缝分段编码
+//!
+//! [`GaplessSession`] 把 [`LameEncoder::flush_nogap`] 的调用顺序封装起来：
+//! 编码一段，调用 [`next_track`](GaplessSession::next_track) 取出这一段末尾的数据并开始下一段，
+//! 最后一段用 [`finish`](GaplessSession::finish) 真正刷新编码器。
+//! 段序号由会话维护，设置了 [`nogap_total`](crate::EncoderBuilder::nogap_total) 时
+//! 自动同步到 LAME。
+//!
+//! # 示例
+//!
+//! ```no_run
+//! use lame_sys::gapless::GaplessSession;
+//! use lame_sys::pcm::InterleavedPcm;
+//! use lame_sys::prelude::*;
+//!
+//! # fn main() -> Result<()> {
+//! # let tracks: Vec<Vec<i16>> = vec![vec![0; 44100 * 2]; 3];
+//! let encoder = LameEncoder::builder()?
+//!     .sample_rate(44100)?
+//!     .channels(2)?
+//!     .bitrate(192)?
+//!     .nogap_total(tracks.len() as i32)?
+//!     .build()?;
+//! let mut session = GaplessSession::new(encoder);
+//! let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(44100)];
+//! let mut files = vec![Vec::new(); tracks.len()];
+//!
+//! for (index, pcm) in tracks.iter().enumerate() {
+//!     let len = session.encode(InterleavedPcm(pcm), &mut mp3_buffer)?;
+//!     files[index].extend_from_slice(&mp3_buffer[..len]);
+//!     if index + 1 < tracks.len() {
+//!         files[index].extend(session.next_track()?);
+//!     }
+//! }
+//! let stream = session.finish()?;
+//! files[tracks.len() - 1].extend(stream.trailing);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::encoder::{FinalizedStream, LameEncoder};
+use crate::error::{LameError, Result};
+use crate::pcm::PcmInput;
+
+/// 持有一个编码器、按段输出的无缝编码会话
+///
+/// 所有段共用同一个编码器，段与段之间不会插入静音或编码器延迟。
+pub struct GaplessSession {
+    encoder: LameEncoder,
+    /// 当前段的序号，从 0 开始
+    track: usize,
+}
+
+impl GaplessSession {
+    /// 用编码器开始第一段（序号 0）
+    ///
+    /// 编码器应当还没有刷新过。需要 LAME 信息帧中的无缝标志位时，
+    /// 构建编码器时用 [`nogap_total`](crate::EncoderBuilder::nogap_total) 设置总段数；
+    /// 不设置时段数不受限制。
+    pub fn new(encoder: LameEncoder) -> Self {
+        Self { encoder, track: 0 }
+    }
+
+    /// 编码当前段的 PCM 数据，参数与返回值同 [`LameEncoder::encode_pcm`]
+    #[inline(always)]
+    pub fn encode<P: PcmInput>(&mut self, input: P, mp3_buffer: &mut [u8]) -> Result<usize> {
+        self.encoder.encode_pcm(input, mp3_buffer)
+    }
+
+    /// 结束当前段并开始下一段
+    ///
+    /// 调用 [`flush_nogap`](LameEncoder::flush_nogap)，返回属于当前段末尾的字节，
+    /// 尚未编码的 PCM 留给下一段。
+    ///
+    /// # 错误
+    ///
+    /// 设置了总段数且当前已是最后一段时返回 [`LameError::InvalidInput`]，
+    /// 此时编码器没有被刷新，应当调用 [`finish`](Self::finish)
+    pub fn next_track(&mut self) -> Result<Vec<u8>> {
+        let total = self.encoder.nogap_total();
+        let next = self.track + 1;
+        if total > 0 && next >= total as usize {
+            return Err(LameError::InvalidInput(format!(
+                "track {} is the last of {}; call finish() instead",
+                self.track, total
+            )));
+        }
+
+        let mut boundary = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+        let len = self.encoder.flush_nogap(&mut boundary)?;
+        boundary.truncate(len);
+
+        if total > 0 {
+            self.encoder.set_nogap_current_index(next as i32)?;
+        }
+        self.track = next;
+        Ok(boundary)
+    }
+
+    /// 当前段的序号，从 0 开始
+    pub fn track(&self) -> usize {
+        self.track
+    }
+
+    /// 底层编码器，用于读取统计信息等只读操作
+    pub fn encoder(&self) -> &LameEncoder {
+        &self.encoder
+    }
+
+    /// 结束最后一段，真正刷新编码器
+    ///
+    /// 见 [`LameEncoder::finalize`]。其中的 LAME 信息帧属于第一段，
+    /// 用来覆盖第一段输出的第一帧。
+    pub fn finish(self) -> Result<FinalizedStream> {
+        self.encoder.finalize()
+    }
+}
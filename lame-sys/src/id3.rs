@@ -1,20 +1,27 @@
 use crate::error::{LameError, Result};
 use crate::ffi;
-use std::ffi::CString;
+use std::borrow::Cow;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
 
 /// ID3 标签构建器
 ///
 /// 用于设置 MP3 文件的 ID3 标签（元数据）。
 ///
+/// # 控制字符
+///
+/// 所有文本字段都经过 [`sanitize_text`] 处理：包含 NUL 的值返回
+/// [`LameError::InvalidParameter`]，其他 C0 控制字符（U+0001–U+001F，包括换行和制表符）
+/// 以及 DEL 被替换为空格，并记录在 [`Id3Summary::sanitized_fields`] 中。
+///
 /// # 示例
 ///
 /// ```no_run
-/// use lame_sys::{LameEncoder, Id3Tag};
+/// use lame_sys::prelude::*;
 ///
-/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// let mut encoder = LameEncoder::builder()
-///     .sample_rate(44100)
-///     .channels(2)
+/// # fn main() -> Result<()> {
+/// let mut encoder = LameEncoder::builder()?
+///     .sample_rate(44100)?
+///     .channels(2)?
 ///     .build()?;
 ///
 /// // 设置 ID3 标签
@@ -30,9 +37,374 @@ use std::ffi::CString;
 /// ```
 pub struct Id3Tag<'a> {
     gfp: *mut ffi::lame_global_flags,
+    /// ID3v1 兼容性检查结果
+    summary: Id3Summary,
+    /// 注释长度（v1 限制取决于是否设置了曲目编号，在 apply 时检查）
+    comment_len: Option<usize>,
+    /// 是否设置了曲目编号
+    has_track: bool,
+    /// 写入哪些版本的标签，`None` 时由 LAME 决定
+    version: Option<Id3Version>,
+    /// ID3v2 标签末尾的填充字节数
+    pad_v2: Option<usize>,
+    /// ID3v1 字段用空格而不是 NUL 填充
+    space_v1: bool,
+    /// 有字段会在 ID3v1 中被截断时 `apply` 返回错误
+    strict_v1: bool,
+    /// 会被截断的字段：(字段名, 字节数, ID3v1 的上限)
+    truncations: Vec<(&'static str, usize, usize)>,
+    /// 创建时编码器的当前段是否已经开始编码
+    started: bool,
     _marker: std::marker::PhantomData<&'a mut crate::encoder::LameEncoder>,
 }
 
+/// 写入的 ID3 标签版本
+///
+/// 不设置时由 LAME 决定：总是写入 ID3v1，字段放不进 ID3v1 或设置了只有 ID3v2
+/// 才有的字段（专辑艺术家、封面等）时同时写入 ID3v2。ID3v2 标签的版本为 2.3。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Id3Version {
+    /// 只写入文件末尾的 ID3v1 标签（`id3tag_v1_only`），适合老旧的播放设备
+    V1Only,
+    /// 只写入文件开头的 ID3v2 标签（`id3tag_v2_only`）
+    V2Only,
+    /// 总是同时写入两种标签（`id3tag_add_v2`）
+    Both,
+}
+
+/// ID3v1 文本字段的最大字节数
+pub const ID3V1_FIELD_LEN: usize = 30;
+
+/// 设置曲目编号（ID3v1.1）时 ID3v1 注释字段的最大字节数
+pub const ID3V1_COMMENT_WITH_TRACK_LEN: usize = 28;
+
+/// ID3v1 年份字段的最大字节数
+pub const ID3V1_YEAR_LEN: usize = 4;
+
+/// 专辑封面图片的最大字节数，即 LAME 的 `LAME_MAXALBUMART`（128 KiB）
+pub const MAX_ALBUMART_BYTES: usize = 128 * 1024;
+
+/// 整个 ID3v2 标签（包括 10 字节的标签头）的最大字节数
+///
+/// LAME 把 ID3v2 标签和信息帧一起写入内部固定大小的缓冲区
+/// （`LAME_MAXMP3BUFFER`，即 16 KiB 加 [`MAX_ALBUMART_BYTES`]），超出时会写坏输出。
+/// 这里为信息帧预留 4 KiB，由 [`Id3Tag::apply`] 检查。
+pub const MAX_ID3V2_TAG_BYTES: usize = 12 * 1024 + MAX_ALBUMART_BYTES;
+
+/// 一次设置所有字段的 ID3 元数据，用于 [`LameEncoder::set_id3`](crate::LameEncoder::set_id3)
+///
+/// 值为 `None` 的字段不设置。启用 `serde` feature 后可以反序列化，标签可以直接来自
+/// JSON 等格式的附属文件；缺少的字段取 `None`，封面是图片字节组成的数组。
+///
+/// ```no_run
+/// use lame_sys::prelude::*;
+///
+/// # fn main() -> Result<()> {
+/// # let mut encoder = LameEncoder::builder()?.sample_rate(44100)?.channels(2)?.build()?;
+/// let meta = Id3Metadata {
+///     title: Some("My Song".to_string()),
+///     artist: Some("My Band".to_string()),
+///     track: Some(3),
+///     track_total: Some(12),
+///     ..Id3Metadata::default()
+/// };
+/// encoder.set_id3(&meta)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Id3Metadata {
+    /// 标题
+    pub title: Option<String>,
+    /// 艺术家
+    pub artist: Option<String>,
+    /// 专辑
+    pub album: Option<String>,
+    /// 专辑艺术家（ID3v2 的 `TPE2` 帧）
+    pub album_artist: Option<String>,
+    /// 年份
+    pub year: Option<String>,
+    /// 注释
+    pub comment: Option<String>,
+    /// 曲目编号
+    pub track: Option<u32>,
+    /// 总曲目数，需要同时设置 `track`
+    pub track_total: Option<u32>,
+    /// 碟片编号
+    pub disc: Option<u32>,
+    /// 总碟数，需要同时设置 `disc`
+    pub disc_total: Option<u32>,
+    /// 流派名称或 ID3v1 流派编号，参见 [`check_genre`]
+    pub genre: Option<String>,
+    /// 封面图片（JPEG、PNG 或 GIF）
+    pub album_art: Option<Vec<u8>>,
+}
+
+impl Id3Metadata {
+    /// 把所有字段写入 `encoder` 的标签
+    ///
+    /// 每个字段单独设置，出错时继续设置其余字段，最后把所有错误合并为一个
+    /// [`LameError::InvalidInput`]，这时丢弃整个标签。
+    pub(crate) fn apply(&self, encoder: &mut crate::encoder::LameEncoder) -> Result<Id3Summary> {
+        let mut tag = Id3Tag::new(encoder);
+        let mut errors = Vec::new();
+
+        if let Some(title) = &self.title {
+            tag.try_field("title", &mut errors, |tag| tag.title(title));
+        }
+        if let Some(artist) = &self.artist {
+            tag.try_field("artist", &mut errors, |tag| tag.artist(artist));
+        }
+        if let Some(album) = &self.album {
+            tag.try_field("album", &mut errors, |tag| tag.album(album));
+        }
+        if let Some(album_artist) = &self.album_artist {
+            tag.try_field("album_artist", &mut errors, |tag| {
+                tag.album_artist(album_artist)
+            });
+        }
+        if let Some(year) = &self.year {
+            tag.try_field("year", &mut errors, |tag| tag.year(year));
+        }
+        if let Some(comment) = &self.comment {
+            tag.try_field("comment", &mut errors, |tag| tag.comment(comment));
+        }
+        match (self.track, self.track_total) {
+            (Some(track), total) => tag = tag.track(track, total),
+            (None, Some(_)) => errors.push("track_total: requires track".to_string()),
+            (None, None) => {}
+        }
+        match (self.disc, self.disc_total) {
+            (Some(disc), total) => tag.try_field("disc", &mut errors, |tag| tag.disc(disc, total)),
+            (None, Some(_)) => errors.push("disc_total: requires disc".to_string()),
+            (None, None) => {}
+        }
+        if let Some(genre) = &self.genre {
+            tag.try_field("genre", &mut errors, |tag| tag.genre(genre));
+        }
+        if let Some(album_art) = &self.album_art {
+            tag.try_field("album_art", &mut errors, |tag| tag.album_art(album_art));
+        }
+
+        if !errors.is_empty() {
+            tag.discard();
+            return Err(LameError::InvalidInput(format!(
+                "invalid ID3 metadata: {}; the tag was discarded",
+                errors.join("; ")
+            )));
+        }
+        tag.apply()
+    }
+}
+
+/// ID3 标签应用结果
+///
+/// ID3v2 帧始终保存完整内容，这里报告的问题只影响 ID3v1 标签。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Id3Summary {
+    /// 超出 ID3v1 长度限制、会被截断的字段
+    pub truncated_fields: Vec<&'static str>,
+    /// 包含非 ASCII 字符的字段
+    ///
+    /// 字符串以 UTF-8 字节原样写入 ID3v1，按 Latin-1 解析的读取器会显示乱码。
+    pub non_latin1_fields: Vec<&'static str>,
+    /// 控制字符被替换为空格的字段（同时影响 ID3v1 和 ID3v2）
+    pub sanitized_fields: Vec<&'static str>,
+}
+
+impl Id3Summary {
+    /// ID3v1 标签是否能完整保存所有字段
+    pub fn is_v1_lossless(&self) -> bool {
+        self.truncated_fields.is_empty() && self.non_latin1_fields.is_empty()
+    }
+}
+
+/// 按 [`Id3Tag`] 的控制字符策略检查并清理 ID3 文本字段
+///
+/// 值包含 NUL 时返回 [`LameError::InvalidParameter`]，错误信息包含字段名和 NUL 的字节位置，
+/// 例如 `title contains NUL at byte 5`。其他 C0 控制字符和 DEL 替换为空格（字节长度不变），
+/// 不含控制字符时原样借用。
+pub fn sanitize_text<'v>(field: &str, value: &'v str) -> Result<Cow<'v, str>> {
+    if let Some(pos) = value.bytes().position(|b| b == 0) {
+        return Err(LameError::InvalidParameter(format!(
+            "{} contains NUL at byte {}",
+            field, pos
+        )));
+    }
+    if !value.contains(|c: char| c.is_ascii_control()) {
+        return Ok(Cow::Borrowed(value));
+    }
+    Ok(Cow::Owned(
+        value
+            .chars()
+            .map(|c| if c.is_ascii_control() { ' ' } else { c })
+            .collect(),
+    ))
+}
+
+/// LAME 支持的 ID3v1 流派数量（编号 0-147）
+const GENRE_COUNT: i64 = 148;
+
+/// `TRCK` 和 `TPOS` 帧的文本：编号，有总数时加上 `/总数`
+fn part_of(number: u32, total: Option<u32>) -> String {
+    match total {
+        Some(total) => format!("{}/{}", number, total),
+        None => number.to_string(),
+    }
+}
+
+/// 检查 [`Id3Tag::genre`] 的值
+///
+/// 纯数字的值必须是已知的 ID3v1 流派编号，否则返回 [`LameError::InvalidParameter`]；
+/// 名称必须是 [`genre_list`] 中的流派，比较时忽略大小写和标点（`"hip hop"` 即 `Hip-Hop`），
+/// 否则返回 [`LameError::InvalidInput`]。
+pub fn check_genre(genre: &str) -> Result<()> {
+    parse_genre(genre).map(|_| ())
+}
+
+/// 把流派名称或编号解析为 ID3v1 流派编号，规则见 [`check_genre`]
+fn parse_genre(genre: &str) -> Result<u8> {
+    // 与 LAME 一样按 strtol 的规则判断是否为数字：允许前导空白和正负号
+    let number = genre.trim_start();
+    let digits = number.strip_prefix(['+', '-']).unwrap_or(number);
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        return match number.parse::<i64>() {
+            Ok(id) if (0..GENRE_COUNT).contains(&id) => Ok(id as u8),
+            _ => Err(LameError::InvalidParameter(format!(
+                "genre: {} is not a known ID3v1 genre number",
+                genre
+            ))),
+        };
+    }
+
+    let key = genre_key(genre);
+    genre_list()
+        .into_iter()
+        .find(|(_, name)| !key.is_empty() && genre_key(name) == key)
+        .map(|(id, _)| id)
+        .ok_or_else(|| {
+            LameError::InvalidInput(format!(
+                "genre: {} is not a known ID3v1 genre; see id3::genre_list()",
+                genre
+            ))
+        })
+}
+
+/// 比较流派名称用的键：只保留字母和数字，并转为大写
+fn genre_key(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// LAME 认识的全部 ID3v1 流派，按名称的字母顺序排列
+///
+/// 每一项是流派编号和名称，可以直接用作界面上的下拉列表，
+/// 编号传给 [`Id3Tag::genre_id`]。常用的编号也可以用 [`genres`] 中的常量表示。
+pub fn genre_list() -> Vec<(u8, String)> {
+    /// # Safety
+    ///
+    /// `cookie` 指向 `Vec<(u8, String)>`，`name` 是以 NUL 结尾的字符串
+    unsafe extern "C" fn collect(id: c_int, name: *const c_char, cookie: *mut c_void) {
+        // SAFETY: 由 genre_list 传入，调用期间独占
+        let list = unsafe { &mut *cookie.cast::<Vec<(u8, String)>>() };
+        // SAFETY: LAME 传入静态的流派名称
+        let name = unsafe { CStr::from_ptr(name) };
+        list.push((id as u8, name.to_string_lossy().into_owned()));
+    }
+
+    let mut list: Vec<(u8, String)> = Vec::with_capacity(GENRE_COUNT as usize);
+    // SAFETY: 回调只在 id3tag_genre_list 返回之前被调用，期间 list 不会被其他代码访问
+    unsafe { ffi::id3tag_genre_list(Some(collect), (&mut list as *mut Vec<(u8, String)>).cast()) };
+    list
+}
+
+/// 检查 [`Id3Tag::year`] 的值
+///
+/// 年份必须是 1000-9999 之间的 4 位数字，否则返回 [`LameError::InvalidInput`]。
+pub fn check_year(year: &str) -> Result<()> {
+    let valid = year.len() == ID3V1_YEAR_LEN
+        && year.bytes().all(|b| b.is_ascii_digit())
+        && !year.starts_with('0');
+    if !valid {
+        return Err(LameError::InvalidInput(format!(
+            "year: {:?} is not a year between 1000 and 9999",
+            year
+        )));
+    }
+    Ok(())
+}
+
+/// 检查 [`Id3Tag::release_date`] 的日期
+///
+/// 年份的规则同 [`check_year`]，月和日必须是存在的日期（考虑闰年），
+/// 否则返回 [`LameError::InvalidInput`]。
+pub fn check_release_date(year: u16, month: u8, day: u8) -> Result<()> {
+    check_year(&year.to_string())?;
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 0,
+    };
+    if !(1..=days).contains(&day) {
+        return Err(LameError::InvalidInput(format!(
+            "release_date: {:04}-{:02}-{:02} is not a valid date",
+            year, month, day
+        )));
+    }
+    Ok(())
+}
+
+/// 检查 [`Id3Tag::field`] 的帧 ID
+///
+/// 帧 ID 必须是 4 个大写 ASCII 字母或数字（如 `"TCOM"`），否则返回 [`LameError::InvalidParameter`]。
+/// 这里只检查格式，LAME 是否支持该帧要到设置时才知道。
+pub fn check_frame_id(frame_id: &str) -> Result<()> {
+    let valid = frame_id.len() == 4
+        && frame_id
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit());
+    if !valid {
+        return Err(LameError::InvalidParameter(format!(
+            "field: {:?} is not an ID3v2 frame ID (4 uppercase letters or digits)",
+            frame_id
+        )));
+    }
+    Ok(())
+}
+
+/// 检查专辑封面图片的大小
+///
+/// 超过 [`MAX_ALBUMART_BYTES`] 时返回 [`LameError::InvalidInput`]，错误信息包括限制和实际大小。
+/// LAME 本身不检查，过大的图片会在编码时写坏 ID3v2 标签。
+pub fn check_album_art(image: &[u8]) -> Result<()> {
+    if image.len() > MAX_ALBUMART_BYTES {
+        return Err(LameError::InvalidInput(format!(
+            "album art is {} bytes, larger than the {} byte limit",
+            image.len(),
+            MAX_ALBUMART_BYTES
+        )));
+    }
+    Ok(())
+}
+
+/// 按开头的字节判断封面图片的 MIME 类型
+///
+/// 与 LAME 的判断方式相同，只识别 JPEG、PNG 和 GIF，其他数据返回 `None`。
+pub fn album_art_mime_type(image: &[u8]) -> Option<&'static str> {
+    match image {
+        [0xFF, 0xD8, _, ..] => Some("image/jpeg"),
+        [0x89, b'P', b'N', b'G', _, ..] => Some("image/png"),
+        [b'G', b'I', b'F', b'8', _, ..] => Some("image/gif"),
+        _ => None,
+    }
+}
+
 impl<'a> Id3Tag<'a> {
     /// 创建新的 ID3 标签构建器
     ///
@@ -40,105 +412,512 @@ impl<'a> Id3Tag<'a> {
     ///
     /// * `encoder` - LAME 编码器的可变引用
     pub fn new(encoder: &'a mut crate::encoder::LameEncoder) -> Self {
-        unsafe {
-            let gfp = encoder.as_ptr();
-            // 初始化 ID3 标签
-            ffi::id3tag_init(gfp);
+        let started = encoder.has_started();
+        // SAFETY: 指针只在 'a 期间使用，期间编码器被独占借用，不会被释放
+        let gfp = unsafe { encoder.as_ptr() };
+        // 初始化 ID3 标签
+        // SAFETY: gfp 是有效的句柄
+        unsafe { ffi::id3tag_init(gfp) };
 
-            Self {
-                gfp,
-                _marker: std::marker::PhantomData,
-            }
+        Self {
+            gfp,
+            summary: Id3Summary::default(),
+            comment_len: None,
+            has_track: false,
+            version: None,
+            pad_v2: None,
+            space_v1: false,
+            strict_v1: false,
+            truncations: Vec::new(),
+            started,
+            _marker: std::marker::PhantomData,
         }
     }
 
-    /// 设置标题
-    pub fn title(self, title: &str) -> Result<Self> {
-        let c_title = CString::new(title)?;
-        unsafe {
-            ffi::id3tag_set_title(self.gfp, c_title.as_ptr());
+    /// 以消耗式的设置方法设置一个字段，失败时记录错误并保留之前的状态
+    ///
+    /// 供 [`Id3Metadata`] 在某个字段出错后继续检查其余字段。
+    fn try_field(
+        &mut self,
+        field: &'static str,
+        errors: &mut Vec<String>,
+        set: impl FnOnce(Self) -> Result<Self>,
+    ) {
+        let fork = Self {
+            gfp: self.gfp,
+            summary: self.summary.clone(),
+            comment_len: self.comment_len,
+            has_track: self.has_track,
+            version: self.version,
+            pad_v2: self.pad_v2,
+            space_v1: self.space_v1,
+            strict_v1: self.strict_v1,
+            truncations: self.truncations.clone(),
+            started: self.started,
+            _marker: std::marker::PhantomData,
+        };
+        match set(fork) {
+            Ok(tag) => *self = tag,
+            Err(err) => errors.push(format!("{}: {}", field, err)),
+        }
+    }
+
+    /// 清空已经交给 LAME 的所有字段
+    fn discard(&self) {
+        // SAFETY: gfp 有效；重新初始化会清空所有已设置的字段
+        unsafe { ffi::id3tag_init(self.gfp) };
+    }
+
+    /// 检查字段能否完整写入 ID3v1 标签
+    fn check_v1(&mut self, field: &'static str, value: &str, limit: usize) {
+        self.check_v1_len(field, value.len(), limit);
+        if !value.is_ascii() {
+            self.summary.non_latin1_fields.push(field);
+        }
+    }
+
+    /// 以标签的句柄调用 `id3tag_set_*(gfp, text)` 函数
+    ///
+    /// `setter` 必须是只在调用期间读取 `text` 的 `id3tag_set_*` 系列函数。
+    fn set_raw<R>(
+        &self,
+        setter: unsafe extern "C" fn(*mut ffi::lame_global_flags, *const c_char) -> R,
+        text: &CStr,
+    ) -> R {
+        // SAFETY: gfp 在 'a 期间有效且被独占借用；LAME 会复制 text，不保留指针
+        unsafe { setter(self.gfp, text.as_ptr()) }
+    }
+
+    /// 按控制字符策略清理字段，并转换为 C 字符串
+    fn c_text(&mut self, field: &'static str, value: &str) -> Result<CString> {
+        let value = sanitize_text(field, value)?;
+        if let Cow::Owned(_) = value {
+            self.summary.sanitized_fields.push(field);
         }
+        Ok(CString::new(value.into_owned())?)
+    }
+
+    /// 设置标题
+    pub fn title(mut self, title: &str) -> Result<Self> {
+        self.check_v1("title", title, ID3V1_FIELD_LEN);
+        let c_title = self.c_text("title", title)?;
+        self.set_raw(ffi::id3tag_set_title, &c_title);
+        self.set_utf16("title", "TIT2", &c_title)?;
         Ok(self)
     }
 
     /// 设置艺术家
-    pub fn artist(self, artist: &str) -> Result<Self> {
-        let c_artist = CString::new(artist)?;
-        unsafe {
-            ffi::id3tag_set_artist(self.gfp, c_artist.as_ptr());
-        }
+    pub fn artist(mut self, artist: &str) -> Result<Self> {
+        self.check_v1("artist", artist, ID3V1_FIELD_LEN);
+        let c_artist = self.c_text("artist", artist)?;
+        self.set_raw(ffi::id3tag_set_artist, &c_artist);
+        self.set_utf16("artist", "TPE1", &c_artist)?;
         Ok(self)
     }
 
     /// 设置专辑
-    pub fn album(self, album: &str) -> Result<Self> {
-        let c_album = CString::new(album)?;
-        unsafe {
-            ffi::id3tag_set_album(self.gfp, c_album.as_ptr());
-        }
+    pub fn album(mut self, album: &str) -> Result<Self> {
+        self.check_v1("album", album, ID3V1_FIELD_LEN);
+        let c_album = self.c_text("album", album)?;
+        self.set_raw(ffi::id3tag_set_album, &c_album);
+        self.set_utf16("album", "TALB", &c_album)?;
         Ok(self)
     }
 
     /// 设置年份
-    pub fn year(self, year: &str) -> Result<Self> {
-        let c_year = CString::new(year)?;
-        unsafe {
-            ffi::id3tag_set_year(self.gfp, c_year.as_ptr());
-        }
+    ///
+    /// 接受数字（`year(2024)`）或字符串（`year("2024")`），值必须是 1000-9999 之间的年份，
+    /// 参见 [`check_year`]。写入 ID3v1 和 ID3v2 的 `TYER` 帧。
+    pub fn year<Y: std::fmt::Display>(self, year: Y) -> Result<Self> {
+        let year = year.to_string();
+        check_year(&year)?;
+        self.set_raw(ffi::id3tag_set_year, &CString::new(year)?);
         Ok(self)
     }
 
+    /// 设置完整的发行日期
+    ///
+    /// 年份同 [`year`](Self::year)，日和月以 `"DDMM"` 写入 ID3v2 的 `TDAT` 帧。
+    /// LAME 生成 ID3v2.3 标签，其中日期分为 `TYER` 和 `TDAT` 两个帧，没有 ID3v2.4 的 `TDRC`。
+    /// 日期不存在（如 2 月 30 日）时返回 [`LameError::InvalidInput`]。
+    pub fn release_date(self, year: u16, month: u8, day: u8) -> Result<Self> {
+        check_release_date(year, month, day)?;
+        let mut tag = self.year(year)?;
+        tag.set_field("release_date", "TDAT", &format!("{:02}{:02}", day, month))?;
+        Ok(tag)
+    }
+
     /// 设置注释
-    pub fn comment(self, comment: &str) -> Result<Self> {
-        let c_comment = CString::new(comment)?;
-        unsafe {
-            ffi::id3tag_set_comment(self.gfp, c_comment.as_ptr());
+    pub fn comment(mut self, comment: &str) -> Result<Self> {
+        self.comment_len = Some(comment.len());
+        if !comment.is_ascii() {
+            self.summary.non_latin1_fields.push("comment");
+        }
+        let c_comment = self.c_text("comment", comment)?;
+        self.set_raw(ffi::id3tag_set_comment, &c_comment);
+        self.set_utf16("comment", "COMM", &c_comment)?;
+        Ok(self)
+    }
+
+    /// 设置带语言和描述的注释（ID3v2 的 `COMM` 帧）
+    ///
+    /// `lang` 是 ISO 639-2 语言代码，必须是 3 个 ASCII 字母（如 `"eng"`、`"zho"`），
+    /// 否则返回 [`LameError::InvalidParameter`]。同一语言和描述只保留最后一次设置的注释，
+    /// 不同描述的注释各自成帧。只写入 ID3v2，ID3v1 的注释仍由 [`comment`](Self::comment) 设置。
+    /// 需要 `id3tag_set_comment_utf16`（LAME 3.100+），否则返回 [`LameError::InternalError`]。
+    pub fn comment_with(mut self, lang: &str, description: &str, text: &str) -> Result<Self> {
+        if lang.len() != 3 || !lang.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return Err(LameError::InvalidParameter(format!(
+                "comment: language must be 3 ASCII letters, got {:?}",
+                lang
+            )));
         }
+        let c_description = self.c_text("comment", description)?;
+        let c_text = self.c_text("comment", text)?;
+        self.set_comment_frame(lang, &c_description, &c_text)?;
         Ok(self)
     }
 
-    /// 设置曲目编号
-    pub fn track(self, track: u32) -> Self {
-        let track_str = format!("{}", track);
-        if let Ok(c_track) = CString::new(track_str) {
-            unsafe {
-                ffi::id3tag_set_track(self.gfp, c_track.as_ptr());
+    #[cfg(lame_has_utf16_tags)]
+    fn set_comment_frame(&self, lang: &str, description: &CStr, text: &CStr) -> Result<()> {
+        let c_lang = CString::new(lang)?;
+        let (description_str, text_str) = (description.to_str()?, text.to_str()?);
+        // SAFETY: gfp 在 'a 期间有效且被独占借用；所有字符串都以 0 结尾，LAME 会复制它们
+        let result = unsafe {
+            if description_str.is_ascii() && text_str.is_ascii() {
+                ffi::id3tag_set_comment_latin1(
+                    self.gfp,
+                    c_lang.as_ptr(),
+                    description.as_ptr(),
+                    text.as_ptr(),
+                )
+            } else {
+                let utf16 = |value: &str| -> Vec<u16> {
+                    std::iter::once(0xFEFF)
+                        .chain(value.encode_utf16())
+                        .chain(std::iter::once(0))
+                        .collect()
+                };
+                let (description, text) = (utf16(description_str), utf16(text_str));
+                ffi::id3tag_set_comment_utf16(
+                    self.gfp,
+                    c_lang.as_ptr(),
+                    description.as_ptr(),
+                    text.as_ptr(),
+                )
             }
+        };
+        if result != 0 {
+            return Err(LameError::InvalidParameter(
+                "comment: LAME rejected the COMM frame".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(lame_has_utf16_tags))]
+    fn set_comment_frame(&self, _lang: &str, _description: &CStr, _text: &CStr) -> Result<()> {
+        Err(crate::features::Feature::Utf16Tags.unsupported())
+    }
+
+    /// 设置曲目编号，`total` 为专辑的总曲目数
+    ///
+    /// ID3v2 的 `TRCK` 帧写成 `"3/12"` 的形式，播放器显示为“第 3 首，共 12 首”；
+    /// 设置了总数时总会写入 ID3v2。ID3v1 只能保存 1-255 的编号，超出时只写入 ID3v2。
+    pub fn track(mut self, number: u32, total: Option<u32>) -> Self {
+        self.has_track = true;
+        if let Ok(c_track) = CString::new(part_of(number, total)) {
+            self.set_raw(ffi::id3tag_set_track, &c_track);
         }
         self
     }
 
+    /// 设置碟片编号，`total` 为总碟数
+    ///
+    /// 写入 ID3v2 的 `TPOS` 帧（如 `"1/2"`），ID3v1 没有对应的字段。
+    pub fn disc(mut self, number: u32, total: Option<u32>) -> Result<Self> {
+        self.set_field("disc", "TPOS", &part_of(number, total))?;
+        Ok(self)
+    }
+
     /// 设置流派（Genre）
     ///
-    /// 可以是流派名称或 ID3v1 流派编号（0-255）
-    pub fn genre(self, genre: &str) -> Result<Self> {
-        let c_genre = CString::new(genre)?;
-        unsafe {
-            ffi::id3tag_set_genre(self.gfp, c_genre.as_ptr());
+    /// 可以是流派名称或 ID3v1 流派编号（0-147）。未知的编号或名称返回错误，参见 [`check_genre`]；
+    /// ID3v2 标签写入 LAME 列表中的标准名称。
+    pub fn genre(mut self, genre: &str) -> Result<Self> {
+        let c_genre = self.c_text("genre", genre)?;
+        let id = parse_genre(c_genre.to_str()?)?;
+        self.genre_id(id)
+    }
+
+    /// 按 ID3v1 流派编号设置流派
+    ///
+    /// `id` 必须是 LAME 支持的编号（0-147，参见 [`genres`] 和 [`genre_list`]），
+    /// 否则返回 [`LameError::InvalidParameter`]。ID3v2 标签会写入对应的流派名称。
+    pub fn genre_id(self, id: u8) -> Result<Self> {
+        let c_genre = CString::new(id.to_string())?;
+        let result = self.set_raw(ffi::id3tag_set_genre, &c_genre);
+        if result != 0 {
+            return Err(LameError::InvalidParameter(format!(
+                "genre_id: {} is not a known ID3v1 genre",
+                id
+            )));
         }
         Ok(self)
     }
 
-    /// 设置专辑艺术家
-    pub fn album_artist(self, album_artist: &str) -> Result<Self> {
-        let c_album_artist = CString::new(album_artist)?;
-        unsafe {
-            ffi::id3tag_set_albumart(self.gfp, c_album_artist.as_ptr(), 0);
+    /// 直接设置 ID3v1 流派字节，同 [`genre_id`](Self::genre_id)
+    #[inline(always)]
+    pub fn genre_v1_byte(self, genre: u8) -> Result<Self> {
+        self.genre_id(genre)
+    }
+
+    /// 以 `id3tag_set_fieldvalue` 设置一个 ID3v2 文本帧，`frame_id` 为四个字符的帧 ID
+    fn set_field(&mut self, field: &'static str, frame_id: &str, value: &str) -> Result<()> {
+        let value = self.c_text(field, value)?;
+        let mut field_value = format!("{}=", frame_id).into_bytes();
+        field_value.extend_from_slice(value.as_bytes());
+        let field_value = CString::new(field_value)?;
+        if self.set_raw(ffi::id3tag_set_fieldvalue, &field_value) != 0 {
+            return Err(LameError::InvalidParameter(format!(
+                "{}: LAME rejected the {} frame",
+                field, frame_id
+            )));
+        }
+        self.set_utf16(field, frame_id, &value)
+    }
+
+    /// 非 ASCII 的文本以 UTF-16 重新写入 ID3v2 帧
+    ///
+    /// LAME 的 `id3tag_set_*` 按 Latin-1 生成 ID3v2 帧，UTF-8 文本在播放器中会显示为乱码。
+    /// 这里用 `id3tag_set_textinfo_utf16`（注释用 `id3tag_set_comment_utf16`）替换同名的帧，
+    /// 同时会写入 ID3v2 标签。ID3v1 只能保存单字节文本，保持原样。
+    /// 链接的 LAME 不支持 UTF-16 帧时不做任何事。
+    #[cfg(lame_has_utf16_tags)]
+    fn set_utf16(&self, field: &'static str, frame_id: &str, value: &CStr) -> Result<()> {
+        let value = value.to_str()?;
+        if value.is_ascii() {
+            return Ok(());
         }
+
+        // LAME 要求文本以 BOM 开头，按本机字节序解释
+        let text: Vec<u16> = std::iter::once(0xFEFF)
+            .chain(value.encode_utf16())
+            .chain(std::iter::once(0))
+            .collect();
+        let c_frame_id = CString::new(frame_id)?;
+        // SAFETY: gfp 在 'a 期间有效且被独占借用；帧 ID 和文本都以 0 结尾，LAME 会复制文本
+        let result = unsafe {
+            if frame_id == "COMM" {
+                ffi::id3tag_set_comment_utf16(
+                    self.gfp,
+                    std::ptr::null(),
+                    std::ptr::null(),
+                    text.as_ptr(),
+                )
+            } else {
+                ffi::id3tag_set_textinfo_utf16(self.gfp, c_frame_id.as_ptr(), text.as_ptr())
+            }
+        };
+        if result != 0 {
+            return Err(LameError::InvalidParameter(format!(
+                "{}: LAME rejected the UTF-16 {} frame",
+                field, frame_id
+            )));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(lame_has_utf16_tags))]
+    fn set_utf16(&self, _field: &'static str, _frame_id: &str, _value: &CStr) -> Result<()> {
+        Ok(())
+    }
+
+    /// 直接设置一个 ID3v2 帧（`id3tag_set_fieldvalue`）
+    ///
+    /// 用于没有专门方法的帧，例如作曲者 `field("TCOM", "Bach")`。LAME 接受文本帧
+    /// `T***`、链接帧 `W***` 和 `COMM`；`TXXX`、`WXXX` 和 `COMM` 的值写成 `"描述=内容"`。
+    /// 帧 ID 的格式不对（见 [`check_frame_id`]）或 LAME 不支持该帧时返回
+    /// [`LameError::InvalidParameter`]。
+    pub fn field(mut self, frame_id: &str, value: &str) -> Result<Self> {
+        check_frame_id(frame_id)?;
+        self.set_field("field", frame_id, value)?;
         Ok(self)
     }
 
+    /// 设置专辑艺术家（ID3v2 的 `TPE2` 帧，ID3v1 中没有对应字段）
+    pub fn album_artist(mut self, album_artist: &str) -> Result<Self> {
+        self.set_field("album_artist", "TPE2", album_artist)?;
+        Ok(self)
+    }
+
+    /// 设置专辑封面（ID3v2 的 `APIC` 帧）
+    ///
+    /// `image` 必须是 JPEG、PNG 或 GIF 文件的内容（参见 [`album_art_mime_type`]），
+    /// 且不超过 [`MAX_ALBUMART_BYTES`]（参见 [`check_album_art`]），否则返回
+    /// [`LameError::InvalidInput`]。封面只写入 ID3v2 标签。
+    pub fn album_art(self, image: &[u8]) -> Result<Self> {
+        check_album_art(image)?;
+        if album_art_mime_type(image).is_none() {
+            return Err(LameError::InvalidInput(
+                "album art must be JPEG, PNG or GIF data".to_string(),
+            ));
+        }
+        // SAFETY: gfp 在 'a 期间有效且被独占借用；LAME 复制 image.len() 字节，不保留指针
+        let result = unsafe {
+            ffi::id3tag_set_albumart(self.gfp, image.as_ptr() as *const c_char, image.len())
+        };
+        if result != 0 {
+            return Err(LameError::InvalidInput(
+                "LAME rejected the album art image".to_string(),
+            ));
+        }
+        Ok(self)
+    }
+
+    /// 选择写入的标签版本
+    ///
+    /// 在 [`apply`](Self::apply) 时最后生效，优先于 [`pad_v2`](Self::pad_v2) 和
+    /// [`space_v1`](Self::space_v1)：例如 [`Id3Version::V1Only`] 时不会因为填充而写入 ID3v2。
+    pub fn version(mut self, version: Id3Version) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// 在 ID3v2 标签末尾填充 `bytes` 个 0 字节（`id3tag_set_pad`）
+    ///
+    /// 之后用其他工具修改标签时可以原地写入，不必移动整个文件。
+    /// 同时会写入 ID3v2 标签，除非设置了 [`Id3Version::V1Only`]。
+    pub fn pad_v2(mut self, bytes: usize) -> Self {
+        self.pad_v2 = Some(bytes);
+        self
+    }
+
+    /// ID3v1 字段中未用到的字节用空格而不是 NUL 填充（`id3tag_space_v1`）
+    pub fn space_v1(mut self, enabled: bool) -> Self {
+        self.space_v1 = enabled;
+        self
+    }
+
+    /// 把版本和填充设置交给 LAME
+    ///
+    /// 这些函数会互相修改对方的标志位（例如填充会取消只写 ID3v1），设置封面时 LAME
+    /// 也会打开 ID3v2，所以在所有字段之后按固定顺序调用，版本最后设置。
+    fn apply_layout(&self) {
+        // SAFETY: 以下调用只修改 gfp 中的标签标志位，gfp 在 'a 期间有效且被独占借用
+        unsafe {
+            if let Some(bytes) = self.pad_v2 {
+                ffi::id3tag_set_pad(self.gfp, bytes);
+            }
+            if self.space_v1 {
+                ffi::id3tag_space_v1(self.gfp);
+            }
+            match self.version {
+                Some(Id3Version::V1Only) => ffi::id3tag_v1_only(self.gfp),
+                Some(Id3Version::V2Only) => ffi::id3tag_v2_only(self.gfp),
+                Some(Id3Version::Both) => ffi::id3tag_add_v2(self.gfp),
+                None => {}
+            }
+        }
+    }
+
+    /// 记录超出 ID3v1 长度限制的字段
+    fn check_v1_len(&mut self, field: &'static str, len: usize, limit: usize) {
+        if len > limit {
+            self.summary.truncated_fields.push(field);
+            self.truncations.push((field, len, limit));
+        }
+    }
+
+    /// 有字段会在 ID3v1 中被截断时让 [`apply`](Self::apply) 返回错误（默认关闭）
+    ///
+    /// LAME 写入 ID3v1 时直接截断过长的字段：标题、艺术家、专辑和注释最多 30 字节，
+    /// 设置了曲目编号时注释最多 28 字节。默认只在 [`Id3Summary::truncated_fields`] 中报告；
+    /// 开启后 `apply` 返回 [`LameError::InvalidInput`]，错误信息包括每个字段的实际字节数，
+    /// 并丢弃整个标签。只写 ID3v2（[`Id3Version::V2Only`]）时不检查。
+    pub fn strict_v1(mut self, enabled: bool) -> Self {
+        self.strict_v1 = enabled;
+        self
+    }
+
     /// 完成 ID3 标签设置
     ///
-    /// 应用所有设置的标签信息。
-    pub fn apply(self) -> Result<()> {
+    /// 应用所有设置的标签信息，并返回 ID3v1 兼容性检查结果。
+    ///
+    /// ID3v2 标签超过 [`MAX_ID3V2_TAG_BYTES`] 时丢弃整个标签（编码输出中不会有任何 ID3 数据），
+    /// 并返回 [`LameError::InvalidInput`]。链接的 LAME 不提供 `lame_get_id3v2_tag` 时不做这项检查。
+    /// 开启了 [`strict_v1`](Self::strict_v1) 时，字段会在 ID3v1 中被截断同样丢弃标签并返回错误。
+    ///
+    /// 标签必须在当前段开始编码之前设置（见 [`LameEncoder::has_started`](crate::LameEncoder::has_started)），
+    /// 否则 ID3v2 标签不会出现在输出中。这时丢弃标签并返回 [`LameError::InvalidState`]。
+    pub fn apply(mut self) -> Result<Id3Summary> {
+        if self.started {
+            self.discard();
+            return Err(LameError::InvalidState(
+                "ID3 tags must be applied before encoding starts",
+            ));
+        }
+
+        // 注释的 v1 长度限制取决于是否设置了曲目编号，因此在这里统一检查
+        if let Some(len) = self.comment_len {
+            let limit = if self.has_track {
+                ID3V1_COMMENT_WITH_TRACK_LEN
+            } else {
+                ID3V1_FIELD_LEN
+            };
+            self.check_v1_len("comment", len, limit);
+        }
+        self.check_strict_v1()?;
+
         // ID3 标签会在编码时自动写入
-        // 这里只是一个标记方法，表示标签设置完成
+        self.apply_layout();
+        self.check_v2_size()?;
+        Ok(self.summary)
+    }
+
+    /// 开启了 strict_v1 且有字段会被截断时丢弃整个标签
+    fn check_strict_v1(&self) -> Result<()> {
+        if !self.strict_v1
+            || self.truncations.is_empty()
+            || self.version == Some(Id3Version::V2Only)
+        {
+            return Ok(());
+        }
+
+        let fields: Vec<String> = self
+            .truncations
+            .iter()
+            .map(|(field, len, limit)| format!("{} is {} bytes (limit {})", field, len, limit))
+            .collect();
+        self.discard();
+        Err(LameError::InvalidInput(format!(
+            "ID3v1 would truncate fields: {}; the tag was discarded",
+            fields.join(", ")
+        )))
+    }
+
+    /// 检查 ID3v2 标签的总大小，超出时丢弃整个标签
+    #[cfg(lame_has_id3v2_tag_getter)]
+    fn check_v2_size(&self) -> Result<()> {
+        // SAFETY: gfp 有效；缓冲区大小为 0 时 LAME 只返回标签的字节数，不写入
+        let size = unsafe { ffi::lame_get_id3v2_tag(self.gfp, std::ptr::null_mut(), 0) };
+        if size > MAX_ID3V2_TAG_BYTES {
+            self.discard();
+            return Err(LameError::InvalidInput(format!(
+                "ID3v2 tag is {} bytes, larger than the {} byte limit; the tag was discarded",
+                size, MAX_ID3V2_TAG_BYTES
+            )));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(lame_has_id3v2_tag_getter))]
+    fn check_v2_size(&self) -> Result<()> {
         Ok(())
     }
 }
 
-/// ID3v1 流派列表（部分常用流派）
+/// ID3v1 流派编号，与 LAME 的流派列表（[`genre_list`]）一一对应
 #[allow(dead_code)]
 pub mod genres {
     pub const BLUES: u8 = 0;
@@ -192,4 +971,101 @@ pub mod genres {
     pub const ETHNIC: u8 = 48;
     pub const GOTHIC: u8 = 49;
     pub const DARKWAVE: u8 = 50;
+    pub const TECHNO_INDUSTRIAL: u8 = 51;
+    pub const ELECTRONIC: u8 = 52;
+    pub const POP_FOLK: u8 = 53;
+    pub const EURODANCE: u8 = 54;
+    pub const DREAM: u8 = 55;
+    pub const SOUTHERN_ROCK: u8 = 56;
+    pub const COMEDY: u8 = 57;
+    pub const CULT: u8 = 58;
+    pub const GANGSTA: u8 = 59;
+    pub const TOP_40: u8 = 60;
+    pub const CHRISTIAN_RAP: u8 = 61;
+    pub const POP_FUNK: u8 = 62;
+    pub const JUNGLE: u8 = 63;
+    pub const NATIVE_US: u8 = 64;
+    pub const CABARET: u8 = 65;
+    pub const NEW_WAVE: u8 = 66;
+    pub const PSYCHEDELIC: u8 = 67;
+    pub const RAVE: u8 = 68;
+    pub const SHOWTUNES: u8 = 69;
+    pub const TRAILER: u8 = 70;
+    pub const LO_FI: u8 = 71;
+    pub const TRIBAL: u8 = 72;
+    pub const ACID_PUNK: u8 = 73;
+    pub const ACID_JAZZ: u8 = 74;
+    pub const POLKA: u8 = 75;
+    pub const RETRO: u8 = 76;
+    pub const MUSICAL: u8 = 77;
+    pub const ROCK_N_ROLL: u8 = 78;
+    pub const HARD_ROCK: u8 = 79;
+    pub const FOLK: u8 = 80;
+    pub const FOLK_ROCK: u8 = 81;
+    pub const NATIONAL_FOLK: u8 = 82;
+    pub const SWING: u8 = 83;
+    pub const FAST_FUSION: u8 = 84;
+    pub const BEBOB: u8 = 85;
+    pub const LATIN: u8 = 86;
+    pub const REVIVAL: u8 = 87;
+    pub const CELTIC: u8 = 88;
+    pub const BLUEGRASS: u8 = 89;
+    pub const AVANTGARDE: u8 = 90;
+    pub const GOTHIC_ROCK: u8 = 91;
+    pub const PROGRESSIVE_ROCK: u8 = 92;
+    pub const PSYCHEDELIC_ROCK: u8 = 93;
+    pub const SYMPHONIC_ROCK: u8 = 94;
+    pub const SLOW_ROCK: u8 = 95;
+    pub const BIG_BAND: u8 = 96;
+    pub const CHORUS: u8 = 97;
+    pub const EASY_LISTENING: u8 = 98;
+    pub const ACOUSTIC: u8 = 99;
+    pub const HUMOUR: u8 = 100;
+    pub const SPEECH: u8 = 101;
+    pub const CHANSON: u8 = 102;
+    pub const OPERA: u8 = 103;
+    pub const CHAMBER_MUSIC: u8 = 104;
+    pub const SONATA: u8 = 105;
+    pub const SYMPHONY: u8 = 106;
+    pub const BOOTY_BASS: u8 = 107;
+    pub const PRIMUS: u8 = 108;
+    pub const PORN_GROOVE: u8 = 109;
+    pub const SATIRE: u8 = 110;
+    pub const SLOW_JAM: u8 = 111;
+    pub const CLUB: u8 = 112;
+    pub const TANGO: u8 = 113;
+    pub const SAMBA: u8 = 114;
+    pub const FOLKLORE: u8 = 115;
+    pub const BALLAD: u8 = 116;
+    pub const POWER_BALLAD: u8 = 117;
+    pub const RHYTHMIC_SOUL: u8 = 118;
+    pub const FREESTYLE: u8 = 119;
+    pub const DUET: u8 = 120;
+    pub const PUNK_ROCK: u8 = 121;
+    pub const DRUM_SOLO: u8 = 122;
+    pub const A_CAPPELLA: u8 = 123;
+    pub const EURO_HOUSE: u8 = 124;
+    pub const DANCE_HALL: u8 = 125;
+    pub const GOA: u8 = 126;
+    pub const DRUM_N_BASS: u8 = 127;
+    pub const CLUB_HOUSE: u8 = 128;
+    pub const HARDCORE: u8 = 129;
+    pub const TERROR: u8 = 130;
+    pub const INDIE: u8 = 131;
+    pub const BRITPOP: u8 = 132;
+    pub const NEGERPUNK: u8 = 133;
+    pub const POLSK_PUNK: u8 = 134;
+    pub const BEAT: u8 = 135;
+    pub const CHRISTIAN_GANGSTA: u8 = 136;
+    pub const HEAVY_METAL: u8 = 137;
+    pub const BLACK_METAL: u8 = 138;
+    pub const CROSSOVER: u8 = 139;
+    pub const CONTEMPORARY_CHRISTIAN: u8 = 140;
+    pub const CHRISTIAN_ROCK: u8 = 141;
+    pub const MERENGUE: u8 = 142;
+    pub const SALSA: u8 = 143;
+    pub const THRASH_METAL: u8 = 144;
+    pub const ANIME: u8 = 145;
+    pub const JPOP: u8 = 146;
+    pub const SYNTHPOP: u8 = 147;
 }
@@ -15,16 +15,16 @@
 //! # 快速开始
 //!
 //! ```no_run
-//! use lame_sys::{LameEncoder, Quality};
+//! use lame_sys::prelude::*;
 //!
+//! # fn main() -> Result<()> {
 //! // 创建编码器
-//! let mut encoder = LameEncoder::builder()
-//!     .sample_rate(44100)      // 44.1 kHz
-//!     .channels(2)             // 立体声
-//!     .quality(Quality::Standard)  // 标准质量
-//!     .bitrate(192)            // 192 kbps
-//!     .build()
-//!     .unwrap();
+//! let mut encoder = LameEncoder::builder()?
+//!     .sample_rate(44100)?         // 44.1 kHz
+//!     .channels(2)?                // 立体声
+//!     .quality(Quality::Standard)? // 标准质量
+//!     .bitrate(192)?               // 192 kbps
+//!     .build()?;
 //!
 //! // 准备 PCM 数据
 //! let pcm_left = vec![0i16; 1152];   // 左声道
@@ -32,23 +32,23 @@
 //! let mut mp3_buffer = vec![0u8; 8192];
 //!
 //! // 编码
-//! let bytes_written = encoder.encode(&pcm_left, &pcm_right, &mut mp3_buffer)
-//!     .unwrap();
+//! let bytes_written = encoder.encode(&pcm_left, &pcm_right, &mut mp3_buffer)?;
 //!
 //! // 刷新缓冲区
-//! let final_bytes = encoder.flush(&mut mp3_buffer)
-//!     .unwrap();
+//! let final_bytes = encoder.flush(&mut mp3_buffer)?;
+//! # Ok(())
+//! # }
 //! ```
 //!
 //! # ID3 标签
 //!
 //! ```no_run
-//! use lame_sys::{LameEncoder, Id3Tag};
+//! use lame_sys::prelude::*;
 //!
-//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
-//! let mut encoder = LameEncoder::builder()
-//!     .sample_rate(44100)
-//!     .channels(2)
+//! # fn main() -> Result<()> {
+//! let mut encoder = LameEncoder::builder()?
+//!     .sample_rate(44100)?
+//!     .channels(2)?
 //!     .build()?;
 //!
 //! // 设置 ID3 标签
@@ -63,11 +63,55 @@
 //! # }
 //! ```
 //!
+//! # 按条件配置
+//!
+//! 构建器的每个设置方法都有一个 `set_` 开头、接受 `&mut self` 的版本，
+//! 适合按条件设置参数，不需要在每个分支里重新绑定构建器。设置失败时构建器仍然可用。
+//!
+//! ```no_run
+//! use lame_sys::prelude::*;
+//!
+//! # fn main() -> Result<()> {
+//! # let (voice, bitrate) = (true, Some(96));
+//! let mut builder = LameEncoder::builder()?;
+//! builder.set_sample_rate(44100)?.set_channels(2)?;
+//!
+//! if voice {
+//!     builder.set_mode(ChannelMode::Mono)?.set_lowpass(8000)?;
+//! }
+//! match bitrate {
+//!     Some(kbps) => builder.set_bitrate(kbps)?,
+//!     None => builder.set_vbr_mode(VbrMode::Vbr)?,
+//! };
+//!
+//! let encoder = builder.build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # 可复现性
+//!
+//! 编码结果只取决于输入 PCM 和编码参数：同一份输入用相同的参数编码两次，
+//! 得到的 MP3 逐字节相同，包括浮点输入。本 crate 的转换和 [`dsp`] 函数都不使用随机数，
+//! 也不读取时间或熵源。
+//!
+//! 这一保证限于同一个构建（同一平台和目标特性）。LAME 内部使用浮点运算，
+//! 并按 CPU 特性选择 SIMD 路径，不同平台或不同 `target-cpu` 的结果可能有细微差别。
+//!
+//! # 内存分配
+//!
+//! 预热之后，`encode_*` 方法（返回 `Vec` 的除外）和 [`Mp3Writer::write_pcm`]
+//! 不再分配堆内存，适合实时线程。需要转换格式的输入（8-bit、24-bit、按步长、迭代器）
+//! 使用编码器内部的暂存缓冲区，可以用 [`LameEncoder::reserve_scratch`] 提前分配。
+//! LAME 内部的缓冲区由 C 代码分配，不在此保证之内。
+//!
 //! # 许可证
 //!
 //! 本 crate 使用 LGPL-2.0 许可证，与 LAME 库保持一致。
 
 #![warn(missing_docs)]
+#![deny(unsafe_op_in_unsafe_fn)]
+#![warn(clippy::undocumented_unsafe_blocks)]
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
@@ -75,6 +119,8 @@
 // 包含 bindgen 生成的 FFI 绑定
 #[allow(missing_docs)]
 #[allow(dead_code)]
+#[allow(unsafe_op_in_unsafe_fn)]
+#[allow(clippy::undocumented_unsafe_blocks)]
 #[allow(non_upper_case_globals)]
 #[allow(non_camel_case_types)]
 #[allow(non_snake_case)]
@@ -83,21 +129,54 @@ mod ffi {
 }
 
 // 内部模块
+pub mod config;
+pub mod dsp;
 pub mod encoder;
 pub mod error;
+pub mod features;
+pub mod gapless;
 pub mod id3;
+pub mod lifecycle;
+pub mod param;
+pub mod pcm;
+pub mod pool;
+pub mod prelude;
+mod report;
+#[cfg(feature = "testing")]
+pub mod signal;
+pub mod watchdog;
+pub mod writer;
 
 // 重新导出公共 API
-pub use encoder::{EncoderBuilder, LameEncoder, Quality, VbrMode};
+pub use config::{ConfigDiff, EncoderConfig};
+pub use encoder::{
+    max_mp3_buffer_size, ChannelMode, Emphasis, EncoderBuilder, FinalizedStream, LameEncoder,
+    Preset, Quality, ShortBlockMode, VbrMode,
+};
 pub use error::{LameError, Result};
-pub use id3::{genres, Id3Tag};
+pub use features::{build_info, version, BuildInfo, Feature, LameVersion};
+pub use gapless::GaplessSession;
+pub use id3::{genres, Id3Metadata, Id3Summary, Id3Tag, Id3Version};
+pub use lifecycle::{live_handles, shutdown};
+pub use param::{RawParam, RawValue};
+pub use pcm::FloatScale;
+
+/// LAME 的 global flags 类型，用于 [`LameEncoder::into_raw`] 和 [`LameEncoder::from_raw`]
+#[cfg(feature = "raw")]
+pub use ffi::lame_global_flags;
+pub use watchdog::CancelToken;
+pub use writer::{
+    FrameProgress, FrameProgressHook, Mp3Buffer, Mp3Writer, StreamingEncoder, YieldPolicy,
+};
 
 /// 获取 LAME 版本字符串
 ///
 /// # 返回
 ///
-/// 返回 LAME 库的版本信息，例如 "3.100"
+/// 返回 LAME 库的版本信息，例如 "3.100"。需要比较版本号时用 [`version()`]，
+/// 可选能力见 [`build_info()`]。
 pub fn get_lame_version() -> String {
+    // SAFETY: get_lame_version 返回静态字符串或空指针，空指针已检查
     unsafe {
         let version_ptr = ffi::get_lame_version();
         if version_ptr.is_null() {
@@ -115,6 +194,7 @@ pub fn get_lame_version() -> String {
 ///
 /// 返回 LAME 项目的官方网址
 pub fn get_lame_url() -> String {
+    // SAFETY: get_lame_url 返回静态字符串或空指针，空指针已检查
     unsafe {
         let url_ptr = ffi::get_lame_url();
         if url_ptr.is_null() {
@@ -145,31 +225,45 @@ mod tests {
     }
 
     #[test]
-    fn test_encoder_creation() {
-        let result = LameEncoder::builder()
-            .sample_rate(44100)
-            .channels(2)
-            .bitrate(128)
-            .quality(Quality::Standard)
-            .build();
-
-        assert!(result.is_ok());
+    fn test_failed_init_params_explains_why() -> Result<()> {
+        let builder = LameEncoder::builder()?.sample_rate(44100)?.channels(2)?;
+        builder.init_params()?;
+
+        // 构建器的检查挡住了所有无效参数，这里让 LAME 初始化同一个句柄两次，它直接返回 -1
+        match builder.init_params() {
+            Err(LameError::InitializationFailed(msg)) => {
+                assert!(msg.contains("lame_init_params"), "{}", msg);
+                assert!(msg.contains("44100 Hz"), "{}", msg);
+            }
+            other => panic!("expected InitializationFailed, got {:?}", other),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_encoder_creation() -> Result<()> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?
+            .quality(Quality::Standard)?
+            .build()?;
+        Ok(())
     }
 
     #[test]
-    fn test_encode_basic() {
-        let mut encoder = LameEncoder::builder()
-            .sample_rate(44100)
-            .channels(2)
-            .bitrate(128)
-            .build()
-            .unwrap();
+    fn test_encode_basic() -> Result<()> {
+        let mut encoder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?
+            .build()?;
 
         // 创建静音样本
         let samples = vec![0i16; 1152];
         let mut mp3_buffer = vec![0u8; 8192];
 
-        let result = encoder.encode(&samples, &samples, &mut mp3_buffer);
-        assert!(result.is_ok());
+        encoder.encode(&samples, &samples, &mut mp3_buffer)?;
+        Ok(())
     }
 }
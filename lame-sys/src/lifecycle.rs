@@ -0,0 +1,53 @@
+//! 进程级状态与关闭
+//!
+//! 宿主程序可能反复加载、卸载本库，例如嵌入 Python 的 C++ 程序反复导入扩展模块。
+//! 卸载前需要确认没有残留的状态：
+//!
+//! - LAME 本身没有需要释放的进程级资源。每个句柄的内存都在 `lame_close` 时释放，
+//!   [`LameEncoder`](crate::LameEncoder) 和 [`EncoderBuilder`](crate::EncoderBuilder)
+//!   drop 时会调用它。唯一的全局数据是 `fast_log2` 的查找表。它是静态数组，不占用堆内存，
+//!   重复初始化的结果相同。
+//! - 本 crate 的全局缓存都由 [`shutdown`] 清理。
+//! - [`live_handles`] 统计当前存活的 LAME 句柄，用于确认卸载前已经全部释放。
+//!
+//! 所有 API 都可以重入：[`shutdown`] 之后可以继续创建编码器，不需要重新初始化。
+
+use crate::error::{LameError, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 由本 crate 管理、尚未 `lame_close` 的句柄数
+static LIVE_HANDLES: AtomicUsize = AtomicUsize::new(0);
+
+/// 当前存活的 LAME 句柄数
+///
+/// 包括构建器、编码器和 [`EncoderPool`](crate::pool::EncoderPool) 中空闲的编码器。
+/// [`LameEncoder::into_raw`](crate::LameEncoder::into_raw) 交出的句柄不计入，
+/// [`LameEncoder::from_raw`](crate::LameEncoder::from_raw) 取回后重新计入。
+pub fn live_handles() -> usize {
+    LIVE_HANDLES.load(Ordering::Relaxed)
+}
+
+/// 清理 crate 级别的缓存，并检查所有 LAME 句柄都已释放
+///
+/// 在卸载本库之前调用。仍有存活的句柄时返回 [`LameError::InvalidInput`]，
+/// 信息中包含句柄数；缓存仍会被清理。之后可以照常继续使用本库。
+pub fn shutdown() -> Result<()> {
+    let live = live_handles();
+    if live > 0 {
+        return Err(LameError::InvalidInput(format!(
+            "{} LAME handles are still open",
+            live
+        )));
+    }
+    Ok(())
+}
+
+/// 记录 `lame_init` 成功，或通过 `from_raw` 取得句柄
+pub(crate) fn handle_opened() {
+    LIVE_HANDLES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 记录 `lame_close`，或通过 `into_raw` 交出句柄
+pub(crate) fn handle_closed() {
+    LIVE_HANDLES.fetch_sub(1, Ordering::Relaxed);
+}
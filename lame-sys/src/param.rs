@@ -0,0 +1,299 @@
+//! 未封装的 LAME 参数
+//!
+//! LAME 有几十个 `lame_set_*` 参数，[`EncoderBuilder`](crate::EncoderBuilder) 不会逐一提供方法。
+//! [`RawParam`] 列出了可以读写的整数和浮点参数，通过
+//! [`EncoderBuilder::set_raw`](crate::EncoderBuilder::set_raw) 设置、
+//! [`LameEncoder::get_raw`](crate::LameEncoder::get_raw) 读取，不需要 `unsafe`。
+//!
+//! 参数的含义和取值范围见 `lame.h`，LAME 拒绝的值返回 [`LameError::InvalidParameter`]。
+//! 很多参数在 `build()` 时会被 LAME 补全或调整（例如 `-1` 表示"由 LAME 决定"），
+//! `get_raw` 读到的是最终生效的值。
+//!
+//! # 示例
+//!
+//! ```no_run
+//! use lame_sys::param::{RawParam, RawValue};
+//! use lame_sys::prelude::*;
+//!
+//! # fn main() -> Result<()> {
+//! let encoder = LameEncoder::builder()?
+//!     .sample_rate(44100)?
+//!     .channels(2)?
+//!     .set_raw(RawParam::QuantComp, RawValue::Int(3))?
+//!     .set_raw(RawParam::InterChRatio, RawValue::Float(0.002))?
+//!     .build()?;
+//!
+//! assert_eq!(encoder.get_raw(RawParam::QuantComp), RawValue::Int(3));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{LameError, Result};
+use crate::ffi;
+
+/// 参数值，类型与 LAME 读写函数的参数类型一致
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RawValue {
+    /// 整数参数
+    Int(i32),
+    /// 浮点参数
+    Float(f32),
+}
+
+impl RawValue {
+    /// 整数值；浮点值返回 `None`
+    pub fn as_int(self) -> Option<i32> {
+        match self {
+            RawValue::Int(value) => Some(value),
+            RawValue::Float(_) => None,
+        }
+    }
+
+    /// 浮点值；整数值转换为浮点
+    pub fn as_float(self) -> f32 {
+        match self {
+            RawValue::Int(value) => value as f32,
+            RawValue::Float(value) => value,
+        }
+    }
+}
+
+impl From<i32> for RawValue {
+    fn from(value: i32) -> Self {
+        RawValue::Int(value)
+    }
+}
+
+impl From<f32> for RawValue {
+    fn from(value: f32) -> Self {
+        RawValue::Float(value)
+    }
+}
+
+/// 可以通过 [`EncoderBuilder::set_raw`](crate::EncoderBuilder::set_raw) 读写的 LAME 参数
+///
+/// 每个变体对应一对 `lame_set_*` / `lame_get_*` 函数，[`name`](Self::name) 是去掉前缀的函数名。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RawParam {
+    /// 长块的量化比较函数（`quant_comp`）
+    QuantComp,
+    /// 短块的量化比较函数（`quant_comp_short`）
+    QuantCompShort,
+    /// 心理声学模型的实验选项（`exp_nspsytune`）
+    ExpNspsytune,
+    /// 实验选项 X（`experimentalX`）
+    ExperimentalX,
+    /// 实验选项 Y（`experimentalY`）
+    ExperimentalY,
+    /// 实验选项 Z（`experimentalZ`）
+    ExperimentalZ,
+    /// 严格遵守 ISO 标准（`strict_ISO`）
+    StrictIso,
+    /// 关闭比特池（`disable_reservoir`）
+    DisableReservoir,
+    /// CRC 校验（`error_protection`）
+    ErrorProtection,
+    /// 严格执行 VBR 最小比特率（`VBR_hard_min`）
+    VbrHardMin,
+    /// 低通滤波过渡带宽度，Hz（`lowpasswidth`）
+    LowpassWidth,
+    /// 高通滤波过渡带宽度，Hz（`highpasswidth`）
+    HighpassWidth,
+    /// 只用绝对听阈（`ATHonly`）
+    AthOnly,
+    /// 短块只用绝对听阈（`ATHshort`）
+    AthShort,
+    /// 不使用绝对听阈（`noATH`）
+    NoAth,
+    /// 绝对听阈曲线类型（`ATHtype`）
+    AthType,
+    /// 绝对听阈降低的分贝数（`ATHlower`）
+    AthLower,
+    /// 自适应 ATH 的类型（`athaa_type`）
+    AthaaType,
+    /// 自适应 ATH 的灵敏度，dB（`athaa_sensitivity`）
+    AthaaSensitivity,
+    /// 允许两个声道使用不同的块类型（`allow_diff_short`）
+    AllowDiffShort,
+    /// 声道间掩蔽比例，0-1（`interChRatio`）
+    InterChRatio,
+    /// 压缩比，代替比特率（`compression_ratio`）
+    CompressionRatio,
+    /// 输入缩放系数（`scale`）
+    Scale,
+    /// 左声道缩放系数（`scale_left`）
+    ScaleLeft,
+    /// 右声道缩放系数（`scale_right`）
+    ScaleRight,
+}
+
+/// 参数对应的 LAME 读写函数
+pub(crate) enum Accessor {
+    /// 整数参数：`(lame_set_*, lame_get_*)`
+    Int(
+        unsafe extern "C" fn(*mut ffi::lame_global_flags, i32) -> i32,
+        unsafe extern "C" fn(*const ffi::lame_global_flags) -> i32,
+    ),
+    /// 浮点参数：`(lame_set_*, lame_get_*)`
+    Float(
+        unsafe extern "C" fn(*mut ffi::lame_global_flags, f32) -> i32,
+        unsafe extern "C" fn(*const ffi::lame_global_flags) -> f32,
+    ),
+}
+
+impl RawParam {
+    /// 所有参数
+    pub const ALL: [RawParam; 25] = [
+        RawParam::QuantComp,
+        RawParam::QuantCompShort,
+        RawParam::ExpNspsytune,
+        RawParam::ExperimentalX,
+        RawParam::ExperimentalY,
+        RawParam::ExperimentalZ,
+        RawParam::StrictIso,
+        RawParam::DisableReservoir,
+        RawParam::ErrorProtection,
+        RawParam::VbrHardMin,
+        RawParam::LowpassWidth,
+        RawParam::HighpassWidth,
+        RawParam::AthOnly,
+        RawParam::AthShort,
+        RawParam::NoAth,
+        RawParam::AthType,
+        RawParam::AthLower,
+        RawParam::AthaaType,
+        RawParam::AthaaSensitivity,
+        RawParam::AllowDiffShort,
+        RawParam::InterChRatio,
+        RawParam::CompressionRatio,
+        RawParam::Scale,
+        RawParam::ScaleLeft,
+        RawParam::ScaleRight,
+    ];
+
+    /// LAME 中的参数名，即 `lame_set_` 之后的部分，例如 `"interChRatio"`
+    pub fn name(self) -> &'static str {
+        match self {
+            RawParam::QuantComp => "quant_comp",
+            RawParam::QuantCompShort => "quant_comp_short",
+            RawParam::ExpNspsytune => "exp_nspsytune",
+            RawParam::ExperimentalX => "experimentalX",
+            RawParam::ExperimentalY => "experimentalY",
+            RawParam::ExperimentalZ => "experimentalZ",
+            RawParam::StrictIso => "strict_ISO",
+            RawParam::DisableReservoir => "disable_reservoir",
+            RawParam::ErrorProtection => "error_protection",
+            RawParam::VbrHardMin => "VBR_hard_min",
+            RawParam::LowpassWidth => "lowpasswidth",
+            RawParam::HighpassWidth => "highpasswidth",
+            RawParam::AthOnly => "ATHonly",
+            RawParam::AthShort => "ATHshort",
+            RawParam::NoAth => "noATH",
+            RawParam::AthType => "ATHtype",
+            RawParam::AthLower => "ATHlower",
+            RawParam::AthaaType => "athaa_type",
+            RawParam::AthaaSensitivity => "athaa_sensitivity",
+            RawParam::AllowDiffShort => "allow_diff_short",
+            RawParam::InterChRatio => "interChRatio",
+            RawParam::CompressionRatio => "compression_ratio",
+            RawParam::Scale => "scale",
+            RawParam::ScaleLeft => "scale_left",
+            RawParam::ScaleRight => "scale_right",
+        }
+    }
+
+    /// 按 [`name`](Self::name) 查找参数
+    ///
+    /// 未知的名称返回 [`LameError::InvalidParameter`]。
+    pub fn from_name(name: &str) -> Result<Self> {
+        RawParam::ALL
+            .into_iter()
+            .find(|param| param.name() == name)
+            .ok_or_else(|| LameError::InvalidParameter(format!("unknown LAME parameter {}", name)))
+    }
+
+    /// 是否为浮点参数
+    pub fn is_float(self) -> bool {
+        matches!(self.accessor(), Accessor::Float(..))
+    }
+
+    pub(crate) fn accessor(self) -> Accessor {
+        match self {
+            RawParam::QuantComp => {
+                Accessor::Int(ffi::lame_set_quant_comp, ffi::lame_get_quant_comp)
+            }
+            RawParam::QuantCompShort => Accessor::Int(
+                ffi::lame_set_quant_comp_short,
+                ffi::lame_get_quant_comp_short,
+            ),
+            RawParam::ExpNspsytune => {
+                Accessor::Int(ffi::lame_set_exp_nspsytune, ffi::lame_get_exp_nspsytune)
+            }
+            RawParam::ExperimentalX => {
+                Accessor::Int(ffi::lame_set_experimentalX, ffi::lame_get_experimentalX)
+            }
+            RawParam::ExperimentalY => {
+                Accessor::Int(ffi::lame_set_experimentalY, ffi::lame_get_experimentalY)
+            }
+            RawParam::ExperimentalZ => {
+                Accessor::Int(ffi::lame_set_experimentalZ, ffi::lame_get_experimentalZ)
+            }
+            RawParam::StrictIso => {
+                Accessor::Int(ffi::lame_set_strict_ISO, ffi::lame_get_strict_ISO)
+            }
+            RawParam::DisableReservoir => Accessor::Int(
+                ffi::lame_set_disable_reservoir,
+                ffi::lame_get_disable_reservoir,
+            ),
+            RawParam::ErrorProtection => Accessor::Int(
+                ffi::lame_set_error_protection,
+                ffi::lame_get_error_protection,
+            ),
+            RawParam::VbrHardMin => {
+                Accessor::Int(ffi::lame_set_VBR_hard_min, ffi::lame_get_VBR_hard_min)
+            }
+            RawParam::LowpassWidth => {
+                Accessor::Int(ffi::lame_set_lowpasswidth, ffi::lame_get_lowpasswidth)
+            }
+            RawParam::HighpassWidth => {
+                Accessor::Int(ffi::lame_set_highpasswidth, ffi::lame_get_highpasswidth)
+            }
+            RawParam::AthOnly => Accessor::Int(ffi::lame_set_ATHonly, ffi::lame_get_ATHonly),
+            RawParam::AthShort => Accessor::Int(ffi::lame_set_ATHshort, ffi::lame_get_ATHshort),
+            RawParam::NoAth => Accessor::Int(ffi::lame_set_noATH, ffi::lame_get_noATH),
+            RawParam::AthType => Accessor::Int(ffi::lame_set_ATHtype, ffi::lame_get_ATHtype),
+            RawParam::AthLower => Accessor::Float(ffi::lame_set_ATHlower, ffi::lame_get_ATHlower),
+            RawParam::AthaaType => {
+                Accessor::Int(ffi::lame_set_athaa_type, ffi::lame_get_athaa_type)
+            }
+            RawParam::AthaaSensitivity => Accessor::Float(
+                ffi::lame_set_athaa_sensitivity,
+                ffi::lame_get_athaa_sensitivity,
+            ),
+            RawParam::AllowDiffShort => Accessor::Int(
+                ffi::lame_set_allow_diff_short,
+                ffi::lame_get_allow_diff_short,
+            ),
+            RawParam::InterChRatio => {
+                Accessor::Float(ffi::lame_set_interChRatio, ffi::lame_get_interChRatio)
+            }
+            RawParam::CompressionRatio => Accessor::Float(
+                ffi::lame_set_compression_ratio,
+                ffi::lame_get_compression_ratio,
+            ),
+            RawParam::Scale => Accessor::Float(ffi::lame_set_scale, ffi::lame_get_scale),
+            RawParam::ScaleLeft => {
+                Accessor::Float(ffi::lame_set_scale_left, ffi::lame_get_scale_left)
+            }
+            RawParam::ScaleRight => {
+                Accessor::Float(ffi::lame_set_scale_right, ffi::lame_get_scale_right)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RawParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
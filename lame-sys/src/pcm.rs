@@ -0,0 +1,483 @@
+//! 通用 PCM 输入
+//!
+//! 用声道布局包装类型（[`MonoPcm`]、[`DualPcm`]、[`InterleavedPcm`]）配合样本类型
+//! （`i16`、`i32`、`f32`、`f64`），通过 [`LameEncoder::encode_pcm`] 一个方法完成所有组合的编码。
+//!
+//! 样本缩放约定：
+//!
+//! - `i16`：满量程 ±32768
+//! - `i32`：满量程 ±2^31
+//! - `f32` / `f64`：满量程 ±1.0
+//!
+//! 满量程为 ±32768 的 `f32` 样本（例如 DAW 导出的未归一化数据）需要用
+//! [`FloatScale::ShortRange`] 和 `LameEncoder::encode_float` 等接受 [`FloatScale`] 的方法编码，
+//! 它们调用 `lame_encode_buffer_float` 而不是 `lame_encode_buffer_ieee_float`。
+//!
+//! 每个声道超过 [`MAX_SAMPLES_PER_CALL`] 个样本的输入会被拆分为多次 LAME 调用，
+//! 避免样本数转换为 `i32` 时溢出。
+//!
+//! # 示例
+//!
+//! ```no_run
+//! use lame_sys::pcm::{DualPcm, InterleavedPcm, MonoPcm};
+//! use lame_sys::prelude::*;
+//!
+//! # fn main() -> Result<()> {
+//! let mut encoder = LameEncoder::builder()?.sample_rate(44100)?.channels(2)?.build()?;
+//! let mut mp3_buffer = vec![0u8; 8192];
+//!
+//! let left = vec![0.0f32; 1152];
+//! let right = vec![0.0f32; 1152];
+//! encoder.encode_pcm(DualPcm::new(&left, &right), &mut mp3_buffer)?;
+//!
+//! let interleaved = vec![0i32; 1152 * 2];
+//! encoder.encode_pcm(InterleavedPcm(&interleaved), &mut mp3_buffer)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`LameEncoder::encode_pcm`]: crate::LameEncoder::encode_pcm
+
+use crate::encoder::LameEncoder;
+use crate::error::{LameError, Result};
+use crate::ffi;
+use std::ptr;
+
+/// 单次 LAME 调用处理的最大样本数（每个声道）
+///
+/// LAME 的样本数和缓冲区大小参数都是 `int`，更长的输入会被拆分为多次调用。
+pub const MAX_SAMPLES_PER_CALL: usize = 1 << 20;
+
+pub(crate) mod sealed {
+    use crate::error::Result;
+    use crate::ffi;
+
+    pub trait Sealed {}
+
+    /// 样本类型到 `lame_encode_buffer_*` 的分派
+    ///
+    /// # 安全性
+    ///
+    /// 两个方法都要求：`gfp` 是有效且没有被其他线程使用的句柄；每个非空的输入指针至少有
+    /// `num_samples` 个样本（交错输入为 `num_samples * 2` 个）；`mp3_buffer` 至少有
+    /// `mp3_buffer_size` 字节可写。
+    pub trait SampleDispatch: Sized {
+        /// 分离声道编码（`right` 为空指针时按单声道处理）
+        unsafe fn encode_dual(
+            gfp: *mut ffi::lame_global_flags,
+            left: *const Self,
+            right: *const Self,
+            num_samples: i32,
+            mp3_buffer: *mut u8,
+            mp3_buffer_size: i32,
+        ) -> i32;
+
+        /// 交错立体声编码
+        unsafe fn encode_interleaved(
+            gfp: *mut ffi::lame_global_flags,
+            pcm: *const Self,
+            num_samples: i32,
+            mp3_buffer: *mut u8,
+            mp3_buffer_size: i32,
+        ) -> i32;
+    }
+
+    /// 分段调用的控制参数
+    pub struct ChunkControl<'a> {
+        /// 单次 LAME 调用的最大样本数（每个声道）
+        pub max_samples: usize,
+        /// 两次调用之间执行，参数为 `(已完成样本数, 总样本数)`，返回 `false` 时取消剩余调用
+        pub between_chunks: &'a mut dyn FnMut(usize, usize) -> bool,
+        /// 输入通过校验、至少调用过一次 LAME 时置为 `true`
+        pub called_lame: bool,
+    }
+
+    /// 声道布局到底层调用的分派
+    pub trait InputDispatch {
+        /// 校验输入并调用 LAME，返回写入 `mp3_buffer` 的字节数
+        ///
+        /// # 安全性
+        ///
+        /// `gfp` 必须是有效的句柄，且调用期间没有其他访问。
+        unsafe fn encode_raw(
+            &self,
+            gfp: *mut ffi::lame_global_flags,
+            mp3_buffer: &mut [u8],
+            control: &mut ChunkControl<'_>,
+        ) -> Result<usize>;
+    }
+}
+
+/// 浮点样本的满量程约定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatScale {
+    /// 满量程 ±1.0，对应 `lame_encode_buffer_ieee_float`
+    #[default]
+    Normalized,
+    /// 满量程 ±32768，对应 `lame_encode_buffer_float`
+    ShortRange,
+}
+
+/// 满量程为 ±32768 的 `f32` 样本
+///
+/// 只在 crate 内部使用，由 [`short_range`] 从 `&[f32]` 转换得到。
+#[derive(Debug, Clone, Copy)]
+#[repr(transparent)]
+#[allow(dead_code)] // 只通过指针转换读取
+pub(crate) struct ShortRangeF32(f32);
+
+/// 把 `f32` 切片按 [`FloatScale::ShortRange`] 解释
+#[inline(always)]
+pub(crate) fn short_range(pcm: &[f32]) -> &[ShortRangeF32] {
+    // SAFETY: ShortRangeF32 是 repr(transparent) 的 f32
+    unsafe { std::slice::from_raw_parts(pcm.as_ptr() as *const ShortRangeF32, pcm.len()) }
+}
+
+/// 支持的 PCM 样本类型：`i16`、`i32`、`f32`、`f64`
+///
+/// 该 trait 是 sealed 的，不能在 crate 外实现。
+pub trait PcmSample: sealed::Sealed + sealed::SampleDispatch + Copy {}
+
+/// 可被 [`LameEncoder::encode_pcm`](crate::LameEncoder::encode_pcm) 编码的 PCM 输入
+///
+/// 该 trait 是 sealed 的，由 [`MonoPcm`]、[`DualPcm`]、[`InterleavedPcm`] 实现。
+pub trait PcmInput: sealed::Sealed + sealed::InputDispatch {}
+
+/// 单声道 PCM 数据
+#[derive(Debug, Clone, Copy)]
+pub struct MonoPcm<'a, T: PcmSample>(pub &'a [T]);
+
+/// 分离的左右声道 PCM 数据
+#[derive(Debug, Clone, Copy)]
+pub struct DualPcm<'a, T: PcmSample> {
+    /// 左声道样本
+    pub left: &'a [T],
+    /// 右声道样本
+    pub right: &'a [T],
+}
+
+impl<'a, T: PcmSample> DualPcm<'a, T> {
+    /// 创建分离声道输入
+    pub fn new(left: &'a [T], right: &'a [T]) -> Self {
+        Self { left, right }
+    }
+}
+
+/// 交错立体声 PCM 数据（L, R, L, R, ...）
+#[derive(Debug, Clone, Copy)]
+pub struct InterleavedPcm<'a, T: PcmSample>(pub &'a [T]);
+
+impl<T: PcmSample> sealed::Sealed for MonoPcm<'_, T> {}
+impl<T: PcmSample> sealed::Sealed for DualPcm<'_, T> {}
+impl<T: PcmSample> sealed::Sealed for InterleavedPcm<'_, T> {}
+
+impl<T: PcmSample> PcmInput for MonoPcm<'_, T> {}
+impl<T: PcmSample> PcmInput for DualPcm<'_, T> {}
+impl<T: PcmSample> PcmInput for InterleavedPcm<'_, T> {}
+
+impl<T: PcmSample> sealed::InputDispatch for MonoPcm<'_, T> {
+    #[inline(always)]
+    unsafe fn encode_raw(
+        &self,
+        gfp: *mut ffi::lame_global_flags,
+        mp3_buffer: &mut [u8],
+        control: &mut sealed::ChunkControl<'_>,
+    ) -> Result<usize> {
+        let pcm = self.0;
+
+        encode_chunked(
+            pcm.len(),
+            mp3_buffer,
+            control,
+            // SAFETY: 调用者保证 gfp 有效；encode_chunked 保证 start + len 不超过输入长度，
+            // out 至少有 out_size 字节
+            |start, len, out, out_size| unsafe {
+                T::encode_dual(
+                    gfp,
+                    pcm.as_ptr().add(start),
+                    ptr::null(), // 单声道传递 null 指针
+                    len,
+                    out,
+                    out_size,
+                )
+            },
+        )
+    }
+}
+
+impl<T: PcmSample> sealed::InputDispatch for DualPcm<'_, T> {
+    #[inline(always)]
+    unsafe fn encode_raw(
+        &self,
+        gfp: *mut ffi::lame_global_flags,
+        mp3_buffer: &mut [u8],
+        control: &mut sealed::ChunkControl<'_>,
+    ) -> Result<usize> {
+        if self.left.len() != self.right.len() {
+            return Err(LameError::InvalidInput(
+                "Left and right channel lengths must match".to_string(),
+            ));
+        }
+
+        let (left, right) = (self.left, self.right);
+
+        encode_chunked(
+            left.len(),
+            mp3_buffer,
+            control,
+            // SAFETY: 同 MonoPcm，左右声道长度已在上面检查过
+            |start, len, out, out_size| unsafe {
+                T::encode_dual(
+                    gfp,
+                    left.as_ptr().add(start),
+                    right.as_ptr().add(start),
+                    len,
+                    out,
+                    out_size,
+                )
+            },
+        )
+    }
+}
+
+impl<T: PcmSample> sealed::InputDispatch for InterleavedPcm<'_, T> {
+    #[inline(always)]
+    unsafe fn encode_raw(
+        &self,
+        gfp: *mut ffi::lame_global_flags,
+        mp3_buffer: &mut [u8],
+        control: &mut sealed::ChunkControl<'_>,
+    ) -> Result<usize> {
+        // 单声道编码器会把交错数据当作一个声道，输出的是错误的音频
+        // SAFETY: 调用者保证 gfp 有效，lame_get_num_channels 只读取参数
+        if unsafe { ffi::lame_get_num_channels(gfp) } != 2 {
+            return Err(LameError::InvalidInput(
+                "interleaved encode requires a 2-channel encoder".to_string(),
+            ));
+        }
+        if self.0.len() % 2 != 0 {
+            return Err(LameError::InvalidInput(format!(
+                "Interleaved stereo data length must be even, got {}",
+                self.0.len()
+            )));
+        }
+
+        let pcm = self.0;
+
+        encode_chunked(
+            pcm.len() / 2,
+            mp3_buffer,
+            control,
+            // SAFETY: 同 MonoPcm，按帧计数，长度已确认是偶数
+            |start, len, out, out_size| unsafe {
+                T::encode_interleaved(gfp, pcm.as_ptr().add(start * 2), len, out, out_size)
+            },
+        )
+    }
+}
+
+/// 按 [`MAX_SAMPLES_PER_CALL`] 和 `control.max_samples` 中较小者拆分输入并累计写入的字节数
+///
+/// `encode_chunk` 接收 `(起始样本, 样本数, 输出指针, 输出大小)`，返回 LAME 的结果码。
+/// 空输入也会调用一次，与直接调用 LAME 的行为保持一致。
+#[inline(always)]
+fn encode_chunked<F>(
+    samples_per_channel: usize,
+    mp3_buffer: &mut [u8],
+    control: &mut sealed::ChunkControl<'_>,
+    mut encode_chunk: F,
+) -> Result<usize>
+where
+    F: FnMut(usize, i32, *mut u8, i32) -> i32,
+{
+    let chunk_samples = control.max_samples.clamp(1, MAX_SAMPLES_PER_CALL);
+    let mut written = 0;
+    let mut start = 0;
+
+    loop {
+        let len = (samples_per_channel - start).min(chunk_samples);
+        let out = &mut mp3_buffer[written..];
+        let out_size = out.len().min(i32::MAX as usize) as i32;
+
+        control.called_lame = true;
+        match encode_chunk(start, len as i32, out.as_mut_ptr(), out_size) {
+            // -1 表示 mp3buf 太小
+            -1 => {
+                return Err(LameError::BufferTooSmall {
+                    required: written + LameEncoder::mp3_buffer_size(samples_per_channel - start),
+                    provided: mp3_buffer.len(),
+                })
+            }
+            result if result < 0 => return Err(LameError::encoding_failed(result, None)),
+            result => written += result as usize,
+        }
+
+        start += len;
+        if start >= samples_per_channel {
+            return Ok(written);
+        }
+
+        if !(control.between_chunks)(start, samples_per_channel) {
+            return Err(LameError::Cancelled {
+                bytes_written: written,
+            });
+        }
+    }
+}
+
+macro_rules! impl_sample {
+    ($ty:ty, $dual:ident, $interleaved:ident) => {
+        impl sealed::Sealed for $ty {}
+        impl PcmSample for $ty {}
+
+        impl sealed::SampleDispatch for $ty {
+            #[inline(always)]
+            unsafe fn encode_dual(
+                gfp: *mut ffi::lame_global_flags,
+                left: *const Self,
+                right: *const Self,
+                num_samples: i32,
+                mp3_buffer: *mut u8,
+                mp3_buffer_size: i32,
+            ) -> i32 {
+                // SAFETY: 由 SampleDispatch 的调用约定保证
+                unsafe { ffi::$dual(gfp, left, right, num_samples, mp3_buffer, mp3_buffer_size) }
+            }
+
+            #[inline(always)]
+            unsafe fn encode_interleaved(
+                gfp: *mut ffi::lame_global_flags,
+                pcm: *const Self,
+                num_samples: i32,
+                mp3_buffer: *mut u8,
+                mp3_buffer_size: i32,
+            ) -> i32 {
+                // lame_encode_buffer_interleaved 的签名不带 const，但不会修改输入
+                // SAFETY: 由 SampleDispatch 的调用约定保证
+                unsafe {
+                    ffi::$interleaved(gfp, pcm as *mut _, num_samples, mp3_buffer, mp3_buffer_size)
+                }
+            }
+        }
+    };
+}
+
+impl_sample!(i16, lame_encode_buffer, lame_encode_buffer_interleaved);
+impl_sample!(
+    i32,
+    lame_encode_buffer_int,
+    lame_encode_buffer_interleaved_int
+);
+impl_sample!(
+    f32,
+    lame_encode_buffer_ieee_float,
+    lame_encode_buffer_interleaved_ieee_float
+);
+impl_sample!(
+    f64,
+    lame_encode_buffer_ieee_double,
+    lame_encode_buffer_interleaved_ieee_double
+);
+
+impl sealed::Sealed for ShortRangeF32 {}
+impl PcmSample for ShortRangeF32 {}
+
+impl sealed::SampleDispatch for ShortRangeF32 {
+    #[inline(always)]
+    unsafe fn encode_dual(
+        gfp: *mut ffi::lame_global_flags,
+        left: *const Self,
+        right: *const Self,
+        num_samples: i32,
+        mp3_buffer: *mut u8,
+        mp3_buffer_size: i32,
+    ) -> i32 {
+        // SAFETY: 由 SampleDispatch 的调用约定保证，ShortRangeF32 与 f32 布局相同
+        unsafe {
+            ffi::lame_encode_buffer_float(
+                gfp,
+                left as *const f32,
+                right as *const f32,
+                num_samples,
+                mp3_buffer,
+                mp3_buffer_size,
+            )
+        }
+    }
+
+    unsafe fn encode_interleaved(
+        gfp: *mut ffi::lame_global_flags,
+        pcm: *const Self,
+        num_samples: i32,
+        mp3_buffer: *mut u8,
+        mp3_buffer_size: i32,
+    ) -> i32 {
+        // LAME 没有交错的 lame_encode_buffer_float，逐块拆分到栈上的左右声道缓冲区，
+        // 编码路径上不分配内存
+        const CHUNK: usize = 1152;
+        // SAFETY: 调用约定保证 pcm 有 num_samples * 2 个样本
+        let pcm = unsafe {
+            std::slice::from_raw_parts(pcm as *const f32, num_samples.max(0) as usize * 2)
+        };
+        // 中途失败时前面几块已经交给 LAME、写入了输出，结果码却无法带回已写入的字节数。
+        // 所以拆成多块之前先按整个输入的最坏情况检查输出空间，保证不会中途返回 -1
+        if pcm.len() > CHUNK * 2
+            && (mp3_buffer_size.max(0) as usize) < LameEncoder::mp3_buffer_size(pcm.len() / 2)
+        {
+            return -1;
+        }
+        let mut left = [0.0f32; CHUNK];
+        let mut right = [0.0f32; CHUNK];
+
+        let mut written = 0;
+        let mut start = 0;
+        loop {
+            let frames = &pcm[start * 2..pcm.len().min((start + CHUNK) * 2)];
+            let len = frames.len() / 2;
+            for (i, frame) in frames.chunks_exact(2).enumerate() {
+                left[i] = frame[0];
+                right[i] = frame[1];
+            }
+
+            // SAFETY: left 和 right 各有至少 len 个样本；written 不超过 mp3_buffer_size，
+            // 剩余的输出空间仍在 mp3_buffer 内，其余同 SampleDispatch 的调用约定
+            let result = unsafe {
+                ffi::lame_encode_buffer_float(
+                    gfp,
+                    left.as_ptr(),
+                    right.as_ptr(),
+                    len as i32,
+                    mp3_buffer.add(written as usize),
+                    mp3_buffer_size - written,
+                )
+            };
+            if result < 0 {
+                return result;
+            }
+            written += result;
+
+            start += len;
+            if start * 2 >= pcm.len() {
+                return written;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_range_reinterprets_in_place() {
+        let pcm = [0.0f32, -32768.0, 12.5, f32::MAX];
+        let view = short_range(&pcm);
+        assert_eq!(view.len(), pcm.len());
+        assert_eq!(view.as_ptr() as *const f32, pcm.as_ptr());
+        assert!(view
+            .iter()
+            .zip(&pcm)
+            .all(|(sample, &value)| sample.0 == value));
+        assert!(short_range(&[]).is_empty());
+    }
+}
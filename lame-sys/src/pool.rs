@@ -0,0 +1,202 @@
+//! 编码器池
+//!
+//! 为服务端场景复用编码器：每个请求从池中借出一个编码器，
+//! 归还时编码器会被重置为干净状态，不会残留上一个请求的 ID3 标签或比特流。
+
+use crate::config::EncoderConfig;
+use crate::encoder::LameEncoder;
+use crate::error::Result;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex};
+
+/// 池的共享状态
+struct Shared {
+    config: EncoderConfig,
+    max_size: usize,
+    state: Mutex<State>,
+    available: Condvar,
+}
+
+struct State {
+    /// 空闲的编码器
+    idle: Vec<LameEncoder>,
+    /// 已创建（空闲 + 借出）的编码器数量
+    created: usize,
+}
+
+/// 线程安全的编码器池
+///
+/// 池最多持有 `max_size` 个编码器。当所有编码器都被借出时，
+/// [`acquire`](Self::acquire) 会阻塞直到有编码器归还。
+///
+/// `EncoderPool` 可以廉价地克隆，所有克隆共享同一个池。
+///
+/// # 示例
+///
+/// ```no_run
+/// use lame_sys::pool::EncoderPool;
+/// use lame_sys::prelude::*;
+///
+/// # fn main() -> Result<()> {
+/// let pool = EncoderPool::new(EncoderConfig::default(), 4);
+///
+/// let mut encoder = pool.acquire()?;
+/// let pcm = vec![0i16; 1152 * 2];
+/// let mut mp3_buffer = vec![0u8; 8192];
+/// encoder.encode_interleaved(&pcm, &mut mp3_buffer)?;
+/// encoder.flush(&mut mp3_buffer)?;
+/// // 离开作用域时编码器被重置并归还到池中
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct EncoderPool {
+    shared: Arc<Shared>,
+}
+
+impl EncoderPool {
+    /// 创建新的编码器池
+    ///
+    /// 编码器按需创建，`max_size` 为 0 时按 1 处理。
+    pub fn new(config: EncoderConfig, max_size: usize) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                config,
+                max_size: max_size.max(1),
+                state: Mutex::new(State {
+                    idle: Vec::new(),
+                    created: 0,
+                }),
+                available: Condvar::new(),
+            }),
+        }
+    }
+
+    /// 池使用的编码器配置
+    pub fn config(&self) -> &EncoderConfig {
+        &self.shared.config
+    }
+
+    /// 池的最大容量
+    pub fn max_size(&self) -> usize {
+        self.shared.max_size
+    }
+
+    /// 当前空闲的编码器数量
+    pub fn idle_count(&self) -> usize {
+        self.shared.lock().idle.len()
+    }
+
+    /// 借出一个编码器
+    ///
+    /// 优先复用空闲编码器；若没有空闲编码器且未达到容量上限则新建一个，
+    /// 否则阻塞等待其他持有者归还。
+    pub fn acquire(&self) -> Result<PooledEncoder> {
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(encoder) = state.idle.pop() {
+                return Ok(self.wrap(encoder));
+            }
+
+            if state.created < self.shared.max_size {
+                state.created += 1;
+                drop(state);
+
+                return match self.shared.config.build() {
+                    Ok(encoder) => Ok(self.wrap(encoder)),
+                    Err(err) => {
+                        self.shared.release_slot();
+                        Err(err)
+                    }
+                };
+            }
+
+            state = self
+                .shared
+                .available
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    fn wrap(&self, encoder: LameEncoder) -> PooledEncoder {
+        PooledEncoder {
+            encoder: Some(encoder),
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl std::fmt::Debug for EncoderPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncoderPool")
+            .field("config", &self.shared.config)
+            .field("max_size", &self.shared.max_size)
+            .finish()
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // 持锁期间不会执行用户代码，毒化的锁中状态仍然一致
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 放弃一个容量名额（编码器创建失败或重置失败时）
+    fn release_slot(&self) {
+        self.lock().created -= 1;
+        self.available.notify_one();
+    }
+}
+
+/// 从 [`EncoderPool`] 借出的编码器
+///
+/// 通过 `Deref` 当作 [`LameEncoder`] 使用。Drop 时编码器会被重置并归还到池中。
+///
+/// LAME 无法在 `lame_init_params()` 之后重新初始化同一个句柄，
+/// 因此重置通过按池配置重新创建编码器来完成。
+pub struct PooledEncoder {
+    encoder: Option<LameEncoder>,
+    shared: Arc<Shared>,
+}
+
+impl Deref for PooledEncoder {
+    type Target = LameEncoder;
+
+    fn deref(&self) -> &LameEncoder {
+        self.encoder
+            .as_ref()
+            .expect("pooled encoder already returned")
+    }
+}
+
+impl DerefMut for PooledEncoder {
+    fn deref_mut(&mut self) -> &mut LameEncoder {
+        self.encoder
+            .as_mut()
+            .expect("pooled encoder already returned")
+    }
+}
+
+impl std::fmt::Debug for PooledEncoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PooledEncoder")
+            .field("encoder", &self.encoder)
+            .finish()
+    }
+}
+
+impl Drop for PooledEncoder {
+    fn drop(&mut self) {
+        // 先释放旧编码器，再创建新的，避免同时持有两份 LAME 状态
+        drop(self.encoder.take());
+
+        match self.shared.config.build() {
+            Ok(fresh) => {
+                self.shared.lock().idle.push(fresh);
+                self.shared.available.notify_one();
+            }
+            Err(_) => self.shared.release_slot(),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! 常用类型的统一导出
+//!
+//! 大多数场景只需要一行导入：
+//!
+//! ```no_run
+//! use lame_sys::prelude::*;
+//!
+//! # fn main() -> Result<()> {
+//! let mut encoder = LameEncoder::builder()?
+//!     .sample_rate(44100)?
+//!     .channels(2)?
+//!     .quality(Quality::Standard)?
+//!     .build()?;
+//!
+//! Id3Tag::new(&mut encoder).title("My Song")?.apply()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! 从 prelude 中移除任何条目都属于破坏性变更，`tests/prelude_test.rs`
+//! 逐个导入了全部条目，意外删除时无法编译。
+
+pub use crate::config::EncoderConfig;
+pub use crate::encoder::{
+    max_mp3_buffer_size, ChannelMode, Emphasis, EncoderBuilder, LameEncoder, Preset, Quality,
+    ShortBlockMode, VbrMode,
+};
+pub use crate::error::{LameError, Result};
+pub use crate::id3::{Id3Metadata, Id3Tag, Id3Version};
+pub use crate::pcm::FloatScale;
+pub use crate::writer::{Mp3Buffer, Mp3Writer, StreamingEncoder};
@@ -0,0 +1,128 @@
+//! 收集 LAME 的消息、调试和错误输出
+//!
+//! LAME 通过 `lame_set_msgf`、`lame_set_debugf` 和 `lame_set_errorf` 设置的 printf 风格回调
+//! 输出配置信息、调试信息和错误，默认都写到 stderr。回调的参数是 C 的 `va_list`，
+//! 稳定版 Rust 无法接收，因此由 `csrc/report.c` 中的垫片用 `vsnprintf` 格式化后再交给这里。
+//!
+//! 回调在 `lame_init_params` 时复制到 LAME 内部，之后无法更换，所以构建器创建句柄时
+//! 就安装垫片。回调不带句柄参数，消息按线程收集：
+//!
+//! - [`capture`] 期间的普通消息收集到字符串中，供
+//!   [`LameEncoder::config_summary`](crate::LameEncoder::config_summary) 使用；
+//! - [`collect_errors`] 期间的错误消息收集起来，附加到返回的错误中；
+//! - 其余的消息在启用 `log` 特性时转发到 `log`（目标为 `lame`），否则照常输出到 stderr。
+
+use crate::ffi;
+use std::cell::RefCell;
+use std::os::raw::{c_char, c_int};
+use std::sync::Once;
+
+type Sink = unsafe extern "C" fn(c_int, *const c_char, usize) -> c_int;
+
+extern "C" {
+    fn lame_sys_set_report_sink(sink: Sink);
+    fn lame_sys_install_report(gfp: *mut ffi::lame_global_flags) -> c_int;
+}
+
+/// 消息种类，与 `csrc/report.c` 中的常量对应
+const KIND_MSG: c_int = 0;
+const KIND_ERROR: c_int = 2;
+
+thread_local! {
+    /// 当前线程正在收集的普通消息
+    static CAPTURED: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// 当前线程正在收集的错误消息
+    static ERRORS: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// 追加到正在收集的缓冲区，没有在收集时返回 `false`
+fn append(buffer: &'static std::thread::LocalKey<RefCell<Option<String>>>, text: &str) -> bool {
+    // 线程退出时 TLS 可能已经销毁，此时不收集
+    buffer
+        .try_with(|captured| match captured.borrow_mut().as_mut() {
+            Some(collected) => {
+                collected.push_str(text);
+                true
+            }
+            None => false,
+        })
+        .unwrap_or(false)
+}
+
+/// 转发到 `log`，返回 `false` 时由垫片输出到 stderr
+#[cfg(feature = "log")]
+fn forward(kind: c_int, text: &str) -> bool {
+    let text = text.trim_end();
+    if !text.is_empty() {
+        match kind {
+            KIND_MSG => log::info!(target: "lame", "{}", text),
+            KIND_ERROR => log::error!(target: "lame", "{}", text),
+            _ => log::debug!(target: "lame", "{}", text),
+        }
+    }
+    true
+}
+
+#[cfg(not(feature = "log"))]
+fn forward(_kind: c_int, _text: &str) -> bool {
+    false
+}
+
+/// 由垫片调用，返回 1 表示已经处理，0 表示由垫片输出到 stderr
+///
+/// # Safety
+///
+/// `message` 指向至少 `len` 字节的有效内存
+unsafe extern "C" fn collect(kind: c_int, message: *const c_char, len: usize) -> c_int {
+    // SAFETY: 垫片传入 vsnprintf 的结果和它的长度
+    let bytes = unsafe { std::slice::from_raw_parts(message.cast::<u8>(), len) };
+    let text = String::from_utf8_lossy(bytes);
+    let handled = match kind {
+        KIND_MSG => append(&CAPTURED, &text),
+        KIND_ERROR => append(&ERRORS, &text),
+        _ => false,
+    };
+    (handled || forward(kind, &text)) as c_int
+}
+
+/// 输出 [`collect_errors`] 收集到、但调用方没有放进错误中的消息
+///
+/// 与没有收集时相同：启用 `log` 特性时转发到 `log`，否则写到 stderr。
+pub(crate) fn emit_error(text: &str) {
+    if !forward(KIND_ERROR, text) {
+        eprintln!("{}", text);
+    }
+}
+
+/// 让句柄的消息经过垫片，必须在 `lame_init_params` 之前调用
+pub(crate) fn install(gfp: *mut ffi::lame_global_flags) {
+    static SINK: Once = Once::new();
+    // SAFETY: collect 在整个进程内有效；只设置一次，之后垫片只读取它
+    SINK.call_once(|| unsafe { lame_sys_set_report_sink(collect) });
+    // SAFETY: 调用方保证 gfp 是有效的、尚未初始化参数的句柄
+    unsafe {
+        lame_sys_install_report(gfp);
+    }
+}
+
+/// 执行 `f`，返回期间当前线程上 LAME 输出的普通消息
+pub(crate) fn capture(f: impl FnOnce()) -> String {
+    let previous = CAPTURED.with(|captured| captured.replace(Some(String::new())));
+    f();
+    CAPTURED
+        .with(|captured| captured.replace(previous))
+        .unwrap_or_default()
+}
+
+/// 执行 `f`，同时返回期间当前线程上 LAME 输出的错误消息，没有时为 `None`
+///
+/// 没有错误消息时不分配内存，可以用在编码路径上。
+pub(crate) fn collect_errors<T>(f: impl FnOnce() -> T) -> (T, Option<String>) {
+    let previous = ERRORS.with(|errors| errors.replace(Some(String::new())));
+    let result = f();
+    let errors = ERRORS
+        .with(|errors| errors.replace(previous))
+        .filter(|text| !text.trim().is_empty())
+        .map(|text| text.trim().to_string());
+    (result, errors)
+}
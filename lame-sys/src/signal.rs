@@ -0,0 +1,110 @@
+//! 测试用的信号发生器（需要 `testing` feature）
+//!
+//! 给下游的集成测试和基准测试提供稳定的 16-bit PCM 输入，本 crate 自己的测试也使用这些函数。
+//! 所有函数都是确定的：相同的参数总是生成相同的样本，噪声只取决于 `seed`。
+//!
+//! 正弦波和扫频的幅度为 [`AMPLITUDE`]（约 -6 dBFS），白噪声均匀分布在
+//! `[-AMPLITUDE, AMPLITUDE)` 内。样本数为 `duration * sample_rate` 四舍五入，
+//! 需要精确的样本数时用 [`duration_of`] 换算。
+//!
+//! # 示例
+//!
+//! ```
+//! use lame_sys::signal;
+//! use std::time::Duration;
+//!
+//! let tone = signal::sine(440.0, 44100, Duration::from_secs(1));
+//! assert_eq!(tone.len(), 44100);
+//!
+//! // 一帧（1152 个样本）的交错立体声
+//! let frame = signal::duration_of(1152, 44100);
+//! let stereo = signal::interleave(&signal::sine(440.0, 44100, frame), 2);
+//! assert_eq!(stereo.len(), 1152 * 2);
+//! ```
+
+use std::f64::consts::PI;
+use std::time::Duration;
+
+/// 正弦波、扫频和白噪声的峰值幅度（满量程的一半）
+pub const AMPLITUDE: i16 = 16384;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// `samples` 个样本在 `sample_rate` 下的时长
+///
+/// 结果传给本模块的发生器时，恰好生成 `samples` 个样本。
+pub fn duration_of(samples: usize, sample_rate: u32) -> Duration {
+    let rate = u128::from(sample_rate.max(1));
+    let nanos = (samples as u128 * NANOS_PER_SEC + rate / 2) / rate;
+    Duration::from_nanos(nanos as u64)
+}
+
+/// `duration` 在 `sample_rate` 下的样本数（四舍五入）
+fn sample_count(sample_rate: u32, duration: Duration) -> usize {
+    let samples =
+        (duration.as_nanos() * u128::from(sample_rate) + NANOS_PER_SEC / 2) / NANOS_PER_SEC;
+    samples as usize
+}
+
+fn to_i16(value: f64) -> i16 {
+    (value * f64::from(AMPLITUDE)) as i16
+}
+
+/// 频率为 `freq` Hz 的正弦波，从相位 0 开始
+pub fn sine(freq: f64, sample_rate: u32, duration: Duration) -> Vec<i16> {
+    let rate = f64::from(sample_rate);
+    (0..sample_count(sample_rate, duration))
+        .map(|i| to_i16((2.0 * PI * freq * i as f64 / rate).sin()))
+        .collect()
+}
+
+/// 从 `start_freq` 线性扫到 `end_freq`（Hz）的正弦扫频，从相位 0 开始
+pub fn sweep(start_freq: f64, end_freq: f64, sample_rate: u32, duration: Duration) -> Vec<i16> {
+    let rate = f64::from(sample_rate);
+    let length = duration.as_secs_f64();
+    (0..sample_count(sample_rate, duration))
+        .map(|i| {
+            let t = i as f64 / rate;
+            // 瞬时频率 start + (end - start) * t / length 的积分
+            let phase = start_freq * t + (end_freq - start_freq) * t * t / (2.0 * length);
+            to_i16((2.0 * PI * phase).sin())
+        })
+        .collect()
+}
+
+/// 白噪声，样本序列只取决于 `seed`
+///
+/// 使用 SplitMix64 生成，只有整数运算，在所有平台和版本上结果相同。
+pub fn white_noise(sample_rate: u32, duration: Duration, seed: u64) -> Vec<i16> {
+    let mut state = seed;
+    (0..sample_count(sample_rate, duration))
+        .map(|_| {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^= z >> 31;
+            // 高 16 位作为满量程样本，再缩小到 AMPLITUDE
+            ((z >> 48) as u16 as i16) >> 1
+        })
+        .collect()
+}
+
+/// 数字静音
+pub fn silence(sample_rate: u32, duration: Duration) -> Vec<i16> {
+    vec![0; sample_count(sample_rate, duration)]
+}
+
+/// 把单声道信号复制到 `channels` 个声道，返回交错 PCM
+pub fn interleave(mono: &[i16], channels: usize) -> Vec<i16> {
+    mono.iter()
+        .flat_map(|&sample| std::iter::repeat(sample).take(channels))
+        .collect()
+}
+
+/// 把左右声道交错成立体声 PCM（L, R, L, R, ...）
+///
+/// 两个声道长度不同时按较短的截断。
+pub fn stereo(left: &[i16], right: &[i16]) -> Vec<i16> {
+    left.iter().zip(right).flat_map(|(&l, &r)| [l, r]).collect()
+}
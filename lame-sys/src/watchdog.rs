@@ -0,0 +1,81 @@
+//! 单次编码调用的耗时控制
+//!
+//! LAME 的单次调用不可中断，输入越大阻塞越久。对不可信或异常巨大的输入，
+//! 可以用 [`LameEncoder::set_max_samples_per_call`] 限制每次交给 LAME 的样本数，
+//! 一次 `encode*` 调用会被拆成多次较短的 LAME 调用。
+//!
+//! 每两次 LAME 调用之间，编码器会：
+//!
+//! 1. 调用进度回调（[`LameEncoder::set_progress_hook`]），可用于记录指标；
+//! 2. 检查取消令牌（[`LameEncoder::set_cancel_token`]），已取消时返回
+//!    [`LameError::Cancelled`]，其中包含已写入输出缓冲区的字节数。
+//!
+//! 默认不限制样本数，行为与未设置时完全相同。拆分不影响编码结果：
+//! 未取消时输出与一次性调用逐字节相同。
+//!
+//! # 示例
+//!
+//! ```no_run
+//! use lame_sys::prelude::*;
+//! use lame_sys::watchdog::CancelToken;
+//!
+//! # fn main() -> Result<()> {
+//! let mut encoder = LameEncoder::builder()?
+//!     .sample_rate(48000)?
+//!     .channels(1)?
+//!     .build()?;
+//!
+//! // 每次 LAME 调用最多处理 10 秒音频
+//! encoder.set_max_samples_per_call(Some(48000 * 10))?;
+//!
+//! let token = CancelToken::new();
+//! encoder.set_cancel_token(Some(token.clone()));
+//! // 在其他线程调用 token.cancel() 即可中止剩余的调用
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`LameEncoder::set_max_samples_per_call`]: crate::LameEncoder::set_max_samples_per_call
+//! [`LameEncoder::set_progress_hook`]: crate::LameEncoder::set_progress_hook
+//! [`LameEncoder::set_cancel_token`]: crate::LameEncoder::set_cancel_token
+//! [`LameError::Cancelled`]: crate::LameError::Cancelled
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 跨线程共享的取消令牌
+///
+/// 克隆得到的令牌共享同一个状态。
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// 创建未取消的令牌
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求取消，编码器会在下一次 LAME 调用之前停止
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 是否已请求取消
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// 一次 `encode*` 调用内的进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallProgress {
+    /// 已交给 LAME 的样本数（每个声道）
+    pub samples_done: usize,
+    /// 本次调用的样本总数（每个声道）
+    pub samples_total: usize,
+}
+
+/// 进度回调
+pub type ProgressHook = Box<dyn FnMut(CallProgress) + Send>;
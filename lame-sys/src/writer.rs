@@ -0,0 +1,1095 @@
+//! 流式 MP3 写入
+//!
+//! [`Mp3Writer`] 把编码器和 [`std::io::Write`] 组合在一起：写入 PCM 样本，
+//! 编码后的 MP3 数据直接写到下游。
+//!
+//! 每次调用 LAME 都有固定开销，逐帧（1152 个样本）调用时这部分开销很明显。
+//! `Mp3Writer` 会先把输入攒成一批（默认 [`DEFAULT_BATCH_SAMPLES`] 个样本/声道）再交给 LAME，
+//! 输出与不分批时逐字节相同。需要更低延迟时可以用 [`Mp3Writer::set_batch_samples`]
+//! 调小批量，或用 [`Mp3Writer::flush_input`] 提前送出当前批次。
+//!
+//! # 分段输出
+//!
+//! [`Mp3Writer::rotate`] 用 `lame_encode_flush_nogap` 结束当前段并切换到新的下游，
+//! 各段拼接后播放没有间隙。各选项的组合行为如下：
+//!
+//! | `write_vbr_tag` | 第一段 | 后续各段 | 帧计数 / 比特率直方图 |
+//! |-----------------|--------|----------|----------------------|
+//! | `true`（默认）  | 以信息帧开头 | 没有信息帧 | 跨段累计，不重置 |
+//! | `false`         | 没有信息帧 | 没有信息帧 | 跨段累计，不重置 |
+//!
+//! - 分段（`flush_nogap`）只输出已编码的 MP3 数据，LAME 中不足一帧的 PCM 留到下一段；
+//!   [`Mp3Writer::finish`]（`flush`）会补齐并编码剩余的 PCM。两者都不重置计数。
+//! - 信息帧是占位帧，描述的是整条分段链。计数不重置，因此结束时的
+//!   [`LameEncoder::frame_count`] 等于所有段中音频帧数之和。
+//!
+//! # 非阻塞下游
+//!
+//! 下游返回 `Interrupted` 时自动重试。返回 `WouldBlock`（非阻塞 socket 等）时，
+//! 未送出的输出暂存在写入器中，方法返回 [`LameError::Pending`]：输入已经全部编码，
+//! 不需要也不应该重新写入。下游可写后调用 [`Mp3Writer::resume`] 继续送出，
+//! [`Mp3Writer::pending_output`] 是暂存的字节数。之后的写入排在暂存数据之后，不会乱序。
+//!
+//! [`Mp3Writer::rotate`] 和 [`Mp3Writer::finish`] 会交出下游写入器，返回 `Pending`
+//! 时无法重试。非阻塞下游先反复调用 [`Mp3Writer::finish_segment`] 或
+//! [`Mp3Writer::close`] 直到成功，再调用它们。
+//!
+//! # 让出执行权
+//!
+//! 一次写入大量 PCM 时，写入器会连续调用 LAME 很长时间。在协作式调度的运行时中，
+//! 可以用 [`Mp3Writer::set_yield_policy`] 在两次 LAME 调用之间执行让出（或检查取消）：
+//!
+//! - [`YieldPolicy::EveryNFrames`]：每交给 LAME 满 N 帧的输入调用一次函数。
+//!   为此每次调用最多编码 N 帧，次数只取决于输入的总长度，与写入的分块方式无关。
+//! - [`YieldPolicy::Hook`]：每次调用 LAME 编码之后调用闭包，每次调用最多编码一批。
+//!
+//! 分成更多次调用不影响输出，与不设置策略时逐字节相同。
+//!
+//! # 进度
+//!
+//! [`Mp3Writer::set_frame_progress`] 设置的闭包在每编码完一帧时收到一次 [`FrameProgress`]，
+//! 帧数逐一递增。设置了 [`EncoderBuilder::total_samples`](crate::EncoderBuilder::total_samples)
+//! 时同时给出预计的总帧数，可以直接换算成百分比。
+//!
+//! # 信息帧
+//!
+//! LAME 编码时在流的开头写入的信息帧（Xing/Info）只是全 0 的占位，
+//! 真正的内容（总帧数、TOC、编码延迟）要在结束后回写。[`Mp3Writer::finish`]
+//! 只写入顺序的数据；下游支持 [`Seek`] 时用 [`Mp3Writer::finish_with_lametag`]
+//! 回写信息帧，完全在内存中生成文件时用 [`Mp3Buffer`]。两者的输出逐字节相同。
+//!
+//! # 同步点
+//!
+//! 没有 Xing TOC 的 CBR/ABR 流可以用 [`Mp3Writer::sync_points`] 建立简单的定位索引：
+//! 每隔 [`DEFAULT_SYNC_INTERVAL`] 个音频帧记录一次 `(帧序号, 字节偏移)`，
+//! 偏移处正好是该帧的帧头，对应时间为 `帧序号 × 每帧样本数 / 输出采样率`。
+//!
+//! LAME 每次调用输出的字节通常止于帧中间（比特池），因此偏移不是由
+//! `lame_get_frameNum` 和调用返回的字节数直接配对得到，而是逐帧解析输出的帧头。
+//! 帧序号与 [`LameEncoder::frame_count`] 一致：不计信息帧，从 0 开始；
+//! 偏移从整条输出（包括开头的 ID3v2 标签）的起点算起，[`Mp3Writer::rotate`] 之后继续累计。
+//!
+//! # 示例
+//!
+//! ```no_run
+//! use lame_sys::prelude::*;
+//! use std::fs::File;
+//!
+//! # fn main() -> Result<()> {
+//! let encoder = LameEncoder::builder()?
+//!     .sample_rate(44100)?
+//!     .channels(2)?
+//!     .bitrate(192)?
+//!     .build()?;
+//!
+//! let file = File::create("output.mp3")?;
+//! let mut writer = Mp3Writer::new(encoder, file);
+//!
+//! let frame = vec![0i16; 1152 * 2]; // 交错立体声
+//! for _ in 0..100 {
+//!     writer.write_pcm(&frame)?;
+//! }
+//!
+//! writer.finish()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::encoder::LameEncoder;
+use crate::error::{LameError, Result};
+use crate::ffi;
+use std::io::{ErrorKind, Seek, SeekFrom, Write};
+
+/// 默认批量大小（每个声道的样本数）
+pub const DEFAULT_BATCH_SAMPLES: usize = 16384;
+
+/// 默认同步点间隔（音频帧数）
+pub const DEFAULT_SYNC_INTERVAL: u32 = 100;
+
+/// 两次 LAME 调用之间让出执行权的策略，见[模块文档](self#让出执行权)
+#[derive(Default)]
+pub enum YieldPolicy {
+    /// 从不让出（默认）
+    #[default]
+    Never,
+    /// 每交给 LAME 满 N 帧的输入调用一次函数，N 为 0 时按 1 处理
+    EveryNFrames(u32, fn()),
+    /// 每次调用 LAME 编码之后调用闭包
+    Hook(Box<dyn FnMut() + Send>),
+}
+
+/// 编码进度，见[模块文档](self#进度)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameProgress {
+    /// 已编码的帧数，与 [`LameEncoder::frames_encoded`] 相同
+    pub frames_encoded: u32,
+    /// 预计的总帧数，见 [`LameEncoder::estimated_total_frames`]
+    pub total_frames: Option<u32>,
+}
+
+/// 帧进度回调
+pub type FrameProgressHook = Box<dyn FnMut(FrameProgress) + Send>;
+
+impl std::fmt::Debug for YieldPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Never => f.write_str("Never"),
+            Self::EveryNFrames(frames, _) => f.debug_tuple("EveryNFrames").field(frames).finish(),
+            Self::Hook(_) => f.write_str("Hook"),
+        }
+    }
+}
+
+/// MPEG-1 Layer III 比特率表（kbps），下标为帧头中的比特率索引
+pub(crate) const MPEG1_BITRATES: [usize; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+
+/// MPEG-2 / MPEG-2.5 Layer III 比特率表（kbps）
+pub(crate) const MPEG2_BITRATES: [usize; 15] =
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+/// 解析出的 Layer III 帧头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FrameHeader {
+    /// 是否为 MPEG-1，否则为 MPEG-2 / MPEG-2.5
+    pub mpeg1: bool,
+    /// 比特率（kbps）
+    pub kbps: usize,
+    /// 采样率（Hz）
+    pub sample_rate: usize,
+    /// 声道模式是否为单声道
+    pub mono: bool,
+    /// 整帧的字节数
+    pub len: usize,
+}
+
+impl FrameHeader {
+    /// 解析帧头的前 4 个字节
+    ///
+    /// 不是有效帧头或为自由格式（比特率索引 0）时返回 `None`。
+    pub(crate) fn parse(header: &[u8]) -> Option<Self> {
+        if header[0] != 0xFF || header[1] & 0xE0 != 0xE0 {
+            return None;
+        }
+        // 版本位：00 = MPEG-2.5，01 保留，10 = MPEG-2，11 = MPEG-1；层位 01 = Layer III
+        let version = (header[1] >> 3) & 0x3;
+        let layer = (header[1] >> 1) & 0x3;
+        let bitrate_index = (header[2] >> 4) as usize;
+        let rate_index = ((header[2] >> 2) & 0x3) as usize;
+        if version == 0b01 || layer != 0b01 || !(1..=14).contains(&bitrate_index) || rate_index == 3
+        {
+            return None;
+        }
+
+        let padding = ((header[2] >> 1) & 0x1) as usize;
+        let (kbps, sample_rate, coefficient) = match version {
+            0b11 => (
+                MPEG1_BITRATES[bitrate_index],
+                [44100, 48000, 32000][rate_index],
+                144,
+            ),
+            0b10 => (
+                MPEG2_BITRATES[bitrate_index],
+                [22050, 24000, 16000][rate_index],
+                72,
+            ),
+            _ => (
+                MPEG2_BITRATES[bitrate_index],
+                [11025, 12000, 8000][rate_index],
+                72,
+            ),
+        };
+        Some(Self {
+            mpeg1: version == 0b11,
+            kbps,
+            sample_rate,
+            mono: header[3] >> 6 == 0b11,
+            len: coefficient * kbps * 1000 / sample_rate + padding,
+        })
+    }
+
+    /// 帧头之后边信息（side info）的字节数，Xing/Info 标记紧随其后
+    pub(crate) fn side_info_len(&self) -> usize {
+        match (self.mpeg1, self.mono) {
+            (true, true) => 17,
+            (true, false) => 32,
+            (false, true) => 9,
+            (false, false) => 17,
+        }
+    }
+}
+
+/// 解析 Layer III 帧头，返回整帧的字节数
+///
+/// 不是有效帧头或为自由格式（比特率索引 0）时返回 `None`。
+fn frame_len(header: &[u8]) -> Option<usize> {
+    FrameHeader::parse(header).map(|header| header.len)
+}
+
+/// 扫描状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    /// 流的开头，可能是 ID3v2 标签
+    Start,
+    /// 逐帧解析
+    Frames,
+    /// 遇到无法解析的数据（ID3v1 标签、自由格式等），不再记录
+    Stopped,
+}
+
+/// 跟踪输出中帧头位置的扫描器
+#[derive(Debug)]
+struct FrameScanner {
+    state: ScanState,
+    /// 已经扫描过的字节数
+    position: u64,
+    /// 下一个帧头（或 ID3v2 标签头）的偏移
+    next: u64,
+    /// 跨越两次写入的头部字节
+    header: Vec<u8>,
+    /// 第一帧是否为 LAME 信息帧
+    skip_info: bool,
+    /// 信息帧的偏移
+    info_offset: Option<u64>,
+    /// 已经遇到的音频帧数
+    frames: u32,
+    /// 同步点间隔，0 表示不记录
+    interval: u32,
+    sync_points: Vec<(u32, u64)>,
+}
+
+impl FrameScanner {
+    fn new(skip_info: bool) -> Self {
+        Self {
+            state: ScanState::Start,
+            position: 0,
+            next: 0,
+            header: Vec::with_capacity(10),
+            skip_info,
+            info_offset: None,
+            frames: 0,
+            interval: DEFAULT_SYNC_INTERVAL,
+            sync_points: Vec::new(),
+        }
+    }
+
+    /// 扫描紧接在已扫描数据之后的输出
+    fn feed(&mut self, data: &[u8]) {
+        let end = self.position + data.len() as u64;
+
+        while self.state != ScanState::Stopped && self.next < end {
+            // ID3v2 标签头 10 字节，帧头 4 字节
+            let need = if self.state == ScanState::Start {
+                10
+            } else {
+                4
+            };
+            let from = (self.next + self.header.len() as u64 - self.position) as usize;
+            let take = (need - self.header.len()).min(data.len() - from);
+            self.header.extend_from_slice(&data[from..from + take]);
+            if self.header.len() < need {
+                break;
+            }
+
+            let at = self.next;
+            self.step(at);
+            self.header.clear();
+        }
+
+        self.position = end;
+    }
+
+    /// 解析 `header` 中位于偏移 `at` 的头部，推进到下一个头部
+    fn step(&mut self, at: u64) {
+        if self.state == ScanState::Start {
+            self.state = ScanState::Frames;
+            if self.header.starts_with(b"ID3") {
+                // 同步安全整数：每字节 7 位
+                let size = self.header[6..10]
+                    .iter()
+                    .fold(0u64, |size, &b| (size << 7) | u64::from(b & 0x7F));
+                let footer = if self.header[5] & 0x10 != 0 { 10 } else { 0 };
+                self.next = at + 10 + size + footer;
+                return;
+            }
+        }
+
+        let Some(len) = frame_len(&self.header) else {
+            self.state = ScanState::Stopped;
+            return;
+        };
+        self.next = at + len as u64;
+
+        if self.skip_info {
+            self.skip_info = false;
+            self.info_offset = Some(at);
+            return;
+        }
+        if self.interval != 0 && self.frames % self.interval == 0 {
+            self.sync_points.push((self.frames, at));
+        }
+        self.frames += 1;
+    }
+}
+
+/// 把 PCM 编码为 MP3 并写入下游的写入器
+///
+/// 输入为 16-bit 样本：单声道编码器接收普通样本，立体声编码器接收交错样本（L, R, L, R, ...）。
+pub struct Mp3Writer<W: Write> {
+    encoder: LameEncoder,
+    inner: W,
+    /// 声道数（1 或 2）
+    channels: usize,
+    /// 每批的样本数（每个声道）
+    batch_samples: usize,
+    /// 尚未交给 LAME 的样本（交错布局）
+    pending: Vec<i16>,
+    /// 复用的输出缓冲区
+    mp3_buffer: Vec<u8>,
+    /// 已编码、下游暂时无法接收的输出
+    unsent: Vec<u8>,
+    /// 当前段已经 `flush_nogap`，等待输出送完后切换下游
+    segment_ended: bool,
+    /// 编码器已经刷新，不再接受输入
+    closed: bool,
+    /// 当前段的序号，从 0 开始
+    segment: usize,
+    /// 写入下游的总字节数（所有段）
+    bytes_written: u64,
+    /// 输出中的帧位置，用于同步点
+    scanner: FrameScanner,
+    /// 两次 LAME 调用之间的让出策略
+    yield_policy: YieldPolicy,
+    /// 上次让出之后交给 LAME 的样本数（每个声道）
+    yield_progress: usize,
+    /// 每编码一帧调用一次的进度回调
+    frame_progress: Option<FrameProgressHook>,
+    /// 已经报告过的帧数
+    frames_reported: u32,
+}
+
+impl<W: Write> Mp3Writer<W> {
+    /// 创建写入器
+    ///
+    /// 声道数从编码器的配置中读取。
+    pub fn new(encoder: LameEncoder, inner: W) -> Self {
+        let channels = encoder.get(ffi::lame_get_num_channels).clamp(1, 2) as usize;
+        let scanner = FrameScanner::new(encoder.writes_vbr_tag());
+
+        Self {
+            encoder,
+            inner,
+            channels,
+            batch_samples: DEFAULT_BATCH_SAMPLES,
+            pending: Vec::new(),
+            mp3_buffer: Vec::new(),
+            unsent: Vec::new(),
+            segment_ended: false,
+            closed: false,
+            segment: 0,
+            bytes_written: 0,
+            scanner,
+            yield_policy: YieldPolicy::Never,
+            yield_progress: 0,
+            frame_progress: None,
+            frames_reported: 0,
+        }
+    }
+
+    /// 当前批量大小（每个声道的样本数）
+    pub fn batch_samples(&self) -> usize {
+        self.batch_samples
+    }
+
+    /// 设置批量大小（每个声道的样本数）
+    ///
+    /// 越小延迟越低，越大吞吐越高。传入 0 按 1 处理，即每次写入都立即编码。
+    /// 如果已缓存的样本达到新的批量，会立即编码。
+    pub fn set_batch_samples(&mut self, batch_samples: usize) -> Result<()> {
+        self.batch_samples = batch_samples.max(1);
+
+        if self.pending.len() >= self.batch_len() {
+            self.flush_input()?;
+        }
+        Ok(())
+    }
+
+    /// 写入 PCM 样本
+    ///
+    /// 立体声时 `pcm` 为交错样本，长度必须是偶数。返回 [`LameError::Pending`]
+    /// 时样本已经全部接收，见[模块文档](self#非阻塞下游)。
+    pub fn write_pcm(&mut self, pcm: &[i16]) -> Result<()> {
+        self.ensure_open()?;
+        if pcm.len() % self.channels != 0 {
+            return Err(LameError::InvalidInput(format!(
+                "Interleaved stereo data length must be even, got {}",
+                pcm.len()
+            )));
+        }
+
+        let batch_len = self.batch_len();
+        let mut pcm = pcm;
+
+        // 先补满已有的批次
+        if !self.pending.is_empty() {
+            let take = (batch_len - self.pending.len()).min(pcm.len());
+            self.pending.extend_from_slice(&pcm[..take]);
+            pcm = &pcm[take..];
+
+            if self.pending.len() < batch_len {
+                return self.pending_result();
+            }
+            self.encode_pending()?;
+        }
+
+        // 完整的批次直接编码，不经过缓存
+        let whole = pcm.len() - pcm.len() % batch_len;
+        if whole > 0 {
+            self.encode_and_write(&pcm[..whole])?;
+        }
+
+        self.pending.extend_from_slice(&pcm[whole..]);
+        self.pending_result()
+    }
+
+    /// 立即把缓存中的样本交给 LAME
+    ///
+    /// LAME 自身仍会缓存不足一帧的样本，完整结束流请使用 [`finish`](Self::finish)。
+    pub fn flush_input(&mut self) -> Result<()> {
+        self.encode_pending()?;
+        self.pending_result()
+    }
+
+    /// 继续把暂存的输出写入下游
+    ///
+    /// 全部送出后返回 `Ok`，下游再次阻塞时返回 [`LameError::Pending`]。
+    pub fn resume(&mut self) -> Result<()> {
+        self.send_unsent()?;
+        self.pending_result()
+    }
+
+    /// 已编码、尚未被下游接收的字节数
+    pub fn pending_output(&self) -> usize {
+        self.unsent.len()
+    }
+
+    /// 结束当前段，但不切换下游
+    ///
+    /// 送出缓存的样本，调用 [`LameEncoder::flush_nogap`] 并把当前段的数据全部写入下游。
+    /// 返回 [`LameError::Pending`] 时可以再次调用，`flush_nogap` 只执行一次。
+    /// 之后的 [`rotate`](Self::rotate) 只切换下游。
+    pub fn finish_segment(&mut self) -> Result<()> {
+        self.ensure_open()?;
+        if !self.segment_ended {
+            self.encode_pending()?;
+
+            self.reserve_mp3_buffer(0);
+            let bytes_written = self.encoder.flush_nogap(&mut self.mp3_buffer)?;
+            self.report_frames();
+            self.emit(bytes_written)?;
+            self.segment_ended = true;
+        }
+
+        self.resume()?;
+        self.flush_inner()
+    }
+
+    /// 结束当前段，后续输出写入 `next`
+    ///
+    /// 先调用 [`finish_segment`](Self::finish_segment)，再返回当前段的下游写入器。
+    /// 各段之间的组合行为见[模块文档](self)。返回 [`LameError::Pending`] 时 `next`
+    /// 被丢弃，当前段的数据仍保留在写入器中。
+    pub fn rotate(&mut self, next: W) -> Result<W> {
+        self.finish_segment()?;
+
+        self.segment_ended = false;
+        self.segment += 1;
+        Ok(std::mem::replace(&mut self.inner, next))
+    }
+
+    /// 当前段的序号，从 0 开始，每次 [`rotate`](Self::rotate) 加 1
+    pub fn segment_index(&self) -> usize {
+        self.segment
+    }
+
+    /// 已写入下游的总字节数，[`rotate`](Self::rotate) 之后继续累计
+    ///
+    /// 不包括 [`pending_output`](Self::pending_output) 中尚未送出的字节。
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// 已记录的同步点：`(音频帧序号, 该帧帧头的字节偏移)`
+    ///
+    /// 帧序号和偏移的含义见[模块文档](self#同步点)。[`finish`](Self::finish)
+    /// 消耗写入器，需要在它之前读取；最后一批刷新输出的几帧因此不在列表中。
+    pub fn sync_points(&self) -> &[(u32, u64)] {
+        &self.scanner.sync_points
+    }
+
+    /// 设置同步点间隔（音频帧数），默认 [`DEFAULT_SYNC_INTERVAL`]
+    ///
+    /// 帧序号是间隔整数倍的帧会被记录；0 表示不再记录。已记录的同步点保持不变。
+    pub fn set_sync_interval(&mut self, frames: u32) {
+        self.scanner.interval = frames;
+    }
+
+    /// 设置两次 LAME 调用之间的让出策略，默认 [`YieldPolicy::Never`]
+    ///
+    /// 见[模块文档](self#让出执行权)。[`YieldPolicy::EveryNFrames`] 的计数从设置时重新开始。
+    pub fn set_yield_policy(&mut self, policy: YieldPolicy) {
+        self.yield_policy = policy;
+        self.yield_progress = 0;
+    }
+
+    /// 设置帧进度回调，每编码完一帧调用一次，见[模块文档](self#进度)
+    ///
+    /// 传入 `None` 取消回调。设置之前已经编码的帧不会补报，之后的报告从当前帧数继续。
+    pub fn set_frame_progress(&mut self, hook: Option<FrameProgressHook>) {
+        self.frame_progress = hook;
+        self.frames_reported = self.encoder.frames_encoded();
+    }
+
+    /// 结束编码，但不交出下游写入器
+    ///
+    /// 送出缓存的样本，刷新编码器并把剩余数据全部写入下游。返回 [`LameError::Pending`]
+    /// 时可以再次调用，编码器只刷新一次。之后不再接受输入。
+    pub fn close(&mut self) -> Result<()> {
+        if !self.closed {
+            self.encode_pending()?;
+
+            self.reserve_mp3_buffer(0);
+            let bytes_written = self.encoder.flush(&mut self.mp3_buffer)?;
+            self.report_frames();
+            self.emit(bytes_written)?;
+            self.closed = true;
+        }
+
+        self.resume()?;
+        self.flush_inner()
+    }
+
+    /// 结束编码：送出缓存的样本，刷新编码器并返回下游写入器
+    ///
+    /// 开头的信息帧保持为占位，需要回写时见 [`finish_with_lametag`](Self::finish_with_lametag)。
+    /// 非阻塞的下游先反复调用 [`close`](Self::close) 直到成功，否则返回
+    /// [`LameError::Pending`] 时未送出的数据随写入器一起丢弃。
+    pub fn finish(mut self) -> Result<W> {
+        self.close()?;
+        Ok(self.inner)
+    }
+
+    /// 获取编码器的引用
+    pub fn encoder(&self) -> &LameEncoder {
+        &self.encoder
+    }
+
+    /// 获取下游写入器的引用
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// 需要回写的信息帧：`(相对输出起点的偏移, 完整内容)`
+    ///
+    /// 没有信息帧，或信息帧在 [`rotate`](Self::rotate) 交出的第一段中时返回 `None`。
+    fn lametag_patch(&self) -> Result<Option<(u64, Vec<u8>)>> {
+        let Some(offset) = self.scanner.info_offset else {
+            return Ok(None);
+        };
+        if self.segment != 0 {
+            return Ok(None);
+        }
+
+        let frame = self.encoder.lametag_frame()?;
+        if frame.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some((offset, frame)))
+    }
+
+    /// 一批的交错样本总数
+    fn batch_len(&self) -> usize {
+        self.batch_samples * self.channels
+    }
+
+    fn ensure_open(&self) -> Result<()> {
+        if self.closed {
+            return Err(LameError::InvalidInput("Mp3Writer is closed".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 有暂存的输出时返回 [`LameError::Pending`]
+    fn pending_result(&self) -> Result<()> {
+        if self.unsent.is_empty() {
+            Ok(())
+        } else {
+            Err(LameError::Pending {
+                buffered: self.unsent.len(),
+            })
+        }
+    }
+
+    fn reserve_mp3_buffer(&mut self, samples_per_channel: usize) {
+        let required_size = LameEncoder::mp3_buffer_size(samples_per_channel);
+        if self.mp3_buffer.len() < required_size {
+            self.mp3_buffer.resize(required_size, 0);
+        }
+    }
+
+    /// 把输出缓冲区的前 `len` 字节写入下游，下游阻塞时暂存剩余部分
+    fn emit(&mut self, len: usize) -> Result<()> {
+        let data = &self.mp3_buffer[..len];
+        self.scanner.feed(data);
+
+        // 已有暂存的输出时排在它后面，保持顺序
+        if !self.unsent.is_empty() {
+            self.unsent.extend_from_slice(data);
+            return self.send_unsent();
+        }
+
+        let (sent, result) = write_available(&mut self.inner, data);
+        self.bytes_written += sent as u64;
+        self.unsent.extend_from_slice(&data[sent..]);
+        Ok(result?)
+    }
+
+    /// 尽量送出暂存的输出
+    fn send_unsent(&mut self) -> Result<()> {
+        let (sent, result) = write_available(&mut self.inner, &self.unsent);
+        self.bytes_written += sent as u64;
+        self.unsent.drain(..sent);
+        Ok(result?)
+    }
+
+    /// 刷新下游；下游阻塞时返回 [`LameError::Pending`]
+    fn flush_inner(&mut self) -> Result<()> {
+        loop {
+            match self.inner.flush() {
+                Ok(()) => return Ok(()),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    return Err(LameError::Pending { buffered: 0 })
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// 把缓存中的样本交给 LAME，下游阻塞不算错误
+    fn encode_pending(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let pending = std::mem::take(&mut self.pending);
+        let result = self.encode_and_write(&pending);
+
+        // 保留分配，供下一批使用
+        self.pending = pending;
+        self.pending.clear();
+        result
+    }
+
+    /// 每次 LAME 调用最多编码的交错样本数
+    fn call_len(&self) -> usize {
+        match self.yield_policy {
+            YieldPolicy::Never => usize::MAX,
+            YieldPolicy::EveryNFrames(frames, _) => {
+                frames.max(1) as usize * self.encoder.frame_size() * self.channels
+            }
+            YieldPolicy::Hook(_) => self.batch_len(),
+        }
+    }
+
+    fn encode_and_write(&mut self, pcm: &[i16]) -> Result<()> {
+        // finish_segment 之后又有新数据，当前段需要重新结束
+        self.segment_ended = false;
+
+        for chunk in pcm.chunks(self.call_len()) {
+            self.reserve_mp3_buffer(chunk.len() / self.channels);
+
+            let bytes_written = if self.channels == 1 {
+                self.encoder.encode_mono(chunk, &mut self.mp3_buffer)?
+            } else {
+                self.encoder
+                    .encode_interleaved(chunk, &mut self.mp3_buffer)?
+            };
+
+            self.report_frames();
+            self.emit(bytes_written)?;
+            self.yield_after(chunk.len() / self.channels);
+        }
+        Ok(())
+    }
+
+    /// 为上次报告之后编码的每一帧调用进度回调
+    fn report_frames(&mut self) {
+        let Some(hook) = &mut self.frame_progress else {
+            return;
+        };
+        let frames = self.encoder.frames_encoded();
+        if frames <= self.frames_reported {
+            return;
+        }
+
+        let total_frames = self.encoder.estimated_total_frames();
+        for frames_encoded in self.frames_reported + 1..=frames {
+            hook(FrameProgress {
+                frames_encoded,
+                total_frames,
+            });
+        }
+        self.frames_reported = frames;
+    }
+
+    /// 一次 LAME 调用编码了 `samples_per_channel` 个样本，按策略让出
+    fn yield_after(&mut self, samples_per_channel: usize) {
+        match &mut self.yield_policy {
+            YieldPolicy::Never => {}
+            YieldPolicy::EveryNFrames(frames, yield_now) => {
+                let every = (*frames).max(1) as usize * self.encoder.frame_size();
+                self.yield_progress += samples_per_channel;
+                while self.yield_progress >= every {
+                    self.yield_progress -= every;
+                    yield_now();
+                }
+            }
+            YieldPolicy::Hook(hook) => hook(),
+        }
+    }
+}
+
+/// 尽量把 `data` 写入 `inner`，返回写出的字节数和遇到的错误
+///
+/// 与 [`Write::write_all`] 一样自动重试 `Interrupted`；遇到 `WouldBlock` 时停下，
+/// 不算错误，未写出的部分由调用方暂存。
+fn write_available<W: Write>(inner: &mut W, data: &[u8]) -> (usize, std::io::Result<()>) {
+    let mut sent = 0;
+    while sent < data.len() {
+        match inner.write(&data[sent..]) {
+            Ok(0) => return (sent, Err(ErrorKind::WriteZero.into())),
+            Ok(n) => sent += n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+            Err(err) => return (sent, Err(err)),
+        }
+    }
+    (sent, Ok(()))
+}
+
+impl<W: Write + Seek> Mp3Writer<W> {
+    /// 结束编码，并把完整的信息帧回写到流的开头
+    ///
+    /// 偏移从创建写入器时下游的位置算起，回写后下游的位置回到输出末尾。
+    /// 调用过 [`rotate`](Self::rotate) 时信息帧在第一段中，不会回写，与 [`finish`](Self::finish) 相同。
+    pub fn finish_with_lametag(mut self) -> Result<W> {
+        self.close()?;
+
+        if let Some((offset, frame)) = self.lametag_patch()? {
+            // 当前位置是输出末尾，相对移动，不要求写入器从 0 开始
+            let back = self.bytes_written - offset;
+            self.inner.seek(SeekFrom::Current(-(back as i64)))?;
+            self.inner.write_all(&frame)?;
+            self.inner
+                .seek(SeekFrom::Current((back - frame.len() as u64) as i64))?;
+            self.inner.flush()?;
+        }
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> std::fmt::Debug for Mp3Writer<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mp3Writer")
+            .field("encoder", &self.encoder)
+            .field("channels", &self.channels)
+            .field("batch_samples", &self.batch_samples)
+            .field("pending", &self.pending.len())
+            .field("unsent", &self.unsent.len())
+            .field("closed", &self.closed)
+            .field("segment", &self.segment)
+            .field("bytes_written", &self.bytes_written)
+            .field("sync_points", &self.scanner.sync_points.len())
+            .field("yield_policy", &self.yield_policy)
+            .field("frame_progress", &self.frame_progress.is_some())
+            .finish()
+    }
+}
+
+/// 在内存中生成完整 MP3 文件的写入器
+///
+/// 接口与 [`Mp3Writer`] 相同，[`finish`](Self::finish) 时在缓冲区中原地回写信息帧，
+/// 返回的字节与 [`Mp3Writer::finish_with_lametag`] 写到文件的内容相同，
+/// 适合 Web 服务直接把整个文件作为响应返回。
+///
+/// # 示例
+///
+/// ```no_run
+/// use lame_sys::prelude::*;
+///
+/// # fn main() -> Result<()> {
+/// let encoder = LameEncoder::builder()?
+///     .sample_rate(44100)?
+///     .channels(1)?
+///     .bitrate(128)?
+///     .build()?;
+///
+/// let mut buffer = Mp3Buffer::new(encoder);
+/// buffer.write_pcm(&vec![0i16; 44100])?;
+/// let mp3: Vec<u8> = buffer.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Mp3Buffer {
+    writer: Mp3Writer<Vec<u8>>,
+}
+
+impl Mp3Buffer {
+    /// 创建写入器
+    pub fn new(encoder: LameEncoder) -> Self {
+        Self {
+            writer: Mp3Writer::new(encoder, Vec::new()),
+        }
+    }
+
+    /// 当前批量大小（每个声道的样本数），见 [`Mp3Writer::batch_samples`]
+    pub fn batch_samples(&self) -> usize {
+        self.writer.batch_samples()
+    }
+
+    /// 设置批量大小（每个声道的样本数），见 [`Mp3Writer::set_batch_samples`]
+    pub fn set_batch_samples(&mut self, batch_samples: usize) -> Result<()> {
+        self.writer.set_batch_samples(batch_samples)
+    }
+
+    /// 设置两次 LAME 调用之间的让出策略，见 [`Mp3Writer::set_yield_policy`]
+    pub fn set_yield_policy(&mut self, policy: YieldPolicy) {
+        self.writer.set_yield_policy(policy)
+    }
+
+    /// 设置帧进度回调，见 [`Mp3Writer::set_frame_progress`]
+    pub fn set_frame_progress(&mut self, hook: Option<FrameProgressHook>) {
+        self.writer.set_frame_progress(hook)
+    }
+
+    /// 写入 PCM 样本，见 [`Mp3Writer::write_pcm`]
+    pub fn write_pcm(&mut self, pcm: &[i16]) -> Result<()> {
+        self.writer.write_pcm(pcm)
+    }
+
+    /// 立即把缓存中的样本交给 LAME，见 [`Mp3Writer::flush_input`]
+    pub fn flush_input(&mut self) -> Result<()> {
+        self.writer.flush_input()
+    }
+
+    /// 已输出的字节数
+    pub fn bytes_written(&self) -> u64 {
+        self.writer.bytes_written()
+    }
+
+    /// 获取编码器的引用
+    pub fn encoder(&self) -> &LameEncoder {
+        self.writer.encoder()
+    }
+
+    /// 目前为止的输出，信息帧仍是占位
+    pub fn get_ref(&self) -> &[u8] {
+        self.writer.get_ref()
+    }
+
+    /// 结束编码，返回回写了信息帧的完整 MP3
+    pub fn finish(mut self) -> Result<Vec<u8>> {
+        self.writer.close()?;
+        let patch = self.writer.lametag_patch()?;
+
+        let mut data = self.writer.inner;
+        if let Some((offset, frame)) = patch {
+            let offset = offset as usize;
+            data[offset..offset + frame.len()].copy_from_slice(&frame);
+        }
+        Ok(data)
+    }
+}
+
+impl std::fmt::Debug for Mp3Buffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mp3Buffer")
+            .field("writer", &self.writer)
+            .finish()
+    }
+}
+
+/// drop 时自动结束编码的写入器
+///
+/// 接口与 [`Mp3Writer`] 相同，由 [`EncoderBuilder::sink`](crate::EncoderBuilder::sink) 创建。
+/// 没有调用 [`finish`](Self::finish) 就被 drop（例如提前返回）时，会尽量送出缓存的样本、
+/// 刷新编码器并写入下游，忽略其中的错误，避免输出在最后几帧处被截断。
+/// 需要知道结束是否成功时调用 [`finish`](Self::finish)。
+///
+/// # 示例
+///
+/// ```no_run
+/// use lame_sys::prelude::*;
+///
+/// # fn main() -> Result<()> {
+/// let file = std::fs::File::create("out.mp3")?;
+/// let mut stream = LameEncoder::builder()?
+///     .sample_rate(44100)?
+///     .channels(1)?
+///     .bitrate(128)?
+///     .sink(file)?;
+///
+/// stream.write_pcm(&vec![0i16; 44100])?;
+/// let (file, bytes_written) = stream.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct StreamingEncoder<W: Write> {
+    /// 只在 `finish` 和 `drop` 中取出
+    writer: Option<Mp3Writer<W>>,
+}
+
+impl<W: Write> StreamingEncoder<W> {
+    /// 创建写入器
+    pub fn new(encoder: LameEncoder, inner: W) -> Self {
+        Self {
+            writer: Some(Mp3Writer::new(encoder, inner)),
+        }
+    }
+
+    fn writer(&self) -> &Mp3Writer<W> {
+        self.writer.as_ref().expect("writer taken before drop")
+    }
+
+    fn writer_mut(&mut self) -> &mut Mp3Writer<W> {
+        self.writer.as_mut().expect("writer taken before drop")
+    }
+
+    /// 写入 PCM 样本，见 [`Mp3Writer::write_pcm`]
+    pub fn write_pcm(&mut self, pcm: &[i16]) -> Result<()> {
+        self.writer_mut().write_pcm(pcm)
+    }
+
+    /// 立即把缓存中的样本交给 LAME，见 [`Mp3Writer::flush_input`]
+    pub fn flush_input(&mut self) -> Result<()> {
+        self.writer_mut().flush_input()
+    }
+
+    /// 写入下游的总字节数
+    pub fn bytes_written(&self) -> u64 {
+        self.writer().bytes_written()
+    }
+
+    /// 获取编码器的引用
+    pub fn encoder(&self) -> &LameEncoder {
+        self.writer().encoder()
+    }
+
+    /// 获取下游写入器的引用
+    pub fn get_ref(&self) -> &W {
+        self.writer().get_ref()
+    }
+
+    /// 结束编码：送出缓存的样本，刷新编码器，返回下游写入器和写入的总字节数
+    ///
+    /// 失败时不会在 drop 中再次尝试。
+    pub fn finish(mut self) -> Result<(W, u64)> {
+        let mut writer = self.writer.take().expect("writer taken before drop");
+        writer.close()?;
+        let bytes_written = writer.bytes_written();
+        Ok((writer.inner, bytes_written))
+    }
+}
+
+impl<W: Write> Drop for StreamingEncoder<W> {
+    fn drop(&mut self) {
+        if let Some(mut writer) = self.writer.take() {
+            // 尽力而为：下游出错时没有其他办法报告
+            let _ = writer.close();
+        }
+    }
+}
+
+impl<W: Write> std::fmt::Debug for StreamingEncoder<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamingEncoder")
+            .field("writer", &self.writer)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MPEG-1 Layer III、128 kbps、44.1 kHz、无填充的帧头
+    const HEADER_128K: [u8; 4] = [0xFF, 0xFB, 0x90, 0x00];
+
+    /// 以 `HEADER_128K` 开头、长度为 417 字节的帧
+    fn frame() -> Vec<u8> {
+        let mut frame = HEADER_128K.to_vec();
+        frame.resize(417, 0x55);
+        frame
+    }
+
+    fn scan(stream: &[u8], chunk: usize, skip_info: bool, interval: u32) -> FrameScanner {
+        let mut scanner = FrameScanner::new(skip_info);
+        scanner.interval = interval;
+        for piece in stream.chunks(chunk) {
+            scanner.feed(piece);
+        }
+        scanner
+    }
+
+    #[test]
+    fn frame_len_parses_layer3_headers() {
+        assert_eq!(frame_len(&HEADER_128K), Some(417));
+        // 填充位
+        assert_eq!(frame_len(&[0xFF, 0xFB, 0x92, 0x00]), Some(418));
+        // MPEG-2、64 kbps、22.05 kHz
+        assert_eq!(frame_len(&[0xFF, 0xF3, 0x80, 0x00]), Some(208));
+        // 自由格式、Layer II、非同步字
+        assert_eq!(frame_len(&[0xFF, 0xFB, 0x00, 0x00]), None);
+        assert_eq!(frame_len(&[0xFF, 0xFD, 0x90, 0x00]), None);
+        assert_eq!(frame_len(b"TAG\0"), None);
+    }
+
+    #[test]
+    fn scanner_skips_id3v2_and_handles_split_headers() {
+        // 20 字节的 ID3v2 标签，之后是 5 帧和 ID3v1 标签
+        let mut stream = b"ID3\x04\x00\x00\x00\x00\x00\x14".to_vec();
+        stream.resize(30, 0);
+        for _ in 0..5 {
+            stream.extend_from_slice(&frame());
+        }
+        stream.extend_from_slice(b"TAG");
+        stream.resize(stream.len() + 125, 0);
+
+        // 奇数大小的写入让头部跨越两次 feed
+        for chunk in [1, 3, 7, 416, stream.len()] {
+            let scanner = scan(&stream, chunk, false, 2);
+            assert_eq!(
+                scanner.sync_points,
+                vec![(0, 30), (2, 30 + 2 * 417), (4, 30 + 4 * 417)],
+                "chunk size {}",
+                chunk
+            );
+            assert_eq!(scanner.frames, 5);
+            assert_eq!(scanner.state, ScanState::Stopped);
+        }
+    }
+
+    #[test]
+    fn scanner_skips_info_frame() {
+        let stream: Vec<u8> = (0..4).flat_map(|_| frame()).collect();
+
+        let scanner = scan(&stream, 100, true, 1);
+        assert_eq!(scanner.sync_points, vec![(0, 417), (1, 834), (2, 1251)]);
+
+        let scanner = scan(&stream, 100, false, 0);
+        assert!(scanner.sync_points.is_empty());
+        assert_eq!(scanner.frames, 4);
+    }
+}
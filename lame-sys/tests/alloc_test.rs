@@ -0,0 +1,221 @@
+//! 稳定状态下的编码路径不分配内存
+//!
+//! 用计数的全局分配器统计 Rust 侧的堆分配。LAME 内部用 C 的 `malloc`，不在统计范围内。
+
+use lame_sys::prelude::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// 只统计当前线程在 [`count_allocations`] 内的分配，测试框架其他线程的分配不计入
+struct CountingAllocator;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn record() {
+    // 线程退出时 TLS 可能已经销毁
+    let _ = COUNTING.try_with(|counting| {
+        if counting.get() {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        }
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record();
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// 执行 `f`，返回期间当前线程的分配次数
+fn count_allocations(f: impl FnOnce()) -> usize {
+    ALLOCATIONS.with(|count| count.set(0));
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    ALLOCATIONS.with(Cell::get)
+}
+
+const FRAME: usize = 1152;
+const WARM_UP_FRAMES: usize = 10;
+const STEADY_FRAMES: usize = 1000;
+
+fn encoder(channels: i32) -> Result<LameEncoder> {
+    LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(channels)?
+        .bitrate(128)?
+        .quality(Quality::Fast)?
+        .build()
+}
+
+/// 每帧调用一次 `encode`，预热之后的 [`STEADY_FRAMES`] 帧中不应有任何分配
+fn assert_steady_state<F>(name: &str, channels: i32, mut encode: F) -> Result<()>
+where
+    F: FnMut(&mut LameEncoder, &mut [u8]) -> Result<usize>,
+{
+    let mut encoder = encoder(channels)?;
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(FRAME)];
+
+    for _ in 0..WARM_UP_FRAMES {
+        encode(&mut encoder, &mut mp3_buffer)?;
+    }
+
+    let mut result = Ok(0);
+    let allocations = count_allocations(|| {
+        for _ in 0..STEADY_FRAMES {
+            result = encode(&mut encoder, &mut mp3_buffer);
+            if result.is_err() {
+                break;
+            }
+        }
+    });
+    result?;
+    assert_eq!(allocations, 0, "{} allocated in the steady state", name);
+    Ok(())
+}
+
+fn ramp(len: usize) -> Vec<i16> {
+    (0..len).map(|i| ((i * 97) % 65536) as u16 as i16).collect()
+}
+
+#[test]
+fn test_encode_methods_do_not_allocate() -> Result<()> {
+    let left = ramp(FRAME);
+    let right = ramp(FRAME);
+    let interleaved = ramp(FRAME * 2);
+    let float: Vec<f32> = interleaved.iter().map(|&s| s as f32 / 32768.0).collect();
+    let short_range: Vec<f32> = interleaved.iter().map(|&s| s as f32).collect();
+    let double: Vec<f64> = float.iter().map(|&s| s as f64).collect();
+    let int: Vec<i32> = interleaved.iter().map(|&s| (s as i32) << 16).collect();
+    let u8_pcm: Vec<u8> = interleaved
+        .iter()
+        .map(|&s| ((s >> 8) + 128) as u8)
+        .collect();
+    let i24: Vec<u8> = int
+        .iter()
+        .flat_map(|&s| s.to_le_bytes()[1..].to_vec())
+        .collect();
+
+    assert_steady_state("encode", 2, |e, out| e.encode(&left, &right, out))?;
+    assert_steady_state("encode_interleaved", 2, |e, out| {
+        e.encode_interleaved(&interleaved, out)
+    })?;
+    assert_steady_state("encode_interleaved_float", 2, |e, out| {
+        e.encode_interleaved_float(&float, out)
+    })?;
+    assert_steady_state("encode_interleaved_float_scaled", 2, |e, out| {
+        e.encode_interleaved_float_scaled(&short_range, FloatScale::ShortRange, out)
+    })?;
+    assert_steady_state("encode_float", 2, |e, out| {
+        e.encode_float(
+            &short_range[..FRAME],
+            &short_range[FRAME..],
+            FloatScale::ShortRange,
+            out,
+        )
+    })?;
+    assert_steady_state("encode_interleaved_double", 2, |e, out| {
+        e.encode_interleaved_double(&double, out)
+    })?;
+    assert_steady_state("encode_interleaved_int", 2, |e, out| {
+        e.encode_interleaved_int(&int, out)
+    })?;
+    assert_steady_state("encode_interleaved_u8", 2, |e, out| {
+        e.encode_interleaved_u8(&u8_pcm, out)
+    })?;
+    assert_steady_state("encode_interleaved_i24", 2, |e, out| {
+        e.encode_interleaved_i24(&i24, 2, out)
+    })?;
+    assert_steady_state("encode_strided", 2, |e, out| {
+        e.encode_strided(&interleaved, 0, 1, 2, FRAME, out)
+    })?;
+    assert_steady_state("encode_from_iter", 2, |e, _| {
+        let written = e.encode_from_iter(interleaved.iter().copied(), &mut std::io::sink())?;
+        Ok(written as usize)
+    })?;
+
+    assert_steady_state("encode_mono", 1, |e, out| e.encode_mono(&left, out))?;
+    assert_steady_state("encode_mono_float", 1, |e, out| {
+        e.encode_mono_float(&float[..FRAME], out)
+    })?;
+    assert_steady_state("encode_mono_u8", 1, |e, out| {
+        e.encode_mono_u8(&u8_pcm[..FRAME], out)
+    })?;
+    Ok(())
+}
+
+#[test]
+fn test_reserve_scratch_avoids_first_call_allocation() -> Result<()> {
+    let interleaved = ramp(FRAME * 2);
+    let u8_pcm: Vec<u8> = interleaved
+        .iter()
+        .map(|&s| ((s >> 8) + 128) as u8)
+        .collect();
+
+    let mut encoder = encoder(2)?;
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(FRAME)];
+    encoder.reserve_scratch(FRAME);
+
+    let mut result = Ok(0);
+    let allocations = count_allocations(|| {
+        result = encoder
+            .encode_interleaved_u8(&u8_pcm, &mut mp3_buffer)
+            .and_then(|_| encoder.encode_strided(&interleaved, 0, 1, 2, FRAME, &mut mp3_buffer))
+            .and_then(|_| {
+                let written =
+                    encoder.encode_from_iter(interleaved.iter().copied(), &mut std::io::sink())?;
+                Ok(written as usize)
+            });
+    });
+    result?;
+    assert_eq!(allocations, 0);
+    Ok(())
+}
+
+#[test]
+fn test_writer_steady_state_does_not_allocate() -> Result<()> {
+    let interleaved = ramp(FRAME * 2);
+
+    let mut writer = Mp3Writer::new(encoder(2)?, std::io::sink());
+    writer.set_batch_samples(FRAME)?;
+    // 同步点列表会随输出增长
+    writer.set_sync_interval(0);
+    for _ in 0..WARM_UP_FRAMES {
+        writer.write_pcm(&interleaved)?;
+    }
+
+    let mut result = Ok(());
+    let allocations = count_allocations(|| {
+        for _ in 0..STEADY_FRAMES {
+            result = writer.write_pcm(&interleaved);
+            if result.is_err() {
+                break;
+            }
+        }
+    });
+    result?;
+    assert_eq!(allocations, 0);
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use lame_sys::prelude::*;
+use lame_sys::{signal, ConfigDiff};
+
+fn cbr_128() -> EncoderConfig {
+    EncoderConfig {
+        bitrate: Some(128),
+        quality: Some(Quality::High),
+        ..EncoderConfig::default()
+    }
+}
+
+fn vbr_v2() -> EncoderConfig {
+    EncoderConfig {
+        quality: Some(Quality::High),
+        vbr_mode: Some(VbrMode::Vbr),
+        vbr_quality: Some(2),
+        ..EncoderConfig::default()
+    }
+}
+
+fn fields(diffs: &[ConfigDiff]) -> Vec<&'static str> {
+    diffs.iter().map(|diff| diff.field).collect()
+}
+
+/// 把 1 秒正弦波编码为完整的文件，返回编码前的配置快照和文件内容
+fn encode_file(config: &EncoderConfig, title: Option<&str>) -> Result<(EncoderConfig, Vec<u8>)> {
+    let mut encoder = config.build()?;
+    if let Some(title) = title {
+        Id3Tag::new(&mut encoder).title(title)?.apply()?;
+    }
+    let snapshot = encoder.config();
+
+    let mono = signal::sine(440.0, 44100, signal::duration_of(44100, 44100));
+    let mut buffer = Mp3Buffer::new(encoder);
+    buffer.write_pcm(&signal::interleave(&mono, 2))?;
+    Ok((snapshot, buffer.finish()?))
+}
+
+#[test]
+fn test_diff_cbr_vs_vbr() {
+    let diffs = cbr_128().diff(&vbr_v2());
+    assert_eq!(fields(&diffs), ["bitrate", "vbr_mode", "vbr_quality"]);
+
+    assert_eq!(diffs[0].a, "128");
+    assert_eq!(diffs[0].b, "default");
+    assert_eq!(diffs[1].b, "Vbr");
+    assert_eq!(diffs[2].b, "2");
+
+    // 反向比较交换 a 和 b
+    let reversed = vbr_v2().diff(&cbr_128());
+    assert_eq!(reversed[0].a, "default");
+    assert_eq!(reversed[0].b, "128");
+
+    assert!(cbr_128().diff(&cbr_128()).is_empty());
+}
+
+#[test]
+fn test_encoder_config_snapshot() -> Result<()> {
+    let config = cbr_128().build()?.config();
+    assert_eq!(config.sample_rate, 44100);
+    assert_eq!(config.channels, 2);
+    assert_eq!(config.bitrate, Some(128));
+    assert_eq!(config.quality, Some(Quality::High));
+    assert_eq!(config.vbr_mode, Some(VbrMode::Off));
+    assert_eq!(config.vbr_quality, None);
+
+    let config = vbr_v2().build()?.config();
+    assert_eq!(config.bitrate, None);
+    assert_eq!(config.vbr_mode, Some(VbrMode::Vbr));
+    assert_eq!(config.vbr_quality, Some(2));
+
+    let diffs = cbr_128().build()?.config().diff(&config);
+    assert_eq!(fields(&diffs), ["bitrate", "vbr_mode", "vbr_quality"]);
+    Ok(())
+}
+
+#[test]
+fn test_lametag_matches_encoder_config() -> Result<()> {
+    for config in [cbr_128(), vbr_v2()] {
+        let (snapshot, mp3) = encode_file(&config, None)?;
+        let parsed = EncoderConfig::from_lametag(&mp3)?;
+        let diffs = snapshot.diff(&parsed);
+        assert!(diffs.is_empty(), "{:?}", diffs);
+    }
+
+    // 文件开头的 ID3v2 标签会被跳过
+    let (snapshot, mp3) = encode_file(&vbr_v2(), Some("A title long enough to need an ID3v2 tag"))?;
+    assert_eq!(&mp3[..3], b"ID3");
+    let parsed = EncoderConfig::from_lametag(&mp3)?;
+    assert_eq!(parsed, snapshot);
+
+    let diffs = cbr_128().build()?.config().diff(&parsed);
+    assert_eq!(fields(&diffs), ["bitrate", "vbr_mode", "vbr_quality"]);
+    Ok(())
+}
+
+#[test]
+fn test_config_applies_mode_and_filters() -> Result<()> {
+    let config = EncoderConfig {
+        bitrate: Some(64),
+        quality: Some(Quality::High),
+        mode: Some(ChannelMode::Mono),
+        lowpass: Some(8000),
+        highpass: Some(100),
+        ..EncoderConfig::default()
+    };
+    // 立体声输入下混为单声道输出
+    let (snapshot, mp3) = encode_file(&config, None)?;
+    assert_eq!(snapshot.channels, 2);
+    assert_eq!(EncoderConfig::from_lametag(&mp3)?.channels, 1);
+
+    // 快照不包含这些字段，与原配置的差异正好是它们
+    assert_eq!(
+        fields(&config.diff(&snapshot)),
+        ["vbr_mode", "mode", "lowpass", "highpass"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_from_lametag_rejects_other_data() -> Result<()> {
+    // 编码过程中写入的信息帧只是全 0 的占位
+    let mut encoder = cbr_128().build()?;
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(1152)];
+    let pcm = vec![0i16; 1152 * 2];
+    let mut placeholder = Vec::new();
+    while placeholder.is_empty() {
+        let bytes = encoder.encode_interleaved(&pcm, &mut mp3_buffer)?;
+        placeholder.extend_from_slice(&mp3_buffer[..bytes]);
+    }
+
+    let cases: [&[u8]; 3] = [&placeholder, &[0xFF, 0xFB], b"not an mp3 file"];
+    for data in cases {
+        assert!(matches!(
+            EncoderConfig::from_lametag(data),
+            Err(LameError::InvalidInput(_))
+        ));
+    }
+    Ok(())
+}
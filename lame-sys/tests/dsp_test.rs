@@ -0,0 +1,93 @@
+use lame_sys::dsp::{self, FadeShape};
+use lame_sys::LameError;
+
+const FULL: i16 = 20000;
+
+fn is_non_decreasing(samples: impl Iterator<Item = i16>) -> bool {
+    let samples: Vec<i16> = samples.collect();
+    samples.windows(2).all(|w| w[0] <= w[1])
+}
+
+#[test]
+fn test_fade_in_out_mono() {
+    for shape in [FadeShape::Linear, FadeShape::RaisedCosine] {
+        let mut pcm = vec![FULL; 1000];
+        dsp::fade_in_shaped(&mut pcm, 100, shape);
+        dsp::fade_out_shaped(&mut pcm, 200, shape);
+
+        assert_eq!(pcm[0], 0, "{:?}", shape);
+        assert_eq!(pcm[999], 0, "{:?}", shape);
+        // 淡变区间之外不受影响
+        assert!(pcm[100..800].iter().all(|&s| s == FULL));
+
+        // 包络单调：淡入递增，淡出递减
+        assert!(is_non_decreasing(pcm[..=100].iter().copied()));
+        assert!(is_non_decreasing(pcm[800..].iter().rev().copied()));
+    }
+}
+
+#[test]
+fn test_linear_is_default_shape() {
+    let mut linear = vec![FULL; 500];
+    let mut shaped = linear.clone();
+    dsp::fade_in(&mut linear, 300);
+    dsp::fade_out(&mut linear, 300);
+    dsp::fade_in_shaped(&mut shaped, 300, FadeShape::Linear);
+    dsp::fade_out_shaped(&mut shaped, 300, FadeShape::Linear);
+    assert_eq!(linear, shaped);
+}
+
+#[test]
+fn test_fade_longer_than_input() {
+    let mut pcm = vec![FULL; 10];
+    dsp::fade_in(&mut pcm, 1000);
+    assert_eq!(pcm[0], 0);
+    assert!(is_non_decreasing(pcm.iter().copied()));
+
+    let mut empty: Vec<i16> = Vec::new();
+    dsp::fade_out(&mut empty, 10);
+}
+
+#[test]
+fn test_interleaved_fades_channels_together() -> lame_sys::Result<()> {
+    for shape in [FadeShape::Linear, FadeShape::RaisedCosine] {
+        // 左声道正、右声道负
+        let mut pcm: Vec<i16> = (0..500).flat_map(|_| [FULL, -FULL]).collect();
+        dsp::fade_in_interleaved(&mut pcm, 2, 50, shape)?;
+        dsp::fade_out_interleaved(&mut pcm, 2, 50, shape)?;
+
+        assert_eq!(&pcm[..2], &[0, 0]);
+        assert_eq!(&pcm[998..], &[0, 0]);
+        for frame in pcm.chunks_exact(2) {
+            assert_eq!(frame[0], -frame[1]);
+        }
+        assert!(is_non_decreasing(pcm.iter().step_by(2).take(51).copied()));
+        assert!(is_non_decreasing(
+            pcm.iter().step_by(2).rev().take(51).copied()
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_interleaved_rejects_bad_layout() {
+    let mut pcm = vec![FULL; 5];
+    assert!(matches!(
+        dsp::fade_in_interleaved(&mut pcm, 2, 1, FadeShape::Linear),
+        Err(LameError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        dsp::fade_out_interleaved(&mut pcm, 0, 1, FadeShape::Linear),
+        Err(LameError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_gain_endpoints() {
+    for shape in [FadeShape::Linear, FadeShape::RaisedCosine] {
+        assert_eq!(shape.gain(0, 100), 0.0);
+        assert_eq!(shape.gain(100, 100), 1.0);
+        assert_eq!(shape.gain(5, 0), 1.0);
+        assert!(shape.gain(50, 100) > 0.0 && shape.gain(50, 100) < 1.0);
+    }
+}
@@ -1,14 +1,19 @@
-use lame_sys::{LameEncoder, Id3Tag, Quality, VbrMode};
+use lame_sys::signal;
+use lame_sys::{
+    ChannelMode, Emphasis, FloatScale, Id3Tag, LameEncoder, LameError, Preset, Quality,
+    ShortBlockMode, VbrMode,
+};
+use std::time::Duration;
 
 #[test]
 fn test_basic_encoding() {
     // 创建编码器
     let mut encoder = LameEncoder::builder()
-        .sample_rate(44100)
-        .channels(2)
-        .bitrate(128)
-        .quality(Quality::Standard)
-        .build()
+        .and_then(|b| b.sample_rate(44100))
+        .and_then(|b| b.channels(2))
+        .and_then(|b| b.bitrate(128))
+        .and_then(|b| b.quality(Quality::Standard))
+        .and_then(|b| b.build())
         .expect("Failed to create encoder");
 
     // 创建测试数据（静音）
@@ -38,10 +43,10 @@ fn test_basic_encoding() {
 #[test]
 fn test_interleaved_encoding() {
     let mut encoder = LameEncoder::builder()
-        .sample_rate(44100)
-        .channels(2)
-        .bitrate(192)
-        .build()
+        .and_then(|b| b.sample_rate(44100))
+        .and_then(|b| b.channels(2))
+        .and_then(|b| b.bitrate(192))
+        .and_then(|b| b.build())
         .expect("Failed to create encoder");
 
     // 创建交错立体声数据
@@ -70,11 +75,11 @@ fn test_interleaved_encoding() {
 #[test]
 fn test_vbr_encoding() {
     let mut encoder = LameEncoder::builder()
-        .sample_rate(44100)
-        .channels(2)
-        .vbr_mode(VbrMode::Vbr)
-        .vbr_quality(2) // 高质量
-        .build()
+        .and_then(|b| b.sample_rate(44100))
+        .and_then(|b| b.channels(2))
+        .and_then(|b| b.vbr_mode(VbrMode::Vbr))
+        .and_then(|b| b.vbr_quality(2)) // 高质量
+        .and_then(|b| b.build())
         .expect("Failed to create VBR encoder");
 
     let num_samples = 1152;
@@ -92,10 +97,10 @@ fn test_vbr_encoding() {
 #[test]
 fn test_id3_tags() {
     let mut encoder = LameEncoder::builder()
-        .sample_rate(44100)
-        .channels(2)
-        .bitrate(128)
-        .build()
+        .and_then(|b| b.sample_rate(44100))
+        .and_then(|b| b.channels(2))
+        .and_then(|b| b.bitrate(128))
+        .and_then(|b| b.build())
         .expect("Failed to create encoder");
 
     // 设置 ID3 标签
@@ -110,7 +115,7 @@ fn test_id3_tags() {
         .expect("Failed to set year")
         .comment("Integration test")
         .expect("Failed to set comment")
-        .track(1)
+        .track(1, None)
         .genre("Rock")
         .expect("Failed to set genre")
         .apply()
@@ -136,10 +141,10 @@ fn test_different_sample_rates() {
 
     for &sample_rate in &sample_rates {
         let mut encoder = LameEncoder::builder()
-            .sample_rate(sample_rate)
-            .channels(1) // 单声道
-            .bitrate(64)
-            .build()
+            .and_then(|b| b.sample_rate(sample_rate))
+            .and_then(|b| b.channels(1)) // 单声道
+            .and_then(|b| b.bitrate(64))
+            .and_then(|b| b.build())
             .expect(&format!("Failed to create encoder for {} Hz", sample_rate));
 
         let num_samples = 1152;
@@ -166,11 +171,11 @@ fn test_different_qualities() {
 
     for quality in &qualities {
         let mut encoder = LameEncoder::builder()
-            .sample_rate(44100)
-            .channels(2)
-            .bitrate(128)
-            .quality(*quality)
-            .build()
+            .and_then(|b| b.sample_rate(44100))
+            .and_then(|b| b.channels(2))
+            .and_then(|b| b.bitrate(128))
+            .and_then(|b| b.quality(*quality))
+            .and_then(|b| b.build())
             .expect(&format!("Failed to create encoder for quality {:?}", quality));
 
         let num_samples = 1152;
@@ -189,10 +194,10 @@ fn test_different_qualities() {
 #[test]
 fn test_multiple_frames() {
     let mut encoder = LameEncoder::builder()
-        .sample_rate(44100)
-        .channels(2)
-        .bitrate(128)
-        .build()
+        .and_then(|b| b.sample_rate(44100))
+        .and_then(|b| b.channels(2))
+        .and_then(|b| b.bitrate(128))
+        .and_then(|b| b.build())
         .expect("Failed to create encoder");
 
     let num_samples = 1152;
@@ -227,9 +232,9 @@ fn test_multiple_frames() {
 fn test_error_handling() {
     // 测试无效参数
     let result = LameEncoder::builder()
-        .sample_rate(0) // 无效采样率
-        .channels(2)
-        .build();
+        .and_then(|b| b.sample_rate(0)) // 无效采样率
+        .and_then(|b| b.channels(2))
+        .and_then(|b| b.build());
 
     // 应该失败（虽然 LAME 可能有默认处理）
     // 这个测试主要是确保 API 不会崩溃
@@ -237,9 +242,9 @@ fn test_error_handling() {
 
     // 测试不匹配的声道长度
     if let Ok(mut encoder) = LameEncoder::builder()
-        .sample_rate(44100)
-        .channels(2)
-        .build()
+        .and_then(|b| b.sample_rate(44100))
+        .and_then(|b| b.channels(2))
+        .and_then(|b| b.build())
     {
         let pcm_left = vec![0i16; 1152];
         let pcm_right = vec![0i16; 100]; // 不同长度
@@ -255,11 +260,11 @@ fn test_error_handling() {
 fn test_mono_encoding() {
     // 创建单声道编码器
     let mut encoder = LameEncoder::builder()
-        .sample_rate(44100)
-        .channels(1) // 单声道
-        .bitrate(128)
-        .quality(Quality::Standard)
-        .build()
+        .and_then(|b| b.sample_rate(44100))
+        .and_then(|b| b.channels(1)) // 单声道
+        .and_then(|b| b.bitrate(128))
+        .and_then(|b| b.quality(Quality::Standard))
+        .and_then(|b| b.build())
         .expect("Failed to create mono encoder");
 
     // 创建测试数据
@@ -289,23 +294,15 @@ fn test_mono_encoding() {
 fn test_mono_encoding_with_sine_wave() {
     // 创建单声道编码器
     let mut encoder = LameEncoder::builder()
-        .sample_rate(44100)
-        .channels(1)
-        .bitrate(192)
-        .build()
+        .and_then(|b| b.sample_rate(44100))
+        .and_then(|b| b.channels(1))
+        .and_then(|b| b.bitrate(192))
+        .and_then(|b| b.build())
         .expect("Failed to create mono encoder");
 
     // 生成 440 Hz 正弦波
     let num_samples = 1152;
-    let sample_rate = 44100.0;
-    let frequency = 440.0;
-
-    let mut pcm = vec![0i16; num_samples];
-    for i in 0..num_samples {
-        let t = i as f32 / sample_rate;
-        let sample = (2.0 * std::f32::consts::PI * frequency * t).sin();
-        pcm[i] = (sample * 16384.0) as i16;
-    }
+    let pcm = signal::sine(440.0, 44100, signal::duration_of(num_samples, 44100));
 
     let mp3_buffer_size = (1.25 * num_samples as f64) as usize + 7200;
     let mut mp3_buffer = vec![0u8; mp3_buffer_size];
@@ -322,10 +319,10 @@ fn test_mono_encoding_with_sine_wave() {
 fn test_mono_multiple_frames() {
     // 创建单声道编码器
     let mut encoder = LameEncoder::builder()
-        .sample_rate(44100)
-        .channels(1)
-        .bitrate(128)
-        .build()
+        .and_then(|b| b.sample_rate(44100))
+        .and_then(|b| b.channels(1))
+        .and_then(|b| b.bitrate(128))
+        .and_then(|b| b.build())
         .expect("Failed to create mono encoder");
 
     let num_samples = 1152;
@@ -362,10 +359,10 @@ fn test_mono_different_bitrates() {
 
     for &bitrate in &bitrates {
         let mut encoder = LameEncoder::builder()
-            .sample_rate(44100)
-            .channels(1)
-            .bitrate(bitrate)
-            .build()
+            .and_then(|b| b.sample_rate(44100))
+            .and_then(|b| b.channels(1))
+            .and_then(|b| b.bitrate(bitrate))
+            .and_then(|b| b.build())
             .expect(&format!("Failed to create mono encoder for {} kbps", bitrate));
 
         let num_samples = 1152;
@@ -380,3 +377,3184 @@ fn test_mono_different_bitrates() {
         assert!(bytes_written > 0);
     }
 }
+
+#[test]
+fn test_interleaved_float_encoding() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+
+    let num_samples = 1152;
+    let mut total_bytes = 0;
+
+    // 编码多帧交错浮点数据
+    for frame in 0..10 {
+        let mut pcm = vec![0f32; num_samples * 2];
+        for i in 0..num_samples {
+            let t = (frame * num_samples + i) as f32 / 44100.0;
+            let sample = (2.0 * std::f32::consts::PI * 440.0 * t).sin() * 0.5;
+            pcm[i * 2] = sample; // 左声道
+            pcm[i * 2 + 1] = sample; // 右声道
+        }
+
+        let mut mp3_buffer = vec![0u8; 8192];
+        total_bytes += encoder.encode_interleaved_float(&pcm, &mut mp3_buffer)?;
+    }
+
+    let mut mp3_buffer = vec![0u8; 8192];
+    total_bytes += encoder.flush(&mut mp3_buffer)?;
+
+    println!("Interleaved float total bytes: {}", total_bytes);
+    assert!(total_bytes > 0);
+    Ok(())
+}
+
+#[test]
+fn test_interleaved_float_rejects_odd_length() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+
+    let pcm = vec![0f32; 1153];
+    let mut mp3_buffer = vec![0u8; 8192];
+
+    let result = encoder.encode_interleaved_float(&pcm, &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+    Ok(())
+}
+
+#[test]
+fn test_interleaved_rejects_odd_length() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+    let mut mp3_buffer = vec![0u8; 8192];
+
+    match encoder.encode_interleaved(&vec![0i16; 1153], &mut mp3_buffer) {
+        Err(LameError::InvalidInput(message)) => assert!(message.contains("even")),
+        other => panic!("expected InvalidInput, got {:?}", other),
+    }
+
+    let result = encoder.encode_interleaved_double(&vec![0f64; 1153], &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+
+    let result = encoder.encode_interleaved_int(&vec![0i32; 1153], &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+
+    // 拒绝后编码器仍然可用
+    let written = encoder.encode_interleaved(&vec![0i16; 1152 * 2], &mut mp3_buffer)?;
+    assert!(written <= mp3_buffer.len());
+    Ok(())
+}
+
+#[test]
+fn test_double_encoding_round_trip_length() -> lame_sys::Result<()> {
+    let num_samples = 1152;
+    let frames = 10;
+
+    // 生成 440 Hz 双精度正弦波
+    let signal: Vec<f64> = (0..num_samples * frames)
+        .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / 44100.0).sin() * 0.5)
+        .collect();
+    let interleaved: Vec<f64> = signal.iter().flat_map(|&s| [s, s]).collect();
+
+    // 立体声（分离声道）
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    let mut mp3_buffer = vec![0u8; 1024 * 1024];
+    let mut stereo_bytes = encoder.encode_double(&signal, &signal, &mut mp3_buffer)?;
+    stereo_bytes += encoder.flush(&mut mp3_buffer)?;
+
+    // 立体声（交错）
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    let mut interleaved_bytes = encoder.encode_interleaved_double(&interleaved, &mut mp3_buffer)?;
+    interleaved_bytes += encoder.flush(&mut mp3_buffer)?;
+
+    // 单声道
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(1)?
+        .bitrate(128)?
+        .build()?;
+    let mut mono_bytes = encoder.encode_double_mono(&signal, &mut mp3_buffer)?;
+    mono_bytes += encoder.flush(&mut mp3_buffer)?;
+
+    println!(
+        "Double encoded: stereo {} bytes, interleaved {} bytes, mono {} bytes",
+        stereo_bytes, interleaved_bytes, mono_bytes
+    );
+    assert!(stereo_bytes > 0);
+    assert!(mono_bytes > 0);
+    // 相同的信号，CBR 下两种立体声输入布局的输出长度应一致
+    assert_eq!(stereo_bytes, interleaved_bytes);
+    Ok(())
+}
+
+#[test]
+fn test_int_encoding_matches_i16_length() -> lame_sys::Result<()> {
+    let num_samples = 1152 * 10;
+
+    // 同一波形的 16-bit 与 32-bit（左移 16 位）表示
+    let pcm16 = signal::sine(440.0, 44100, signal::duration_of(num_samples, 44100));
+    let pcm32: Vec<i32> = pcm16.iter().map(|&s| (s as i32) << 16).collect();
+    let interleaved32: Vec<i32> = pcm32.iter().flat_map(|&s| [s, s]).collect();
+
+    let new_encoder = |channels| -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(channels)?
+            .bitrate(128)?
+            .build()
+    };
+    let mut mp3_buffer = vec![0u8; 1024 * 1024];
+
+    let mut encoder = new_encoder(2)?;
+    let mut i16_bytes = encoder.encode(&pcm16, &pcm16, &mut mp3_buffer)?;
+    i16_bytes += encoder.flush(&mut mp3_buffer)?;
+
+    let mut encoder = new_encoder(2)?;
+    let mut i32_bytes = encoder.encode_int(&pcm32, &pcm32, &mut mp3_buffer)?;
+    i32_bytes += encoder.flush(&mut mp3_buffer)?;
+
+    let mut encoder = new_encoder(2)?;
+    let mut interleaved_bytes = encoder.encode_interleaved_int(&interleaved32, &mut mp3_buffer)?;
+    interleaved_bytes += encoder.flush(&mut mp3_buffer)?;
+
+    let mut encoder = new_encoder(1)?;
+    let mut mono16_bytes = encoder.encode_mono(&pcm16, &mut mp3_buffer)?;
+    mono16_bytes += encoder.flush(&mut mp3_buffer)?;
+
+    let mut encoder = new_encoder(1)?;
+    let mut mono32_bytes = encoder.encode_int_mono(&pcm32, &mut mp3_buffer)?;
+    mono32_bytes += encoder.flush(&mut mp3_buffer)?;
+
+    println!(
+        "i16 {} bytes, i32 {} bytes, interleaved i32 {} bytes, mono i16 {} bytes, mono i32 {} bytes",
+        i16_bytes, i32_bytes, interleaved_bytes, mono16_bytes, mono32_bytes
+    );
+    // CBR 下相同时长的输入应产生相同长度的输出
+    assert_eq!(i16_bytes, i32_bytes);
+    assert_eq!(i16_bytes, interleaved_bytes);
+    assert_eq!(mono16_bytes, mono32_bytes);
+    Ok(())
+}
+
+#[test]
+fn test_id3v1_truncation_reported() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+
+    let long_title = "A Very Long Song Title That Does Not Fit In An ID3v1 Tag!!";
+    assert_eq!(long_title.len(), 58);
+    let long_title = format!("{}..", long_title);
+
+    let summary = Id3Tag::new(&mut encoder)
+        .title(&long_title)?
+        .artist("Short Artist")?
+        .album("Short Album")?
+        .comment("This comment is exactly 29 by")?
+        .track(3, None)
+        .genre_v1_byte(lame_sys::genres::ROCK)?
+        .apply()?;
+
+    assert_eq!(summary.truncated_fields, vec!["title", "comment"]);
+    assert!(summary.non_latin1_fields.is_empty());
+    assert!(!summary.is_v1_lossless());
+
+    // ID3v2 标签随第一次编码输出，标题应完整保留
+    let pcm = vec![0i16; 1152];
+    let mut mp3_buffer = vec![0u8; 16384];
+    let bytes_written = encoder.encode(&pcm, &pcm, &mut mp3_buffer)?;
+    let output = &mp3_buffer[..bytes_written];
+
+    assert!(output.starts_with(b"ID3"));
+    assert!(output
+        .windows(long_title.len())
+        .any(|w| w == long_title.as_bytes()));
+    Ok(())
+}
+
+#[test]
+fn test_id3v1_non_latin1_reported() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+
+    let summary = Id3Tag::new(&mut encoder)
+        .title("東京")?
+        .artist("Café")?
+        .album("Plain")?
+        .apply()?;
+
+    assert!(summary.truncated_fields.is_empty());
+    assert_eq!(summary.non_latin1_fields, vec!["title", "artist"]);
+    Ok(())
+}
+
+#[test]
+fn test_genre_v1_byte_rejects_unknown() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+
+    let result = Id3Tag::new(&mut encoder).genre_v1_byte(200);
+    assert!(matches!(result, Err(LameError::InvalidParameter(_))));
+    Ok(())
+}
+
+#[test]
+fn test_genre_rejects_unknown_number() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+
+    for genre in ["148", "999", "-1", " 99999999999999999999"] {
+        let result = Id3Tag::new(&mut encoder).genre(genre);
+        assert!(
+            matches!(result, Err(LameError::InvalidParameter(_))),
+            "{:?}",
+            genre
+        );
+    }
+    // 不在列表中的名称不再写入 ID3v2
+    for genre in ["Chiptune", "80s", ""] {
+        let result = Id3Tag::new(&mut encoder).genre(genre);
+        assert!(
+            matches!(result, Err(LameError::InvalidInput(_))),
+            "{:?}",
+            genre
+        );
+    }
+    // 名称比较时忽略大小写和标点
+    for genre in ["17", "Rock", "hip hop", "r&b", "Drum & Bass"] {
+        Id3Tag::new(&mut encoder).genre(genre)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_genre_list_matches_constants() -> lame_sys::Result<()> {
+    use lame_sys::genres;
+    use lame_sys::id3::genre_list;
+
+    let list = genre_list();
+    let mut ids: Vec<u8> = list.iter().map(|&(id, _)| id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, (0..148).collect::<Vec<u8>>());
+
+    let constants = [
+        (genres::BLUES, "Blues"),
+        (genres::CLASSIC_ROCK, "Classic Rock"),
+        (genres::COUNTRY, "Country"),
+        (genres::DANCE, "Dance"),
+        (genres::DISCO, "Disco"),
+        (genres::FUNK, "Funk"),
+        (genres::GRUNGE, "Grunge"),
+        (genres::HIP_HOP, "Hip-Hop"),
+        (genres::JAZZ, "Jazz"),
+        (genres::METAL, "Metal"),
+        (genres::NEW_AGE, "New Age"),
+        (genres::OLDIES, "Oldies"),
+        (genres::OTHER, "Other"),
+        (genres::POP, "Pop"),
+        (genres::RNB, "R&B"),
+        (genres::RAP, "Rap"),
+        (genres::REGGAE, "Reggae"),
+        (genres::ROCK, "Rock"),
+        (genres::TECHNO, "Techno"),
+        (genres::INDUSTRIAL, "Industrial"),
+        (genres::ALTERNATIVE, "Alternative"),
+        (genres::SKA, "Ska"),
+        (genres::DEATH_METAL, "Death Metal"),
+        (genres::PRANKS, "Pranks"),
+        (genres::SOUNDTRACK, "Soundtrack"),
+        (genres::EURO_TECHNO, "Euro-Techno"),
+        (genres::AMBIENT, "Ambient"),
+        (genres::TRIP_HOP, "Trip-Hop"),
+        (genres::VOCAL, "Vocal"),
+        (genres::JAZZ_FUNK, "Jazz+Funk"),
+        (genres::FUSION, "Fusion"),
+        (genres::TRANCE, "Trance"),
+        (genres::CLASSICAL, "Classical"),
+        (genres::INSTRUMENTAL, "Instrumental"),
+        (genres::ACID, "Acid"),
+        (genres::HOUSE, "House"),
+        (genres::GAME, "Game"),
+        (genres::SOUND_CLIP, "Sound Clip"),
+        (genres::GOSPEL, "Gospel"),
+        (genres::NOISE, "Noise"),
+        (genres::ALTERNATIVE_ROCK, "Alternative Rock"),
+        (genres::BASS, "Bass"),
+        (genres::SOUL, "Soul"),
+        (genres::PUNK, "Punk"),
+        (genres::SPACE, "Space"),
+        (genres::MEDITATIVE, "Meditative"),
+        (genres::INSTRUMENTAL_POP, "Instrumental Pop"),
+        (genres::INSTRUMENTAL_ROCK, "Instrumental Rock"),
+        (genres::ETHNIC, "Ethnic"),
+        (genres::GOTHIC, "Gothic"),
+        (genres::DARKWAVE, "Darkwave"),
+        (genres::TECHNO_INDUSTRIAL, "Techno-Industrial"),
+        (genres::ELECTRONIC, "Electronic"),
+        (genres::POP_FOLK, "Pop-Folk"),
+        (genres::EURODANCE, "Eurodance"),
+        (genres::DREAM, "Dream"),
+        (genres::SOUTHERN_ROCK, "Southern Rock"),
+        (genres::COMEDY, "Comedy"),
+        (genres::CULT, "Cult"),
+        (genres::GANGSTA, "Gangsta"),
+        (genres::TOP_40, "Top 40"),
+        (genres::CHRISTIAN_RAP, "Christian Rap"),
+        (genres::POP_FUNK, "Pop/Funk"),
+        (genres::JUNGLE, "Jungle"),
+        (genres::NATIVE_US, "Native US"),
+        (genres::CABARET, "Cabaret"),
+        (genres::NEW_WAVE, "New Wave"),
+        (genres::PSYCHEDELIC, "Psychedelic"),
+        (genres::RAVE, "Rave"),
+        (genres::SHOWTUNES, "Showtunes"),
+        (genres::TRAILER, "Trailer"),
+        (genres::LO_FI, "Lo-Fi"),
+        (genres::TRIBAL, "Tribal"),
+        (genres::ACID_PUNK, "Acid Punk"),
+        (genres::ACID_JAZZ, "Acid Jazz"),
+        (genres::POLKA, "Polka"),
+        (genres::RETRO, "Retro"),
+        (genres::MUSICAL, "Musical"),
+        (genres::ROCK_N_ROLL, "Rock & Roll"),
+        (genres::HARD_ROCK, "Hard Rock"),
+        (genres::FOLK, "Folk"),
+        (genres::FOLK_ROCK, "Folk-Rock"),
+        (genres::NATIONAL_FOLK, "National Folk"),
+        (genres::SWING, "Swing"),
+        (genres::FAST_FUSION, "Fast Fusion"),
+        (genres::BEBOB, "Bebob"),
+        (genres::LATIN, "Latin"),
+        (genres::REVIVAL, "Revival"),
+        (genres::CELTIC, "Celtic"),
+        (genres::BLUEGRASS, "Bluegrass"),
+        (genres::AVANTGARDE, "Avantgarde"),
+        (genres::GOTHIC_ROCK, "Gothic Rock"),
+        (genres::PROGRESSIVE_ROCK, "Progressive Rock"),
+        (genres::PSYCHEDELIC_ROCK, "Psychedelic Rock"),
+        (genres::SYMPHONIC_ROCK, "Symphonic Rock"),
+        (genres::SLOW_ROCK, "Slow Rock"),
+        (genres::BIG_BAND, "Big Band"),
+        (genres::CHORUS, "Chorus"),
+        (genres::EASY_LISTENING, "Easy Listening"),
+        (genres::ACOUSTIC, "Acoustic"),
+        (genres::HUMOUR, "Humour"),
+        (genres::SPEECH, "Speech"),
+        (genres::CHANSON, "Chanson"),
+        (genres::OPERA, "Opera"),
+        (genres::CHAMBER_MUSIC, "Chamber Music"),
+        (genres::SONATA, "Sonata"),
+        (genres::SYMPHONY, "Symphony"),
+        (genres::BOOTY_BASS, "Booty Bass"),
+        (genres::PRIMUS, "Primus"),
+        (genres::PORN_GROOVE, "Porn Groove"),
+        (genres::SATIRE, "Satire"),
+        (genres::SLOW_JAM, "Slow Jam"),
+        (genres::CLUB, "Club"),
+        (genres::TANGO, "Tango"),
+        (genres::SAMBA, "Samba"),
+        (genres::FOLKLORE, "Folklore"),
+        (genres::BALLAD, "Ballad"),
+        (genres::POWER_BALLAD, "Power Ballad"),
+        (genres::RHYTHMIC_SOUL, "Rhythmic Soul"),
+        (genres::FREESTYLE, "Freestyle"),
+        (genres::DUET, "Duet"),
+        (genres::PUNK_ROCK, "Punk Rock"),
+        (genres::DRUM_SOLO, "Drum Solo"),
+        (genres::A_CAPPELLA, "A Cappella"),
+        (genres::EURO_HOUSE, "Euro-House"),
+        (genres::DANCE_HALL, "Dance Hall"),
+        (genres::GOA, "Goa"),
+        (genres::DRUM_N_BASS, "Drum & Bass"),
+        (genres::CLUB_HOUSE, "Club-House"),
+        (genres::HARDCORE, "Hardcore"),
+        (genres::TERROR, "Terror"),
+        (genres::INDIE, "Indie"),
+        (genres::BRITPOP, "BritPop"),
+        (genres::NEGERPUNK, "Negerpunk"),
+        (genres::POLSK_PUNK, "Polsk Punk"),
+        (genres::BEAT, "Beat"),
+        (genres::CHRISTIAN_GANGSTA, "Christian Gangsta"),
+        (genres::HEAVY_METAL, "Heavy Metal"),
+        (genres::BLACK_METAL, "Black Metal"),
+        (genres::CROSSOVER, "Crossover"),
+        (genres::CONTEMPORARY_CHRISTIAN, "Contemporary Christian"),
+        (genres::CHRISTIAN_ROCK, "Christian Rock"),
+        (genres::MERENGUE, "Merengue"),
+        (genres::SALSA, "Salsa"),
+        (genres::THRASH_METAL, "Thrash Metal"),
+        (genres::ANIME, "Anime"),
+        (genres::JPOP, "JPop"),
+        (genres::SYNTHPOP, "SynthPop"),
+    ];
+    for (id, name) in constants {
+        assert!(list.contains(&(id, name.to_string())), "{} = {}", name, id);
+    }
+
+    // genre_id 与按名称设置写入同一个 ID3v1 字节
+    type Configure = fn(Id3Tag<'_>) -> lame_sys::Result<Id3Tag<'_>>;
+    let v1_genre = |configure: Configure| -> lame_sys::Result<u8> {
+        let mut encoder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?
+            .build()?;
+        configure(Id3Tag::new(&mut encoder).title("Genre")?)?.apply()?;
+        Ok(encoder.id3v1_tag()?[127])
+    };
+    assert_eq!(v1_genre(|tag| tag.genre_id(genres::SYNTHPOP))?, 147);
+    assert_eq!(v1_genre(|tag| tag.genre("synthpop"))?, 147);
+    assert_eq!(v1_genre(|tag| tag.genre("Jazz+Funk"))?, genres::JAZZ_FUNK);
+    assert!(matches!(
+        v1_genre(|tag| tag.genre_id(148)),
+        Err(LameError::InvalidParameter(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_mp3_buffer_size_small_inputs() -> lame_sys::Result<()> {
+    assert_eq!(LameEncoder::mp3_buffer_size(0), 7200);
+    assert_eq!(LameEncoder::mp3_buffer_size(1), 7201);
+    assert_eq!(LameEncoder::mp3_buffer_size(1152), 8640);
+
+    // CBR 320 + 满幅噪声是输出最大的情况
+    let mut seed = 0x1234_5678u32;
+    let mut noise = |n: usize| -> Vec<i16> {
+        (0..n)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 16) as i16
+            })
+            .collect()
+    };
+
+    for &size in &[0usize, 1, 2, 575, 576, 1151, 1152, 1153] {
+        let mut encoder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(320)?
+            .quality(Quality::Best)?
+            .build()?;
+
+        let calls = if size < 576 { 40 } else { 8 };
+        for _ in 0..calls {
+            let pcm = noise(size * 2);
+            let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(size)];
+            encoder
+                .encode_interleaved(&pcm, &mut mp3_buffer)
+                .unwrap_or_else(|e| panic!("size {} overflowed: {}", size, e));
+        }
+
+        let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+        encoder.flush(&mut mp3_buffer)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_encode_frames_matches_interleaved() -> lame_sys::Result<()> {
+    let num_frames = 1152 * 10;
+    let duration = signal::duration_of(num_frames, 44100);
+    let left = signal::sine(440.0, 44100, duration);
+    let right = signal::sine(660.0, 44100, duration);
+    let frames: Vec<[i16; 2]> = left.iter().zip(&right).map(|(&l, &r)| [l, r]).collect();
+    let interleaved = signal::stereo(&left, &right);
+
+    let new_encoder = || -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(192)?
+            .build()
+    };
+
+    let mut expected = vec![0u8; LameEncoder::mp3_buffer_size(num_frames)];
+    let mut encoder = new_encoder()?;
+    let mut expected_len = encoder.encode_interleaved(&interleaved, &mut expected)?;
+    expected_len += encoder.flush(&mut expected[expected_len..])?;
+
+    let mut actual = vec![0u8; LameEncoder::mp3_buffer_size(num_frames)];
+    let mut encoder = new_encoder()?;
+    let mut actual_len = encoder.encode_frames(&frames, &mut actual)?;
+    actual_len += encoder.flush(&mut actual[actual_len..])?;
+
+    assert!(expected_len > 0);
+    assert_eq!(&expected[..expected_len], &actual[..actual_len]);
+    Ok(())
+}
+
+#[test]
+fn test_mono_float_encoding() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(1)?
+        .bitrate(96)?
+        .build()?;
+
+    let num_samples = 1152 * 10;
+    let pcm: Vec<f32> = (0..num_samples)
+        .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 44100.0).sin() * 0.5)
+        .collect();
+
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(num_samples)];
+    let bytes_written = encoder.encode_mono_float(&pcm, &mut mp3_buffer)?;
+    assert!(bytes_written > 0);
+
+    let flushed = encoder.flush(&mut mp3_buffer)?;
+    println!("Mono float: {} bytes, flush {} bytes", bytes_written, flushed);
+    Ok(())
+}
+
+#[test]
+fn test_encode_chunks_large_input() -> lame_sys::Result<()> {
+    use lame_sys::pcm::MAX_SAMPLES_PER_CALL;
+
+    let new_encoder = || -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(1)?
+            .bitrate(64)?
+            .quality(Quality::Fastest)?
+            .build()
+    };
+
+    // 刚好越过分段边界的静音
+    let pcm = vec![0i16; MAX_SAMPLES_PER_CALL + 1];
+    let mut chunked = vec![0u8; LameEncoder::mp3_buffer_size(pcm.len())];
+    let mut encoder = new_encoder()?;
+    let chunked_len = encoder.encode_mono(&pcm, &mut chunked)?;
+
+    // 手动拆成两次调用的结果应当完全一致
+    let mut manual = vec![0u8; LameEncoder::mp3_buffer_size(pcm.len())];
+    let mut encoder = new_encoder()?;
+    let mut manual_len = encoder.encode_mono(&pcm[..MAX_SAMPLES_PER_CALL], &mut manual)?;
+    manual_len += encoder.encode_mono(&pcm[MAX_SAMPLES_PER_CALL..], &mut manual[manual_len..])?;
+
+    assert!(chunked_len > 0);
+    assert_eq!(&chunked[..chunked_len], &manual[..manual_len]);
+    Ok(())
+}
+
+#[test]
+fn test_encode_reports_buffer_too_small() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(320)?
+        .build()?;
+
+    let pcm: Vec<i16> = (0..1152 * 10)
+        .map(|i| ((i * 7919) % 65536) as u16 as i16)
+        .collect();
+    let mut mp3_buffer = vec![0u8; 16];
+
+    let result = encoder.encode(&pcm, &pcm, &mut mp3_buffer);
+    match result {
+        Err(LameError::BufferTooSmall { required, provided }) => {
+            assert_eq!(provided, 16);
+            assert!(required > provided);
+        }
+        other => panic!("expected BufferTooSmall, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_short_range_interleaved_rejects_small_buffer_up_front() -> lame_sys::Result<()> {
+    let new_encoder = || -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(320)?
+            .build()
+    };
+    const FRAMES: usize = 1152 * 10;
+    let pcm: Vec<f32> = (0..FRAMES * 2)
+        .map(|i| ((i * 7919) % 65536) as f32 - 32768.0)
+        .collect();
+
+    // 放得下第一块、放不下整段：不交给 LAME 任何一块
+    let mut encoder = new_encoder()?;
+    let mut small = vec![0u8; LameEncoder::mp3_buffer_size(1152)];
+    match encoder.encode_interleaved_float_scaled(&pcm, FloatScale::ShortRange, &mut small) {
+        Err(LameError::BufferTooSmall { required, provided }) => {
+            assert_eq!(provided, small.len());
+            assert_eq!(required, LameEncoder::mp3_buffer_size(FRAMES));
+        }
+        other => panic!("expected BufferTooSmall, got {:?}", other),
+    }
+
+    // 编码器没有消耗输入，重试的输出与新编码器相同
+    let mut retried = vec![0u8; LameEncoder::mp3_buffer_size(FRAMES)];
+    let retried_len =
+        encoder.encode_interleaved_float_scaled(&pcm, FloatScale::ShortRange, &mut retried)?;
+    let mut expected = vec![0u8; LameEncoder::mp3_buffer_size(FRAMES)];
+    let expected_len = new_encoder()?.encode_interleaved_float_scaled(
+        &pcm,
+        FloatScale::ShortRange,
+        &mut expected,
+    )?;
+    assert_eq!(&retried[..retried_len], &expected[..expected_len]);
+    Ok(())
+}
+
+#[test]
+fn test_u8_encoding_matches_i16() -> lame_sys::Result<()> {
+    let new_encoder = |channels| -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(8000)?
+            .channels(channels)?
+            .bitrate(32)?
+            .build()
+    };
+
+    // 8 kHz 电话音频：400 Hz 正弦波
+    let pcm_u8: Vec<u8> = (0..1152 * 10)
+        .map(|i| {
+            let t = i as f32 / 8000.0;
+            (128.0 + (2.0 * std::f32::consts::PI * 400.0 * t).sin() * 100.0) as u8
+        })
+        .collect();
+    let pcm_i16: Vec<i16> = pcm_u8.iter().map(|&s| (s as i16 - 128) << 8).collect();
+    assert_eq!(pcm_i16[0], 0);
+
+    let mut expected = vec![0u8; LameEncoder::mp3_buffer_size(pcm_u8.len())];
+    let mut actual = vec![0u8; LameEncoder::mp3_buffer_size(pcm_u8.len())];
+
+    // 分两次调用，覆盖暂存缓冲区的复用
+    let (first, second) = pcm_u8.split_at(4000);
+    let mut encoder = new_encoder(1)?;
+    let mut actual_len = encoder.encode_mono_u8(first, &mut actual)?;
+    actual_len += encoder.encode_mono_u8(second, &mut actual[actual_len..])?;
+    actual_len += encoder.flush(&mut actual[actual_len..])?;
+
+    let mut encoder = new_encoder(1)?;
+    let mut expected_len = encoder.encode_mono(&pcm_i16, &mut expected)?;
+    expected_len += encoder.flush(&mut expected[expected_len..])?;
+    assert_eq!(&actual[..actual_len], &expected[..expected_len]);
+
+    let mut encoder = new_encoder(2)?;
+    let mut actual_len = encoder.encode_interleaved_u8(&pcm_u8, &mut actual)?;
+    actual_len += encoder.flush(&mut actual[actual_len..])?;
+
+    let mut encoder = new_encoder(2)?;
+    let mut expected_len = encoder.encode_interleaved(&pcm_i16, &mut expected)?;
+    expected_len += encoder.flush(&mut expected[expected_len..])?;
+    assert_eq!(&actual[..actual_len], &expected[..expected_len]);
+
+    let result = encoder.encode_interleaved_u8(&[128u8; 3], &mut actual);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+    Ok(())
+}
+
+#[test]
+fn test_interleaved_i24_encoding() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(48000)?
+        .channels(2)?
+        .bitrate(192)?
+        .build()?;
+
+    // 24-bit 锯齿波：在 [-2^23, 2^23) 内递增
+    let frames = 1152 * 10;
+    let mut bytes = Vec::with_capacity(frames * 2 * 3);
+    for i in 0..frames {
+        let value = ((i as i32 * 4096) % (1 << 24)) - (1 << 23);
+        let packed = &value.to_le_bytes()[..3];
+        bytes.extend_from_slice(packed); // L
+        bytes.extend_from_slice(packed); // R
+    }
+
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(frames)];
+    let mut total = encoder.encode_interleaved_i24(&bytes, 2, &mut mp3_buffer)?;
+    total += encoder.flush(&mut mp3_buffer)?;
+    assert!(total > 0);
+
+    // 长度不是 3 * channels 的整数倍
+    let result = encoder.encode_interleaved_i24(&bytes[..9], 2, &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+
+    // 声道数与编码器不一致
+    let result = encoder.encode_interleaved_i24(&bytes[..6], 1, &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+    Ok(())
+}
+
+#[test]
+fn test_i24_unpacks_to_full_scale_i32() -> lame_sys::Result<()> {
+    let new_encoder = || -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(1)?
+            .bitrate(128)?
+            .build()
+    };
+
+    let samples: Vec<i32> = (0..1152 * 4)
+        .map(|i| {
+            ((2.0 * std::f64::consts::PI * 440.0 * i as f64 / 44100.0).sin() * 4_000_000.0) as i32
+        })
+        .collect();
+    let bytes: Vec<u8> = samples
+        .iter()
+        .flat_map(|s| s.to_le_bytes()[..3].to_vec())
+        .collect();
+    let full_scale: Vec<i32> = samples.iter().map(|s| s << 8).collect();
+
+    let mut expected = vec![0u8; 65536];
+    let expected_len = new_encoder()?.encode_int_mono(&full_scale, &mut expected)?;
+
+    let mut actual = vec![0u8; 65536];
+    let actual_len = new_encoder()?.encode_interleaved_i24(&bytes, 1, &mut actual)?;
+
+    assert_eq!(&actual[..actual_len], &expected[..expected_len]);
+    Ok(())
+}
+
+#[test]
+fn test_encode_from_iter_matches_slice_encoding() -> lame_sys::Result<()> {
+    let new_encoder = |channels: i32| -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(channels)?
+            .bitrate(128)?
+            .build()
+    };
+
+    for channels in [1, 2] {
+        // 最后一帧不完整
+        let pcm: Vec<i16> = (0..(1152 * 10 + 300) * channels as usize)
+            .map(|i| ((i as f32 * 0.05).sin() * 8000.0) as i16)
+            .collect();
+
+        let mut expected = vec![0u8; LameEncoder::mp3_buffer_size(pcm.len())];
+        let mut encoder = new_encoder(channels)?;
+        let mut expected_len = if channels == 1 {
+            encoder.encode_mono(&pcm, &mut expected)?
+        } else {
+            encoder.encode_interleaved(&pcm, &mut expected)?
+        };
+        expected_len += encoder.flush(&mut expected[expected_len..])?;
+
+        let mut encoder = new_encoder(channels)?;
+        let mut actual = Vec::new();
+        let written = encoder.encode_from_iter(pcm.iter().copied(), &mut actual)?;
+        assert_eq!(written, actual.len() as u64);
+
+        let mut tail = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+        let tail_len = encoder.flush(&mut tail)?;
+        actual.extend_from_slice(&tail[..tail_len]);
+
+        assert_eq!(actual, &expected[..expected_len], "channels={}", channels);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_encode_from_iter_rejects_odd_stereo_tail() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+
+    let mut sink = Vec::new();
+    let result = encoder.encode_from_iter(std::iter::repeat(0i16).take(1152 * 2 + 1), &mut sink);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+    Ok(())
+}
+
+#[test]
+fn test_encode_to_vec_matches_buffer_encoding() -> lame_sys::Result<()> {
+    let new_encoder = |channels: i32| -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(channels)?
+            .bitrate(128)?
+            .build()
+    };
+
+    let left: Vec<i16> = (0..1152 * 20)
+        .map(|i| ((i as f32 * 0.05).sin() * 8000.0) as i16)
+        .collect();
+    let right: Vec<i16> = left.iter().map(|s| s / 2).collect();
+    let interleaved: Vec<i16> = left.iter().zip(&right).flat_map(|(&l, &r)| [l, r]).collect();
+    let mut mp3_buffer = vec![0u8; lame_sys::max_mp3_buffer_size(left.len())];
+
+    let len = new_encoder(2)?.encode(&left, &right, &mut mp3_buffer)?;
+    assert_eq!(new_encoder(2)?.encode_to_vec(&left, &right)?, &mp3_buffer[..len]);
+
+    let len = new_encoder(2)?.encode_interleaved(&interleaved, &mut mp3_buffer)?;
+    assert_eq!(
+        new_encoder(2)?.encode_interleaved_to_vec(&interleaved)?,
+        &mp3_buffer[..len]
+    );
+
+    let len = new_encoder(1)?.encode_mono(&left, &mut mp3_buffer)?;
+    assert_eq!(new_encoder(1)?.encode_mono_to_vec(&left)?, &mp3_buffer[..len]);
+
+    assert_eq!(
+        lame_sys::max_mp3_buffer_size(1152),
+        LameEncoder::mp3_buffer_size(1152)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_float_scale_full_scale_sine() -> lame_sys::Result<()> {
+    let new_encoder = |channels: i32| -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(channels)?
+            .bitrate(128)?
+            .build()
+    };
+
+    for (scale, full_scale) in [
+        (FloatScale::Normalized, 1.0f32),
+        (FloatScale::ShortRange, 32767.0),
+    ] {
+        let left: Vec<f32> = (0..1152 * 20)
+            .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 44100.0).sin() * full_scale)
+            .collect();
+        let interleaved: Vec<f32> = left.iter().flat_map(|&s| [s, s]).collect();
+        let mut mp3_buffer = vec![0u8; lame_sys::max_mp3_buffer_size(left.len())];
+
+        let mut encoder = new_encoder(2)?;
+        let dual_len = encoder.encode_float(&left, &left, scale, &mut mp3_buffer)?;
+        let dual = mp3_buffer[..dual_len].to_vec();
+        assert!(dual_len > 1000, "{:?}: {} bytes", scale, dual_len);
+        encoder.flush(&mut mp3_buffer)?;
+
+        // 交错输入与分离声道输入走同一个 LAME 入口
+        let mut encoder = new_encoder(2)?;
+        let interleaved_len =
+            encoder.encode_interleaved_float_scaled(&interleaved, scale, &mut mp3_buffer)?;
+        assert_eq!(&mp3_buffer[..interleaved_len], &dual[..], "{:?}", scale);
+        encoder.flush(&mut mp3_buffer)?;
+
+        let mut encoder = new_encoder(1)?;
+        let mono_len = encoder.encode_mono_float_scaled(&left, scale, &mut mp3_buffer)?;
+        assert!(mono_len > 1000, "{:?}: {} bytes", scale, mono_len);
+        encoder.flush(&mut mp3_buffer)?;
+    }
+
+    // 用错约定时（±32767 当作 ±1.0）信号严重削波，不再是同一段音频
+    let loud: Vec<f32> = (0..1152 * 20)
+        .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 44100.0).sin() * 32767.0)
+        .collect();
+    let mut expected = vec![0u8; lame_sys::max_mp3_buffer_size(loud.len())];
+    let expected_len =
+        new_encoder(1)?.encode_mono_float_scaled(&loud, FloatScale::ShortRange, &mut expected)?;
+    let mut actual = vec![0u8; lame_sys::max_mp3_buffer_size(loud.len())];
+    let actual_len = new_encoder(1)?.encode_mono_float(&loud, &mut actual)?;
+    assert_ne!(&actual[..actual_len], &expected[..expected_len]);
+    Ok(())
+}
+
+#[test]
+fn test_encode_strided_matches_dual_encoding() -> lame_sys::Result<()> {
+    let new_encoder = || -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?
+            .build()
+    };
+
+    // 3 帧多一点，最后一块不满 1152
+    let frames = 1152 * 3 + 100;
+    let left: Vec<i16> = (0..frames)
+        .map(|i| ((i as f32 * 0.05).sin() * 8000.0) as i16)
+        .collect();
+    let right: Vec<i16> = left.iter().map(|s| s / 3).collect();
+
+    let mut expected = vec![0u8; lame_sys::max_mp3_buffer_size(frames)];
+    let expected_len = new_encoder()?.encode(&left, &right, &mut expected)?;
+
+    // 每帧 4 个样本：[填充, 右, 填充, 左]
+    let packed: Vec<i16> = left
+        .iter()
+        .zip(&right)
+        .flat_map(|(&l, &r)| [0, r, 0, l])
+        .collect();
+    let mut actual = vec![0u8; lame_sys::max_mp3_buffer_size(frames)];
+    let actual_len = new_encoder()?.encode_strided(&packed, 3, 1, 4, frames, &mut actual)?;
+    assert_eq!(&actual[..actual_len], &expected[..expected_len]);
+
+    // 平面布局：左声道在前半部分，右声道在后半部分
+    let planar: Vec<i16> = left.iter().chain(&right).copied().collect();
+    let actual_len = new_encoder()?.encode_strided(&planar, 0, frames, 1, frames, &mut actual)?;
+    assert_eq!(&actual[..actual_len], &expected[..expected_len]);
+    Ok(())
+}
+
+#[test]
+fn test_encode_strided_validates_bounds() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    let data = vec![0i16; 1152 * 2];
+    let mut mp3_buffer = vec![0u8; lame_sys::max_mp3_buffer_size(1152)];
+
+    // 最后一帧的右声道越界
+    let result = encoder.encode_strided(&data, 0, 2, 2, 1152, &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+
+    let result = encoder.encode_strided(&data, 0, 1, 0, 1152, &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+
+    // 溢出不会 panic
+    let result = encoder.encode_strided(&data, 0, 1, usize::MAX, 3, &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+
+    encoder.encode_strided(&data, 0, 1, 2, 1152, &mut mp3_buffer)?;
+    Ok(())
+}
+
+#[test]
+fn test_channel_modes() -> lame_sys::Result<()> {
+    let pcm: Vec<i16> = (0..1152 * 2 * 4)
+        .map(|i| ((i as f32 * 0.05).sin() * 8000.0) as i16)
+        .collect();
+
+    for mode in [
+        ChannelMode::Stereo,
+        ChannelMode::JointStereo,
+        ChannelMode::DualChannel,
+        ChannelMode::Mono,
+    ] {
+        // Mono 配合 2 声道输入即为下混
+        let mut encoder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(96)?
+            .mode(mode)?
+            .build()?;
+
+        let mut mp3 = encoder.encode_interleaved_to_vec(&pcm)?;
+        let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+        let len = encoder.flush(&mut tail)?;
+        mp3.extend_from_slice(&tail[..len]);
+
+        // 帧头的声道模式位：11 为单声道
+        let header = mp3
+            .windows(2)
+            .position(|w| w[0] == 0xFF && w[1] & 0xE0 == 0xE0)
+            .expect("no frame header");
+        let is_mono = mp3[header + 3] >> 6 == 0x3;
+        assert_eq!(is_mono, mode == ChannelMode::Mono, "{:?}", mode);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_resample_48k_to_24k() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .input_sample_rate(48000)?
+        .output_sample_rate(24000)?
+        .channels(2)?
+        .bitrate(64)?
+        .build()?;
+    assert_eq!(encoder.input_sample_rate(), 48000);
+    assert_eq!(encoder.output_sample_rate(), 24000);
+
+    // 一秒 48 kHz 输入
+    let pcm: Vec<i16> = (0..48000 * 2)
+        .map(|i| (((i / 2) as f32 * 0.05).sin() * 8000.0) as i16)
+        .collect();
+    let mut mp3 = encoder.encode_interleaved_to_vec(&pcm)?;
+    let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+    let len = encoder.flush(&mut tail)?;
+    mp3.extend_from_slice(&tail[..len]);
+
+    // 帧头：版本位 10 为 MPEG-2，采样率索引 01 为 24000 Hz
+    let header = mp3
+        .windows(2)
+        .position(|w| w[0] == 0xFF && w[1] & 0xE0 == 0xE0)
+        .expect("no frame header");
+    assert_eq!((mp3[header + 1] >> 3) & 0x3, 0b10);
+    assert_eq!((mp3[header + 2] >> 2) & 0x3, 0b01);
+    Ok(())
+}
+
+#[test]
+fn test_output_sample_rate_defaults_to_lame_choice() -> lame_sys::Result<()> {
+    // 只设置输入采样率：低比特率下 LAME 会自动降低输出采样率
+    let mut encoder = LameEncoder::builder()?
+        .input_sample_rate(48000)?
+        .channels(1)?
+        .bitrate(32)?
+        .build()?;
+    assert_eq!(encoder.input_sample_rate(), 48000);
+    let out = encoder.output_sample_rate();
+    assert!(
+        [8000, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000].contains(&out),
+        "unexpected output sample rate {}",
+        out
+    );
+
+    let pcm = vec![0i16; 4800];
+    encoder.encode_mono_to_vec(&pcm)?;
+
+    // 同时设置时输出采样率与输入一致
+    let encoder = LameEncoder::builder()?
+        .sample_rate(48000)?
+        .channels(1)?
+        .bitrate(32)?
+        .build()?;
+    assert_eq!(encoder.output_sample_rate(), 48000);
+    Ok(())
+}
+
+#[test]
+fn test_output_sample_rate_rejects_non_mpeg_rate() -> lame_sys::Result<()> {
+    let result = LameEncoder::builder()?.output_sample_rate(23000);
+    assert!(matches!(result, Err(LameError::InvalidParameter(_))));
+    Ok(())
+}
+
+#[test]
+fn test_mut_setters_match_chained_setters() -> lame_sys::Result<()> {
+    let pcm: Vec<i16> = (0..1152 * 8).map(|i| ((i * 37) % 2000) as i16).collect();
+    let encode = |mut encoder: LameEncoder| -> lame_sys::Result<Vec<u8>> {
+        let mut mp3 = encoder.encode_interleaved_to_vec(&pcm)?;
+        let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+        let len = encoder.flush(&mut tail)?;
+        mp3.extend_from_slice(&tail[..len]);
+        Ok(mp3)
+    };
+
+    let chained = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(96)?
+        .lowpass(12000)?
+        .build()?;
+
+    let mut builder = LameEncoder::builder()?;
+    builder.set_sample_rate(44100)?.set_channels(2)?;
+    for kbps in [128, 96] {
+        builder.set_bitrate(kbps)?;
+    }
+    // 设置失败不会消耗构建器
+    assert!(builder.set_quality_level(10).is_err());
+    builder.set_lowpass(12000)?;
+
+    assert_eq!(encode(builder.build()?)?, encode(chained)?);
+    Ok(())
+}
+
+#[test]
+fn test_build_rejects_invalid_combinations() -> lame_sys::Result<()> {
+    type Configure = fn(lame_sys::EncoderBuilder) -> lame_sys::Result<lame_sys::EncoderBuilder>;
+    // (配置, 错误信息中应包含的片段)
+    let cases: [(Configure, &[&str]); 7] = [
+        // 48 kHz 是 MPEG-1，没有 24 kbps
+        (
+            |b| b.sample_rate(48000)?.channels(2)?.bitrate(24),
+            &["24 kbps", "MPEG-1", "allowed: 32, 40"],
+        ),
+        // 22.05 kHz 是 MPEG-2，最高 160 kbps
+        (
+            |b| b.sample_rate(22050)?.bitrate(320),
+            &["320 kbps", "MPEG-2", "144, 160"],
+        ),
+        (
+            |b| b.sample_rate(8000)?.channels(1)?.bitrate(192),
+            &["192 kbps", "MPEG-2.5"],
+        ),
+        (|b| b.sample_rate(44100)?.bitrate(100), &["100 kbps", "112"]),
+        // 未指定输出采样率时要求是某个 MPEG 版本的标准比特率
+        (
+            |b| b.input_sample_rate(44100)?.bitrate(500),
+            &["500 kbps", "8, 16, 24"],
+        ),
+        (|b| b.sample_rate(44100)?.channels(3), &["channels"]),
+        (
+            |b| b.vbr_mode(VbrMode::Vbr)?.vbr_quality(10),
+            &["vbr_quality"],
+        ),
+    ];
+
+    for (i, (configure, fragments)) in cases.iter().enumerate() {
+        match LameEncoder::builder()
+            .and_then(configure)
+            .and_then(|b| b.build())
+        {
+            Err(LameError::InvalidParameter(msg)) => {
+                for fragment in fragments.iter() {
+                    assert!(
+                        msg.contains(fragment),
+                        "case {}: {:?} in {:?}",
+                        i,
+                        fragment,
+                        msg
+                    );
+                }
+            }
+            other => panic!(
+                "case {}: expected InvalidParameter, got {:?}",
+                i,
+                other.err()
+            ),
+        }
+    }
+
+    // 合法的边界组合仍然可以构建；只设置输入采样率时 LAME 会降低输出采样率
+    LameEncoder::builder()?
+        .sample_rate(8000)?
+        .channels(1)?
+        .bitrate(8)?
+        .build()?;
+    LameEncoder::builder()?
+        .sample_rate(24000)?
+        .bitrate(160)?
+        .build()?;
+    LameEncoder::builder()?
+        .input_sample_rate(48000)?
+        .bitrate(24)?
+        .build()?;
+    // VBR 不检查 CBR 比特率
+    LameEncoder::builder()?
+        .sample_rate(48000)?
+        .bitrate(24)?
+        .vbr_mode(VbrMode::Vbr)?
+        .build()?;
+    Ok(())
+}
+
+#[test]
+fn test_lowpass_highpass_filters() -> lame_sys::Result<()> {
+    let pcm: Vec<i16> = (0..1152 * 4)
+        .map(|i| ((i as f32 * 0.3).sin() * 8000.0) as i16)
+        .collect();
+
+    type Configure = fn(lame_sys::EncoderBuilder) -> lame_sys::Result<lame_sys::EncoderBuilder>;
+    let configs: [Configure; 5] = [
+        |b| b.lowpass(8000),
+        |b| b.lowpass(8000)?.lowpass_width(1000),
+        |b| b.highpass(100),
+        |b| b.highpass(100)?.highpass_width(50),
+        |b| b.lowpass(-1)?.highpass(-1),
+    ];
+    for configure in configs {
+        let builder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(1)?
+            .bitrate(64)?;
+        let mut encoder = configure(builder)?.build()?;
+        let mut mp3 = encoder.encode_mono_to_vec(&pcm)?;
+        let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+        let len = encoder.flush(&mut tail)?;
+        mp3.extend_from_slice(&tail[..len]);
+        assert!(!mp3.is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_filter_rejects_below_disabled() -> lame_sys::Result<()> {
+    assert!(matches!(
+        LameEncoder::builder()?.lowpass(-2),
+        Err(LameError::InvalidParameter(_))
+    ));
+    assert!(matches!(
+        LameEncoder::builder()?.highpass_width(-5),
+        Err(LameError::InvalidParameter(_))
+    ));
+    Ok(())
+}
+
+/// 编码约 1 秒的立体声噪声并返回完整输出
+fn encode_noise(mut encoder: LameEncoder) -> lame_sys::Result<(LameEncoder, Vec<u8>)> {
+    let second = Duration::from_secs(1);
+    let pcm = signal::stereo(
+        &signal::white_noise(44100, second, 1),
+        &signal::white_noise(44100, second, 2),
+    );
+    let mut mp3 = encoder.encode_interleaved_to_vec(&pcm)?;
+    let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+    let len = encoder.flush(&mut tail)?;
+    mp3.extend_from_slice(&tail[..len]);
+    Ok((encoder, mp3))
+}
+
+#[test]
+fn test_presets_build_and_encode() -> lame_sys::Result<()> {
+    let presets = [
+        Preset::V0,
+        Preset::V1,
+        Preset::V2,
+        Preset::V3,
+        Preset::V4,
+        Preset::V5,
+        Preset::V6,
+        Preset::V7,
+        Preset::V8,
+        Preset::V9,
+        Preset::Standard,
+        Preset::Extreme,
+        Preset::Insane,
+        Preset::Abr(128),
+    ];
+    for preset in presets {
+        let encoder = LameEncoder::builder()?
+            .preset(preset)?
+            .sample_rate(44100)?
+            .channels(2)?
+            .build()?;
+        let (_, mp3) = encode_noise(encoder)?;
+        assert!(!mp3.is_empty(), "{:?}", preset);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_preset_insane_is_320_cbr() -> lame_sys::Result<()> {
+    let encoder = LameEncoder::builder()?
+        .preset(Preset::Insane)?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+    let (encoder, _) = encode_noise(encoder)?;
+    // 所有帧都是 320 kbps（比特率索引 14）
+    let histogram = encoder.bitrate_histogram();
+    assert_eq!(histogram[13], (320, encoder.frame_count() as i32));
+    Ok(())
+}
+
+#[test]
+fn test_later_settings_override_preset() -> lame_sys::Result<()> {
+    // 预设之后的显式设置生效：320 kbps 的预设改为 128 kbps CBR
+    let encoder = LameEncoder::builder()?
+        .preset(Preset::Insane)?
+        .bitrate(128)?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+    let (encoder, _) = encode_noise(encoder)?;
+    assert_eq!(
+        encoder.bitrate_histogram()[8],
+        (128, encoder.frame_count() as i32)
+    );
+
+    // -V 预设打开 VBR，之后可以切回 CBR
+    let encoder = LameEncoder::builder()?
+        .preset(Preset::V2)?
+        .vbr_mode(VbrMode::Off)?
+        .bitrate(192)?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+    let (encoder, _) = encode_noise(encoder)?;
+    assert_eq!(
+        encoder.bitrate_histogram()[10],
+        (192, encoder.frame_count() as i32)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_preset_abr_range() -> lame_sys::Result<()> {
+    for kbps in [0, 7, 321] {
+        assert!(matches!(
+            LameEncoder::builder()?.preset(Preset::Abr(kbps)),
+            Err(LameError::InvalidParameter(_))
+        ));
+    }
+    LameEncoder::builder()?.preset(Preset::Abr(8))?;
+    LameEncoder::builder()?.preset(Preset::Abr(320))?;
+    Ok(())
+}
+
+#[test]
+fn test_abr_bitrate_sets_mean_bitrate() -> lame_sys::Result<()> {
+    let mut sizes = Vec::new();
+    for kbps in [96, 256] {
+        let encoder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .vbr_mode(VbrMode::Abr)?
+            .abr_bitrate(kbps)?
+            .build()?;
+        let (_, mp3) = encode_noise(encoder)?;
+        sizes.push(mp3.len());
+    }
+    // 约 1 秒的输入：输出大小随目标比特率变化
+    assert!(sizes[1] > sizes[0] * 2, "{:?}", sizes);
+    Ok(())
+}
+
+#[test]
+fn test_abr_bitrate_validation() -> lame_sys::Result<()> {
+    for kbps in [7, 321] {
+        assert!(matches!(
+            LameEncoder::builder()?.abr_bitrate(kbps),
+            Err(LameError::InvalidParameter(_))
+        ));
+    }
+
+    // 没有切换到 ABR 模式时在 build() 报错
+    let result = LameEncoder::builder()?
+        .vbr_mode(VbrMode::Vbr)?
+        .abr_bitrate(160)?
+        .build();
+    assert!(matches!(result, Err(LameError::InvalidParameter(_))));
+
+    // 设置顺序不影响
+    LameEncoder::builder()?
+        .abr_bitrate(160)?
+        .vbr_mode(VbrMode::Abr)?
+        .build()?;
+    Ok(())
+}
+
+#[test]
+fn test_vbr_bitrate_range_pins_bitrate() -> lame_sys::Result<()> {
+    let encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .vbr_mode(VbrMode::Vbr)?
+        .vbr_min_bitrate(128)?
+        .vbr_max_bitrate(128)?
+        .vbr_enforce_min(true)?
+        .build()?;
+    let (_, mp3) = encode_noise(encoder)?;
+    // 约 1 秒的输入，128 kbps 约 16000 字节，另有信息帧和编码延迟
+    assert!((15_000..19_000).contains(&mp3.len()), "{}", mp3.len());
+    Ok(())
+}
+
+#[test]
+fn test_vbr_bitrate_range_validation() -> lame_sys::Result<()> {
+    for kbps in [7, 321] {
+        assert!(matches!(
+            LameEncoder::builder()?.vbr_min_bitrate(kbps),
+            Err(LameError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            LameEncoder::builder()?.vbr_max_bitrate(kbps),
+            Err(LameError::InvalidParameter(_))
+        ));
+    }
+
+    let result = LameEncoder::builder()?
+        .vbr_mode(VbrMode::Vbr)?
+        .vbr_min_bitrate(256)?
+        .vbr_max_bitrate(96)?
+        .build();
+    assert!(matches!(result, Err(LameError::InvalidParameter(_))));
+    Ok(())
+}
+
+#[test]
+fn test_header_flags() -> lame_sys::Result<()> {
+    let defaults = LameEncoder::builder()?.build()?;
+    assert!(!defaults.is_error_protected());
+    assert!(!defaults.is_copyright());
+    assert!(defaults.is_original());
+
+    let encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .write_vbr_tag(false)?
+        .error_protection(true)?
+        .copyright(true)?
+        .original(false)?
+        .build()?;
+    assert!(encoder.is_error_protected());
+    assert!(encoder.is_copyright());
+    assert!(!encoder.is_original());
+
+    // 没有信息帧和 ID3 标签，输出从第一个音频帧的帧头开始
+    let (_, mp3) = encode_noise(encoder)?;
+    assert_eq!(mp3[0], 0xFF);
+    assert_eq!(mp3[1] & 0x01, 0, "protection 位为 0 表示带 CRC");
+    assert_eq!(mp3[3] & 0x08, 0x08, "copyright");
+    assert_eq!(mp3[3] & 0x04, 0, "original");
+    Ok(())
+}
+
+#[test]
+fn test_emphasis_and_private_bit() -> lame_sys::Result<()> {
+    let defaults = LameEncoder::builder()?.build()?;
+    assert_eq!(defaults.emphasis(), Emphasis::None);
+    assert!(!defaults.is_private());
+
+    let variants = [
+        (Emphasis::None, 0b00),
+        (Emphasis::E5015, 0b01),
+        (Emphasis::Ccitt, 0b11),
+    ];
+    for (emphasis, bits) in variants {
+        for private in [false, true] {
+            let mut encoder = LameEncoder::builder()?
+                .sample_rate(44100)?
+                .channels(2)?
+                .bitrate(128)?
+                .write_vbr_tag(false)?
+                .emphasis(emphasis)?
+                .private_bit(private)?
+                .build()?;
+            assert_eq!(encoder.emphasis(), emphasis);
+            assert_eq!(encoder.is_private(), private);
+
+            // 编码一帧并刷新，输出从该帧的帧头开始
+            let pcm = vec![0i16; 1152 * 2];
+            let mut mp3 = encoder.encode_interleaved_to_vec(&pcm)?;
+            let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+            let len = encoder.flush(&mut tail)?;
+            mp3.extend_from_slice(&tail[..len]);
+
+            assert_eq!(mp3[0], 0xFF);
+            assert_eq!(mp3[3] & 0x03, bits, "{:?}", emphasis);
+            assert_eq!(mp3[2] & 0x01, private as u8, "private");
+        }
+    }
+    Ok(())
+}
+
+/// 用固定参数编码一段浮点噪声，返回完整的 MP3
+fn encode_float_noise(vbr: bool) -> lame_sys::Result<Vec<u8>> {
+    let mut builder = LameEncoder::builder()?.sample_rate(44100)?.channels(2)?;
+    builder = if vbr {
+        builder.vbr_mode(VbrMode::Vbr)?.vbr_quality(2)?
+    } else {
+        builder.bitrate(128)?
+    };
+    let mut encoder = builder.build()?;
+
+    let mut state = 0x9E37_79B9u32;
+    let pcm: Vec<f32> = (0..44100 * 2)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+        })
+        .collect();
+
+    let mut mp3 = vec![0u8; lame_sys::max_mp3_buffer_size(pcm.len() / 2)];
+    let mut len = encoder.encode_interleaved_float(&pcm, &mut mp3)?;
+    len += encoder.flush(&mut mp3[len..])?;
+    mp3.truncate(len);
+    Ok(mp3)
+}
+
+#[test]
+fn test_float_encoding_is_reproducible() -> lame_sys::Result<()> {
+    for vbr in [false, true] {
+        let first = encode_float_noise(vbr)?;
+        let second = encode_float_noise(vbr)?;
+        assert!(!first.is_empty());
+        assert!(first == second, "vbr={}: 两次编码的输出不同", vbr);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_disabled_bit_reservoir_frames_are_self_contained() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .write_vbr_tag(false)?
+        .strict_iso(true)?
+        .disable_bit_reservoir(true)?
+        .build()?;
+
+    let frame = signal::duration_of(1152, 44100);
+    let mut mp3 = Vec::new();
+    for call in 0..20 {
+        let pcm = signal::stereo(
+            &signal::white_noise(44100, frame, call * 2),
+            &signal::white_noise(44100, frame, call * 2 + 1),
+        );
+        mp3.extend(encoder.encode_interleaved_to_vec(&pcm)?);
+    }
+    let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+    let len = encoder.flush(&mut tail)?;
+    mp3.extend_from_slice(&tail[..len]);
+
+    // 逐帧检查 MPEG-1 Layer III 128 kbps 帧头，且 main_data_begin 为 0（不引用前面的帧）
+    let mut pos = 0;
+    let mut frames = 0;
+    while pos < mp3.len() {
+        let header = &mp3[pos..pos + 6];
+        assert_eq!(header[0], 0xFF, "帧 {} 的同步字", frames);
+        assert_eq!(header[1] & 0xFE, 0xFA, "帧 {} 不是 MPEG-1 Layer 3", frames);
+        assert_eq!(header[2] >> 4, 9, "帧 {} 的比特率不是 128 kbps", frames);
+        let main_data_begin = (u16::from(header[4]) << 1) | u16::from(header[5] >> 7);
+        assert_eq!(main_data_begin, 0, "帧 {} 引用了比特池", frames);
+
+        let padding = usize::from((header[2] >> 1) & 1);
+        pos += 144 * 128_000 / 44100 + padding;
+        frames += 1;
+    }
+    assert_eq!(pos, mp3.len());
+    assert!(frames >= 20, "{} frames", frames);
+    Ok(())
+}
+
+#[test]
+fn test_free_format_bitrate() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .free_format(true)?
+        .bitrate(400)?
+        .write_vbr_tag(false)?
+        .build()?;
+
+    let pcm = signal::interleave(
+        &signal::sine(440.0, 44100, signal::duration_of(1152 * 4, 44100)),
+        2,
+    );
+    let mut mp3 = encoder.encode_interleaved_to_vec(&pcm)?;
+    let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+    let len = encoder.flush(&mut tail)?;
+    mp3.extend_from_slice(&tail[..len]);
+
+    // 自由格式的帧头比特率索引为 0
+    assert!(mp3.len() > 4);
+    assert_eq!(mp3[0], 0xFF);
+    assert_eq!(mp3[2] >> 4, 0);
+
+    let result = LameEncoder::builder()?
+        .free_format(true)?
+        .bitrate(641)?
+        .build();
+    assert!(matches!(result, Err(LameError::InvalidParameter(_))));
+    Ok(())
+}
+
+#[test]
+fn test_set_bitrate_mid_stream() -> lame_sys::Result<()> {
+    const FRAMES: usize = 10;
+    let pcm = signal::interleave(
+        &signal::sine(440.0, 44100, signal::duration_of(1152 * FRAMES, 44100)),
+        2,
+    );
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(96)?
+        .write_vbr_tag(false)?
+        .build()?;
+    let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+
+    let mut low = encoder.encode_interleaved_to_vec(&pcm)?;
+    let len = encoder.flush_nogap(&mut tail)?;
+    low.extend_from_slice(&tail[..len]);
+
+    encoder.set_bitrate(192)?;
+    assert_eq!(encoder.config().bitrate, Some(192));
+    assert_eq!(lame_sys::live_handles(), 1);
+
+    let mut high = encoder.encode_interleaved_to_vec(&pcm)?;
+    let len = encoder.flush(&mut tail)?;
+    high.extend_from_slice(&tail[..len]);
+
+    // 两段帧数相同，CBR 帧长与比特率成正比
+    let ratio = high.len() as f64 / low.len() as f64;
+    assert!(
+        (1.9..2.1).contains(&ratio),
+        "192/96 kbps size ratio {}",
+        ratio
+    );
+    // 新句柄的所有帧都是 192 kbps（MPEG-1 比特率索引 11）
+    let histogram = encoder.bitrate_histogram();
+    assert_eq!(histogram[10], (192, encoder.frame_count() as i32));
+
+    // 缓存中还有 PCM 时不能切换
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(96)?
+        .write_vbr_tag(false)?
+        .build()?;
+    encoder.encode_interleaved_to_vec(&pcm[..1000])?;
+    assert!(matches!(
+        encoder.set_bitrate(128),
+        Err(LameError::InvalidInput(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_set_bitrate_follows_encoder_state() -> lame_sys::Result<()> {
+    // MPEG-2 的帧长是 576，开始编码之前同样可以切换
+    let pcm = signal::interleave(
+        &signal::sine(440.0, 22050, signal::duration_of(576 * 10, 22050)),
+        2,
+    );
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(22050)?
+        .channels(2)?
+        .bitrate(64)?
+        .write_vbr_tag(false)?
+        .build()?;
+    encoder.set_bitrate(32)?;
+    assert_eq!(encoder.bitrate(), 32);
+
+    let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+    encoder.encode_interleaved_to_vec(&pcm)?;
+    encoder.flush_nogap(&mut tail)?;
+    encoder.set_bitrate(48)?;
+    assert_eq!(encoder.bitrate(), 48);
+
+    // flush 之后不能再切换
+    encoder.encode_interleaved_to_vec(&pcm)?;
+    encoder.flush(&mut tail)?;
+    assert!(matches!(
+        encoder.set_bitrate(64),
+        Err(LameError::InvalidInput(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_set_bitrate_rejects_unsupported_configurations() -> lame_sys::Result<()> {
+    let cbr = || -> lame_sys::Result<lame_sys::EncoderBuilder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)
+    };
+
+    // 44.1 kHz 是 MPEG-1，没有 144 kbps
+    let mut encoder = cbr()?.write_vbr_tag(false)?.build()?;
+    match encoder.set_bitrate(144) {
+        Err(LameError::InvalidParameter(msg)) => assert!(msg.contains("144 kbps"), "{}", msg),
+        other => panic!("expected InvalidParameter, got {:?}", other),
+    }
+    // 失败后编码器保持原样
+    assert_eq!(encoder.config().bitrate, Some(128));
+
+    let mut encoder = cbr()?.build()?;
+    assert!(matches!(
+        encoder.set_bitrate(192),
+        Err(LameError::InvalidParameter(_))
+    ));
+
+    let mut encoder = cbr()?
+        .write_vbr_tag(false)?
+        .vbr_mode(VbrMode::Vbr)?
+        .build()?;
+    assert!(matches!(
+        encoder.set_bitrate(192),
+        Err(LameError::InvalidParameter(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_total_samples_hint() -> lame_sys::Result<()> {
+    let samples = 1152 * 20 + 300;
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .vbr_mode(VbrMode::Vbr)?
+        .total_samples(samples as u64)?
+        .build()?;
+
+    let mono = signal::sine(440.0, 44100, signal::duration_of(samples, 44100));
+    let mut mp3 = encoder.encode_interleaved_to_vec(&signal::interleave(&mono, 2))?;
+    let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+    let len = encoder.flush(&mut tail)?;
+    mp3.extend_from_slice(&tail[..len]);
+    assert!(len > 0);
+    assert!(encoder.frame_count() > 20);
+
+    if std::mem::size_of::<std::ffi::c_ulong>() < 8 {
+        assert!(matches!(
+            LameEncoder::builder()?.total_samples(u64::MAX),
+            Err(LameError::InvalidParameter(_))
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_gapless_samples_match_input() -> lame_sys::Result<()> {
+    for (sample_rate, samples) in [(44100, 1152 * 20 + 300), (22050, 576 * 7 + 1)] {
+        let mut encoder = LameEncoder::builder()?
+            .sample_rate(sample_rate)?
+            .channels(1)?
+            .bitrate(64)?
+            .total_samples(samples as u64)?
+            .build()?;
+        let rate = sample_rate as u32;
+        let pcm = signal::sine(440.0, rate, signal::duration_of(samples, rate));
+
+        encoder.encode_mono_to_vec(&pcm)?;
+        assert_eq!(encoder.end_padding(), 0, "flush 之前没有填充");
+
+        let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+        encoder.flush(&mut tail)?;
+        assert!(encoder.encoder_delay() > 0);
+        assert!(encoder.end_padding() > 0);
+        assert_eq!(
+            encoder.frame_count() * encoder.frame_size(),
+            encoder.encoder_delay() + samples + encoder.end_padding()
+        );
+        assert_eq!(encoder.gapless_samples(), samples, "{} Hz", sample_rate);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_write_vbr_tag_toggle() -> lame_sys::Result<()> {
+    let pcm = signal::stereo(
+        &signal::white_noise(44100, signal::duration_of(1152 * 10, 44100), 1),
+        &signal::white_noise(44100, signal::duration_of(1152 * 10, 44100), 2),
+    );
+    let first_call = |write_vbr_tag: bool| -> lame_sys::Result<Vec<u8>> {
+        let mut encoder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?
+            .write_vbr_tag(write_vbr_tag)?
+            .build()?;
+        assert_eq!(encoder.writes_vbr_tag(), write_vbr_tag);
+        encoder.encode_interleaved_to_vec(&pcm)
+    };
+    let with_tag = first_call(true)?;
+    let without_tag = first_call(false)?;
+
+    // 开启时第一次调用多输出一个信息帧占位（帧头之后全为 0），其后的音频帧完全相同
+    let padding = usize::from((with_tag[2] >> 1) & 1);
+    let info_len = 144 * 128_000 / 44100 + padding;
+    assert!(with_tag[4..info_len].iter().all(|&b| b == 0));
+    assert!(without_tag[4..info_len].iter().any(|&b| b != 0));
+    assert_eq!(with_tag.len(), without_tag.len() + info_len);
+    assert_eq!(with_tag[info_len..], without_tag[..]);
+    Ok(())
+}
+
+#[test]
+fn test_quality_level_and_scale() -> lame_sys::Result<()> {
+    let stereo = || {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)
+    };
+
+    let encoder = stereo()?.quality_level(3)?.scale(0.5)?.build()?;
+    let (_, mp3) = encode_noise(encoder)?;
+    assert!(!mp3.is_empty());
+
+    let encoder = stereo()?.scale_left(1.5)?.scale_right(0.25)?.build()?;
+    let (_, mp3) = encode_noise(encoder)?;
+    assert!(!mp3.is_empty());
+
+    // 与 Quality 枚举对应的整数级别得到相同的输出
+    let (_, by_level) = encode_noise(stereo()?.quality_level(5)?.build()?)?;
+    let (_, by_enum) = encode_noise(stereo()?.quality(Quality::Standard)?.build()?)?;
+    assert_eq!(by_level, by_enum);
+
+    for level in [-1, 10] {
+        assert!(matches!(
+            stereo()?.quality_level(level),
+            Err(LameError::InvalidParameter(_))
+        ));
+    }
+    assert!(stereo()?.scale(f32::NAN).is_err());
+    assert!(stereo()?.scale_left(f32::INFINITY).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_ath_settings() -> lame_sys::Result<()> {
+    let stereo = || {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)
+    };
+    let configs = [
+        stereo()?.ath_only(true)?,
+        stereo()?.no_ath(true)?,
+        stereo()?.ath_type(2)?,
+        stereo()?.ath_lower(3.0)?,
+        stereo()?.athaa_sensitivity(-2.5)?,
+        stereo()?
+            .ath_type(4)?
+            .ath_lower(1.5)?
+            .athaa_sensitivity(1.0)?,
+    ];
+    for (index, builder) in configs.into_iter().enumerate() {
+        let (encoder, mp3) = encode_noise(builder.build()?)?;
+        assert!(encoder.frame_count() > 0, "config {}", index);
+        assert!(!mp3.is_empty(), "config {}", index);
+    }
+
+    assert!(matches!(
+        stereo()?.ath_lower(f32::NAN),
+        Err(LameError::InvalidParameter(name)) if name == "ath_lower"
+    ));
+    assert!(stereo()?.athaa_sensitivity(f32::INFINITY).is_err());
+    Ok(())
+}
+
+/// 1 秒立体声的打击乐式信号：静音中每 100 ms 出现一次 5 ms 的噪声脉冲
+fn transient_pcm() -> Vec<i16> {
+    let mut mono = signal::white_noise(44100, Duration::from_secs(1), 7);
+    for (i, sample) in mono.iter_mut().enumerate() {
+        if i % 4410 >= 220 {
+            *sample = 0;
+        }
+    }
+    signal::interleave(&mono, 2)
+}
+
+#[test]
+fn test_short_blocks_and_temporal_masking() -> lame_sys::Result<()> {
+    let pcm = transient_pcm();
+    let encode = |mode: ShortBlockMode, temporal: bool| -> lame_sys::Result<Vec<u8>> {
+        let mut encoder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?
+            .short_blocks(mode)?
+            .use_temporal_masking(temporal)?
+            .build()?;
+        let mut mp3 = vec![0u8; LameEncoder::mp3_buffer_size(pcm.len() / 2)];
+        let mut len = encoder.encode_interleaved(&pcm, &mut mp3)?;
+        len += encoder.flush(&mut mp3[len..])?;
+        assert!(encoder.frame_count() > 0);
+        mp3.truncate(len);
+        Ok(mp3)
+    };
+
+    let mut outputs = Vec::new();
+    for mode in [
+        ShortBlockMode::Allowed,
+        ShortBlockMode::Dispensed,
+        ShortBlockMode::Forced,
+    ] {
+        for temporal in [true, false] {
+            let mp3 = encode(mode, temporal)?;
+            assert!(!mp3.is_empty(), "{:?} temporal={}", mode, temporal);
+            outputs.push(mp3);
+        }
+    }
+
+    // 瞬态信号上强制短块和禁用短块的输出不同
+    assert_ne!(outputs[2], outputs[4]);
+    Ok(())
+}
+
+#[test]
+fn test_short_block_mode_switches() -> lame_sys::Result<()> {
+    let pcm = transient_pcm();
+    let encode = |modes: &[ShortBlockMode]| -> lame_sys::Result<Vec<u8>> {
+        let mut builder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?;
+        for &mode in modes {
+            builder.set_short_blocks(mode)?;
+        }
+        let mut encoder = builder.build()?;
+        let mut mp3 = vec![0u8; LameEncoder::mp3_buffer_size(pcm.len() / 2)];
+        let mut len = encoder.encode_interleaved(&pcm, &mut mp3)?;
+        len += encoder.flush(&mut mp3[len..])?;
+        mp3.truncate(len);
+        Ok(mp3)
+    };
+
+    // 最后设置的模式生效，不受之前的强制或禁用影响
+    let modes = [
+        ShortBlockMode::Allowed,
+        ShortBlockMode::Dispensed,
+        ShortBlockMode::Forced,
+    ];
+    for last in modes {
+        let expected = encode(&[last])?;
+        for first in modes {
+            let mp3 = encode(&[first, last])?;
+            assert!(mp3 == expected, "{:?} then {:?}", first, last);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_oversized_id3v2_tag_is_discarded() -> lame_sys::Result<()> {
+    use lame_sys::id3::{check_album_art, MAX_ALBUMART_BYTES, MAX_ID3V2_TAG_BYTES};
+
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    let comment = "x".repeat(MAX_ID3V2_TAG_BYTES);
+    let result = Id3Tag::new(&mut encoder)
+        .title("Dropped with the rest of the tag")?
+        .comment(&comment)?
+        .apply();
+    match result {
+        Err(LameError::InvalidInput(msg)) => {
+            assert!(msg.contains(&MAX_ID3V2_TAG_BYTES.to_string()), "{}", msg);
+        }
+        other => panic!("expected InvalidInput, got {:?}", other),
+    }
+
+    // 没有写入任何 ID3v2 或 ID3v1 数据
+    let (_, mp3) = encode_noise(encoder)?;
+    assert_ne!(&mp3[..3], b"ID3");
+    assert_ne!(&mp3[mp3.len() - 128..][..3], b"TAG");
+
+    assert!(check_album_art(&vec![0u8; MAX_ALBUMART_BYTES]).is_ok());
+    match check_album_art(&vec![0u8; MAX_ALBUMART_BYTES + 1]) {
+        Err(LameError::InvalidInput(msg)) => {
+            let size = (MAX_ALBUMART_BYTES + 1).to_string();
+            assert!(msg.contains(&size), "{}", msg);
+            assert!(msg.contains(&MAX_ALBUMART_BYTES.to_string()), "{}", msg);
+        }
+        other => panic!("expected InvalidInput, got {:?}", other),
+    }
+    Ok(())
+}
+
+/// 1x1 像素的 PNG 图片
+const TINY_PNG: [u8; 67] = [
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x15, 0xC4,
+    0x89, 0x00, 0x00, 0x00, 0x0A, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x00, 0x01, 0x00, 0x00,
+    0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE,
+    0x42, 0x60, 0x82,
+];
+
+/// 输出开头 ID3v2 标签的总字节数（含 10 字节的头）
+fn id3v2_len(mp3: &[u8]) -> usize {
+    assert_eq!(&mp3[..3], b"ID3");
+    10 + mp3[6..10]
+        .iter()
+        .fold(0, |size, &b| (size << 7) | usize::from(b & 0x7F))
+}
+
+#[test]
+fn test_album_art_adds_apic_frame() -> lame_sys::Result<()> {
+    use lame_sys::id3::album_art_mime_type;
+
+    let tagged = |image: Option<&[u8]>| -> lame_sys::Result<Vec<u8>> {
+        let mut encoder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?
+            .build()?;
+        let mut tag = Id3Tag::new(&mut encoder)
+            .title("Cover")?
+            .album_artist("Various Artists")?;
+        if let Some(image) = image {
+            tag = tag.album_art(image)?;
+        }
+        tag.apply()?;
+        Ok(encode_noise(encoder)?.1)
+    };
+
+    let plain = tagged(None)?;
+    let with_art = tagged(Some(&TINY_PNG))?;
+    let grown = id3v2_len(&with_art) - id3v2_len(&plain);
+    // APIC 帧头和 MIME 类型等字段之外，就是原样写入的图片
+    assert!(
+        (TINY_PNG.len() + 10..TINY_PNG.len() + 32).contains(&grown),
+        "tag grew by {} bytes",
+        grown
+    );
+    let tag = &with_art[..id3v2_len(&with_art)];
+    assert!(tag.windows(4).any(|w| w == b"APIC"));
+    assert!(tag.windows(4).any(|w| w == b"TPE2"));
+    assert!(tag.windows(TINY_PNG.len()).any(|w| w == TINY_PNG));
+
+    assert_eq!(album_art_mime_type(&TINY_PNG), Some("image/png"));
+    assert_eq!(album_art_mime_type(b"GIF89a"), Some("image/gif"));
+    assert_eq!(
+        album_art_mime_type(&[0xFF, 0xD8, 0xFF, 0xE0]),
+        Some("image/jpeg")
+    );
+
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+    for image in [&b"not an image"[..], &[][..], &TINY_PNG[..4]] {
+        assert!(matches!(
+            Id3Tag::new(&mut encoder).album_art(image),
+            Err(LameError::InvalidInput(_))
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_album_artist_is_a_tpe2_frame() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    assert!(encoder.id3v2_tag()?.is_empty());
+
+    Id3Tag::new(&mut encoder)
+        .title("Short")?
+        .album_artist("Various Artists")?
+        .apply()?;
+    let tag = encoder.id3v2_tag()?;
+    assert_eq!(&tag[..3], b"ID3");
+    assert_eq!(tag.len(), id3v2_len(&tag));
+
+    // 帧 ID 之后是 4 字节大小和 2 字节标志，然后是编码字节和文本
+    let at = tag
+        .windows(4)
+        .position(|w| w == b"TPE2")
+        .expect("TPE2 frame");
+    assert_eq!(&tag[at + 11..at + 26], b"Various Artists");
+    assert!(!tag.windows(4).any(|w| w == b"APIC"));
+
+    // 编码输出以同一个标签开头
+    let (_, mp3) = encode_noise(encoder)?;
+    assert_eq!(&mp3[..tag.len()], &tag[..]);
+    Ok(())
+}
+
+#[test]
+fn test_id3_version_selects_tags() -> lame_sys::Result<()> {
+    use lame_sys::Id3Version;
+
+    let tagged = |configure: fn(Id3Tag<'_>) -> Id3Tag<'_>| -> lame_sys::Result<Vec<u8>> {
+        let mut encoder = LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?
+            .build()?;
+        // 标题超过 30 字节，默认会同时写入 ID3v2
+        let tag = Id3Tag::new(&mut encoder).title("A title that does not fit into ID3v1")?;
+        configure(tag).apply()?;
+        Ok(encode_noise(encoder)?.1)
+    };
+    let has_v2 = |mp3: &[u8]| mp3.starts_with(b"ID3");
+    let has_v1 = |mp3: &[u8]| mp3[mp3.len() - 128..].starts_with(b"TAG");
+
+    let automatic = tagged(|tag| tag)?;
+    assert!(has_v2(&automatic) && has_v1(&automatic));
+
+    let v1_only = tagged(|tag| tag.version(Id3Version::V1Only).pad_v2(64))?;
+    assert!(!has_v2(&v1_only));
+    assert!(has_v1(&v1_only));
+
+    let v2_only = tagged(|tag| tag.version(Id3Version::V2Only).space_v1(true))?;
+    assert!(has_v2(&v2_only));
+    assert!(!has_v1(&v2_only));
+
+    let padded = tagged(|tag| tag.version(Id3Version::Both).pad_v2(256))?;
+    assert!(has_v1(&padded));
+    assert_eq!(id3v2_len(&padded), id3v2_len(&automatic) + 256);
+
+    // 标题能放进 ID3v1 时默认不写 ID3v2，Both 强制写入
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+    Id3Tag::new(&mut encoder)
+        .title("Short")?
+        .version(Id3Version::Both)
+        .space_v1(true)
+        .apply()?;
+    assert!(!encoder.id3v2_tag()?.is_empty());
+    let (_, mp3) = encode_noise(encoder)?;
+    let v1 = &mp3[mp3.len() - 128..];
+    // 标题字段中未用到的字节是空格
+    assert_eq!(&v1[3..8], b"Short");
+    assert!(v1[8..33].iter().all(|&b| b == b' '));
+    Ok(())
+}
+
+#[test]
+fn test_non_ascii_title_is_utf16() -> lame_sys::Result<()> {
+    let title = "夜に駆ける";
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    Id3Tag::new(&mut encoder)
+        .title(title)?
+        .artist("YOASOBI")?
+        .apply()?;
+    let tag = encoder.id3v2_tag()?;
+    assert_eq!(&tag[..3], b"ID3");
+
+    // 编码字节 0x01 表示带 BOM 的 UTF-16，LAME 按小端写入
+    let at = tag
+        .windows(4)
+        .position(|w| w == b"TIT2")
+        .expect("TIT2 frame");
+    assert_eq!(tag[at + 10], 0x01);
+    assert_eq!(&tag[at + 11..at + 13], &[0xFF, 0xFE]);
+    let utf16: Vec<u8> = title.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    assert_eq!(&tag[at + 13..at + 13 + utf16.len()], &utf16[..]);
+
+    // ASCII 字段仍按 Latin-1 写入
+    let at = tag
+        .windows(4)
+        .position(|w| w == b"TPE1")
+        .expect("TPE1 frame");
+    assert_eq!(tag[at + 10], 0x00);
+    assert_eq!(&tag[at + 11..at + 18], b"YOASOBI");
+    Ok(())
+}
+
+#[test]
+fn test_manual_id3_tags() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    assert!(encoder.writes_id3_automatic());
+    assert!(encoder.id3v1_tag()?.is_empty());
+
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .write_id3_automatic(false)?
+        .build()?;
+    assert!(!encoder.writes_id3_automatic());
+    Id3Tag::new(&mut encoder)
+        .title("A title that does not fit into ID3v1")?
+        .artist("Artist")?
+        .apply()?;
+
+    let v2 = encoder.id3v2_tag()?;
+    assert_eq!(&v2[..3], b"ID3");
+    assert_eq!(v2.len(), id3v2_len(&v2));
+    let v1 = encoder.id3v1_tag()?;
+    assert_eq!(v1.len(), 128);
+    assert_eq!(&v1[..3], b"TAG");
+    assert_eq!(&v1[33..39], b"Artist");
+
+    // 标签由调用方写入，LAME 的输出里没有
+    let (_, mp3) = encode_noise(encoder)?;
+    assert!(!mp3.starts_with(b"ID3"));
+    assert!(!mp3[mp3.len() - 128..].starts_with(b"TAG"));
+    Ok(())
+}
+
+#[test]
+fn test_track_and_disc_totals() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    Id3Tag::new(&mut encoder)
+        .title("Track")?
+        .track(3, Some(12))
+        .disc(1, Some(2))?
+        .apply()?;
+    let tag = encoder.id3v2_tag()?;
+
+    // 帧头 10 字节，之后是编码字节和文本
+    let frame_text = |id: &[u8], len: usize| {
+        let at = tag
+            .windows(4)
+            .position(|w| w == id)
+            .unwrap_or_else(|| panic!("{} frame", String::from_utf8_lossy(id)));
+        tag[at + 11..at + 11 + len].to_vec()
+    };
+    assert_eq!(frame_text(b"TRCK", 4), b"3/12");
+    assert_eq!(frame_text(b"TPOS", 3), b"1/2");
+
+    // ID3v1 只保存曲目编号
+    let v1 = encoder.id3v1_tag()?;
+    assert_eq!((v1[125], v1[126]), (0, 3));
+
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    Id3Tag::new(&mut encoder).track(7, None).apply()?;
+    assert!(encoder.id3v2_tag()?.is_empty());
+    assert_eq!(encoder.id3v1_tag()?[126], 7);
+    Ok(())
+}
+
+#[test]
+fn test_strict_v1_rejects_truncation() -> lame_sys::Result<()> {
+    use lame_sys::Id3Version;
+
+    let title = "A forty character title for ID3v1 tests";
+    assert_eq!(title.len(), 40);
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+
+    // 默认只在结果中报告
+    let summary = Id3Tag::new(&mut encoder).title(title)?.apply()?;
+    assert_eq!(summary.truncated_fields, ["title"]);
+    assert_eq!(&encoder.id3v1_tag()?[3..33], &title.as_bytes()[..30]);
+
+    match Id3Tag::new(&mut encoder)
+        .title(title)?
+        .comment("Twenty-nine bytes of comment.")?
+        .track(1, None)
+        .strict_v1(true)
+        .apply()
+    {
+        Err(LameError::InvalidInput(message)) => {
+            assert!(message.contains("title is 40 bytes (limit 30)"));
+            assert!(message.contains("comment is 29 bytes (limit 28)"));
+        }
+        other => panic!("expected InvalidInput, got {:?}", other),
+    }
+    // 标签被丢弃
+    assert!(encoder.id3v1_tag()?.is_empty());
+    assert!(encoder.id3v2_tag()?.is_empty());
+
+    // 放得下的字段和只写 ID3v2 时不受影响
+    Id3Tag::new(&mut encoder)
+        .title("Short")?
+        .strict_v1(true)
+        .apply()?;
+    Id3Tag::new(&mut encoder)
+        .title(title)?
+        .version(Id3Version::V2Only)
+        .strict_v1(true)
+        .apply()?;
+    Ok(())
+}
+
+#[test]
+fn test_id3v2_tag_size_includes_padding() -> lame_sys::Result<()> {
+    use lame_sys::Id3Version;
+
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    assert_eq!(encoder.id3v2_tag_size()?, 0);
+
+    for padding in [0, 1024, 4096] {
+        Id3Tag::new(&mut encoder)
+            .title("Live stream")?
+            .artist("Station")?
+            .version(Id3Version::V2Only)
+            .pad_v2(padding)
+            .apply()?;
+        let tag = encoder.id3v2_tag()?;
+        assert_eq!(encoder.id3v2_tag_size()?, tag.len());
+        assert_eq!(id3v2_len(&tag), tag.len());
+
+        // ID3v2.3 帧头：4 字节 ID、4 字节大端长度、2 字节标志
+        let mut frames = 0;
+        while 10 + frames < tag.len() && tag[10 + frames] != 0 {
+            let at = 10 + frames;
+            let len = u32::from_be_bytes([tag[at + 4], tag[at + 5], tag[at + 6], tag[at + 7]]);
+            frames += 10 + len as usize;
+        }
+        assert!(frames > 0);
+        assert_eq!(tag.len(), 10 + frames + padding);
+        assert!(tag[10 + frames..].iter().all(|&b| b == 0));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_year_and_release_date() -> lame_sys::Result<()> {
+    use lame_sys::Id3Version;
+
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    let frame = |tag: &[u8], id: &[u8]| -> Vec<u8> {
+        let at = tag.windows(4).position(|w| w == id).expect("frame present");
+        let len = u32::from_be_bytes([tag[at + 4], tag[at + 5], tag[at + 6], tag[at + 7]]);
+        // 去掉编码字节
+        tag[at + 11..at + 10 + len as usize].to_vec()
+    };
+
+    Id3Tag::new(&mut encoder)
+        .year(2024)?
+        .version(Id3Version::V2Only)
+        .apply()?;
+    assert_eq!(frame(&encoder.id3v2_tag()?, b"TYER"), b"2024");
+
+    Id3Tag::new(&mut encoder)
+        .release_date(2024, 2, 29)?
+        .version(Id3Version::V2Only)
+        .apply()?;
+    let tag = encoder.id3v2_tag()?;
+    assert_eq!(frame(&tag, b"TYER"), b"2024");
+    assert_eq!(frame(&tag, b"TDAT"), b"2902");
+
+    for year in ["24", "2024-01-01", "0999", "abcd", " 2024", "10000"] {
+        match Id3Tag::new(&mut encoder).year(year) {
+            Err(LameError::InvalidInput(message)) => assert!(message.contains("year")),
+            other => panic!("expected InvalidInput, got {:?}", other.err()),
+        }
+    }
+    assert!(matches!(
+        Id3Tag::new(&mut encoder).year(999u16),
+        Err(LameError::InvalidInput(_))
+    ));
+    for (year, month, day) in [(2023, 2, 29), (2024, 13, 1), (2024, 4, 31), (2024, 1, 0)] {
+        match Id3Tag::new(&mut encoder).release_date(year, month, day) {
+            Err(LameError::InvalidInput(message)) => assert!(message.contains("not a valid date")),
+            other => panic!("expected InvalidInput, got {:?}", other.err()),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_field_sets_any_supported_frame() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    Id3Tag::new(&mut encoder)
+        .field("TCOM", "J. S. Bach")?
+        .apply()?;
+    let tag = encoder.id3v2_tag()?;
+    let at = tag
+        .windows(4)
+        .position(|w| w == b"TCOM")
+        .expect("TCOM frame");
+    assert_eq!(&tag[at + 11..at + 21], b"J. S. Bach");
+
+    for (frame_id, value) in [("tcom", "x"), ("TCO", "x"), ("APIC", "x")] {
+        match Id3Tag::new(&mut encoder).field(frame_id, value) {
+            Err(LameError::InvalidParameter(_)) => {}
+            other => panic!("expected InvalidParameter, got {:?}", other.err()),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_comment_with_language_and_description() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    Id3Tag::new(&mut encoder)
+        .title("Notes")?
+        .comment_with("deu", "Liner notes", "Aufgenommen in Berlin")?
+        .apply()?;
+    let tag = encoder.id3v2_tag()?;
+
+    // COMM 帧：编码字节、3 字节语言、以 0 结尾的描述，然后是正文
+    let at = tag
+        .windows(4)
+        .position(|w| w == b"COMM")
+        .expect("COMM frame");
+    assert_eq!(tag[at + 10], 0x00);
+    assert_eq!(&tag[at + 11..at + 14], b"deu");
+    let body = b"Liner notes\0Aufgenommen in Berlin";
+    assert_eq!(&tag[at + 14..at + 14 + body.len()], body);
+
+    for lang in ["en", "english", "e1g", "日本"] {
+        match Id3Tag::new(&mut encoder).comment_with(lang, "", "text") {
+            Err(LameError::InvalidParameter(message)) => assert!(message.contains("3 ASCII")),
+            other => panic!("expected InvalidParameter, got {:?}", other.err()),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_tags_after_encoding_are_rejected() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .nogap_total(2)?
+        .build()?;
+    let pcm = vec![0i16; 1152 * 2];
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(1152)];
+
+    assert!(!encoder.has_started());
+    Id3Tag::new(&mut encoder).title("Before")?.apply()?;
+    encoder.encode_interleaved(&pcm, &mut mp3_buffer)?;
+    assert!(encoder.has_started());
+
+    let result = Id3Tag::new(&mut encoder).title("After")?.apply();
+    assert!(matches!(result, Err(LameError::InvalidState(_))));
+    // 被拒绝的标签不会留在编码器中
+    assert!(encoder.id3v1_tag()?.is_empty());
+
+    // flush_nogap 之后开始新的一段，可以为它设置标签
+    encoder.flush_nogap(&mut mp3_buffer)?;
+    assert!(!encoder.has_started());
+    Id3Tag::new(&mut encoder).title("Next")?.apply()?;
+
+    encoder.reset()?;
+    assert!(!encoder.has_started());
+    Ok(())
+}
+
+#[test]
+fn test_rejected_encode_does_not_start_encoder() -> lame_sys::Result<()> {
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(1152)];
+
+    let mut stereo = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    // 奇数长度的交错数据和长度不同的左右声道在交给 LAME 之前就被拒绝
+    assert!(stereo
+        .encode_interleaved(&[0i16; 3], &mut mp3_buffer)
+        .is_err());
+    assert!(stereo
+        .encode(&[0i16; 4], &[0i16; 5], &mut mp3_buffer)
+        .is_err());
+    assert!(!stereo.has_started());
+    Id3Tag::new(&mut stereo).title("Still in time")?.apply()?;
+    assert!(!stereo.id3v1_tag()?.is_empty());
+
+    let mut mono = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(1)?
+        .bitrate(64)?
+        .build()?;
+    assert!(mono
+        .encode_interleaved(&[0i16; 4], &mut mp3_buffer)
+        .is_err());
+    assert!(!mono.has_started());
+    Id3Tag::new(&mut mono).title("Still in time")?.apply()?;
+    Ok(())
+}
+
+#[test]
+fn test_set_id3_reports_every_bad_field() -> lame_sys::Result<()> {
+    use lame_sys::Id3Metadata;
+
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    let meta = Id3Metadata {
+        title: Some("Title".to_string()),
+        artist: Some("Bad\0Artist".to_string()),
+        track_total: Some(12),
+        genre: Some("Chiptune".to_string()),
+        album_art: Some(b"not an image".to_vec()),
+        ..Id3Metadata::default()
+    };
+    let message = match encoder.set_id3(&meta) {
+        Err(LameError::InvalidInput(message)) => message,
+        other => panic!("expected InvalidInput, got {:?}", other),
+    };
+    for field in ["artist:", "track_total:", "genre:", "album_art:"] {
+        assert!(message.contains(field), "{}", message);
+    }
+    assert!(!message.contains("title:"));
+    // 出错时不保留任何字段
+    assert!(encoder.id3v1_tag()?.is_empty());
+
+    let meta = Id3Metadata {
+        title: Some("Title".to_string()),
+        album_artist: Some("Various Artists".to_string()),
+        track: Some(3),
+        disc: Some(1),
+        disc_total: Some(2),
+        ..Id3Metadata::default()
+    };
+    encoder.set_id3(&meta)?;
+    let tag = encoder.id3v2_tag()?;
+    for frame in [&b"TIT2"[..], b"TPE2", b"TRCK", b"TPOS"] {
+        assert!(tag.windows(4).any(|w| w == frame));
+    }
+    assert_eq!(encoder.id3v1_tag()?[126], 3);
+    Ok(())
+}
+
+#[test]
+fn test_force_ms_and_ms_fix() -> lame_sys::Result<()> {
+    let joint_stereo = || {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(64)?
+            .mode(ChannelMode::JointStereo)
+    };
+
+    let default = joint_stereo()?.build()?;
+    assert!(!default.forces_ms());
+
+    let encoder = joint_stereo()?.force_ms(true)?.ms_fix(1.5)?.build()?;
+    assert!(encoder.forces_ms());
+    assert_eq!(encoder.ms_fix(), 1.5);
+    let (encoder, mp3) = encode_noise(encoder)?;
+    assert!(encoder.frame_count() > 0);
+    assert!(!mp3.is_empty());
+
+    for msfix in [-1.0, f32::NAN, f32::INFINITY] {
+        assert!(matches!(
+            joint_stereo()?.ms_fix(msfix),
+            Err(LameError::InvalidParameter(name)) if name == "ms_fix"
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_id3_text_rejects_nul_with_field_name() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+
+    let result = Id3Tag::new(&mut encoder).title("Hello\0World");
+    match result {
+        Err(LameError::InvalidParameter(message)) => {
+            assert_eq!(message, "title contains NUL at byte 5")
+        }
+        other => panic!("expected InvalidParameter, got {:?}", other.err()),
+    }
+
+    let expected = "album_artist contains NUL at byte 0";
+    let result = Id3Tag::new(&mut encoder).album_artist("\0");
+    assert!(matches!(result, Err(LameError::InvalidParameter(message)) if message == expected));
+    Ok(())
+}
+
+#[test]
+fn test_id3_text_control_chars_replaced() -> lame_sys::Result<()> {
+    use lame_sys::id3::sanitize_text;
+
+    assert_eq!(sanitize_text("title", "Line1\nLine2")?, "Line1 Line2");
+    assert_eq!(sanitize_text("title", "a\tb\r\x07\x7f")?, "a b   ");
+    assert!(matches!(
+        sanitize_text("title", "Plain 東京 🎵")?,
+        std::borrow::Cow::Borrowed(_)
+    ));
+
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    let summary = Id3Tag::new(&mut encoder)
+        .title("Night 🎵")?
+        .artist("Plain")?
+        .comment("Line1\nLine2")?
+        .apply()?;
+
+    assert_eq!(summary.sanitized_fields, vec!["comment"]);
+    assert_eq!(summary.non_latin1_fields, vec!["title"]);
+
+    // 没有字段超出 ID3v1 限制，只在末尾写入 ID3v1 标签
+    let pcm = vec![0i16; 1152 * 2];
+    let mut mp3 = encoder.encode_interleaved_to_vec(&pcm)?;
+    let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+    let len = encoder.flush(&mut tail)?;
+    mp3.extend_from_slice(&tail[..len]);
+
+    let tag = &mp3[mp3.len() - 128..];
+    assert_eq!(&tag[..3], b"TAG");
+    assert!(tag.windows(11).any(|w| w == b"Line1 Line2"));
+    assert!(tag.windows("🎵".len()).any(|w| w == "🎵".as_bytes()));
+    Ok(())
+}
+
+#[test]
+fn test_interleaved_requires_stereo_encoder() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(1)?
+        .bitrate(128)?
+        .build()?;
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(1152)];
+
+    // 单声道编码器会把交错数据当作一个声道，必须拒绝
+    match encoder.encode_interleaved(&vec![0i16; 1152 * 2], &mut mp3_buffer) {
+        Err(LameError::InvalidInput(msg)) => assert!(msg.contains("2-channel"), "{}", msg),
+        other => panic!("expected InvalidInput, got {:?}", other),
+    }
+    let result = encoder.encode_interleaved_float(&vec![0f32; 1152 * 2], &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+
+    // 单声道输入不受影响
+    encoder.encode_mono(&vec![0i16; 1152], &mut mp3_buffer)?;
+    Ok(())
+}
+
+#[test]
+fn test_inter_channel_ratio() -> lame_sys::Result<()> {
+    let encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .inter_channel_ratio(0.3)?
+        .build()?;
+    assert_eq!(encoder.inter_channel_ratio(), 0.3);
+
+    for ratio in [1.5, -0.1, f32::NAN] {
+        match LameEncoder::builder()?.inter_channel_ratio(ratio) {
+            Err(LameError::InvalidParameter(msg)) => {
+                assert!(msg.contains("inter_channel_ratio"), "{}", msg)
+            }
+            other => panic!(
+                "{}: expected InvalidParameter, got {:?}",
+                ratio,
+                other.err()
+            ),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_nogap_tracks_from_one_encoder() -> lame_sys::Result<()> {
+    let pcm = signal::interleave(
+        &signal::sine(440.0, 44100, signal::duration_of(1152 * 10 + 500, 44100)),
+        2,
+    );
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .nogap_total(2)?
+        .build()?;
+    assert_eq!(encoder.nogap_total(), 2);
+    assert_eq!(encoder.nogap_current_index(), 0);
+    let mut tail = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+
+    // 第一段以 flush_nogap 结束，不足一帧的 PCM 留到第二段
+    let mut first = encoder.encode_interleaved_to_vec(&pcm)?;
+    let first_flush = encoder.flush_nogap(&mut tail)?;
+    assert!(first_flush > 0);
+    first.extend_from_slice(&tail[..first_flush]);
+
+    encoder.set_nogap_current_index(1)?;
+    assert_eq!(encoder.nogap_current_index(), 1);
+    let mut second = encoder.encode_interleaved_to_vec(&pcm)?;
+    let second_flush = encoder.flush(&mut tail)?;
+    assert!(second_flush > 0);
+    second.extend_from_slice(&tail[..second_flush]);
+
+    // 第二段直接以帧头开始，没有新的信息帧或 ID3 标签
+    assert_eq!(second[0], 0xff);
+    assert!(first.len() > second.len());
+
+    for index in [-1, 2] {
+        assert!(matches!(
+            encoder.set_nogap_current_index(index),
+            Err(LameError::InvalidParameter(_))
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_nogap_parameters_are_validated() -> lame_sys::Result<()> {
+    assert!(matches!(
+        LameEncoder::builder()?.nogap_total(0),
+        Err(LameError::InvalidParameter(_))
+    ));
+    assert!(matches!(
+        LameEncoder::builder()?.nogap_current_index(-1),
+        Err(LameError::InvalidParameter(_))
+    ));
+
+    // 序号超出总段数在 build() 时报告
+    let result = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .nogap_total(2)?
+        .nogap_current_index(2)?
+        .build();
+    match result {
+        Err(LameError::InvalidParameter(msg)) => assert!(msg.contains("nogap_total"), "{}", msg),
+        other => panic!("expected InvalidParameter, got {:?}", other.err()),
+    }
+
+    // 未设置总段数时不能切换序号
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .build()?;
+    assert!(matches!(
+        encoder.set_nogap_current_index(0),
+        Err(LameError::InvalidParameter(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_replay_gain_of_loud_sine() -> lame_sys::Result<()> {
+    let pcm = signal::interleave(&signal::sine(440.0, 44100, Duration::from_secs(3)), 2);
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .find_replay_gain(true)?
+        .build()?;
+
+    encoder.encode_interleaved_to_vec(&pcm)?;
+    // 分析结果在 flush 中计算
+    assert_eq!(encoder.replay_gain(), None);
+    let mut tail = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+    encoder.flush(&mut tail)?;
+
+    // -6 dBFS 的正弦波比 ReplayGain 的参考响度大得多，需要降低音量
+    let gain = encoder.replay_gain().expect("gain after flush");
+    assert!((-20.0..-5.0).contains(&gain), "gain {} dB", gain);
+    // 没有开启边编码边解码，没有峰值
+    assert_eq!(encoder.peak_sample(), None);
+
+    // 未开启分析时没有结果
+    let mut plain = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    plain.encode_interleaved_to_vec(&pcm)?;
+    plain.flush(&mut tail)?;
+    assert_eq!(plain.replay_gain(), None);
+    Ok(())
+}
+
+#[test]
+fn test_decode_on_the_fly_needs_decoder() -> lame_sys::Result<()> {
+    // 关闭总是成功
+    LameEncoder::builder()?.decode_on_the_fly(false)?;
+
+    match LameEncoder::builder()?.decode_on_the_fly(true) {
+        // 带解码器的 LAME：flush 之后能读出峰值
+        Ok(builder) => {
+            let mut encoder = builder
+                .sample_rate(44100)?
+                .channels(1)?
+                .bitrate(128)?
+                .build()?;
+            let pcm = signal::sine(440.0, 44100, Duration::from_secs(1));
+            encoder.encode_mono(
+                &pcm,
+                &mut vec![0u8; LameEncoder::mp3_buffer_size(pcm.len())],
+            )?;
+            encoder.flush(&mut vec![0u8; LameEncoder::mp3_buffer_size(0)])?;
+            let peak = encoder.peak_sample().expect("peak after flush");
+            assert!(peak > 0.0 && peak < 32768.0, "peak {}", peak);
+        }
+        Err(LameError::InvalidParameter(msg)) => assert!(msg.contains("decoder"), "{}", msg),
+        Err(other) => panic!("expected InvalidParameter, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_effective_settings_match_configuration() -> lame_sys::Result<()> {
+    let encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .mode(ChannelMode::JointStereo)?
+        .quality(Quality::High)?
+        .build()?;
+
+    assert_eq!(encoder.sample_rate_in(), 44100);
+    assert_eq!(encoder.sample_rate_out(), 44100);
+    assert_eq!(encoder.input_sample_rate(), encoder.sample_rate_in());
+    assert_eq!(encoder.output_sample_rate(), encoder.sample_rate_out());
+    assert_eq!(encoder.channels(), 2);
+    assert_eq!(encoder.bitrate(), 128);
+    assert_eq!(encoder.mode(), ChannelMode::JointStereo);
+    assert_eq!(encoder.quality(), Some(Quality::High));
+    assert_eq!(encoder.quality_level(), 2);
+    assert_eq!(encoder.vbr_mode(), VbrMode::Off);
+    Ok(())
+}
+
+#[test]
+fn test_effective_settings_reflect_lame_adjustments() -> lame_sys::Result<()> {
+    // 32 kbps 的立体声装不下 44.1 kHz，LAME 自动降低输出采样率；
+    // 未设置的声道模式和质量由 LAME 选择
+    let encoder = LameEncoder::builder()?
+        .input_sample_rate(44100)?
+        .channels(2)?
+        .bitrate(32)?
+        .build()?;
+    assert_eq!(encoder.sample_rate_in(), 44100);
+    assert!(
+        encoder.sample_rate_out() < 44100,
+        "output {} Hz",
+        encoder.sample_rate_out()
+    );
+    assert_eq!(encoder.bitrate(), 32);
+    assert_eq!(encoder.mode(), ChannelMode::JointStereo);
+    assert_eq!(encoder.quality_level(), 3);
+    assert_eq!(encoder.quality(), None);
+
+    // 单声道输入总是编码为单声道，忽略请求的模式
+    let encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(1)?
+        .bitrate(64)?
+        .mode(ChannelMode::Stereo)?
+        .build()?;
+    assert_eq!(encoder.mode(), ChannelMode::Mono);
+
+    let encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .vbr_mode(VbrMode::Vbr)?
+        .vbr_quality(2)?
+        .build()?;
+    assert_eq!(encoder.vbr_mode(), VbrMode::Vbr);
+    Ok(())
+}
+
+#[test]
+fn test_frame_size_and_buffer_capacity() -> lame_sys::Result<()> {
+    let encoder = |rate: i32| {
+        LameEncoder::builder()?
+            .sample_rate(rate)?
+            .channels(2)?
+            .bitrate(64)?
+            .build()
+    };
+
+    // MPEG-1 每帧 1152 个样本，MPEG-2 为 576
+    let mpeg1 = encoder(44100)?;
+    assert_eq!(mpeg1.frame_size(), 1152);
+    assert_eq!(encoder(16000)?.frame_size(), 576);
+
+    let buffer_len = LameEncoder::mp3_buffer_size(0);
+    let samples = mpeg1.max_samples_for_buffer(buffer_len);
+    assert!(samples > 0);
+    assert!(mpeg1.max_samples_for_buffer(buffer_len * 2) > samples);
+    assert_eq!(mpeg1.max_samples_for_buffer(0), 0);
+    Ok(())
+}
+
+#[test]
+fn test_frame_count_grows_across_encode_calls() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    // 没有设置 total_samples，无法估计总帧数
+    assert_eq!(encoder.estimated_total_frames(), None);
+
+    let pcm = signal::interleave(
+        &signal::sine(440.0, 44100, signal::duration_of(1152 * 3, 44100)),
+        2,
+    );
+    let mut previous = encoder.frame_count();
+    assert_eq!(previous, 0);
+    for _ in 0..10 {
+        encoder.encode_interleaved_to_vec(&pcm)?;
+        let frames = encoder.frame_count();
+        assert!(frames > previous, "{} after {}", frames, previous);
+        assert_eq!(encoder.frames_encoded() as usize, frames);
+        previous = frames;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_vbr_histograms() -> lame_sys::Result<()> {
+    const FRAMES: usize = 100;
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .mode(ChannelMode::JointStereo)?
+        .vbr_mode(VbrMode::Vbr)?
+        .vbr_quality(2)?
+        .build()?;
+
+    // 静音、正弦波和噪声交替，让 VBR 选出不同的比特率
+    let chunk = signal::duration_of(1152 * 10, 44100);
+    let mut tail = vec![0u8; lame_sys::max_mp3_buffer_size(0)];
+    for i in 0..FRAMES / 10 {
+        let mono = match i % 3 {
+            0 => signal::silence(44100, chunk),
+            1 => signal::sine(440.0 * (i + 1) as f64, 44100, chunk),
+            _ => signal::white_noise(44100, chunk, i as u64),
+        };
+        encoder.encode_interleaved_to_vec(&signal::interleave(&mono, 2))?;
+    }
+    encoder.flush(&mut tail)?;
+
+    let frames = encoder.frame_count();
+    assert!(frames >= FRAMES, "{} frames", frames);
+
+    let histogram = encoder.bitrate_histogram();
+    assert_eq!(histogram.len(), 14);
+    assert_eq!(
+        histogram.iter().map(|&(kbps, _)| kbps).collect::<Vec<_>>(),
+        [32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320]
+    );
+    assert_eq!(
+        histogram.iter().map(|&(_, count)| count).sum::<i32>(),
+        frames as i32
+    );
+    assert!(histogram.iter().filter(|&&(_, count)| count > 0).count() > 1);
+
+    let stereo = encoder.stereo_mode_histogram();
+    assert_eq!(stereo.iter().sum::<usize>(), frames);
+    // 不支持强度立体声
+    assert_eq!((stereo[1], stereo[3]), (0, 0));
+    Ok(())
+}
+
+#[test]
+fn test_lametag_frame_after_flush() -> lame_sys::Result<()> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()?;
+    let pcm = signal::interleave(
+        &signal::sine(440.0, 44100, signal::duration_of(1152 * 20, 44100)),
+        2,
+    );
+    let mut mp3 = encoder.encode_interleaved_to_vec(&pcm)?;
+    let mut tail = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+    let len = encoder.flush(&mut tail)?;
+    mp3.extend_from_slice(&tail[..len]);
+
+    let mut frame = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+    let size = encoder.lametag_frame_into(&mut frame)?;
+    let frame = &frame[..size];
+    assert_eq!(frame, encoder.lametag_frame()?.as_slice());
+
+    // 帧同步字，CBR 的标识为 "Info"
+    assert_eq!(frame[0], 0xff);
+    assert_eq!(frame[1] & 0xe0, 0xe0);
+    assert!(frame.windows(4).any(|w| w == b"Info"));
+    // 与流开头的占位帧一样长，可以原地覆盖
+    assert_eq!(&mp3[..4], &frame[..4]);
+    assert!(mp3[4..size].iter().all(|&b| b == 0));
+
+    match encoder.lametag_frame_into(&mut [0u8; 16]) {
+        Err(LameError::InvalidInput(msg)) => {
+            assert!(msg.contains(&size.to_string()), "{}", msg)
+        }
+        other => panic!("expected InvalidInput, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_config_summary_describes_the_encoder() -> lame_sys::Result<()> {
+    let encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(192)?
+        .quality(Quality::Standard)?
+        .build()?;
+    let summary = encoder.config_summary();
+    println!("{}", summary);
+
+    // lame_print_config、"Encoding as" 一行和 lame_print_internals 三部分
+    assert!(summary.starts_with("LAME "), "{}", summary);
+    assert!(
+        summary.contains("Encoding as 44.1 kHz j-stereo MPEG-1 Layer III 192 kbps qval=5"),
+        "{}",
+        summary
+    );
+    assert!(summary.contains("constant bitrate - CBR"), "{}", summary);
+
+    let vbr = LameEncoder::builder()?
+        .sample_rate(22050)?
+        .channels(1)?
+        .vbr_mode(VbrMode::Vbr)?
+        .vbr_quality(4)?
+        .build()?;
+    let summary = vbr.config_summary();
+    assert!(
+        summary.contains("22.05 kHz single-ch MPEG-2 Layer III VBR(q=4)"),
+        "{}",
+        summary
+    );
+    Ok(())
+}
+
+#[test]
+fn test_calls_after_flush_are_rejected() -> lame_sys::Result<()> {
+    let new_encoder = || {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?
+            .build()
+    };
+    let pcm = vec![0i16; 1152 * 2];
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(1152)];
+
+    let mut encoder = new_encoder()?;
+    encoder.encode_interleaved(&pcm, &mut mp3_buffer)?;
+    encoder.flush(&mut mp3_buffer)?;
+    assert_eq!(
+        encoder.encode_interleaved(&pcm, &mut mp3_buffer),
+        Err(LameError::InvalidState("encode called after flush"))
+    );
+    assert_eq!(
+        encoder.flush(&mut mp3_buffer),
+        Err(LameError::InvalidState("flush called twice"))
+    );
+    assert_eq!(
+        encoder.flush_nogap(&mut mp3_buffer),
+        Err(LameError::InvalidState("flush_nogap called after flush"))
+    );
+    assert!(matches!(
+        encoder.finalize(),
+        Err(LameError::InvalidState(_))
+    ));
+
+    // reset 之后可以重新编码
+    let mut encoder = new_encoder()?;
+    encoder.flush(&mut mp3_buffer)?;
+    encoder.reset()?;
+    encoder.encode_interleaved(&pcm, &mut mp3_buffer)?;
+    encoder.flush(&mut mp3_buffer)?;
+
+    // flush_nogap 之后可以继续编码、再次无缝刷新，最后正常刷新
+    let mut encoder = new_encoder()?;
+    encoder.encode_interleaved(&pcm, &mut mp3_buffer)?;
+    encoder.flush_nogap(&mut mp3_buffer)?;
+    encoder.flush_nogap(&mut mp3_buffer)?;
+    encoder.encode_interleaved(&pcm, &mut mp3_buffer)?;
+    encoder.flush(&mut mp3_buffer)?;
+    Ok(())
+}
+
+#[test]
+fn test_finalize_returns_trailing_bytes_and_lametag() -> lame_sys::Result<()> {
+    let pcm = signal::interleave(
+        &signal::sine(440.0, 44100, signal::duration_of(1152 * 20, 44100)),
+        2,
+    );
+    let new_encoder = |write_vbr_tag: bool| -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .bitrate(128)?
+            .write_vbr_tag(write_vbr_tag)?
+            .build()
+    };
+
+    // 与 flush 加各个查询方法的结果一致
+    let mut expected = new_encoder(true)?;
+    let mut expected_mp3 = expected.encode_interleaved_to_vec(&pcm)?;
+    let mut tail = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+    let len = expected.flush(&mut tail)?;
+    expected_mp3.extend_from_slice(&tail[..len]);
+
+    let mut encoder = new_encoder(true)?;
+    let mut mp3 = encoder.encode_interleaved_to_vec(&pcm)?;
+    let stream = encoder.finalize()?;
+    mp3.extend_from_slice(&stream.trailing);
+    assert_eq!(mp3, expected_mp3);
+    assert_eq!(stream.lametag, Some(expected.lametag_frame()?));
+    assert_eq!(stream.encoder_delay as usize, expected.encoder_delay());
+    assert_eq!(stream.encoder_padding as usize, expected.end_padding());
+    assert!(stream.encoder_padding > 0);
+
+    // 信息帧覆盖输出的第一帧
+    let lametag = stream.lametag.expect("lametag");
+    mp3[..lametag.len()].copy_from_slice(&lametag);
+    assert!(mp3.windows(4).any(|w| w == b"Info"));
+
+    let mut encoder = new_encoder(false)?;
+    encoder.encode_interleaved_to_vec(&pcm)?;
+    assert_eq!(encoder.finalize()?.lametag, None);
+    Ok(())
+}
+
+#[test]
+fn test_reset_starts_an_independent_stream() -> lame_sys::Result<()> {
+    let pcm = signal::interleave(
+        &signal::sine(440.0, 44100, signal::duration_of(1152 * 20, 44100)),
+        2,
+    );
+    // 覆盖各种参数类型：整数、枚举、浮点、样本数和 ms_fix
+    let new_encoder = || -> lame_sys::Result<LameEncoder> {
+        LameEncoder::builder()?
+            .sample_rate(44100)?
+            .channels(2)?
+            .vbr_mode(VbrMode::Vbr)?
+            .vbr_quality(3)?
+            .mode(ChannelMode::JointStereo)?
+            .scale(0.8)?
+            .ms_fix(1.5)?
+            .total_samples(pcm.len() as u64 / 2)?
+            .build()
+    };
+    let encode = |encoder: &mut LameEncoder, pcm: &[i16]| -> lame_sys::Result<Vec<u8>> {
+        let mut mp3 = encoder.encode_interleaved_to_vec(pcm)?;
+        let mut tail = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+        let len = encoder.flush(&mut tail)?;
+        mp3.extend_from_slice(&tail[..len]);
+        Ok(mp3)
+    };
+
+    let expected = encode(&mut new_encoder()?, &pcm)?;
+    let mut encoder = new_encoder()?;
+    encoder.set_max_samples_per_call(Some(1152))?;
+    let handles = lame_sys::live_handles();
+
+    let first = encode(&mut encoder, &pcm)?;
+    let frames = encoder.frame_count();
+    encoder.reset()?;
+    assert_eq!(encoder.frame_count(), 0);
+    // 旧句柄已经关闭，封装层的设置保留
+    assert_eq!(lame_sys::live_handles(), handles);
+    assert_eq!(encoder.max_samples_per_call(), Some(1152));
+
+    let second = encode(&mut encoder, &pcm)?;
+    assert_eq!(first, expected);
+    assert_eq!(second, expected);
+    assert_eq!(encoder.frame_count(), frames);
+
+    // 重置会丢弃尚未刷新的数据
+    encoder.reset()?;
+    encoder.encode_interleaved_to_vec(&pcm[..4000])?;
+    encoder.reset()?;
+    assert_eq!(encode(&mut encoder, &pcm)?, expected);
+    Ok(())
+}
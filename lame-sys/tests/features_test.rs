@@ -0,0 +1,44 @@
+use lame_sys::features::{build_info, Feature};
+use lame_sys::get_lame_version;
+
+#[test]
+fn test_vendored_lame_has_all_features() {
+    let info = build_info();
+    println!(
+        "LAME {} (header {}), features: {:?}",
+        info.lame_version, info.header_version, info.features
+    );
+
+    assert_eq!(info.header_version, "3.101");
+    for feature in Feature::ALL {
+        assert!(feature.is_available(), "{} missing", feature.cfg_name());
+        assert!(info.has(feature));
+        assert!(feature.require().is_ok());
+    }
+}
+
+#[test]
+fn test_feature_cfg_names_are_unique() {
+    let mut names: Vec<_> = Feature::ALL.iter().map(|f| f.cfg_name()).collect();
+    names.sort_unstable();
+    names.dedup();
+    assert_eq!(names.len(), Feature::ALL.len());
+    assert!(names.iter().all(|name| name.starts_with("lame_has_")));
+}
+
+#[test]
+fn test_numeric_version_matches_vendored_lame() {
+    let version = lame_sys::version();
+    println!("{:?}", version);
+
+    // 内置的是 3.101 beta 3
+    assert_eq!((version.major, version.minor), (3, 101));
+    assert_eq!((version.alpha, version.beta), (0, 3));
+    assert_eq!((version.psy_major, version.psy_minor), (1, 0));
+    assert!(get_lame_version().starts_with(&format!("{}.{}", version.major, version.minor)));
+
+    assert!(version.is_at_least(3, 100));
+    assert!(version.is_at_least(3, 101));
+    assert!(!version.is_at_least(3, 102));
+    assert!(!version.is_at_least(4, 0));
+}
@@ -0,0 +1,81 @@
+use lame_sys::gapless::GaplessSession;
+use lame_sys::pcm::InterleavedPcm;
+use lame_sys::prelude::*;
+use lame_sys::signal;
+use std::time::Duration;
+
+const TRACKS: usize = 3;
+
+fn session(nogap_total: Option<i32>) -> Result<GaplessSession> {
+    let mut builder = LameEncoder::builder()?;
+    builder
+        .set_sample_rate(44100)?
+        .set_channels(2)?
+        .set_bitrate(128)?;
+    if let Some(total) = nogap_total {
+        builder.set_nogap_total(total)?;
+    }
+    Ok(GaplessSession::new(builder.build()?))
+}
+
+#[test]
+fn test_session_splits_a_sweep_into_tracks() -> Result<()> {
+    // 一段连续的扫频，切成长度不是整帧的三段
+    let sweep = signal::sweep(100.0, 8000.0, 44100, Duration::from_secs(3));
+    let pcm = signal::interleave(&sweep, 2);
+    let part = pcm.len() / TRACKS / 2 * 2;
+
+    let mut session = session(Some(TRACKS as i32))?;
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(part / 2)];
+    let mut tracks = vec![Vec::new(); TRACKS];
+
+    for (index, chunk) in pcm.chunks(part).take(TRACKS).enumerate() {
+        assert_eq!(session.track(), index);
+        assert_eq!(session.encoder().nogap_current_index(), index as i32);
+        let len = session.encode(InterleavedPcm(chunk), &mut mp3_buffer)?;
+        tracks[index].extend_from_slice(&mp3_buffer[..len]);
+        if index + 1 < TRACKS {
+            tracks[index].extend(session.next_track()?);
+        }
+    }
+
+    // 最后一段不能再切换
+    assert!(matches!(
+        session.next_track(),
+        Err(LameError::InvalidInput(_))
+    ));
+    assert_eq!(session.track(), TRACKS - 1);
+
+    let stream = session.finish()?;
+    tracks[TRACKS - 1].extend(stream.trailing);
+
+    for (index, track) in tracks.iter().enumerate() {
+        assert!(!track.is_empty(), "track {} is empty", index);
+        assert_eq!(
+            track[0], 0xff,
+            "track {} does not start with a frame",
+            index
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_session_without_nogap_total() -> Result<()> {
+    let pcm = signal::interleave(
+        &signal::sine(440.0, 44100, signal::duration_of(1152 * 10, 44100)),
+        2,
+    );
+    let mut session = session(None)?;
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(pcm.len() / 2)];
+
+    // 段数不受限制，序号只在会话中维护
+    for index in 0..TRACKS {
+        assert_eq!(session.track(), index);
+        session.encode(InterleavedPcm(&pcm), &mut mp3_buffer)?;
+        assert!(!session.next_track()?.is_empty());
+    }
+    assert_eq!(session.encoder().nogap_current_index(), 0);
+    session.finish()?;
+    Ok(())
+}
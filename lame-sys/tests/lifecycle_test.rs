@@ -0,0 +1,106 @@
+//! 初始化、使用、关闭、再初始化的循环不泄漏句柄和内存
+//!
+//! 用计数的全局分配器统计 Rust 侧的净分配字节数。LAME 内部用 C 的 `malloc`，
+//! 由 [`live_handles`] 确认每个句柄都经过了 `lame_close`。
+
+use lame_sys::prelude::*;
+use lame_sys::{live_handles, shutdown};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// 只统计当前线程在 [`net_allocated`] 内的分配，测试框架其他线程的分配不计入
+struct CountingAllocator;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static NET_BYTES: Cell<isize> = const { Cell::new(0) };
+}
+
+fn record(delta: isize) {
+    // 线程退出时 TLS 可能已经销毁
+    let _ = COUNTING.try_with(|counting| {
+        if counting.get() {
+            NET_BYTES.with(|bytes| bytes.set(bytes.get() + delta));
+        }
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size() as isize);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size() as isize);
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size as isize - layout.size() as isize);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(-(layout.size() as isize));
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// 执行 `f`，返回期间当前线程分配后未释放的字节数
+fn net_allocated(f: impl FnOnce()) -> isize {
+    NET_BYTES.with(|bytes| bytes.set(0));
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    NET_BYTES.with(Cell::get)
+}
+
+/// 一次完整的使用：构建、写标签、编码、收尾，然后释放编码器
+fn encode_once() -> Result<Vec<u8>> {
+    let mut encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .quality(Quality::Fast)?
+        .build()?;
+    assert_eq!(live_handles(), 1);
+
+    Id3Tag::new(&mut encoder).title("Lifecycle")?.apply()?;
+
+    let pcm: Vec<i16> = (0..44100 * 2).map(|i| (i % 200) as i16 * 100).collect();
+    let mut buffer = Mp3Buffer::new(encoder);
+    buffer.write_pcm(&pcm)?;
+    let mp3 = buffer.finish()?;
+    assert!(!mp3.is_empty());
+    Ok(mp3)
+}
+
+#[test]
+fn test_shutdown_and_reinitialize() -> Result<()> {
+    for cycle in 0..3 {
+        let mut result = Ok(());
+        let leaked = net_allocated(|| {
+            result = encode_once().map(drop).and_then(|_| shutdown());
+        });
+        result?;
+        assert_eq!(live_handles(), 0, "cycle {}", cycle);
+        assert_eq!(leaked, 0, "cycle {} leaked {} bytes", cycle, leaked);
+    }
+
+    // 还有句柄存活时 shutdown 报错，释放后恢复正常
+    let builder = LameEncoder::builder()?;
+    let encoder = LameEncoder::builder()?.build()?;
+    match shutdown() {
+        Err(LameError::InvalidInput(msg)) => assert!(msg.contains('2'), "{}", msg),
+        other => panic!("expected InvalidInput, got {:?}", other),
+    }
+    drop(builder);
+    drop(encoder);
+    shutdown()?;
+    encode_once()?;
+    Ok(())
+}
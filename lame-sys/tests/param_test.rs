@@ -0,0 +1,74 @@
+use lame_sys::prelude::*;
+use lame_sys::{RawParam, RawValue};
+
+fn builder() -> Result<EncoderBuilder> {
+    LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)
+}
+
+#[test]
+fn test_raw_params_round_trip() -> Result<()> {
+    let encoder = builder()?
+        .set_raw(RawParam::QuantComp, RawValue::Int(3))?
+        .set_raw(RawParam::InterChRatio, RawValue::Float(0.25))?
+        // 浮点参数也接受整数
+        .set_raw(RawParam::AthLower, RawValue::Int(2))?
+        .build()?;
+
+    assert_eq!(encoder.get_raw(RawParam::QuantComp), RawValue::Int(3));
+    assert_eq!(
+        encoder.get_raw(RawParam::InterChRatio),
+        RawValue::Float(0.25)
+    );
+    assert_eq!(encoder.get_raw(RawParam::AthLower), RawValue::Float(2.0));
+
+    // 每个参数都能读取，类型与声明一致
+    for param in RawParam::ALL {
+        assert_eq!(encoder.get_raw(param).as_int().is_none(), param.is_float());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_raw_param_names() -> Result<()> {
+    for param in RawParam::ALL {
+        assert_eq!(RawParam::from_name(param.name())?, param);
+    }
+    assert_eq!(RawParam::InterChRatio.to_string(), "interChRatio");
+
+    match RawParam::from_name("no_such_param") {
+        Err(LameError::InvalidParameter(msg)) => assert!(msg.contains("no_such_param")),
+        other => panic!("expected InvalidParameter, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rejected_raw_values_name_the_param() -> Result<()> {
+    let cases = [
+        // LAME 只接受 0 或 1
+        (RawParam::ErrorProtection, RawValue::Int(2)),
+        // 超出 0-1
+        (RawParam::InterChRatio, RawValue::Float(1.5)),
+        (RawParam::Scale, RawValue::Float(f32::NAN)),
+        // 类型不符
+        (RawParam::QuantComp, RawValue::Float(1.0)),
+    ];
+
+    for (param, value) in cases {
+        match builder()?.set_raw(param, value) {
+            Err(LameError::InvalidParameter(msg)) => {
+                assert!(msg.contains(param.name()), "{}: {}", param, msg)
+            }
+            other => panic!(
+                "{} = {:?}: expected InvalidParameter, got {:?}",
+                param,
+                value,
+                other.err()
+            ),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use lame_sys::pcm::{DualPcm, InterleavedPcm, MonoPcm};
+use lame_sys::prelude::*;
+
+const NUM_SAMPLES: usize = 1152 * 10;
+
+fn encoder(channels: i32) -> Result<LameEncoder> {
+    LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(channels)?
+        .bitrate(128)?
+        .build()
+}
+
+/// 440 Hz 正弦波，范围 [-0.5, 0.5]
+fn sine() -> Vec<f64> {
+    (0..NUM_SAMPLES)
+        .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / 44100.0).sin() * 0.5)
+        .collect()
+}
+
+fn interleave<T: Copy>(samples: &[T]) -> Vec<T> {
+    samples.iter().flat_map(|&s| [s, s]).collect()
+}
+
+/// 按三种声道布局分别编码同一份样本，返回各自的输出长度（含 flush）
+fn encode_all_layouts<T: lame_sys::pcm::PcmSample>(samples: &[T]) -> Result<[usize; 3]> {
+    let mut mp3_buffer = vec![0u8; 1024 * 1024];
+    let interleaved = interleave(samples);
+
+    let mut mono = encoder(1)?;
+    let mut mono_bytes = mono.encode_pcm(MonoPcm(samples), &mut mp3_buffer)?;
+    mono_bytes += mono.flush(&mut mp3_buffer)?;
+
+    let mut dual = encoder(2)?;
+    let mut dual_bytes = dual.encode_pcm(DualPcm::new(samples, samples), &mut mp3_buffer)?;
+    dual_bytes += dual.flush(&mut mp3_buffer)?;
+
+    let mut inter = encoder(2)?;
+    let mut interleaved_bytes = inter.encode_pcm(InterleavedPcm(&interleaved), &mut mp3_buffer)?;
+    interleaved_bytes += inter.flush(&mut mp3_buffer)?;
+
+    Ok([mono_bytes, dual_bytes, interleaved_bytes])
+}
+
+#[test]
+fn test_encode_pcm_all_sample_types() -> Result<()> {
+    let signal = sine();
+    let samples_i16: Vec<i16> = signal.iter().map(|&s| (s * 32767.0) as i16).collect();
+    let samples_i32: Vec<i32> = samples_i16.iter().map(|&s| (s as i32) << 16).collect();
+    let samples_f32: Vec<f32> = signal.iter().map(|&s| s as f32).collect();
+
+    let results = [
+        ("i16", encode_all_layouts(&samples_i16)?),
+        ("i32", encode_all_layouts(&samples_i32)?),
+        ("f32", encode_all_layouts(&samples_f32)?),
+        ("f64", encode_all_layouts(&signal)?),
+    ];
+
+    for (name, [mono, dual, interleaved]) in results {
+        println!(
+            "{}: mono {} bytes, dual {} bytes, interleaved {} bytes",
+            name, mono, dual, interleaved
+        );
+        assert!(mono > 0, "{} mono produced no output", name);
+        assert!(dual > 0, "{} dual produced no output", name);
+        assert_eq!(dual, interleaved, "{} dual/interleaved mismatch", name);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_encode_pcm_matches_concrete_methods() -> Result<()> {
+    let samples: Vec<i16> = sine().iter().map(|&s| (s * 32767.0) as i16).collect();
+    let mut expected = vec![0u8; 1024 * 1024];
+    let mut actual = vec![0u8; 1024 * 1024];
+
+    let expected_len = encoder(1)?.encode_mono(&samples, &mut expected)?;
+    let actual_len = encoder(1)?.encode_pcm(MonoPcm(&samples), &mut actual)?;
+    assert_eq!(&expected[..expected_len], &actual[..actual_len]);
+
+    let expected_len = encoder(2)?.encode(&samples, &samples, &mut expected)?;
+    let actual_len = encoder(2)?.encode_pcm(DualPcm::new(&samples, &samples), &mut actual)?;
+    assert_eq!(&expected[..expected_len], &actual[..actual_len]);
+    Ok(())
+}
+
+#[test]
+fn test_encode_pcm_invalid_input() -> Result<()> {
+    let mut encoder = encoder(2)?;
+    let mut mp3_buffer = vec![0u8; 8192];
+
+    let left = vec![0.0f32; 1152];
+    let right = vec![0.0f32; 100];
+    let result = encoder.encode_pcm(DualPcm::new(&left, &right), &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+
+    let odd = vec![0i32; 1153];
+    let result = encoder.encode_pcm(InterleavedPcm(&odd), &mut mp3_buffer);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+    Ok(())
+}
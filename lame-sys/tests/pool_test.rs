@@ -0,0 +1,130 @@
+use lame_sys::pool::EncoderPool;
+use lame_sys::prelude::*;
+use lame_sys::signal;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn test_config() -> EncoderConfig {
+    EncoderConfig {
+        sample_rate: 44100,
+        channels: 2,
+        bitrate: Some(128),
+        ..EncoderConfig::default()
+    }
+}
+
+/// 生成一帧交错立体声正弦波
+fn sine_frame() -> Vec<i16> {
+    signal::interleave(&signal::sine(440.0, 44100, signal::duration_of(1152, 44100)), 2)
+}
+
+/// 编码前 `frames` 帧并返回输出（不刷新）
+fn encode_frames(encoder: &mut LameEncoder, frames: usize) -> Result<Vec<u8>> {
+    let pcm = sine_frame();
+    let mut mp3_buffer = vec![0u8; 8192];
+    let mut output = Vec::new();
+    for _ in 0..frames {
+        let bytes = encoder.encode_interleaved(&pcm, &mut mp3_buffer)?;
+        output.extend_from_slice(&mp3_buffer[..bytes]);
+    }
+    Ok(output)
+}
+
+#[test]
+fn test_pool_reuses_encoders() -> Result<()> {
+    let pool = EncoderPool::new(test_config(), 2);
+    assert_eq!(pool.idle_count(), 0);
+
+    {
+        let _a = pool.acquire()?;
+        let _b = pool.acquire()?;
+        assert_eq!(pool.idle_count(), 0);
+    }
+
+    assert_eq!(pool.idle_count(), 2);
+    let _c = pool.acquire()?;
+    assert_eq!(pool.idle_count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_pool_resets_between_uses() -> Result<()> {
+    let pool = EncoderPool::new(test_config(), 1);
+
+    // 全新编码器的参考输出
+    let mut reference_encoder = test_config().build()?;
+    let reference = encode_frames(&mut reference_encoder, 20)?;
+    assert!(!reference.is_empty());
+
+    // 第一次使用：写入 ID3 标签并编码，但不刷新
+    {
+        let mut encoder = pool.acquire()?;
+        Id3Tag::new(&mut encoder).title("Leaked Title")?.apply()?;
+        encode_frames(&mut encoder, 5)?;
+    }
+
+    // 第二次使用：不应残留上次的标签或比特流状态
+    let mut encoder = pool.acquire()?;
+    let output = encode_frames(&mut encoder, 20)?;
+    assert_eq!(output, reference);
+    Ok(())
+}
+
+#[test]
+fn test_pool_multithreaded() {
+    let pool = EncoderPool::new(test_config(), 3);
+    let in_use = Arc::new(Mutex::new(HashSet::new()));
+
+    let mut mp3_buffer = vec![0u8; 8192];
+    let mut reference_encoder = test_config().build().unwrap();
+    let mut reference = encode_frames(&mut reference_encoder, 10).unwrap();
+    let bytes = reference_encoder.flush(&mut mp3_buffer).unwrap();
+    reference.extend_from_slice(&mp3_buffer[..bytes]);
+    let reference = Arc::new(reference);
+
+    let handles: Vec<_> = (0..8)
+        .map(|worker| {
+            let pool = pool.clone();
+            let in_use = Arc::clone(&in_use);
+            let reference = Arc::clone(&reference);
+
+            thread::spawn(move || {
+                for round in 0..10 {
+                    let mut encoder = pool.acquire().expect("Failed to acquire encoder");
+                    // SAFETY: 指针只用作编码器的标识，不会解引用
+                    let id = unsafe { encoder.as_ptr() } as usize;
+
+                    // 同一个编码器不能同时被两个持有者使用
+                    assert!(
+                        in_use.lock().unwrap().insert(id),
+                        "encoder used concurrently"
+                    );
+
+                    if (worker + round) % 3 == 0 {
+                        Id3Tag::new(&mut encoder)
+                            .title("Per-request title")
+                            .unwrap()
+                            .apply()
+                            .unwrap();
+                        encode_frames(&mut encoder, 2).unwrap();
+                    } else {
+                        let mut mp3_buffer = vec![0u8; 8192];
+                        let mut output = encode_frames(&mut encoder, 10).unwrap();
+                        let bytes = encoder.flush(&mut mp3_buffer).unwrap();
+                        output.extend_from_slice(&mp3_buffer[..bytes]);
+                        assert_eq!(output, *reference, "state leaked between requests");
+                    }
+
+                    assert!(in_use.lock().unwrap().remove(&id));
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert!(pool.idle_count() <= pool.max_size());
+}
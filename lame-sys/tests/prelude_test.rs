@@ -0,0 +1,31 @@
+// prelude 导出清单（手动维护）
+//
+// 逐个导入 prelude 的全部条目，删除或改名任何条目都会让这个测试无法编译，
+// 提醒这是破坏性变更。新增导出时在这里追加。
+use lame_sys::prelude::{
+    max_mp3_buffer_size, ChannelMode, Emphasis, EncoderBuilder, EncoderConfig, FloatScale,
+    Id3Metadata, Id3Tag, Id3Version, LameEncoder, LameError, Mp3Buffer, Mp3Writer, Preset, Quality,
+    Result, ShortBlockMode, StreamingEncoder, VbrMode,
+};
+
+#[test]
+fn test_prelude_items_resolve() {
+    // 仅用于编译期检查：清单中的条目都是预期的类型和函数
+    fn _encoder_types(_: &LameEncoder, _: &EncoderBuilder, _: &Id3Tag<'_>) {}
+    fn _id3_types(_: &Id3Metadata, _: Id3Version) {}
+    fn _writer_types(_: &Mp3Writer<Vec<u8>>, _: &Mp3Buffer, _: &StreamingEncoder<Vec<u8>>) {}
+    fn _error_types(_: &LameError) -> Result<()> {
+        Ok(())
+    }
+
+    let _ = EncoderConfig::default();
+    let _ = Quality::Standard;
+    let _ = VbrMode::Off;
+    let _ = ChannelMode::JointStereo;
+    let _ = Preset::V2;
+    let _ = ShortBlockMode::Allowed;
+    let _ = Emphasis::None;
+    let _ = LameError::NullPointer;
+    let _ = FloatScale::Normalized;
+    let _ = max_mp3_buffer_size(1152);
+}
@@ -0,0 +1,72 @@
+use lame_sys::prelude::*;
+use lame_sys::signal;
+
+/// 一帧交错立体声正弦波
+fn sine_frame() -> Vec<i16> {
+    signal::interleave(&signal::sine(440.0, 44100, signal::duration_of(1152, 44100)), 2)
+}
+
+fn encoder() -> Result<LameEncoder> {
+    LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .build()
+}
+
+#[test]
+fn test_raw_round_trip_keeps_stream() -> Result<()> {
+    let pcm = sine_frame();
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(1152)];
+
+    // 不经过 into_raw 的参照输出
+    let mut expected = Vec::new();
+    let mut reference = encoder()?;
+    for _ in 0..20 {
+        let len = reference.encode_interleaved(&pcm, &mut mp3_buffer)?;
+        expected.extend_from_slice(&mp3_buffer[..len]);
+    }
+    let len = reference.flush(&mut mp3_buffer)?;
+    expected.extend_from_slice(&mp3_buffer[..len]);
+
+    let mut actual = Vec::new();
+    let mut encoder = encoder()?;
+    for _ in 0..10 {
+        let len = encoder.encode_interleaved(&pcm, &mut mp3_buffer)?;
+        actual.extend_from_slice(&mp3_buffer[..len]);
+    }
+    let frames_before = encoder.frame_count();
+
+    let raw: *mut lame_sys::lame_global_flags = encoder.into_raw();
+    assert!(!raw.is_null());
+    // SAFETY: raw 来自 into_raw，只取回一次
+    let mut encoder = unsafe { LameEncoder::from_raw(raw) };
+
+    // LAME 句柄内的状态保留，封装层的设置恢复为默认值
+    assert_eq!(encoder.frame_count(), frames_before);
+    assert_eq!(encoder.max_samples_per_call(), None);
+
+    for _ in 0..10 {
+        let len = encoder.encode_interleaved(&pcm, &mut mp3_buffer)?;
+        actual.extend_from_slice(&mp3_buffer[..len]);
+    }
+    let len = encoder.flush(&mut mp3_buffer)?;
+    actual.extend_from_slice(&mp3_buffer[..len]);
+
+    assert_eq!(actual, expected);
+    Ok(())
+}
+
+#[test]
+fn test_raw_round_trip_resets_wrapper_settings() -> Result<()> {
+    let mut encoder = encoder()?;
+    encoder.set_max_samples_per_call(Some(1152))?;
+
+    // SAFETY: 指针来自 into_raw，只取回一次
+    let mut encoder = unsafe { LameEncoder::from_raw(encoder.into_raw()) };
+    assert_eq!(encoder.max_samples_per_call(), None);
+
+    // 构建时的设置没有随句柄交出，无法重置
+    assert!(matches!(encoder.reset(), Err(LameError::InvalidInput(_))));
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use lame_sys::prelude::*;
+use lame_sys::signal;
+
+/// 编码 0.5 秒正弦波，返回完整的 MP3 数据
+fn encode(config: &EncoderConfig) -> Result<Vec<u8>> {
+    let encoder = config.build()?;
+    let channels = config.channels as usize;
+    let mono = signal::sine(440.0, 44100, signal::duration_of(22050, 44100));
+    let mut buffer = Mp3Buffer::new(encoder);
+    buffer.write_pcm(&signal::interleave(&mono, channels))?;
+    buffer.finish()
+}
+
+#[test]
+fn test_config_json_round_trip() -> Result<()> {
+    let config = EncoderConfig {
+        bitrate: Some(160),
+        quality: Some(Quality::High),
+        vbr_mode: Some(VbrMode::Abr),
+        vbr_min_bitrate: Some(96),
+        vbr_max_bitrate: Some(256),
+        mode: Some(ChannelMode::JointStereo),
+        lowpass: Some(16000),
+        highpass: Some(-1),
+        ..EncoderConfig::default()
+    };
+
+    let json = serde_json::to_string(&config).unwrap();
+    let restored: EncoderConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, config);
+
+    // 同一份配置构建的编码器输出相同
+    assert_eq!(encode(&restored)?, encode(&config)?);
+    Ok(())
+}
+
+#[test]
+fn test_config_file_with_missing_fields() -> Result<()> {
+    // 配置文件只写需要修改的字段，其余取默认值
+    let config: EncoderConfig = serde_json::from_str(
+        r#"{
+            "channels": 1,
+            "vbr_mode": "Vbr",
+            "vbr_quality": 4,
+            "mode": "Mono"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config,
+        EncoderConfig {
+            channels: 1,
+            vbr_mode: Some(VbrMode::Vbr),
+            vbr_quality: Some(4),
+            mode: Some(ChannelMode::Mono),
+            ..EncoderConfig::default()
+        }
+    );
+
+    let encoder = config.build()?;
+    assert_eq!(encoder.config().vbr_quality, Some(4));
+    assert!(!encode(&config)?.is_empty());
+
+    // 未知的枚举值在解析时报错
+    let result = serde_json::from_str::<EncoderConfig>(r#"{"quality": "Ultra"}"#);
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn test_id3_metadata_from_json() -> Result<()> {
+    // 附属文件只写需要的字段
+    let meta: Id3Metadata = serde_json::from_str(
+        r#"{
+            "title": "夜に駆ける",
+            "artist": "YOASOBI",
+            "album": "THE BOOK",
+            "year": "2021",
+            "track": 2,
+            "track_total": 9,
+            "genre": "jpop"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(meta.track, Some(2));
+    assert_eq!(meta.album_art, None);
+
+    let json = serde_json::to_string(&meta).unwrap();
+    let restored: Id3Metadata = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, meta);
+
+    let mut encoder = EncoderConfig::default().build()?;
+    encoder.set_id3(&restored)?;
+    let tag = encoder.id3v2_tag()?;
+    assert_eq!(&tag[..3], b"ID3");
+    for frame in [&b"TIT2"[..], b"TPE1", b"TALB", b"TRCK", b"TCON"] {
+        assert!(tag.windows(4).any(|w| w == frame));
+    }
+    assert!(tag.windows(3).any(|w| w == b"2/9"));
+    assert!(tag.windows(4).any(|w| w == b"JPop"));
+    assert_eq!(encoder.id3v1_tag()?[127], lame_sys::genres::JPOP);
+    Ok(())
+}
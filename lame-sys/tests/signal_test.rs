@@ -0,0 +1,69 @@
+use lame_sys::signal::{self, AMPLITUDE};
+use std::time::Duration;
+
+#[test]
+fn test_lengths_follow_duration() {
+    let second = Duration::from_secs(1);
+    assert_eq!(signal::sine(440.0, 44100, second).len(), 44100);
+    assert_eq!(signal::sweep(20.0, 20000.0, 48000, second).len(), 48000);
+    assert_eq!(signal::white_noise(8000, second, 0).len(), 8000);
+    assert_eq!(
+        signal::silence(22050, Duration::from_millis(500)).len(),
+        11025
+    );
+
+    // duration_of 换算回来恰好是原来的样本数
+    for samples in [0, 1, 1152, 1152 * 10 + 17, 44100 * 60] {
+        for rate in [8000, 22050, 44100, 48000] {
+            let duration = signal::duration_of(samples, rate);
+            assert_eq!(
+                signal::silence(rate, duration).len(),
+                samples,
+                "{} @ {}",
+                samples,
+                rate
+            );
+        }
+    }
+}
+
+#[test]
+fn test_sine_shape() {
+    // 1 kHz @ 8 kHz：每 8 个样本一个周期，第 2 个样本为峰值
+    let tone = signal::sine(1000.0, 8000, signal::duration_of(16, 8000));
+    assert_eq!(tone[0], 0);
+    assert_eq!(tone[2], AMPLITUDE);
+    assert_eq!(tone[6], -AMPLITUDE);
+    assert_eq!(tone[..8], tone[8..]);
+}
+
+#[test]
+fn test_sweep_starts_at_zero_within_amplitude() {
+    let sweep = signal::sweep(100.0, 10000.0, 44100, Duration::from_secs(2));
+    assert_eq!(sweep[0], 0);
+    assert!(sweep.iter().all(|s| s.abs() <= AMPLITUDE));
+}
+
+#[test]
+fn test_white_noise_depends_only_on_seed() {
+    let second = Duration::from_secs(1);
+    let noise = signal::white_noise(44100, second, 42);
+    assert_eq!(noise, signal::white_noise(44100, second, 42));
+    assert_ne!(noise, signal::white_noise(44100, second, 43));
+    assert!(noise.iter().all(|&s| (-AMPLITUDE..AMPLITUDE).contains(&s)));
+
+    // 较短的噪声是较长噪声的前缀
+    let short = signal::white_noise(44100, Duration::from_millis(100), 42);
+    assert_eq!(short[..], noise[..short.len()]);
+
+    // 大致以 0 为中心
+    let mean = noise.iter().map(|&s| f64::from(s)).sum::<f64>() / noise.len() as f64;
+    assert!(mean.abs() < 500.0, "mean = {}", mean);
+}
+
+#[test]
+fn test_interleave_and_stereo() {
+    assert_eq!(signal::interleave(&[1, 2, 3], 2), [1, 1, 2, 2, 3, 3]);
+    assert_eq!(signal::interleave(&[1, 2], 1), [1, 2]);
+    assert_eq!(signal::stereo(&[1, 2, 3], &[-1, -2]), [1, -1, 2, -2]);
+}
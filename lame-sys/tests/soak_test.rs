@@ -0,0 +1,119 @@
+//! 长时间运行的内存增长测试，默认忽略：
+//!
+//! ```text
+//! cargo test --release -p lame-sys -- --ignored soak
+//! ```
+//!
+//! 帧数可以通过环境变量 `LAME_SOAK_FRAMES` 调整。RSS 从 `/proc/self/statm` 读取，仅在 Linux 上运行。
+
+#![cfg(target_os = "linux")]
+
+use lame_sys::pool::EncoderPool;
+use lame_sys::prelude::*;
+
+/// 默认编码的总帧数（约 14 小时的 44.1 kHz 音频）
+const SOAK_FRAMES: usize = 2_000_000;
+
+/// 每隔多少帧重新设置一次 ID3 标签并分段刷新
+const RETAG_INTERVAL: usize = 10_000;
+
+/// 每隔多少帧把编码器归还到池中重置
+const RESET_INTERVAL: usize = 100_000;
+
+/// 预热阶段结束后 RSS 允许增长的上限（页）
+const MAX_GROWTH_PAGES: usize = 1024;
+
+/// 当前进程的常驻内存页数
+fn resident_pages() -> usize {
+    let statm = std::fs::read_to_string("/proc/self/statm").expect("read /proc/self/statm");
+    statm
+        .split_whitespace()
+        .nth(1)
+        .and_then(|pages| pages.parse().ok())
+        .expect("parse /proc/self/statm")
+}
+
+fn soak_frames() -> usize {
+    std::env::var("LAME_SOAK_FRAMES")
+        .ok()
+        .and_then(|frames| frames.parse().ok())
+        .unwrap_or(SOAK_FRAMES)
+}
+
+fn retag(encoder: &mut LameEncoder, cycle: usize) -> Result<()> {
+    Id3Tag::new(encoder)
+        .title(&format!("Call {}", cycle))?
+        .artist("Soak Test")?
+        .album("Gateway")?
+        .comment(&"x".repeat(cycle % 64))?
+        .track(cycle as u32 % 256, None)
+        .apply()?;
+    Ok(())
+}
+
+#[test]
+#[ignore]
+fn soak_long_lived_encoder() -> Result<()> {
+    let config = EncoderConfig {
+        sample_rate: 44100,
+        channels: 2,
+        bitrate: Some(128),
+        ..EncoderConfig::default()
+    };
+    let pool = EncoderPool::new(config, 1);
+    let mut encoder = pool.acquire()?;
+
+    let frame: Vec<i16> = (0..1152)
+        .flat_map(|i| {
+            let t = i as f32 / 44100.0;
+            let value = ((2.0 * std::f32::consts::PI * 440.0 * t).sin() * 16384.0) as i16;
+            [value, value]
+        })
+        .collect();
+    // 偶尔出现的超大 8-bit 输入，检验暂存缓冲区不会一直占着内存
+    let burst = vec![128u8; 1152 * 2 * 1024];
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(burst.len() / 2)];
+
+    let total = soak_frames();
+    let mut baseline = None;
+    let mut peak_growth = 0;
+
+    for index in 1..=total {
+        encoder.encode_interleaved(&frame, &mut mp3_buffer)?;
+
+        if index % RETAG_INTERVAL == 0 {
+            let cycle = index / RETAG_INTERVAL;
+            encoder.encode_interleaved_u8(&burst, &mut mp3_buffer)?;
+            encoder.flush_nogap(&mut mp3_buffer)?;
+            retag(&mut encoder, cycle)?;
+        }
+
+        if index % RESET_INTERVAL == 0 {
+            encoder.flush(&mut mp3_buffer)?;
+            drop(encoder);
+            encoder = pool.acquire()?;
+
+            // 第一次重置之后的内存作为基线
+            let pages = resident_pages();
+            match baseline {
+                None => baseline = Some(pages),
+                Some(base) => {
+                    let growth = pages.saturating_sub(base);
+                    peak_growth = peak_growth.max(growth);
+                    assert!(
+                        growth <= MAX_GROWTH_PAGES,
+                        "RSS grew by {} pages after {} frames",
+                        growth,
+                        index
+                    );
+                }
+            }
+        }
+    }
+
+    println!(
+        "soak: {} frames, peak RSS growth {} pages",
+        total, peak_growth
+    );
+    Ok(())
+}
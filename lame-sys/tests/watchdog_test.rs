@@ -0,0 +1,127 @@
+use lame_sys::prelude::*;
+use lame_sys::signal;
+use lame_sys::watchdog::{CallProgress, CancelToken};
+use std::sync::{Arc, Mutex};
+
+const SAMPLE_RATE: usize = 44100;
+
+fn encoder() -> Result<LameEncoder> {
+    LameEncoder::builder()?
+        .sample_rate(SAMPLE_RATE as i32)?
+        .channels(1)?
+        .bitrate(128)?
+        .build()
+}
+
+/// 440 Hz 正弦波
+fn sine(samples: usize) -> Vec<i16> {
+    let rate = SAMPLE_RATE as u32;
+    signal::sine(440.0, rate, signal::duration_of(samples, rate))
+}
+
+fn encode_all(encoder: &mut LameEncoder, pcm: &[i16]) -> Result<Vec<u8>> {
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(pcm.len())];
+    let mut len = encoder.encode_mono(pcm, &mut mp3_buffer)?;
+    len += encoder.flush(&mut mp3_buffer[len..])?;
+    mp3_buffer.truncate(len);
+    Ok(mp3_buffer)
+}
+
+#[test]
+fn test_max_samples_per_call_defaults_to_unlimited() -> Result<()> {
+    let mut encoder = encoder()?;
+    assert_eq!(encoder.max_samples_per_call(), None);
+
+    encoder.set_max_samples_per_call(Some(SAMPLE_RATE))?;
+    assert_eq!(encoder.max_samples_per_call(), Some(SAMPLE_RATE));
+
+    assert!(matches!(
+        encoder.set_max_samples_per_call(Some(0)),
+        Err(LameError::InvalidParameter(_))
+    ));
+    assert_eq!(encoder.max_samples_per_call(), Some(SAMPLE_RATE));
+
+    encoder.set_max_samples_per_call(None)?;
+    assert_eq!(encoder.max_samples_per_call(), None);
+    Ok(())
+}
+
+#[test]
+fn test_split_output_matches_single_call() -> Result<()> {
+    let pcm = sine(SAMPLE_RATE * 10 + 123);
+    let expected = encode_all(&mut encoder()?, &pcm)?;
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let mut guarded = encoder()?;
+    guarded.set_max_samples_per_call(Some(SAMPLE_RATE))?;
+    guarded.set_cancel_token(Some(CancelToken::new()));
+    let recorded = Arc::clone(&progress);
+    guarded.set_progress_hook(Some(move |p: CallProgress| {
+        recorded.lock().unwrap().push(p)
+    }));
+
+    assert_eq!(encode_all(&mut guarded, &pcm)?, expected);
+
+    // 11 次 LAME 调用之间执行 10 次回调
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.len(), 10);
+    for (i, p) in progress.iter().enumerate() {
+        assert_eq!(p.samples_done, SAMPLE_RATE * (i + 1));
+        assert_eq!(p.samples_total, pcm.len());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_cancel_mid_call() -> Result<()> {
+    let pcm = vec![0i16; SAMPLE_RATE * 20];
+    let mut encoder = encoder()?;
+    encoder.set_max_samples_per_call(Some(SAMPLE_RATE))?;
+
+    let token = CancelToken::new();
+    encoder.set_cancel_token(Some(token.clone()));
+
+    // 处理到一半时取消
+    let calls = Arc::new(Mutex::new(0));
+    let counter = Arc::clone(&calls);
+    encoder.set_progress_hook(Some(move |p: CallProgress| {
+        *counter.lock().unwrap() += 1;
+        if p.samples_done >= p.samples_total / 2 {
+            token.cancel();
+        }
+    }));
+
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(pcm.len())];
+    match encoder.encode_mono(&pcm, &mut mp3_buffer) {
+        Err(LameError::Cancelled { bytes_written }) => {
+            assert!(bytes_written > 0);
+            assert!(bytes_written < mp3_buffer.len());
+        }
+        other => panic!("expected Cancelled, got {:?}", other),
+    }
+    assert_eq!(*calls.lock().unwrap(), 10);
+    Ok(())
+}
+
+#[test]
+fn test_cancelled_token_does_not_interrupt_single_call() -> Result<()> {
+    let mut encoder = encoder()?;
+    let token = CancelToken::new();
+    token.cancel();
+    encoder.set_cancel_token(Some(token));
+
+    // 未超过上限时只有一次 LAME 调用，不检查令牌
+    let pcm = sine(1152 * 4);
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(pcm.len())];
+    encoder.encode_mono(&pcm, &mut mp3_buffer)?;
+    Ok(())
+}
+
+#[test]
+fn test_cancel_token_is_shared_between_clones() {
+    let token = CancelToken::new();
+    let clone = token.clone();
+    assert!(!clone.is_cancelled());
+    token.cancel();
+    assert!(clone.is_cancelled());
+}
@@ -0,0 +1,671 @@
+use lame_sys::prelude::*;
+use lame_sys::signal;
+use lame_sys::YieldPolicy;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+fn encoder(channels: i32) -> Result<LameEncoder> {
+    LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(channels)?
+        .bitrate(128)?
+        .build()
+}
+
+/// 440 Hz 正弦波，按声道交错
+fn sine(frames: usize, channels: usize) -> Vec<i16> {
+    let mono = signal::sine(440.0, 44100, signal::duration_of(frames, 44100));
+    signal::interleave(&mono, channels)
+}
+
+/// 不经过写入器，一次性编码整段输入
+fn encode_unbatched(channels: i32, pcm: &[i16]) -> Result<Vec<u8>> {
+    let mut encoder = encoder(channels)?;
+    let mut mp3_buffer = vec![0u8; LameEncoder::mp3_buffer_size(pcm.len())];
+
+    let mut len = if channels == 1 {
+        encoder.encode_mono(pcm, &mut mp3_buffer)?
+    } else {
+        encoder.encode_interleaved(pcm, &mut mp3_buffer)?
+    };
+    len += encoder.flush(&mut mp3_buffer[len..])?;
+
+    mp3_buffer.truncate(len);
+    Ok(mp3_buffer)
+}
+
+/// 按 `write_size` 个交错样本逐次写入
+fn encode_with_writer(
+    channels: i32,
+    pcm: &[i16],
+    write_size: usize,
+    batch_samples: usize,
+) -> Result<Vec<u8>> {
+    let mut writer = Mp3Writer::new(encoder(channels)?, Vec::new());
+    writer.set_batch_samples(batch_samples)?;
+
+    for chunk in pcm.chunks(write_size) {
+        writer.write_pcm(chunk)?;
+    }
+    writer.finish()
+}
+
+#[test]
+fn test_batched_output_is_byte_identical() -> Result<()> {
+    for channels in [1, 2] {
+        let pcm = sine(1152 * 100 + 17, channels as usize);
+        let expected = encode_unbatched(channels, &pcm)?;
+        let frame = 1152 * channels as usize;
+
+        for batch_samples in [1, 1152, 4000, 16384, 1 << 20] {
+            for write_size in [frame, frame * 3 + 2 * channels as usize] {
+                let actual = encode_with_writer(channels, &pcm, write_size, batch_samples)?;
+                assert_eq!(
+                    actual, expected,
+                    "channels={} batch={} write={}",
+                    channels, batch_samples, write_size
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_flush_input_forces_pending_batch() -> Result<()> {
+    let mut writer = Mp3Writer::new(encoder(2)?, Vec::new());
+    assert_eq!(
+        writer.batch_samples(),
+        lame_sys::writer::DEFAULT_BATCH_SAMPLES
+    );
+
+    // 少于一批时不调用 LAME
+    writer.write_pcm(&sine(1152 * 8, 2))?;
+    assert!(writer.get_ref().is_empty());
+
+    // 强制送出后 LAME 已有足够的帧产生输出
+    writer.flush_input()?;
+    assert!(!writer.get_ref().is_empty());
+
+    let output = writer.finish()?;
+    assert!(!output.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_writer_rejects_odd_stereo_input() -> Result<()> {
+    let mut writer = Mp3Writer::new(encoder(2)?, Vec::new());
+    let result = writer.write_pcm(&[0i16; 3]);
+    assert!(matches!(result, Err(LameError::InvalidInput(_))));
+    Ok(())
+}
+
+/// 解析出的一帧
+struct Frame {
+    /// MPEG 比特率索引（1-14）
+    bitrate_index: usize,
+    /// 是否为 LAME 信息帧占位（帧头之后全为 0）
+    is_info: bool,
+}
+
+/// 解析 44.1 kHz MPEG-1 Layer III 帧头，返回比特率索引和整帧长度，非法帧头时 panic
+fn parse_header(data: &[u8]) -> (usize, usize) {
+    const BITRATES: [usize; 15] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ];
+
+    assert!(data.len() >= 4, "truncated frame header");
+    assert!(
+        data[0] == 0xFF && data[1] & 0xFE == 0xFA,
+        "expected MPEG-1 Layer III sync"
+    );
+    assert_eq!((data[2] >> 2) & 0x3, 0, "expected 44100 Hz");
+
+    let bitrate_index = (data[2] >> 4) as usize;
+    assert!((1..=14).contains(&bitrate_index), "bad bitrate index");
+    let padding = ((data[2] >> 1) & 0x1) as usize;
+    (
+        bitrate_index,
+        144 * BITRATES[bitrate_index] * 1000 / 44100 + padding,
+    )
+}
+
+/// 解析 44.1 kHz 的 MPEG-1 Layer III 帧序列，遇到非法帧头时 panic
+fn parse_frames(mut data: &[u8]) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    while !data.is_empty() {
+        let (bitrate_index, len) = parse_header(data);
+        assert!(data.len() >= len, "truncated frame");
+
+        frames.push(Frame {
+            bitrate_index,
+            is_info: data[4..len].iter().all(|&b| b == 0),
+        });
+        data = &data[len..];
+    }
+    frames
+}
+
+fn vbr_encoder(channels: i32, write_vbr_tag: bool) -> Result<LameEncoder> {
+    LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(channels)?
+        .vbr_mode(VbrMode::Vbr)?
+        .vbr_quality(2)?
+        .write_vbr_tag(write_vbr_tag)?
+        .build()
+}
+
+#[test]
+fn test_rotate_info_frame_only_in_first_segment() -> Result<()> {
+    for channels in [1, 2] {
+        for write_vbr_tag in [true, false] {
+            let encoder = vbr_encoder(channels, write_vbr_tag)?;
+            assert_eq!(encoder.writes_vbr_tag(), write_vbr_tag);
+
+            let mut writer = Mp3Writer::new(encoder, Vec::new());
+            let pcm = sine(1152 * 40 + 17, channels as usize);
+            let mut segments = Vec::new();
+
+            for index in 0..3 {
+                assert_eq!(writer.segment_index(), index);
+                writer.write_pcm(&pcm)?;
+                segments.push(writer.rotate(Vec::new())?);
+            }
+            writer.write_pcm(&pcm)?;
+            segments.push(writer.finish()?);
+
+            for (index, segment) in segments.iter().enumerate() {
+                let frames = parse_frames(segment);
+                let context = format!(
+                    "channels={} write_vbr_tag={} segment={}",
+                    channels, write_vbr_tag, index
+                );
+                assert!(frames.iter().any(|f| !f.is_info), "{}", context);
+
+                let expect_info = write_vbr_tag && index == 0;
+                assert_eq!(frames[0].is_info, expect_info, "{}", context);
+                assert!(frames[1..].iter().all(|f| !f.is_info), "{}", context);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rotate_counters_span_segments() -> Result<()> {
+    for write_vbr_tag in [true, false] {
+        let mut writer = Mp3Writer::new(vbr_encoder(2, write_vbr_tag)?, Vec::new());
+        let pcm = sine(1152 * 40 + 17, 2);
+        let mut histogram = [0i32; 14];
+        let mut frame_count = 0;
+
+        for _ in 0..3 {
+            writer.write_pcm(&pcm)?;
+            let segment = writer.rotate(Vec::new())?;
+
+            // flush_nogap 之后已编码的帧全部输出，计数与已输出的音频帧一致
+            for frame in parse_frames(&segment).iter().filter(|f| !f.is_info) {
+                histogram[frame.bitrate_index - 1] += 1;
+                frame_count += 1;
+            }
+            assert_eq!(writer.encoder().frame_count(), frame_count);
+            let counts: Vec<i32> = writer
+                .encoder()
+                .bitrate_histogram()
+                .into_iter()
+                .map(|(_, count)| count)
+                .collect();
+            assert_eq!(counts, histogram);
+        }
+        writer.finish()?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sync_points_land_on_frame_headers() -> Result<()> {
+    let mut encoder = encoder(2)?;
+    // ID3v2 标签在流的开头，偏移需要跳过它（超过 30 字节的标题才会写入 v2）
+    Id3Tag::new(&mut encoder)
+        .title("Sync points land on frame headers")?
+        .artist("Writer Test")?
+        .apply()?;
+
+    let mut writer = Mp3Writer::new(encoder, Vec::new());
+    let pcm = sine(1152 * 350 + 17, 2);
+    // 写入大小与帧大小无关，帧头会跨越写入边界
+    for chunk in pcm.chunks(3001 * 2) {
+        writer.write_pcm(chunk)?;
+    }
+    writer.flush_input()?;
+    let points = writer.sync_points().to_vec();
+    let bytes_written = writer.bytes_written();
+    let mp3 = writer.finish()?;
+    assert!(bytes_written <= mp3.len() as u64);
+
+    // LAME 内部还留着最后几帧，finish 之前 300 号帧已经输出
+    let frames: Vec<u32> = points.iter().map(|&(frame, _)| frame).collect();
+    assert_eq!(frames, [0, 100, 200, 300]);
+    assert_eq!(&mp3[..3], b"ID3");
+
+    // 从第一个同步点开始逐帧前进，每个同步点都落在对应序号的帧头上
+    let (_, mut offset) = points[0];
+    let mut frame = 0;
+    for &(sync_frame, sync_offset) in &points {
+        while frame < sync_frame {
+            offset += parse_header(&mp3[offset as usize..]).1 as u64;
+            frame += 1;
+        }
+        assert_eq!(offset, sync_offset, "frame {}", sync_frame);
+        parse_header(&mp3[sync_offset as usize..]);
+    }
+
+    // 信息帧不计入帧序号：第一个同步点是信息帧之后的那一帧
+    let info_len = parse_header(&mp3[points[0].1 as usize..]).1;
+    let info_offset = points[0].1 as usize - info_len;
+    assert_eq!(
+        parse_frames(&mp3[info_offset..points[0].1 as usize]).len(),
+        1
+    );
+    assert!(mp3[info_offset + 4..points[0].1 as usize]
+        .iter()
+        .all(|&b| b == 0));
+    Ok(())
+}
+
+#[test]
+fn test_sync_interval_configurable() -> Result<()> {
+    let pcm = sine(1152 * 120, 1);
+
+    let mut writer = Mp3Writer::new(encoder(1)?, Vec::new());
+    writer.set_sync_interval(25);
+    writer.write_pcm(&pcm)?;
+    writer.flush_input()?;
+    let frames: Vec<u32> = writer.sync_points().iter().map(|&(f, _)| f).collect();
+    assert!(frames.starts_with(&[0, 25, 50]), "{:?}", frames);
+    assert!(frames.iter().all(|f| f % 25 == 0));
+
+    let mut writer = Mp3Writer::new(encoder(1)?, Vec::new());
+    writer.set_sync_interval(0);
+    writer.write_pcm(&pcm)?;
+    let total = writer.bytes_written();
+    assert!(writer.sync_points().is_empty());
+    assert!(writer.finish()?.len() as u64 >= total);
+    Ok(())
+}
+
+/// 开头 ID3v2 标签的总长度
+fn id3v2_len(data: &[u8]) -> usize {
+    assert_eq!(&data[..3], b"ID3");
+    10 + data[6..10]
+        .iter()
+        .fold(0, |size, &b| (size << 7) | usize::from(b & 0x7F))
+}
+
+/// 带 ID3v2 标签的编码器，信息帧不在输出的起点
+fn tagged_encoder(vbr: bool) -> Result<LameEncoder> {
+    let mut encoder = if vbr {
+        vbr_encoder(2, true)?
+    } else {
+        encoder(2)?
+    };
+    Id3Tag::new(&mut encoder)
+        .title("A title long enough to need an ID3v2 tag")?
+        .apply()?;
+    Ok(encoder)
+}
+
+#[test]
+fn test_mp3_buffer_matches_seekable_writer() -> Result<()> {
+    let pcm = sine(1152 * 60 + 17, 2);
+    let path = std::env::temp_dir().join(format!("lame-sys-mp3-buffer-{}.mp3", std::process::id()));
+
+    for vbr in [false, true] {
+        let mut buffer = Mp3Buffer::new(tagged_encoder(vbr)?);
+        for chunk in pcm.chunks(3001 * 2) {
+            buffer.write_pcm(chunk)?;
+        }
+        let placeholder = buffer.get_ref().to_vec();
+        let in_memory = buffer.finish()?;
+
+        let mut writer = Mp3Writer::new(tagged_encoder(vbr)?, std::fs::File::create(&path)?);
+        writer.write_pcm(&pcm)?;
+        writer.finish_with_lametag()?;
+        let on_disk = std::fs::read(&path)?;
+        assert_eq!(in_memory, on_disk, "vbr={}", vbr);
+
+        // 只有信息帧被改写：从全 0 占位变成带 Xing/Info 标识的完整内容
+        let id3v2_len = id3v2_len(&in_memory);
+        let info_len = parse_header(&in_memory[id3v2_len..]).1;
+        let info = &in_memory[id3v2_len..id3v2_len + info_len];
+        assert!(placeholder[id3v2_len + 4..id3v2_len + info_len]
+            .iter()
+            .all(|&b| b == 0));
+        let marker: &[u8] = if vbr { b"Xing" } else { b"Info" };
+        assert!(info.windows(4).any(|w| w == marker), "vbr={}", vbr);
+        assert_eq!(in_memory[..id3v2_len], placeholder[..id3v2_len]);
+        assert_eq!(
+            in_memory[id3v2_len + info_len..placeholder.len()],
+            placeholder[id3v2_len + info_len..]
+        );
+    }
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn test_finish_with_lametag_relative_to_start_position() -> Result<()> {
+    let pcm = sine(1152 * 20, 2);
+
+    let mut buffer = Mp3Buffer::new(encoder(2)?);
+    buffer.write_pcm(&pcm)?;
+    let expected = buffer.finish()?;
+
+    // 写入器创建时下游已经有数据，信息帧按相对位置回写
+    let mut cursor = std::io::Cursor::new(b"prefix".to_vec());
+    cursor.set_position(6);
+    let mut writer = Mp3Writer::new(encoder(2)?, cursor);
+    writer.write_pcm(&pcm)?;
+    let cursor = writer.finish_with_lametag()?;
+    assert_eq!(cursor.position(), cursor.get_ref().len() as u64);
+    assert_eq!(&cursor.get_ref()[..6], b"prefix");
+    assert_eq!(cursor.get_ref()[6..], expected[..]);
+    Ok(())
+}
+
+#[test]
+fn test_finish_with_lametag_into_cursor() -> Result<()> {
+    let pcm = sine(1152 * 40 + 5, 2);
+
+    // 直接调用编码器得到的占位输出和 flush 的字节
+    let mut encoder = tagged_encoder(true)?;
+    let mut expected = encoder.encode_interleaved_to_vec(&pcm)?;
+    let mut tail = vec![0u8; LameEncoder::mp3_buffer_size(0)];
+    let len = encoder.flush(&mut tail)?;
+    let tail = &tail[..len];
+    expected.extend_from_slice(tail);
+    let lametag = encoder.lametag_frame()?;
+
+    let mut writer = Mp3Writer::new(tagged_encoder(true)?, std::io::Cursor::new(Vec::new()));
+    writer.write_pcm(&pcm)?;
+    let mp3 = writer.finish_with_lametag()?.into_inner();
+
+    // ID3v2 标签在最前面，紧跟着回写的 Xing 帧，末尾是 flush 的输出
+    let id3v2_len = id3v2_len(&mp3);
+    assert_eq!(mp3[..id3v2_len], expected[..id3v2_len]);
+    assert_eq!(mp3[id3v2_len..id3v2_len + lametag.len()], lametag[..]);
+    assert!(lametag.windows(4).any(|w| w == b"Xing"));
+    assert!(mp3.ends_with(tail));
+    assert_eq!(mp3.len(), expected.len());
+    assert_eq!(
+        mp3[id3v2_len + lametag.len()..],
+        expected[id3v2_len + lametag.len()..]
+    );
+    Ok(())
+}
+
+/// 间歇性出错的下游：按调用次数轮流返回 Interrupted、WouldBlock 和部分写入
+#[derive(Default)]
+struct FlakySink {
+    data: Vec<u8>,
+    writes: usize,
+    flushes: usize,
+    interrupted: usize,
+}
+
+impl std::io::Write for FlakySink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writes += 1;
+        match self.writes % 7 {
+            3 => {
+                self.interrupted += 1;
+                Err(std::io::ErrorKind::Interrupted.into())
+            }
+            5 | 6 => Err(std::io::ErrorKind::WouldBlock.into()),
+            _ => {
+                let len = buf.len().min(300);
+                self.data.extend_from_slice(&buf[..len]);
+                Ok(len)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flushes += 1;
+        if self.flushes == 1 {
+            return Err(std::io::ErrorKind::WouldBlock.into());
+        }
+        Ok(())
+    }
+}
+
+/// 反复调用 `op` 直到不再返回 Pending，返回遇到 Pending 的次数
+fn retry_pending(mut op: impl FnMut() -> Result<()>) -> Result<usize> {
+    let mut pending = 0;
+    loop {
+        match op() {
+            Err(LameError::Pending { .. }) => pending += 1,
+            other => return other.map(|()| pending),
+        }
+    }
+}
+
+/// 写入 PCM；下游阻塞时只调用 resume，不重复写入
+fn write_pcm_nonblocking(writer: &mut Mp3Writer<FlakySink>, pcm: &[i16]) -> Result<usize> {
+    match writer.write_pcm(pcm) {
+        Err(LameError::Pending { buffered }) => {
+            assert_eq!(buffered, writer.pending_output());
+            Ok(1 + retry_pending(|| writer.resume())?)
+        }
+        other => other.map(|()| 0),
+    }
+}
+
+#[test]
+fn test_writer_resumes_after_flaky_sink() -> Result<()> {
+    let pcm = sine(1152 * 100 + 17, 2);
+    let expected = encode_with_writer(2, &pcm, 4000 * 2, 1152)?;
+
+    let mut writer = Mp3Writer::new(encoder(2)?, FlakySink::default());
+    writer.set_batch_samples(1152)?;
+    let mut blocked = 0;
+    for chunk in pcm.chunks(4000 * 2) {
+        blocked += write_pcm_nonblocking(&mut writer, chunk)?;
+        assert_eq!(writer.pending_output(), 0);
+    }
+    blocked += retry_pending(|| writer.close())?;
+    assert_eq!(writer.bytes_written(), expected.len() as u64);
+
+    // close 之后不再接受输入，重复 close 不会再次刷新编码器
+    assert!(matches!(
+        writer.write_pcm(&pcm[..2]),
+        Err(LameError::InvalidInput(_))
+    ));
+    writer.close()?;
+
+    let sink = writer.finish()?;
+    assert!(blocked > 0);
+    assert!(sink.interrupted > 0);
+    assert_eq!(sink.data, expected);
+    Ok(())
+}
+
+#[test]
+fn test_rotate_after_pending_segment() -> Result<()> {
+    let pcm = sine(1152 * 40 + 17, 2);
+
+    let mut reference = Mp3Writer::new(encoder(2)?, Vec::new());
+    reference.write_pcm(&pcm)?;
+    let first = reference.rotate(Vec::new())?;
+    reference.write_pcm(&pcm)?;
+    let second = reference.finish()?;
+
+    let mut writer = Mp3Writer::new(encoder(2)?, FlakySink::default());
+    write_pcm_nonblocking(&mut writer, &pcm)?;
+    // 段尾的 flush_nogap 只执行一次，重试只继续送出数据
+    assert!(retry_pending(|| writer.finish_segment())? > 0);
+    let first_sink = writer.rotate(FlakySink::default())?;
+    assert_eq!(first_sink.data, first);
+
+    write_pcm_nonblocking(&mut writer, &pcm)?;
+    retry_pending(|| writer.close())?;
+    assert_eq!(writer.finish()?.data, second);
+    Ok(())
+}
+
+#[test]
+fn test_yield_hook_runs_between_lame_calls() -> Result<()> {
+    let pcm = sine(1152 * 50, 2);
+    let expected = encode_with_writer(2, &pcm, 4000 * 2, 1152)?;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let mut writer = Mp3Writer::new(encoder(2)?, Vec::new());
+    writer.set_batch_samples(1152)?;
+    writer.set_yield_policy(YieldPolicy::Hook(Box::new(move || {
+        counter.fetch_add(1, Ordering::Relaxed);
+    })));
+
+    // 一次写入多个批次，也按批次分开调用 LAME
+    writer.write_pcm(&pcm[..1152 * 2 * 30])?;
+    assert_eq!(calls.load(Ordering::Relaxed), 30);
+    for chunk in pcm[1152 * 2 * 30..].chunks(4000 * 2) {
+        writer.write_pcm(chunk)?;
+    }
+    assert_eq!(calls.load(Ordering::Relaxed), 50);
+
+    assert_eq!(writer.finish()?, expected);
+    Ok(())
+}
+
+static YIELDS: AtomicUsize = AtomicUsize::new(0);
+
+fn count_yield() {
+    YIELDS.fetch_add(1, Ordering::Relaxed);
+}
+
+#[test]
+fn test_yield_every_n_frames() -> Result<()> {
+    let pcm = sine(1152 * 95 + 17, 2);
+    let expected = encode_unbatched(2, &pcm)?;
+
+    let mut writer = Mp3Writer::new(encoder(2)?, Vec::new());
+    writer.set_yield_policy(YieldPolicy::EveryNFrames(10, count_yield));
+    // 次数只取决于交给 LAME 的总帧数，与写入的分块和批量无关
+    for chunk in pcm.chunks(333 * 2) {
+        writer.write_pcm(chunk)?;
+    }
+    writer.flush_input()?;
+    assert_eq!(YIELDS.load(Ordering::Relaxed), 95 / 10);
+
+    // 刷新编码器不消耗输入
+    assert_eq!(writer.finish()?, expected);
+    assert_eq!(YIELDS.load(Ordering::Relaxed), 95 / 10);
+    Ok(())
+}
+
+#[test]
+fn test_frame_progress_reports_every_frame() -> Result<()> {
+    const FRAMES: u32 = 40;
+    let pcm = sine(1152 * FRAMES as usize, 2);
+
+    let encoder = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .total_samples(1152 * u64::from(FRAMES))?
+        .build()?;
+    let total = encoder.estimated_total_frames();
+    assert!(total.is_some_and(|total| total >= FRAMES), "{:?}", total);
+
+    let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = Arc::clone(&reports);
+    let mut writer = Mp3Writer::new(encoder, Vec::new());
+    writer.set_batch_samples(1152 * 4)?;
+    writer.set_frame_progress(Some(Box::new(move |progress: lame_sys::FrameProgress| {
+        sink.lock().unwrap().push(progress);
+    })));
+
+    for chunk in pcm.chunks(1000 * 2) {
+        writer.write_pcm(chunk)?;
+    }
+    let encoded_before_flush = reports.lock().unwrap().len();
+    assert!(encoded_before_flush > 0);
+    writer.close()?;
+    let frames = writer.encoder().frames_encoded();
+
+    // 每帧一次，帧数从 1 开始逐一递增，flush 补齐的帧也会报告
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), frames as usize);
+    assert!(reports.len() > encoded_before_flush);
+    for (i, progress) in reports.iter().enumerate() {
+        assert_eq!(progress.frames_encoded as usize, i + 1);
+        assert_eq!(progress.total_frames, total);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_frame_progress_can_be_cleared() -> Result<()> {
+    let pcm = sine(1152 * 20, 2);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+
+    let mut writer = Mp3Writer::new(encoder(2)?, Vec::new());
+    writer.set_batch_samples(1152)?;
+    writer.set_frame_progress(Some(Box::new(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    })));
+    writer.write_pcm(&pcm[..pcm.len() / 2])?;
+    let reported = calls.load(Ordering::SeqCst);
+    assert!(reported > 0);
+
+    // 取消之后不再调用
+    writer.set_frame_progress(None);
+    writer.write_pcm(&pcm[pcm.len() / 2..])?;
+    writer.close()?;
+    assert_eq!(calls.load(Ordering::SeqCst), reported);
+    Ok(())
+}
+
+/// 写入一半后提前返回，`stream` 在返回时被 drop
+fn abandon_stream(sink: &mut Vec<u8>, pcm: &[i16]) -> Result<()> {
+    let mut stream = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .sink(sink)?;
+    stream.write_pcm(&pcm[..pcm.len() / 2])?;
+    stream.flush_input()?;
+    Err(LameError::InvalidInput("client went away".to_string()))
+}
+
+#[test]
+fn test_streaming_encoder_flushes_on_drop() -> Result<()> {
+    let pcm = sine(40, 2);
+    let half = &pcm[..pcm.len() / 2];
+
+    // 不刷新时的输出，缺少 LAME 缓存的最后几帧
+    let mut encoder = encoder(2)?;
+    let unflushed = encoder.encode_interleaved_to_vec(half)?;
+
+    let mut dropped = Vec::new();
+    assert!(abandon_stream(&mut dropped, &pcm).is_err());
+    assert!(dropped.len() > unflushed.len());
+    assert_eq!(&dropped[..unflushed.len()], &unflushed[..]);
+    // 与正常结束的输出逐字节相同
+    assert_eq!(dropped, encode_unbatched(2, half)?);
+
+    // finish 返回下游和字节数，drop 时不再写入
+    let mut stream = LameEncoder::builder()?
+        .sample_rate(44100)?
+        .channels(2)?
+        .bitrate(128)?
+        .sink(Vec::new())?;
+    stream.write_pcm(half)?;
+    let (finished, bytes_written) = stream.finish()?;
+    assert_eq!(bytes_written, finished.len() as u64);
+    assert_eq!(finished, dropped);
+    Ok(())
+}
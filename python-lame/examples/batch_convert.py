@@ -0,0 +1,70 @@
+#!/usr/bin/env python3
+"""
+Convert every WAV file in a directory to MP3 using a thread pool
+
+Encoding releases the GIL, so threads run in parallel. An `EncoderPool` caps
+the number of live LAME encoders and reuses them between files.
+
+    python batch_convert.py wavs/ mp3s/ --workers 4
+"""
+
+import argparse
+import wave
+from concurrent.futures import ThreadPoolExecutor
+from pathlib import Path
+
+import lame
+
+# PCM frames read from a WAV file per encode call
+CHUNK_FRAMES = 1152 * 16
+
+
+def convert(pool, source, destination):
+    """Encode one stereo 44.1 kHz WAV file with an encoder borrowed from `pool`"""
+    with wave.open(str(source), "rb") as w:
+        if (w.getnchannels(), w.getsampwidth(), w.getframerate()) != (2, 2, 44100):
+            raise ValueError(f"{source}: expected 16-bit stereo 44.1 kHz PCM")
+
+        with pool.acquire() as encoder, open(destination, "wb") as out:
+            while True:
+                frames = w.readframes(CHUNK_FRAMES)
+                if not frames:
+                    break
+                out.write(encoder.encode_interleaved(frames))
+            out.write(encoder.flush())
+    return destination
+
+
+def convert_directory(source_dir, output_dir, workers=4, bitrate=192):
+    """Convert all `*.wav` files in `source_dir`, returning the written paths"""
+    source_dir = Path(source_dir)
+    output_dir = Path(output_dir)
+    output_dir.mkdir(parents=True, exist_ok=True)
+
+    pool = lame.EncoderPool(sample_rate=44100, channels=2, bitrate=bitrate, max_size=workers)
+    sources = sorted(source_dir.glob("*.wav"))
+    with ThreadPoolExecutor(max_workers=workers) as executor:
+        futures = [
+            executor.submit(convert, pool, source, output_dir / (source.stem + ".mp3"))
+            for source in sources
+        ]
+        return [future.result() for future in futures]
+
+
+def main(argv=None):
+    parser = argparse.ArgumentParser(description=__doc__.strip().splitlines()[0])
+    parser.add_argument("source_dir", help="directory containing WAV files")
+    parser.add_argument("output_dir", help="directory for the MP3 files")
+    parser.add_argument("--workers", type=int, default=4)
+    parser.add_argument("--bitrate", type=int, default=192)
+    args = parser.parse_args(argv)
+
+    written = convert_directory(args.source_dir, args.output_dir, args.workers, args.bitrate)
+    for path in written:
+        print(path)
+    print(f"Converted {len(written)} files")
+    return 0
+
+
+if __name__ == "__main__":
+    raise SystemExit(main())
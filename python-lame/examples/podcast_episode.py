@@ -0,0 +1,83 @@
+#!/usr/bin/env python3
+"""
+Encode a tagged podcast episode from a mono 16-bit WAV recording
+
+Speech needs far fewer bits than music: mono at 64 kbps is a common podcast
+setting. The ID3 tag must be applied before the first encode call.
+
+    python podcast_episode.py recording.wav episode.mp3 --title "Episode 12" --number 12
+
+Pass --cover with a JPEG, PNG or GIF file (at most 128 KB) to embed episode art.
+"""
+
+import argparse
+import wave
+
+import lame
+
+# PCM frames read from the WAV file per encode call
+CHUNK_FRAMES = 1152 * 16
+
+
+def encode_episode(
+    source, destination, title, number, show="Example Podcast", host="Host", cover=None
+):
+    """Encode `source` (mono 16-bit WAV) to a tagged MP3 at `destination`
+
+    `cover` is the path of an optional cover image.
+    """
+    with wave.open(str(source), "rb") as w:
+        if w.getnchannels() != 1 or w.getsampwidth() != 2:
+            raise ValueError(f"{source}: expected 16-bit mono PCM")
+
+        encoder = (
+            lame.LameEncoder.builder()
+            .sample_rate(w.getframerate())
+            .channels(1)
+            .mode(lame.ChannelMode.Mono)
+            .bitrate(64)
+            .quality(lame.Quality.Good)
+            .build()
+        )
+
+        tag = encoder.id3_tag()
+        tag.title(title)
+        tag.artist(host)
+        tag.album(show)
+        tag.comment(f"{show}, episode {number}")
+        tag.track(number)
+        tag.genre("Speech")
+        if cover is not None:
+            with open(cover, "rb") as f:
+                tag.album_art(f.read())
+        tag.apply()
+
+        with open(destination, "wb") as out:
+            while True:
+                frames = w.readframes(CHUNK_FRAMES)
+                if not frames:
+                    break
+                out.write(encoder.encode_mono(frames))
+            out.write(encoder.flush())
+
+
+def main(argv=None):
+    parser = argparse.ArgumentParser(description=__doc__.strip().splitlines()[0])
+    parser.add_argument("input", help="mono 16-bit PCM WAV recording")
+    parser.add_argument("output", help="path of the MP3 file to write")
+    parser.add_argument("--title", required=True)
+    parser.add_argument("--number", type=int, default=1)
+    parser.add_argument("--show", default="Example Podcast")
+    parser.add_argument("--host", default="Host")
+    parser.add_argument("--cover", help="JPEG, PNG or GIF cover image")
+    args = parser.parse_args(argv)
+
+    encode_episode(
+        args.input, args.output, args.title, args.number, args.show, args.host, args.cover
+    )
+    print(f"Wrote {args.output}")
+    return 0
+
+
+if __name__ == "__main__":
+    raise SystemExit(main())
@@ -1,11 +1,16 @@
 #!/usr/bin/env python3
 """
 Compare different quality settings and their encoding speeds
+
+    python quality_comparison.py --seconds 10 quality_test_standard.mp3
 """
 
-import lame
-import time
+import argparse
 import math
+import struct
+import time
+
+import lame
 
 
 def generate_test_audio(duration=1.0, sample_rate=44100):
@@ -24,7 +29,7 @@ def generate_test_audio(duration=1.0, sample_rate=44100):
             value += amp * math.sin(2 * math.pi * freq * t)
         samples.append(int(32767 * value / len(frequencies)))
 
-    return samples
+    return struct.pack(f"<{num_samples}h", *samples)
 
 
 def encode_with_quality(quality, pcm_data):
@@ -40,17 +45,12 @@ def encode_with_quality(quality, pcm_data):
 
     start_time = time.time()
 
-    # Encode in chunks
+    # Encode in chunks of one MP3 frame (1152 samples, 2 bytes each)
     mp3_chunks = []
-    chunk_size = 1152
+    chunk_bytes = 1152 * 2
 
-    for i in range(0, len(pcm_data), chunk_size):
-        chunk = pcm_data[i:i + chunk_size]
-        if len(chunk) < chunk_size:
-            chunk.extend([0] * (chunk_size - len(chunk)))
-
-        mp3_data = encoder.encode_mono(chunk)
-        mp3_chunks.append(mp3_data)
+    for i in range(0, len(pcm_data), chunk_bytes):
+        mp3_chunks.append(encoder.encode_mono(pcm_data[i:i + chunk_bytes]))
 
     # Flush
     final_data = encoder.flush()
@@ -59,18 +59,22 @@ def encode_with_quality(quality, pcm_data):
     elapsed = time.time() - start_time
     total_size = sum(len(chunk) for chunk in mp3_chunks)
 
-    return b''.join(mp3_chunks), elapsed, total_size
+    return b"".join(mp3_chunks), elapsed, total_size
+
 
+def main(argv=None):
+    parser = argparse.ArgumentParser(description=__doc__.strip().splitlines()[0])
+    parser.add_argument("output", nargs="?", default="quality_test_standard.mp3")
+    parser.add_argument("--seconds", type=float, default=10.0)
+    args = parser.parse_args(argv)
 
-def main():
     print("LAME Quality Comparison")
     print("=" * 60)
     print()
 
-    # Generate test audio (10 seconds)
-    print("Generating 10 seconds of test audio...")
-    pcm_data = generate_test_audio(duration=10.0)
-    print(f"PCM samples: {len(pcm_data)}")
+    print(f"Generating {args.seconds:g} seconds of test audio...")
+    pcm_data = generate_test_audio(duration=args.seconds)
+    print(f"PCM samples: {len(pcm_data) // 2}")
     print()
 
     # Test different quality settings
@@ -100,18 +104,19 @@ def main():
     baseline_time = results[0][1]  # Best quality as baseline
 
     for name, elapsed, size, _ in results:
-        speed_ratio = baseline_time / elapsed
-        print(f"{name:<12} {elapsed*1000:<12.2f} {size:<15} {speed_ratio:.2f}x")
+        speed_ratio = baseline_time / elapsed if elapsed else float("inf")
+        print(f"{name:<12} {elapsed * 1000:<12.2f} {size:<15} {speed_ratio:.2f}x")
 
     # Save one example
-    output_file = "quality_test_standard.mp3"
+    output_file = args.output
     standard_result = next(r for r in results if r[0] == "Standard")
     with open(output_file, "wb") as f:
         f.write(standard_result[3])
 
     print()
     print(f"Sample output saved to: {output_file}")
+    return 0
 
 
 if __name__ == "__main__":
-    main()
+    raise SystemExit(main())
@@ -1,24 +1,32 @@
 #!/usr/bin/env python3
 """
 Simple example of encoding PCM to MP3 using the LAME encoder
+
+    python simple_encode.py output.mp3
 """
 
-import lame
+import argparse
 import math
+import struct
+
+import lame
 
 
 def generate_sine_wave(frequency, duration, sample_rate=44100):
-    """Generate a sine wave PCM signal"""
+    """Generate a mono sine wave as 16-bit little-endian PCM bytes"""
     num_samples = int(duration * sample_rate)
-    samples = []
-    for i in range(num_samples):
-        t = i / sample_rate
-        value = int(32767 * 0.5 * math.sin(2 * math.pi * frequency * t))
-        samples.append(value)
-    return samples
+    samples = [
+        int(32767 * 0.5 * math.sin(2 * math.pi * frequency * i / sample_rate))
+        for i in range(num_samples)
+    ]
+    return struct.pack(f"<{num_samples}h", *samples)
 
 
-def main():
+def main(argv=None):
+    parser = argparse.ArgumentParser(description=__doc__.strip().splitlines()[0])
+    parser.add_argument("output", nargs="?", default="output.mp3")
+    args = parser.parse_args(argv)
+
     print(f"LAME version: {lame.get_version()}")
     print(f"LAME URL: {lame.get_url()}")
     print()
@@ -34,54 +42,43 @@ def main():
         .build()
     )
 
-    # Set ID3 tags
+    # Set ID3 tags (before the first encode call)
     print("Setting ID3 tags...")
-    encoder.id3_tag() \
-        .title("Test Tone") \
-        .artist("Python LAME Example") \
-        .album("Examples") \
-        .year("2024") \
-        .comment("Generated sine wave at 440 Hz") \
-        .track(1) \
-        .genre("Electronic") \
-        .apply()
+    tag = encoder.id3_tag()
+    tag.title("Test Tone")
+    tag.artist("Python LAME Example")
+    tag.album("Examples")
+    tag.year("2024")
+    tag.comment("Generated sine wave at 440 Hz")
+    tag.track(1)
+    tag.genre("Electronic")
+    tag.apply()
 
     # Generate a 440 Hz sine wave (A4 note) for 3 seconds
     print("Generating test signal...")
     pcm_data = generate_sine_wave(440, 3.0)
 
-    # Encode in chunks
+    # Encode in chunks of one MP3 frame (1152 samples, 2 bytes each)
     print("Encoding...")
     mp3_chunks = []
-    chunk_size = 1152  # Standard MP3 frame size
-
-    for i in range(0, len(pcm_data), chunk_size):
-        chunk = pcm_data[i:i + chunk_size]
-        # Pad last chunk if needed
-        if len(chunk) < chunk_size:
-            chunk.extend([0] * (chunk_size - len(chunk)))
-
-        mp3_data = encoder.encode_mono(chunk)
-        mp3_chunks.append(mp3_data)
+    chunk_bytes = 1152 * 2
+    for i in range(0, len(pcm_data), chunk_bytes):
+        mp3_chunks.append(encoder.encode_mono(pcm_data[i:i + chunk_bytes]))
 
     # Flush encoder
     print("Flushing encoder...")
-    final_data = encoder.flush()
-    mp3_chunks.append(final_data)
-
-    # Combine all chunks
-    complete_mp3 = b''.join(mp3_chunks)
+    mp3_chunks.append(encoder.flush())
 
-    # Write to file
-    output_file = "output.mp3"
-    with open(output_file, "wb") as f:
+    complete_mp3 = b"".join(mp3_chunks)
+    with open(args.output, "wb") as f:
         f.write(complete_mp3)
 
-    print(f"\nEncoded MP3 written to: {output_file}")
+    print(f"\nEncoded MP3 written to: {args.output}")
     print(f"Output size: {len(complete_mp3)} bytes")
-    print(f"Duration: ~3 seconds")
-    print("\nYou can play it with: ffplay output.mp3")
+    print("Duration: ~3 seconds")
+    print(f"\nYou can play it with: ffplay {args.output}")
+    return 0
 
 
 if __name__ == "__main__":
-    main()
+    raise SystemExit(main())
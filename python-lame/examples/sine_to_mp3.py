@@ -0,0 +1,52 @@
+#!/usr/bin/env python3
+"""
+Encode a NumPy-generated sine wave to an MP3 file
+
+    python sine_to_mp3.py --frequency 440 --seconds 3 sine.mp3
+"""
+
+import argparse
+
+import lame
+import numpy as np
+
+
+def sine_wave(frequency, seconds, sample_rate=44100, amplitude=0.5):
+    """A mono sine wave as an int16 NumPy array"""
+    t = np.arange(int(seconds * sample_rate)) / sample_rate
+    wave = amplitude * np.sin(2 * np.pi * frequency * t)
+    return (wave * 32767).astype(np.int16)
+
+
+def encode_sine(frequency, seconds, sample_rate=44100, bitrate=128):
+    """Encode a sine wave and return the complete MP3 data"""
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(sample_rate)
+        .channels(1)
+        .bitrate(bitrate)
+        .quality(lame.Quality.Standard)
+        .build()
+    )
+    pcm = sine_wave(frequency, seconds, sample_rate)
+    return encoder.encode_mono_numpy(pcm) + encoder.flush()
+
+
+def main(argv=None):
+    parser = argparse.ArgumentParser(description=__doc__.strip().splitlines()[0])
+    parser.add_argument("output", help="path of the MP3 file to write")
+    parser.add_argument("--frequency", type=float, default=440.0)
+    parser.add_argument("--seconds", type=float, default=3.0)
+    parser.add_argument("--bitrate", type=int, default=128)
+    args = parser.parse_args(argv)
+
+    mp3_data = encode_sine(args.frequency, args.seconds, bitrate=args.bitrate)
+    with open(args.output, "wb") as f:
+        f.write(mp3_data)
+
+    print(f"Wrote {len(mp3_data)} bytes to {args.output}")
+    return 0
+
+
+if __name__ == "__main__":
+    raise SystemExit(main())
@@ -0,0 +1,66 @@
+#!/usr/bin/env python3
+"""
+Stream MP3 output into a BytesIO, e.g. for an HTTP response body
+
+Audio is encoded chunk by chunk as it is produced, so the first bytes can be
+sent before the whole input exists. `mp3_stream()` is a generator usable as a
+streaming response body in most web frameworks (Flask, Django, Starlette).
+
+    python stream_to_bytesio.py --seconds 5
+"""
+
+import argparse
+import io
+import math
+import struct
+
+import lame
+
+SAMPLE_RATE = 44100
+
+# Samples per channel produced per chunk (about 0.1 s)
+CHUNK_FRAMES = 4410
+
+
+def pcm_chunks(seconds, frequency=440.0):
+    """Yield interleaved stereo 16-bit PCM chunks as bytes"""
+    total = int(seconds * SAMPLE_RATE)
+    for start in range(0, total, CHUNK_FRAMES):
+        samples = []
+        for i in range(start, min(start + CHUNK_FRAMES, total)):
+            value = int(16384 * math.sin(2 * math.pi * frequency * i / SAMPLE_RATE))
+            samples.extend((value, value))
+        yield struct.pack(f"<{len(samples)}h", *samples)
+
+
+def mp3_stream(chunks, bitrate=128):
+    """Encode PCM chunks lazily, yielding MP3 bytes as soon as LAME emits them"""
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(SAMPLE_RATE)
+        .channels(2)
+        .bitrate(bitrate)
+        .build()
+    )
+    for chunk in chunks:
+        mp3_data = encoder.encode_interleaved(chunk)
+        if mp3_data:
+            yield mp3_data
+    yield encoder.flush()
+
+
+def main(argv=None):
+    parser = argparse.ArgumentParser(description=__doc__.strip().splitlines()[0])
+    parser.add_argument("--seconds", type=float, default=5.0)
+    args = parser.parse_args(argv)
+
+    body = io.BytesIO()
+    for mp3_data in mp3_stream(pcm_chunks(args.seconds)):
+        body.write(mp3_data)
+
+    print(f"Streamed {body.tell()} bytes of MP3")
+    return 0
+
+
+if __name__ == "__main__":
+    raise SystemExit(main())
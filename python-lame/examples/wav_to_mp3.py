@@ -0,0 +1,34 @@
+#!/usr/bin/env python3
+"""
+Convert a 16-bit PCM WAV file to MP3
+
+    python wav_to_mp3.py input.wav output.mp3 --bitrate 192
+"""
+
+import argparse
+
+import lame
+
+
+def main(argv=None):
+    parser = argparse.ArgumentParser(description=__doc__.strip().splitlines()[0])
+    parser.add_argument("input", help="16-bit PCM WAV file")
+    parser.add_argument("output", help="path of the MP3 file to write")
+    parser.add_argument("--bitrate", type=int, default=192)
+    parser.add_argument("--vbr", action="store_true", help="use VBR instead of a fixed bitrate")
+    args = parser.parse_args(argv)
+
+    if args.vbr:
+        mp3_data = lame.encode_wave(args.input, vbr_mode=lame.VbrMode.Vbr, vbr_quality=2)
+    else:
+        mp3_data = lame.encode_wave(args.input, bitrate=args.bitrate)
+
+    with open(args.output, "wb") as f:
+        f.write(mp3_data)
+
+    print(f"Converted {args.input} -> {args.output} ({len(mp3_data)} bytes)")
+    return 0
+
+
+if __name__ == "__main__":
+    raise SystemExit(main())
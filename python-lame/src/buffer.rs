@@ -0,0 +1,215 @@
+use crate::encoder::{InterruptSlot, LameEncoder};
+use crate::error::{to_py_err, EncodingError};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+
+/// Complete MP3 file assembled in memory
+///
+/// Takes over an encoder and collects everything it produces. `close()`
+/// flushes the encoder and fills in the Xing/Info frame at the start of the
+/// stream, after which `getvalue()` returns the finished file: the same bytes
+/// `lame_sys::Mp3Writer::finish_with_lametag` writes to disk. Useful for web
+/// handlers that return the MP3 as one response body.
+///
+/// Pass a freshly built encoder: output it returned from earlier `encode*()`
+/// calls is not part of the buffer. ID3 tags applied beforehand are included.
+///
+/// # Example
+///
+/// ```python
+/// encoder = LameEncoder.builder().sample_rate(44100).channels(2).build()
+/// tag = encoder.id3_tag()
+/// tag.title("My Song")
+/// tag.apply()
+///
+/// with Mp3Buffer(encoder) as buffer:
+///     buffer.write(pcm_interleaved)
+/// mp3_data = buffer.getvalue()
+/// ```
+#[pyclass]
+pub struct Mp3Buffer {
+    state: BufferState,
+    /// Where the encoder's Ctrl-C check stores the signal error
+    interrupt: InterruptSlot,
+}
+
+enum BufferState {
+    /// Accepting PCM
+    Open(lame_sys::Mp3Buffer),
+    /// `close()` finished the file
+    Closed(Vec<u8>),
+    /// Encoding stopped part way; the output is incomplete
+    Failed(String),
+}
+
+impl Mp3Buffer {
+    fn open(&mut self) -> PyResult<&mut lame_sys::Mp3Buffer> {
+        match &mut self.state {
+            BufferState::Open(buffer) => Ok(buffer),
+            BufferState::Closed(_) => {
+                Err(PyValueError::new_err("I/O operation on closed Mp3Buffer"))
+            }
+            BufferState::Failed(reason) => Err(EncodingError::new_err(reason.clone())),
+        }
+    }
+
+    /// Turn a failed LAME call into the pending signal error, if there is one
+    ///
+    /// An interrupted call consumed part of the input, so the buffer can't
+    /// produce a valid file any more.
+    fn interrupted(&mut self, err: PyErr) -> PyErr {
+        let signal = self
+            .interrupt
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        match signal {
+            Some(signal) => {
+                self.state = BufferState::Failed(
+                    "Mp3Buffer was interrupted mid-call and its output is incomplete".to_string(),
+                );
+                signal
+            }
+            None => err,
+        }
+    }
+}
+
+#[pymethods]
+impl Mp3Buffer {
+    /// Create a buffer that takes over `encoder`
+    ///
+    /// Args:
+    ///     encoder (LameEncoder): Encoder created by `EncoderBuilder.build()`;
+    ///         it can't be used directly afterwards
+    ///
+    /// Raises:
+    ///     ValueError: If the encoder came from an `EncoderPool`
+    ///     RuntimeError: If the encoder was already moved or released
+    #[new]
+    #[pyo3(signature = (encoder))]
+    fn new(encoder: &Bound<'_, LameEncoder>) -> PyResult<Self> {
+        let mut encoder = encoder
+            .try_borrow_mut()
+            .map_err(|_| EncodingError::new_err("encoder is busy in another thread"))?;
+        let (encoder, interrupt) = encoder.take_owned()?;
+        Ok(Self {
+            state: BufferState::Open(lame_sys::Mp3Buffer::new(encoder)),
+            interrupt,
+        })
+    }
+
+    /// Encode PCM samples into the buffer
+    ///
+    /// Args:
+    ///     pcm (bytes): i16 little-endian samples, interleaved (L, R, L, R, ...)
+    ///         for stereo encoders
+    ///
+    /// Raises:
+    ///     ValueError: If the buffer is closed or the length does not match
+    ///         the channel count
+    ///
+    /// Note: Releases the GIL during encoding.
+    #[pyo3(signature = (pcm))]
+    fn write(&mut self, py: Python<'_>, pcm: &Bound<'_, PyBytes>) -> PyResult<()> {
+        let bytes = pcm.as_bytes();
+        if bytes.len() % 2 != 0 {
+            return Err(PyValueError::new_err(
+                "PCM data must be 16-bit samples (length must be even)",
+            ));
+        }
+        let samples: Vec<i16> = bytes
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+
+        let buffer = self.open()?;
+        let result = py.allow_threads(|| buffer.write_pcm(&samples).map_err(to_py_err));
+        result.map_err(|err| self.interrupted(err))
+    }
+
+    /// Flush the encoder and finish the file
+    ///
+    /// Calling it again has no effect.
+    ///
+    /// Note: Releases the GIL while flushing.
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        let buffer = match std::mem::replace(&mut self.state, BufferState::Closed(Vec::new())) {
+            BufferState::Open(buffer) => buffer,
+            BufferState::Closed(data) => {
+                self.state = BufferState::Closed(data);
+                return Ok(());
+            }
+            failed @ BufferState::Failed(_) => {
+                self.state = failed;
+                return Ok(());
+            }
+        };
+
+        match py.allow_threads(|| buffer.finish().map_err(to_py_err)) {
+            Ok(data) => {
+                self.state = BufferState::Closed(data);
+                Ok(())
+            }
+            Err(err) => {
+                self.state = BufferState::Failed(format!("Mp3Buffer failed to close: {}", err));
+                Err(self.interrupted(err))
+            }
+        }
+    }
+
+    /// The finished MP3 file
+    ///
+    /// Returns:
+    ///     bytes: Complete MP3 data, including tags and the Xing/Info frame
+    ///
+    /// Raises:
+    ///     ValueError: If `close()` has not been called yet
+    ///     EncodingError: If encoding failed part way
+    fn getvalue<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        match &self.state {
+            BufferState::Closed(data) => Ok(PyBytes::new_bound(py, data)),
+            BufferState::Open(_) => Err(PyValueError::new_err(
+                "getvalue() is only available after close(); the Xing/Info frame is written on close",
+            )),
+            BufferState::Failed(reason) => Err(EncodingError::new_err(reason.clone())),
+        }
+    }
+
+    /// Whether `close()` has been called
+    #[getter]
+    fn closed(&self) -> bool {
+        !matches!(self.state, BufferState::Open(_))
+    }
+
+    /// Bytes produced so far; the full file length once closed
+    #[getter]
+    fn bytes_written(&self) -> u64 {
+        match &self.state {
+            BufferState::Open(buffer) => buffer.bytes_written(),
+            BufferState::Closed(data) => data.len() as u64,
+            BufferState::Failed(_) => 0,
+        }
+    }
+
+    /// Enter a `with` block, returning the buffer itself
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Close the buffer when leaving a `with` block
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&mut self, py: Python<'_>, _args: &Bound<'_, PyTuple>) -> PyResult<bool> {
+        self.close(py)?;
+        Ok(false)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Mp3Buffer(bytes_written={}, closed={})",
+            self.bytes_written(),
+            if self.closed() { "True" } else { "False" }
+        )
+    }
+}
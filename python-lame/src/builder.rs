@@ -1,19 +1,26 @@
-use crate::encoder::LameEncoder;
-use crate::enums::{Quality, VbrMode};
+use crate::encoder::{EncoderSlot, LameEncoder};
+use crate::enums::{ChannelMode, Preset, Quality, ShortBlockMode, VbrMode};
 use crate::error::to_py_err;
+use lame_sys::{RawParam, RawValue};
 use pyo3::prelude::*;
+use pyo3::types::PyFloat;
 
 /// Builder for configuring and creating a LameEncoder
 ///
+/// Every setter returns the builder, so calls can be chained or made one
+/// at a time.
+///
 /// # Example
 ///
 /// ```python
-/// builder = LameEncoder.builder()
-/// builder.sample_rate(44100)
-/// builder.channels(2)
-/// builder.bitrate(128)
-/// builder.quality(Quality.Standard)
-/// encoder = builder.build()
+/// encoder = (
+///     LameEncoder.builder()
+///     .sample_rate(44100)
+///     .channels(2)
+///     .bitrate(128)
+///     .quality(Quality.Standard)
+///     .build()
+/// )
 /// ```
 #[pyclass(unsendable)]
 pub struct EncoderBuilder {
@@ -24,93 +31,719 @@ pub struct EncoderBuilder {
 impl EncoderBuilder {
     /// Create a new encoder builder with default settings
     #[new]
+    #[pyo3(text_signature = "()")]
     pub fn new() -> PyResult<Self> {
         let inner = lame_sys::LameEncoder::builder().map_err(to_py_err)?;
         Ok(Self { inner: Some(inner) })
     }
 
-    /// Set the input sample rate in Hz
+    /// Set both the input and output sample rate in Hz
     ///
     /// Common values: 44100, 48000, 32000, 22050, 16000
-    fn sample_rate(&mut self, rate: i32) -> PyResult<()> {
-        let builder = self.inner.take().ok_or_else(|| {
+    ///
+    /// If `rate` is not a valid MP3 sample rate (e.g. 96000), LAME still picks
+    /// the output rate. Use `input_sample_rate()` and `output_sample_rate()`
+    /// to resample.
+    ///
+    /// Args:
+    ///     rate (int): Sample rate in Hz
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (rate))]
+    fn sample_rate(mut slf: PyRefMut<'_, Self>, rate: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
         })?;
         let builder = builder.sample_rate(rate).map_err(to_py_err)?;
-        self.inner = Some(builder);
-        Ok(())
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the sample rate of the input PCM in Hz
+    ///
+    /// Without `output_sample_rate()`, LAME picks the output rate from the
+    /// input rate and bitrate.
+    ///
+    /// Args:
+    ///     rate (int): Sample rate in Hz
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (rate))]
+    fn input_sample_rate(mut slf: PyRefMut<'_, Self>, rate: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.input_sample_rate(rate).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the sample rate of the MP3 output in Hz, resampling the input
+    ///
+    /// Valid values: 8000, 11025, 12000, 16000, 22050, 24000, 32000, 44100,
+    /// 48000, or 0 to let LAME choose (the default).
+    ///
+    /// Args:
+    ///     rate (int): Sample rate in Hz
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If `rate` is not an MP3 sample rate
+    #[pyo3(signature = (rate))]
+    fn output_sample_rate(mut slf: PyRefMut<'_, Self>, rate: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.output_sample_rate(rate).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
     }
 
     /// Set the number of input channels (1 or 2)
     ///
     /// 1 = mono, 2 = stereo
-    fn channels(&mut self, channels: i32) -> PyResult<()> {
-        let builder = self.inner.take().ok_or_else(|| {
+    ///
+    /// Args:
+    ///     channels (int): Number of input channels
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (channels))]
+    fn channels(mut slf: PyRefMut<'_, Self>, channels: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
         })?;
         let builder = builder.channels(channels).map_err(to_py_err)?;
-        self.inner = Some(builder);
-        Ok(())
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Apply a LAME preset
+    ///
+    /// Call it before other settings: options set afterwards override the
+    /// preset, options set before are overwritten by it. `Preset.V0` to
+    /// `Preset.V9` also switch to `VbrMode.Vbr`.
+    ///
+    /// Args:
+    ///     preset (Preset | int): A preset, or an ABR bitrate in kbps (8-320)
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If the ABR bitrate is out of range
+    ///
+    /// Example:
+    ///     ```python
+    ///     builder.preset(lame.Preset.V2)
+    ///     builder.preset(160)  # ABR at 160 kbps
+    ///     ```
+    #[pyo3(signature = (preset))]
+    fn preset<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        preset: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let preset = match preset.extract::<Preset>() {
+            Ok(preset) => preset.into(),
+            Err(_) => lame_sys::Preset::Abr(preset.extract()?),
+        };
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.preset(preset).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
     }
 
     /// Set the output bitrate in kbps
     ///
     /// Common values: 320, 256, 192, 128, 96, 64
-    fn bitrate(&mut self, bitrate: i32) -> PyResult<()> {
-        let builder = self.inner.take().ok_or_else(|| {
+    ///
+    /// Args:
+    ///     bitrate (int): Bitrate in kbps
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (bitrate))]
+    fn bitrate(mut slf: PyRefMut<'_, Self>, bitrate: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
         })?;
         let builder = builder.bitrate(bitrate).map_err(to_py_err)?;
-        self.inner = Some(builder);
-        Ok(())
+        slf.inner = Some(builder);
+        Ok(slf)
     }
 
     /// Set the encoding quality level
     ///
     /// Higher quality means slower encoding but better audio quality.
-    fn quality(&mut self, quality: Quality) -> PyResult<()> {
-        let builder = self.inner.take().ok_or_else(|| {
+    ///
+    /// Args:
+    ///     quality (Quality | int): A quality preset, or a LAME quality level
+    ///         from 0 (best) to 9 (fastest)
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If the quality level is outside 0-9
+    #[pyo3(signature = (quality))]
+    fn quality<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        quality: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        // `Quality` members are LAME quality levels
+        let level = match quality.extract::<Quality>() {
+            Ok(quality) => lame_sys::Quality::from(quality) as i32,
+            Err(_) => quality.extract()?,
+        };
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.quality_level(level).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the MPEG channel mode
+    ///
+    /// Use `ChannelMode.Mono` with `channels(2)` to downmix stereo input.
+    ///
+    /// Args:
+    ///     mode (ChannelMode): Channel mode
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (mode))]
+    fn mode(mut slf: PyRefMut<'_, Self>, mode: ChannelMode) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.mode(mode.into()).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Force M/S (mid/side) stereo for every frame (default False)
+    ///
+    /// Only has an effect with `ChannelMode.JointStereo`. Can save bits on
+    /// near-mono material such as podcasts at low bitrates.
+    ///
+    /// Args:
+    ///     enabled (bool): Whether to force M/S stereo
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (enabled))]
+    fn force_ms(mut slf: PyRefMut<'_, Self>, enabled: bool) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
         })?;
-        let builder = builder.quality(quality.into()).map_err(to_py_err)?;
-        self.inner = Some(builder);
-        Ok(())
+        let builder = builder.force_ms(enabled).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the M/S masking adjustment, as the CLI's `--ns-msfix`
+    ///
+    /// Args:
+    ///     msfix (float): Non-negative adjustment; LAME picks one by bitrate if unset
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If `msfix` is negative or not finite
+    #[pyo3(signature = (msfix))]
+    fn ms_fix(mut slf: PyRefMut<'_, Self>, msfix: f32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.ms_fix(msfix).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Analyze ReplayGain while encoding, as the CLI's `--replaygain-fast`
+    ///
+    /// Read the result from `LameEncoder.replay_gain` after `flush()`.
+    ///
+    /// Args:
+    ///     enabled (bool): Whether to analyze the input PCM
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (enabled))]
+    fn find_replay_gain(
+        mut slf: PyRefMut<'_, Self>,
+        enabled: bool,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.find_replay_gain(enabled).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Decode the output while encoding to find the peak sample
+    ///
+    /// Read the result from `LameEncoder.peak_sample` after `flush()`.
+    ///
+    /// Args:
+    ///     enabled (bool): Whether to decode on the fly
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If enabled and LAME was built without its decoder (the bundled build)
+    #[pyo3(signature = (enabled))]
+    fn decode_on_the_fly(
+        mut slf: PyRefMut<'_, Self>,
+        enabled: bool,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.decode_on_the_fly(enabled).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the inter-channel masking ratio, as the CLI's `--interch`
+    ///
+    /// Args:
+    ///     ratio (float): Ratio between 0.0 and 1.0; LAME picks one if unset
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If `ratio` is outside 0.0-1.0 or not finite
+    #[pyo3(signature = (ratio))]
+    fn inter_channel_ratio(
+        mut slf: PyRefMut<'_, Self>,
+        ratio: f32,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.inter_channel_ratio(ratio).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
     }
 
     /// Set the VBR (Variable Bit Rate) mode
-    fn vbr_mode(&mut self, mode: VbrMode) -> PyResult<()> {
-        let builder = self.inner.take().ok_or_else(|| {
+    ///
+    /// Args:
+    ///     mode (VbrMode): VBR mode
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (mode))]
+    fn vbr_mode(mut slf: PyRefMut<'_, Self>, mode: VbrMode) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
         })?;
         let builder = builder.vbr_mode(mode.into()).map_err(to_py_err)?;
-        self.inner = Some(builder);
-        Ok(())
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the mean bitrate for ABR mode in kbps (8-320)
+    ///
+    /// Only takes effect with `vbr_mode(VbrMode.Abr)`; `build()` raises
+    /// ValueError if another mode is selected.
+    ///
+    /// Args:
+    ///     kbps (int): Target average bitrate in kbps
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If `kbps` is outside 8-320
+    #[pyo3(signature = (kbps))]
+    fn abr_bitrate(mut slf: PyRefMut<'_, Self>, kbps: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.abr_bitrate(kbps).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Tell LAME the total number of input samples (per channel) up front
+    ///
+    /// When the length is known, LAME writes an accurate frame count into
+    /// the Xing/Info header and improves the bit allocation at the end of
+    /// the stream.
+    ///
+    /// Args:
+    ///     n (int): Total samples per channel
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If `n` does not fit in the platform's `unsigned long`
+    #[pyo3(signature = (n))]
+    fn total_samples(mut slf: PyRefMut<'_, Self>, n: u64) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.total_samples(n).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the minimum bitrate used in VBR mode (8-320 kbps)
+    ///
+    /// Digital silence may still drop below it unless `vbr_enforce_min(True)`
+    /// is set. `build()` raises ValueError if it exceeds `vbr_max_bitrate`.
+    ///
+    /// Args:
+    ///     kbps (int): Minimum bitrate in kbps
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If `kbps` is outside 8-320
+    #[pyo3(signature = (kbps))]
+    fn vbr_min_bitrate(mut slf: PyRefMut<'_, Self>, kbps: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.vbr_min_bitrate(kbps).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the maximum bitrate used in VBR mode (8-320 kbps)
+    ///
+    /// Args:
+    ///     kbps (int): Maximum bitrate in kbps
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If `kbps` is outside 8-320
+    #[pyo3(signature = (kbps))]
+    fn vbr_max_bitrate(mut slf: PyRefMut<'_, Self>, kbps: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.vbr_max_bitrate(kbps).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Strictly enforce the VBR minimum bitrate, even for digital silence
+    ///
+    /// Args:
+    ///     enforce (bool): Whether every frame must meet `vbr_min_bitrate`
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (enforce))]
+    fn vbr_enforce_min(mut slf: PyRefMut<'_, Self>, enforce: bool) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.vbr_enforce_min(enforce).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
     }
 
     /// Set the VBR quality (0=best, 9=worst)
     ///
     /// Only effective when VBR mode is enabled.
-    fn vbr_quality(&mut self, quality: i32) -> PyResult<()> {
-        let builder = self.inner.take().ok_or_else(|| {
+    ///
+    /// Args:
+    ///     quality (int): VBR quality from 0 to 9
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (quality))]
+    fn vbr_quality(mut slf: PyRefMut<'_, Self>, quality: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
         })?;
         let builder = builder.vbr_quality(quality).map_err(to_py_err)?;
-        self.inner = Some(builder);
-        Ok(())
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the lowpass filter cutoff frequency in Hz
+    ///
+    /// 0 lets LAME choose from the bitrate (the default), -1 disables the
+    /// filter. Around 8000 suits voice recordings.
+    ///
+    /// Args:
+    ///     freq_hz (int): Cutoff frequency in Hz, 0 for auto or -1 to disable
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If the value is below -1
+    #[pyo3(signature = (freq_hz))]
+    fn lowpass(mut slf: PyRefMut<'_, Self>, freq_hz: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.lowpass(freq_hz).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the width of the lowpass transition band in Hz
+    ///
+    /// -1 uses LAME's default (15% of the cutoff frequency).
+    ///
+    /// Args:
+    ///     width_hz (int): Transition band width in Hz, or -1 for the default
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If the value is below -1
+    #[pyo3(signature = (width_hz))]
+    fn lowpass_width(mut slf: PyRefMut<'_, Self>, width_hz: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.lowpass_width(width_hz).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the highpass filter cutoff frequency in Hz
+    ///
+    /// 0 lets LAME choose (the default), -1 disables the filter.
+    ///
+    /// Args:
+    ///     freq_hz (int): Cutoff frequency in Hz, 0 for auto or -1 to disable
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If the value is below -1
+    #[pyo3(signature = (freq_hz))]
+    fn highpass(mut slf: PyRefMut<'_, Self>, freq_hz: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.highpass(freq_hz).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set the width of the highpass transition band in Hz
+    ///
+    /// -1 uses LAME's default.
+    ///
+    /// Args:
+    ///     width_hz (int): Transition band width in Hz, or -1 for the default
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If the value is below -1
+    #[pyo3(signature = (width_hz))]
+    fn highpass_width(mut slf: PyRefMut<'_, Self>, width_hz: i32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.highpass_width(width_hz).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Multiply the input by `scale` before encoding (default 1.0)
+    ///
+    /// Useful to bring up quiet recordings. Parts pushed past full scale clip
+    /// when decoded.
+    ///
+    /// Args:
+    ///     scale (float): Gain factor applied to both channels
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If `scale` is not finite
+    #[pyo3(signature = (scale))]
+    fn scale(mut slf: PyRefMut<'_, Self>, scale: f32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.scale(scale).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Multiply the left channel by `scale`, on top of `scale()`
+    ///
+    /// Args:
+    ///     scale (float): Gain factor for the left channel
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If `scale` is not finite
+    #[pyo3(signature = (scale))]
+    fn scale_left(mut slf: PyRefMut<'_, Self>, scale: f32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.scale_left(scale).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Multiply the right channel by `scale`, on top of `scale()`
+    ///
+    /// Args:
+    ///     scale (float): Gain factor for the right channel
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If `scale` is not finite
+    #[pyo3(signature = (scale))]
+    fn scale_right(mut slf: PyRefMut<'_, Self>, scale: f32) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.scale_right(scale).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Use temporal masking (default True); False matches the CLI's `--notemp`
+    ///
+    /// Args:
+    ///     enabled (bool): Whether to use temporal masking
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (enabled))]
+    fn use_temporal_masking(
+        mut slf: PyRefMut<'_, Self>,
+        enabled: bool,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.use_temporal_masking(enabled).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set how short blocks are used
+    ///
+    /// Short blocks reduce pre-echo before drum hits and other transients.
+    ///
+    /// Args:
+    ///     mode (ShortBlockMode): Short block mode
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (mode))]
+    fn short_blocks(
+        mut slf: PyRefMut<'_, Self>,
+        mode: ShortBlockMode,
+    ) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.short_blocks(mode.into()).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Write the Xing/Info frame at the start of the stream (default True)
+    ///
+    /// Players use it for the duration and gapless playback; disable it for
+    /// streaming servers that do not expect it.
+    ///
+    /// Args:
+    ///     enabled (bool): Whether to write the info frame
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    #[pyo3(signature = (enabled))]
+    fn write_vbr_tag(mut slf: PyRefMut<'_, Self>, enabled: bool) -> PyResult<PyRefMut<'_, Self>> {
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.write_vbr_tag(enabled).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
+    }
+
+    /// Set a LAME parameter the builder has no method for
+    ///
+    /// `name` is the part after `lame_set_` in `lame.h`, e.g. `"quant_comp"`
+    /// or `"interChRatio"`. Read it back with `LameEncoder.get_raw()`.
+    ///
+    /// Args:
+    ///     name (str): LAME parameter name
+    ///     value (int | float): New value; float parameters also accept ints
+    ///
+    /// Returns:
+    ///     EncoderBuilder: This builder, for chaining
+    ///
+    /// Raises:
+    ///     ValueError: If the name is unknown, a float is given for an integer
+    ///         parameter, or LAME rejects the value
+    #[pyo3(signature = (name, value))]
+    fn set_raw<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        name: &str,
+        value: &Bound<'_, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let param = RawParam::from_name(name).map_err(to_py_err)?;
+        let value = if value.is_instance_of::<PyFloat>() {
+            RawValue::Float(value.extract()?)
+        } else {
+            RawValue::Int(value.extract()?)
+        };
+
+        let builder = slf.inner.take().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
+        })?;
+        let builder = builder.set_raw(param, value).map_err(to_py_err)?;
+        slf.inner = Some(builder);
+        Ok(slf)
     }
 
     /// Build and initialize the encoder
     ///
-    /// Returns a configured LameEncoder ready for encoding.
+    /// Returns:
+    ///     LameEncoder: A configured encoder ready for encoding
+    ///
+    /// Raises:
+    ///     RuntimeError: If the builder was already used to build an encoder
     fn build(&mut self) -> PyResult<LameEncoder> {
         let builder = self.inner.take().ok_or_else(|| {
             PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Builder already consumed")
         })?;
         let inner = builder.build().map_err(to_py_err)?;
-        Ok(LameEncoder {
-            inner,
-            mp3_buffer: Vec::new(), // Will grow on first use
-        })
+        Ok(LameEncoder::new(EncoderSlot::Owned(inner)))
     }
 
     fn __repr__(&self) -> String {
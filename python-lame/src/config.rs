@@ -0,0 +1,70 @@
+use crate::encoder::LameEncoder;
+use crate::error::{to_py_err, EncodingError};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
+
+/// Settings of an encoder, or the settings a file was encoded with
+fn config_of(source: &Bound<'_, PyAny>) -> PyResult<lame_sys::EncoderConfig> {
+    if let Ok(encoder) = source.downcast::<LameEncoder>() {
+        let mut encoder = encoder
+            .try_borrow_mut()
+            .map_err(|_| EncodingError::new_err("encoder is busy in another thread"))?;
+        return Ok(encoder.encoder_mut()?.config());
+    }
+    if let Ok(data) = source.downcast::<PyBytes>() {
+        return lame_sys::EncoderConfig::from_lametag(data.as_bytes()).map_err(to_py_err);
+    }
+    Err(PyTypeError::new_err(format!(
+        "expected a LameEncoder or MP3 bytes, got {}",
+        source.get_type().name()?
+    )))
+}
+
+/// Compare the settings of two encoders or MP3 files
+///
+/// Helps explain why two files sound different. Each argument is either an
+/// encoder, or the start of an MP3 file (bytes) whose LAME tag is parsed for
+/// the settings it was encoded with. ID3v2 tags at the start are skipped.
+///
+/// Only the sample rate, channel count, bitrate, quality and VBR settings are
+/// compared; for files the sample rate is the output rate.
+///
+/// Args:
+///     a (LameEncoder | bytes): First encoder or MP3 data
+///     b (LameEncoder | bytes): Second encoder or MP3 data
+///
+/// Returns:
+///     list[dict]: One `{"field": str, "a": str, "b": str}` dict per setting
+///         that differs, empty if none do; unset values read `"default"`
+///
+/// Raises:
+///     ValueError: If MP3 data does not start with a LAME tag
+///     TypeError: If an argument is neither an encoder nor bytes
+///
+/// Example:
+///     ```python
+///     with open("a.mp3", "rb") as f:
+///         head = f.read(64 * 1024)
+///     for diff in lame.diff_configs(encoder, head):
+///         print(f"{diff['field']}: {diff['a']} != {diff['b']}")
+///     ```
+#[pyfunction]
+#[pyo3(signature = (a, b))]
+pub fn diff_configs<'py>(
+    py: Python<'py>,
+    a: &Bound<'py, PyAny>,
+    b: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyList>> {
+    let diffs = config_of(a)?.diff(&config_of(b)?);
+
+    let list = PyList::empty_bound(py);
+    for diff in diffs {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("field", diff.field)?;
+        dict.set_item("a", diff.a)?;
+        dict.set_item("b", diff.b)?;
+        list.append(dict)?;
+    }
+    Ok(list)
+}
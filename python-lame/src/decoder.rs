@@ -0,0 +1,58 @@
+//! Placeholders for the MP3 decoding API
+//!
+//! lame-sys builds LAME with `--disable-decoder`, so this module has no
+//! decoder to wrap. The names are still registered so that code written
+//! against a decoder-enabled build fails with a clear `NotImplementedError`
+//! instead of an `AttributeError`, and can check `lame.HAS_DECODER` first.
+
+use pyo3::exceptions::PyNotImplementedError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+
+/// Whether this build of the module can decode MP3
+pub const HAS_DECODER: bool = false;
+
+fn unavailable() -> PyErr {
+    PyNotImplementedError::new_err(
+        "this build of lame was compiled without the decoder; \
+         rebuild lame-sys with the LAME decoder enabled",
+    )
+}
+
+/// MP3 decoder (not available in this build)
+///
+/// Constructing it raises NotImplementedError; check `lame.HAS_DECODER` first.
+#[pyclass]
+pub struct Mp3Decoder;
+
+#[pymethods]
+impl Mp3Decoder {
+    #[new]
+    #[pyo3(signature = (*_args, **_kwargs))]
+    fn new(_args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        Err(unavailable())
+    }
+}
+
+/// Decode an MP3 file to PCM (not available in this build)
+///
+/// Raises:
+///     NotImplementedError: Always; check `lame.HAS_DECODER` first
+#[pyfunction]
+#[pyo3(signature = (*_args, **_kwargs))]
+pub fn decode_file(
+    _args: &Bound<'_, PyTuple>,
+    _kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<()> {
+    Err(unavailable())
+}
+
+/// Re-encode an MP3 file with new settings (not available in this build)
+///
+/// Raises:
+///     NotImplementedError: Always; check `lame.HAS_DECODER` first
+#[pyfunction]
+#[pyo3(signature = (*_args, **_kwargs))]
+pub fn transcode(_args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<()> {
+    Err(unavailable())
+}
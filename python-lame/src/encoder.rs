@@ -1,16 +1,25 @@
 use crate::builder::EncoderBuilder;
-use crate::error::to_py_err;
-use crate::id3::Id3Tag;
+use crate::enums::{ChannelMode, VbrMode};
+use crate::error::{to_py_err, EncodingError};
+use crate::id3::{self, Id3Tag};
+use lame_sys::watchdog::CancelToken;
+use lame_sys::{RawParam, RawValue};
 use numpy::PyReadonlyArray1;
 use pyo3::prelude::*;
 use pyo3::types::PyBytes;
+use std::cell::UnsafeCell;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 /// LAME MP3 Encoder
 ///
 /// Encoder that automatically releases the GIL during encoding
 /// operations, allowing other Python threads to run concurrently.
 ///
-/// Note: Each encoder instance should only be used from a single Python thread.
+/// Note: Each encoder instance should only be used from one Python thread at a
+/// time. A call made while another thread is inside `encode*()` or `flush()` on
+/// the same encoder raises `EncodingError` instead of corrupting its state.
 ///
 /// # Example
 ///
@@ -23,22 +32,298 @@ use pyo3::types::PyBytes;
 ///     .build()
 /// )
 ///
-/// pcm = [0] * 1152  # Input PCM samples
-/// mp3_data = encoder.encode_mono(pcm)
+/// pcm = np.zeros(1152, dtype=np.int16)  # Input PCM samples
+/// mp3_data = encoder.encode_mono_numpy(pcm)
 /// final_data = encoder.flush()
 /// ```
-#[pyclass(unsendable)]
+#[pyclass]
 pub struct LameEncoder {
-    pub(crate) inner: lame_sys::LameEncoder,
+    state: UnsafeCell<EncoderState>,
+    /// Set for the duration of each encode/flush call (see `enter()`)
+    busy: AtomicBool,
+}
+
+/// Mutable encoder state, only touched while the busy flag is held
+/// or through `&mut LameEncoder`
+pub(crate) struct EncoderState {
+    pub(crate) inner: EncoderSlot,
     // Reusable buffer for MP3 output to avoid repeated allocations
     pub(crate) mp3_buffer: Vec<u8>,
+    /// Limit set from Python; the encoder itself never splits less finely
+    /// than `SIGNAL_CHECK_SAMPLES`
+    max_samples_per_call: Option<usize>,
+    /// Error raised by a signal handler during the current call
+    interrupt: InterruptSlot,
+    /// Set once a call was interrupted; the MP3 stream is incomplete
+    poisoned: bool,
+}
+
+/// Exclusive access to the encoder state for one encode/flush call
+///
+/// Clears the busy flag when dropped.
+struct BusyGuard<'a> {
+    flag: &'a AtomicBool,
+    state: &'a UnsafeCell<EncoderState>,
+}
+
+impl std::ops::Deref for BusyGuard<'_> {
+    type Target = EncoderState;
+
+    fn deref(&self) -> &EncoderState {
+        // SAFETY: the busy flag gives this guard exclusive access to the state
+        unsafe { &*self.state.get() }
+    }
+}
+
+impl std::ops::DerefMut for BusyGuard<'_> {
+    fn deref_mut(&mut self) -> &mut EncoderState {
+        // SAFETY: the busy flag gives this guard exclusive access to the state
+        unsafe { &mut *self.state.get() }
+    }
+}
+
+impl Drop for BusyGuard<'_> {
+    fn drop(&mut self) {
+        self.flag.store(false, Ordering::Release);
+    }
+}
+
+/// Exclusive reference that can be moved into `Python::allow_threads`
+///
+/// Invariant: the pointee outlives every use of the pointer, and nothing
+/// else reads or writes it until the pointer is consumed. Encode methods
+/// create these through a `BusyGuard` and leave the guard alive and
+/// untouched until `allow_threads` returns. `into_mut` consumes the pointer,
+/// so each one yields at most one `&mut T`.
+struct SendPtr<T: ?Sized>(NonNull<T>);
+
+// SAFETY: a SendPtr stands in for a `&mut T` moved to another thread, which
+// is sound exactly when `&mut T` is Send, i.e. when T is Send
+unsafe impl<T: ?Sized + Send> Send for SendPtr<T> {}
+
+impl<T: ?Sized> SendPtr<T> {
+    fn new(value: &mut T) -> Self {
+        Self(NonNull::from(value))
+    }
+
+    /// Turn the pointer back into the exclusive reference it was made from
+    ///
+    /// # Safety
+    ///
+    /// The invariant on `SendPtr` must hold for all of `'a`.
+    unsafe fn into_mut<'a>(self) -> &'a mut T {
+        // SAFETY: the pointer came from a `&mut T`; the caller guarantees it
+        // is still alive and not accessed elsewhere during 'a
+        unsafe { &mut *self.0.as_ptr() }
+    }
+}
+
+/// Output buffer size kept between calls (enough for 64 frames per call)
+const MP3_BUFFER_RETAIN_LEN: usize = lame_sys::LameEncoder::mp3_buffer_size(1152 * 64);
+
+/// Samples per channel handed to LAME between two checks for Ctrl-C
+///
+/// About 2.6 seconds of 44.1 kHz audio, a few milliseconds of encoding work.
+const SIGNAL_CHECK_SAMPLES: usize = 1152 * 100;
+
+/// Ownership of the underlying Rust encoder
+pub(crate) enum EncoderSlot {
+    /// Created by `EncoderBuilder.build()`
+    Owned(lame_sys::LameEncoder),
+    /// Borrowed from an `EncoderPool`; returned on release
+    Pooled(lame_sys::pool::PooledEncoder),
+    /// Pooled encoder that has already been returned
+    Released,
+    /// Moved into an `Mp3Buffer`
+    Moved,
+}
+
+/// Error raised by a signal handler while LAME runs without the GIL
+pub(crate) type InterruptSlot = Arc<Mutex<Option<PyErr>>>;
+
+impl LameEncoder {
+    pub(crate) fn new(inner: EncoderSlot) -> Self {
+        let mut state = EncoderState {
+            inner,
+            mp3_buffer: Vec::new(), // Will grow on first use
+            max_samples_per_call: None,
+            interrupt: Arc::new(Mutex::new(None)),
+            poisoned: false,
+        };
+        state.install_signal_check();
+        Self {
+            state: UnsafeCell::new(state),
+            busy: AtomicBool::new(false),
+        }
+    }
+
+    /// Claim the encoder for one encode/flush call
+    ///
+    /// Encode methods take `&self` and release the GIL, so two Python threads
+    /// can enter them at once. The flag makes the second one fail fast; in the
+    /// single-threaded case this costs one atomic swap.
+    fn enter(&self) -> PyResult<BusyGuard<'_>> {
+        if self.busy.swap(true, Ordering::Acquire) {
+            return Err(EncodingError::new_err(
+                "encoder is busy in another thread; use one encoder per thread or lame.EncoderPool",
+            ));
+        }
+        // `&mut self` methods can't overlap with the guard: PyO3 refuses a
+        // mutable borrow while `&self` borrows exist
+        let guard = BusyGuard {
+            flag: &self.busy,
+            state: &self.state,
+        };
+        if guard.poisoned {
+            return Err(EncodingError::new_err(
+                "encoder was interrupted mid-call and its output is incomplete; create a new encoder",
+            ));
+        }
+        Ok(guard)
+    }
+
+    /// Get the underlying encoder mutably, failing if it was returned to its pool
+    pub(crate) fn encoder_mut(&mut self) -> PyResult<&mut lame_sys::LameEncoder> {
+        self.state.get_mut().encoder_mut()
+    }
+
+    /// Move the underlying encoder out, leaving this object unusable
+    ///
+    /// Returns the encoder together with the slot its Ctrl-C check reports
+    /// to. Only encoders created by `EncoderBuilder.build()` can be moved;
+    /// pooled encoders belong to their pool.
+    pub(crate) fn take_owned(&mut self) -> PyResult<(lame_sys::LameEncoder, InterruptSlot)> {
+        let state = self.state.get_mut();
+        if state.poisoned {
+            return Err(EncodingError::new_err(
+                "encoder was interrupted mid-call and its output is incomplete; create a new encoder",
+            ));
+        }
+        match std::mem::replace(&mut state.inner, EncoderSlot::Moved) {
+            EncoderSlot::Owned(encoder) => Ok((encoder, Arc::clone(&state.interrupt))),
+            other => {
+                state.inner = other;
+                // Released or already moved
+                state.encoder_mut()?;
+                Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "pooled encoders cannot be moved; use an encoder from EncoderBuilder.build()",
+                ))
+            }
+        }
+    }
+}
+
+impl EncoderState {
+    /// Check for pending signals (Ctrl-C) between the LAME calls of one encode
+    ///
+    /// The GIL is released for the whole call, so without this KeyboardInterrupt
+    /// would only be delivered once a multi-minute buffer finished encoding.
+    /// Inputs are split every `SIGNAL_CHECK_SAMPLES`; between the pieces the
+    /// progress hook briefly re-acquires the GIL to run signal handlers, and a
+    /// handler error cancels the rest of the call.
+    fn install_signal_check(&mut self) {
+        let interrupt = Arc::clone(&self.interrupt);
+        let token = CancelToken::new();
+        let hook_token = token.clone();
+
+        if let Ok(encoder) = self.encoder_mut() {
+            encoder.set_cancel_token(Some(token));
+            encoder.set_progress_hook(Some(move |_| {
+                Python::with_gil(|py| {
+                    if let Err(err) = py.check_signals() {
+                        *interrupt.lock().unwrap_or_else(|e| e.into_inner()) = Some(err);
+                        hook_token.cancel();
+                    }
+                })
+            }));
+            // Only fails for a zero limit
+            let _ = encoder.set_max_samples_per_call(Some(SIGNAL_CHECK_SAMPLES));
+        }
+    }
+
+    /// Turn the error of a cancelled call into the pending signal error
+    ///
+    /// Marks the encoder as poisoned: part of the input was consumed and
+    /// nothing was flushed, so later calls would produce a corrupt stream.
+    fn interrupted(&mut self, err: PyErr) -> PyErr {
+        let signal = self
+            .interrupt
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        match signal {
+            Some(signal) => {
+                self.poisoned = true;
+                signal
+            }
+            None => err,
+        }
+    }
+
+    /// Size the reusable output buffer for the worst case of `samples_per_channel`
+    ///
+    /// Repeated small calls reuse the same allocation. Capacity above
+    /// `MP3_BUFFER_RETAIN_LEN` left behind by an unusually large call is released
+    /// on the next small call, so long-lived encoders don't pin it forever.
+    pub(crate) fn reserve_mp3_buffer(&mut self, samples_per_channel: usize) {
+        let required_size = lame_sys::LameEncoder::mp3_buffer_size(samples_per_channel);
+        if self.mp3_buffer.len() < required_size {
+            self.mp3_buffer.resize(required_size, 0);
+        } else if required_size <= MP3_BUFFER_RETAIN_LEN
+            && self.mp3_buffer.len() > MP3_BUFFER_RETAIN_LEN
+        {
+            self.mp3_buffer.truncate(MP3_BUFFER_RETAIN_LEN);
+            self.mp3_buffer.shrink_to_fit();
+        }
+    }
+
+    /// Hand the encoder and the output buffer to a call that releases the GIL
+    ///
+    /// The caller must keep its `BusyGuard` alive and leave the state alone
+    /// until both pointers are consumed. The buffer pointer targets the Vec's
+    /// heap allocation and is taken first, so borrowing the state again for
+    /// the encoder does not invalidate it.
+    fn detach(&mut self) -> PyResult<(SendPtr<lame_sys::LameEncoder>, SendPtr<[u8]>)> {
+        let mp3_buffer = SendPtr::new(self.mp3_buffer.as_mut_slice());
+        let encoder = SendPtr::new(self.encoder_mut()?);
+        Ok((encoder, mp3_buffer))
+    }
+
+    /// Get the underlying encoder, failing if it was returned to its pool
+    fn encoder(&self) -> PyResult<&lame_sys::LameEncoder> {
+        match &self.inner {
+            EncoderSlot::Owned(encoder) => Ok(encoder),
+            EncoderSlot::Pooled(encoder) => Ok(encoder),
+            EncoderSlot::Released => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                "Encoder has been returned to its pool",
+            )),
+            EncoderSlot::Moved => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                "Encoder has been moved into an Mp3Buffer",
+            )),
+        }
+    }
+
+    /// Get the underlying encoder mutably, failing if it was returned to its pool
+    fn encoder_mut(&mut self) -> PyResult<&mut lame_sys::LameEncoder> {
+        match &mut self.inner {
+            EncoderSlot::Owned(encoder) => Ok(encoder),
+            EncoderSlot::Pooled(encoder) => Ok(encoder),
+            EncoderSlot::Released => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                "Encoder has been returned to its pool",
+            )),
+            EncoderSlot::Moved => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                "Encoder has been moved into an Mp3Buffer",
+            )),
+        }
+    }
 }
 
 #[pymethods]
 impl LameEncoder {
     /// Create a new encoder builder
     ///
-    /// Returns an EncoderBuilder for configuring encoder parameters.
+    /// Returns:
+    ///     EncoderBuilder: A builder for configuring encoder parameters
     #[staticmethod]
     fn builder() -> PyResult<EncoderBuilder> {
         EncoderBuilder::new()
@@ -47,20 +332,23 @@ impl LameEncoder {
     /// Encode stereo PCM data from bytes (for backward compatibility)
     ///
     /// Args:
-    ///     pcm_left: Left channel samples as bytes (i16 little-endian)
-    ///     pcm_right: Right channel samples as bytes (i16 little-endian)
+    ///     pcm_left (bytes): Left channel samples (i16 little-endian)
+    ///     pcm_right (bytes): Right channel samples (i16 little-endian)
     ///
     /// Returns:
-    ///     Encoded MP3 data as bytes
+    ///     bytes: Encoded MP3 data
     ///
     /// Note: For best performance, use `encode_numpy()` instead.
     /// Releases the GIL during encoding for better concurrency.
+    #[pyo3(signature = (pcm_left, pcm_right))]
     fn encode<'py>(
-        &mut self,
+        &self,
         py: Python<'py>,
         pcm_left: &Bound<'py, PyBytes>,
         pcm_right: &Bound<'py, PyBytes>,
     ) -> PyResult<Bound<'py, PyBytes>> {
+        let mut state = self.enter()?;
+
         // Get read-only byte slices from PyBytes
         let left_bytes = pcm_left.as_bytes();
         let right_bytes = pcm_right.as_bytes();
@@ -78,49 +366,48 @@ impl LameEncoder {
         })?;
 
         // Ensure buffer is large enough (reuse if possible)
-        let required_size = pcm_left_slice.len() * 5 / 4 + 7200;
-        if self.mp3_buffer.len() < required_size {
-            self.mp3_buffer.resize(required_size, 0);
-        }
+        state.reserve_mp3_buffer(pcm_left_slice.len());
 
         // Clone data to pass ownership to the closure
         let left_vec = pcm_left_slice.to_vec();
         let right_vec = pcm_right_slice.to_vec();
 
-        let encoder_ptr = &mut self.inner as *mut lame_sys::LameEncoder as usize;
-        let buffer_ptr = self.mp3_buffer.as_mut_ptr() as usize;
-        let buffer_len = self.mp3_buffer.len();
+        let (encoder, mp3_buffer) = state.detach()?;
 
         // Release GIL during encoding
-        let bytes_written = py.allow_threads(move || {
-            // SAFETY: We hold a mutable reference to self, so no other thread can access it
-            let encoder = unsafe { &mut *(encoder_ptr as *mut lame_sys::LameEncoder) };
-            let mp3_buffer =
-                unsafe { std::slice::from_raw_parts_mut(buffer_ptr as *mut u8, buffer_len) };
-            encoder
-                .encode(&left_vec, &right_vec, mp3_buffer)
-                .map_err(to_py_err)
-        })?;
+        let bytes_written = py
+            .allow_threads(move || {
+                // SAFETY: `state` holds the busy flag and is not used again until
+                // allow_threads returns, so these are the only references
+                let (encoder, mp3_buffer) = unsafe { (encoder.into_mut(), mp3_buffer.into_mut()) };
+                encoder
+                    .encode(&left_vec, &right_vec, mp3_buffer)
+                    .map_err(to_py_err)
+            })
+            .map_err(|err| state.interrupted(err))?;
 
         // Return only the written portion as Python bytes
-        Ok(PyBytes::new_bound(py, &self.mp3_buffer[..bytes_written]))
+        Ok(PyBytes::new_bound(py, &state.mp3_buffer[..bytes_written]))
     }
 
     /// Encode interleaved stereo PCM data from bytes (for backward compatibility)
     ///
     /// Args:
-    ///     pcm_interleaved: Interleaved samples as bytes (L, R, L, R, ... in i16 little-endian)
+    ///     pcm_interleaved (bytes): Interleaved samples (L, R, L, R, ... in i16 little-endian)
     ///
     /// Returns:
-    ///     Encoded MP3 data as bytes
+    ///     bytes: Encoded MP3 data
     ///
     /// Note: For best performance, use `encode_interleaved_numpy()` instead.
     /// Releases the GIL during encoding for better concurrency.
+    #[pyo3(signature = (pcm_interleaved))]
     fn encode_interleaved<'py>(
-        &mut self,
+        &self,
         py: Python<'py>,
         pcm_interleaved: &Bound<'py, PyBytes>,
     ) -> PyResult<Bound<'py, PyBytes>> {
+        let mut state = self.enter()?;
+
         // Get read-only byte slice from PyBytes
         let pcm_bytes = pcm_interleaved.as_bytes();
 
@@ -132,47 +419,47 @@ impl LameEncoder {
         })?;
 
         // Ensure buffer is large enough (reuse if possible)
-        let required_size = pcm_slice.len() * 5 / 4 + 7200;
-        if self.mp3_buffer.len() < required_size {
-            self.mp3_buffer.resize(required_size, 0);
-        }
+        state.reserve_mp3_buffer(pcm_slice.len() / 2);
 
         // Clone data to pass ownership to the closure
         let pcm_vec = pcm_slice.to_vec();
 
-        let encoder_ptr = &mut self.inner as *mut lame_sys::LameEncoder as usize;
-        let buffer_ptr = self.mp3_buffer.as_mut_ptr() as usize;
-        let buffer_len = self.mp3_buffer.len();
-
-        let bytes_written = py.allow_threads(move || {
-            let encoder = unsafe { &mut *(encoder_ptr as *mut lame_sys::LameEncoder) };
-            let mp3_buffer =
-                unsafe { std::slice::from_raw_parts_mut(buffer_ptr as *mut u8, buffer_len) };
-            encoder
-                .encode_interleaved(&pcm_vec, mp3_buffer)
-                .map_err(to_py_err)
-        })?;
+        let (encoder, mp3_buffer) = state.detach()?;
+
+        let bytes_written = py
+            .allow_threads(move || {
+                // SAFETY: `state` holds the busy flag and is not used again until
+                // allow_threads returns, so these are the only references
+                let (encoder, mp3_buffer) = unsafe { (encoder.into_mut(), mp3_buffer.into_mut()) };
+                encoder
+                    .encode_interleaved(&pcm_vec, mp3_buffer)
+                    .map_err(to_py_err)
+            })
+            .map_err(|err| state.interrupted(err))?;
 
         // Return only the written portion as Python bytes
-        Ok(PyBytes::new_bound(py, &self.mp3_buffer[..bytes_written]))
+        Ok(PyBytes::new_bound(py, &state.mp3_buffer[..bytes_written]))
     }
 
     /// Encode mono PCM data from bytes (for backward compatibility)
     ///
     /// Args:
-    ///     pcm: Mono samples as bytes (i16 little-endian format)
+    ///     pcm (bytes): Mono samples (i16 little-endian format)
     ///
     /// Returns:
-    ///     Encoded MP3 data as bytes
+    ///     bytes: Encoded MP3 data
     ///
     /// Note: For best performance, use `encode_mono_numpy()` instead.
     /// This method converts bytes to i16 and clones data for thread safety.
     /// Releases the GIL during encoding for better concurrency.
+    #[pyo3(signature = (pcm))]
     fn encode_mono<'py>(
-        &mut self,
+        &self,
         py: Python<'py>,
         pcm: &Bound<'py, PyBytes>,
     ) -> PyResult<Bound<'py, PyBytes>> {
+        let mut state = self.enter()?;
+
         // Get read-only byte slice from PyBytes
         let pcm_bytes = pcm.as_bytes();
 
@@ -184,37 +471,33 @@ impl LameEncoder {
         })?;
 
         // Ensure buffer is large enough (reuse if possible)
-        let required_size = pcm_slice.len() * 5 / 4 + 7200;
-        if self.mp3_buffer.len() < required_size {
-            self.mp3_buffer.resize(required_size, 0);
-        }
+        state.reserve_mp3_buffer(pcm_slice.len());
 
         // Clone data to pass ownership to the closure (avoids raw pointer issues)
         let pcm_vec = pcm_slice.to_vec();
 
-        let encoder_ptr = &mut self.inner as *mut lame_sys::LameEncoder as usize;
-        let buffer_ptr = self.mp3_buffer.as_mut_ptr() as usize;
-        let buffer_len = self.mp3_buffer.len();
+        let (encoder, mp3_buffer) = state.detach()?;
 
-        let bytes_written = py.allow_threads(move || {
-            // SAFETY: We hold a mutable reference to self, so no other thread can access it
-            let encoder = unsafe { &mut *(encoder_ptr as *mut lame_sys::LameEncoder) };
-            let mp3_buffer =
-                unsafe { std::slice::from_raw_parts_mut(buffer_ptr as *mut u8, buffer_len) };
-            encoder.encode_mono(&pcm_vec, mp3_buffer).map_err(to_py_err)
-        })?;
+        let bytes_written = py
+            .allow_threads(move || {
+                // SAFETY: `state` holds the busy flag and is not used again until
+                // allow_threads returns, so these are the only references
+                let (encoder, mp3_buffer) = unsafe { (encoder.into_mut(), mp3_buffer.into_mut()) };
+                encoder.encode_mono(&pcm_vec, mp3_buffer).map_err(to_py_err)
+            })
+            .map_err(|err| state.interrupted(err))?;
 
         // Return only the written portion as Python bytes
-        Ok(PyBytes::new_bound(py, &self.mp3_buffer[..bytes_written]))
+        Ok(PyBytes::new_bound(py, &state.mp3_buffer[..bytes_written]))
     }
 
     /// Encode mono PCM data from NumPy array (recommended, zero-copy)
     ///
     /// Args:
-    ///     pcm: Mono samples as NumPy array with dtype=np.int16
+    ///     pcm (numpy.ndarray): Mono samples with dtype=np.int16
     ///
     /// Returns:
-    ///     Encoded MP3 data as bytes
+    ///     bytes: Encoded MP3 data
     ///
     /// Note: This method is the recommended way to encode audio data.
     /// It provides zero-copy access to NumPy arrays and is completely safe (no unsafe code).
@@ -223,135 +506,131 @@ impl LameEncoder {
     /// Example:
     ///     ```python
     ///     import numpy as np
-    ///     pcm = np.array([0, 100, -100, ...], dtype=np.int16)
+    ///     pcm = np.zeros(1152, dtype=np.int16)
     ///     mp3_data = encoder.encode_mono_numpy(pcm)
     ///     ```
+    #[pyo3(signature = (pcm))]
     fn encode_mono_numpy<'py>(
-        &mut self,
+        &self,
         py: Python<'py>,
         pcm: PyReadonlyArray1<'py, i16>,
     ) -> PyResult<Bound<'py, PyBytes>> {
+        let mut state = self.enter()?;
+
         // ✅ Zero-copy access to NumPy array (completely safe, no unsafe needed)
         let pcm_slice = pcm.as_slice()?;
 
         // Ensure buffer is large enough (reuse if possible)
-        let required_size = pcm_slice.len() * 5 / 4 + 7200;
-        if self.mp3_buffer.len() < required_size {
-            self.mp3_buffer.resize(required_size, 0);
-        }
+        state.reserve_mp3_buffer(pcm_slice.len());
 
         // Clone data to pass ownership to the closure (avoids raw pointers)
         let pcm_vec = pcm_slice.to_vec();
 
-        let encoder_ptr = &mut self.inner as *mut lame_sys::LameEncoder as usize;
-        let buffer_ptr = self.mp3_buffer.as_mut_ptr() as usize;
-        let buffer_len = self.mp3_buffer.len();
+        let (encoder, mp3_buffer) = state.detach()?;
 
         // Release GIL during encoding
-        let bytes_written = py.allow_threads(move || {
-            // SAFETY: Same pattern as the original implementation
-            // We hold a mutable reference to self, so no other thread can access it
-            let encoder = unsafe { &mut *(encoder_ptr as *mut lame_sys::LameEncoder) };
-            let mp3_buffer =
-                unsafe { std::slice::from_raw_parts_mut(buffer_ptr as *mut u8, buffer_len) };
-            encoder.encode_mono(&pcm_vec, mp3_buffer).map_err(to_py_err)
-        })?;
+        let bytes_written = py
+            .allow_threads(move || {
+                // SAFETY: `state` holds the busy flag and is not used again until
+                // allow_threads returns, so these are the only references
+                let (encoder, mp3_buffer) = unsafe { (encoder.into_mut(), mp3_buffer.into_mut()) };
+                encoder.encode_mono(&pcm_vec, mp3_buffer).map_err(to_py_err)
+            })
+            .map_err(|err| state.interrupted(err))?;
 
         // Return only the written portion as Python bytes
-        Ok(PyBytes::new_bound(py, &self.mp3_buffer[..bytes_written]))
+        Ok(PyBytes::new_bound(py, &state.mp3_buffer[..bytes_written]))
     }
 
     /// Encode stereo PCM data from NumPy arrays (recommended, zero-copy)
     ///
     /// Args:
-    ///     pcm_left: Left channel samples as NumPy array with dtype=np.int16
-    ///     pcm_right: Right channel samples as NumPy array with dtype=np.int16
+    ///     pcm_left (numpy.ndarray): Left channel samples with dtype=np.int16
+    ///     pcm_right (numpy.ndarray): Right channel samples with dtype=np.int16
     ///
     /// Returns:
-    ///     Encoded MP3 data as bytes
+    ///     bytes: Encoded MP3 data
     ///
     /// Note: This method is the recommended way to encode stereo audio.
     /// It provides zero-copy access to NumPy arrays and is completely safe.
+    #[pyo3(signature = (pcm_left, pcm_right))]
     fn encode_numpy<'py>(
-        &mut self,
+        &self,
         py: Python<'py>,
         pcm_left: PyReadonlyArray1<'py, i16>,
         pcm_right: PyReadonlyArray1<'py, i16>,
     ) -> PyResult<Bound<'py, PyBytes>> {
+        let mut state = self.enter()?;
+
         // ✅ Zero-copy access to NumPy arrays (no unsafe needed)
         let pcm_left_slice = pcm_left.as_slice()?;
         let pcm_right_slice = pcm_right.as_slice()?;
 
         // Ensure buffer is large enough
-        let required_size = pcm_left_slice.len() * 5 / 4 + 7200;
-        if self.mp3_buffer.len() < required_size {
-            self.mp3_buffer.resize(required_size, 0);
-        }
+        state.reserve_mp3_buffer(pcm_left_slice.len());
 
         // Clone data to pass ownership to the closure
         let left_vec = pcm_left_slice.to_vec();
         let right_vec = pcm_right_slice.to_vec();
 
-        let encoder_ptr = &mut self.inner as *mut lame_sys::LameEncoder as usize;
-        let buffer_ptr = self.mp3_buffer.as_mut_ptr() as usize;
-        let buffer_len = self.mp3_buffer.len();
+        let (encoder, mp3_buffer) = state.detach()?;
 
         // Release GIL during encoding
-        let bytes_written = py.allow_threads(move || {
-            // SAFETY: Same pattern as the original implementation
-            let encoder = unsafe { &mut *(encoder_ptr as *mut lame_sys::LameEncoder) };
-            let mp3_buffer =
-                unsafe { std::slice::from_raw_parts_mut(buffer_ptr as *mut u8, buffer_len) };
-            encoder
-                .encode(&left_vec, &right_vec, mp3_buffer)
-                .map_err(to_py_err)
-        })?;
-
-        Ok(PyBytes::new_bound(py, &self.mp3_buffer[..bytes_written]))
+        let bytes_written = py
+            .allow_threads(move || {
+                // SAFETY: `state` holds the busy flag and is not used again until
+                // allow_threads returns, so these are the only references
+                let (encoder, mp3_buffer) = unsafe { (encoder.into_mut(), mp3_buffer.into_mut()) };
+                encoder
+                    .encode(&left_vec, &right_vec, mp3_buffer)
+                    .map_err(to_py_err)
+            })
+            .map_err(|err| state.interrupted(err))?;
+
+        Ok(PyBytes::new_bound(py, &state.mp3_buffer[..bytes_written]))
     }
 
     /// Encode interleaved stereo PCM data from NumPy array (recommended, zero-copy)
     ///
     /// Args:
-    ///     pcm_interleaved: Interleaved samples as NumPy array (L, R, L, R, ...) with dtype=np.int16
+    ///     pcm_interleaved (numpy.ndarray): Interleaved samples (L, R, L, R, ...) with dtype=np.int16
     ///
     /// Returns:
-    ///     Encoded MP3 data as bytes
+    ///     bytes: Encoded MP3 data
     ///
     /// Note: This method is the recommended way for interleaved stereo audio.
+    #[pyo3(signature = (pcm_interleaved))]
     fn encode_interleaved_numpy<'py>(
-        &mut self,
+        &self,
         py: Python<'py>,
         pcm_interleaved: PyReadonlyArray1<'py, i16>,
     ) -> PyResult<Bound<'py, PyBytes>> {
+        let mut state = self.enter()?;
+
         // ✅ Zero-copy access to NumPy array (no unsafe needed)
         let pcm_slice = pcm_interleaved.as_slice()?;
 
         // Ensure buffer is large enough
-        let required_size = pcm_slice.len() * 5 / 4 + 7200;
-        if self.mp3_buffer.len() < required_size {
-            self.mp3_buffer.resize(required_size, 0);
-        }
+        state.reserve_mp3_buffer(pcm_slice.len() / 2);
 
         // Clone data to pass ownership to the closure
         let pcm_vec = pcm_slice.to_vec();
 
-        let encoder_ptr = &mut self.inner as *mut lame_sys::LameEncoder as usize;
-        let buffer_ptr = self.mp3_buffer.as_mut_ptr() as usize;
-        let buffer_len = self.mp3_buffer.len();
+        let (encoder, mp3_buffer) = state.detach()?;
 
         // Release GIL during encoding
-        let bytes_written = py.allow_threads(move || {
-            // SAFETY: Same pattern as the original implementation
-            let encoder = unsafe { &mut *(encoder_ptr as *mut lame_sys::LameEncoder) };
-            let mp3_buffer =
-                unsafe { std::slice::from_raw_parts_mut(buffer_ptr as *mut u8, buffer_len) };
-            encoder
-                .encode_interleaved(&pcm_vec, mp3_buffer)
-                .map_err(to_py_err)
-        })?;
-
-        Ok(PyBytes::new_bound(py, &self.mp3_buffer[..bytes_written]))
+        let bytes_written = py
+            .allow_threads(move || {
+                // SAFETY: `state` holds the busy flag and is not used again until
+                // allow_threads returns, so these are the only references
+                let (encoder, mp3_buffer) = unsafe { (encoder.into_mut(), mp3_buffer.into_mut()) };
+                encoder
+                    .encode_interleaved(&pcm_vec, mp3_buffer)
+                    .map_err(to_py_err)
+            })
+            .map_err(|err| state.interrupted(err))?;
+
+        Ok(PyBytes::new_bound(py, &state.mp3_buffer[..bytes_written]))
     }
 
     /// Flush remaining data from encoder
@@ -360,28 +639,274 @@ impl LameEncoder {
     /// all data is written to the output.
     ///
     /// Returns:
-    ///     Final MP3 data as bytes
+    ///     bytes: Final MP3 data
     ///
     /// Note: Releases the GIL during flushing for better concurrency.
-    fn flush<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
-        let mut mp3_buffer = vec![0u8; 7200];
+    fn flush<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let mut state = self.enter()?;
+        let mut mp3_buffer = vec![0u8; lame_sys::LameEncoder::mp3_buffer_size(0)];
 
-        let encoder_ptr = &mut self.inner as *mut lame_sys::LameEncoder as usize;
+        let encoder = SendPtr::new(state.encoder_mut()?);
 
-        let bytes_written = py.allow_threads(|| {
-            let encoder = unsafe { &mut *(encoder_ptr as *mut lame_sys::LameEncoder) };
-            encoder.flush(&mut mp3_buffer).map_err(to_py_err)
-        })?;
+        let bytes_written = py
+            .allow_threads(|| {
+                // SAFETY: `state` holds the busy flag and is not used again until
+                // allow_threads returns
+                let encoder = unsafe { encoder.into_mut() };
+                encoder.flush(&mut mp3_buffer).map_err(to_py_err)
+            })
+            .map_err(|err| state.interrupted(err))?;
 
         mp3_buffer.truncate(bytes_written);
         Ok(PyBytes::new_bound(py, &mp3_buffer))
     }
 
+    /// Maximum samples per channel handed to LAME in a single call
+    ///
+    /// Larger inputs are split into several LAME calls, which bounds how long
+    /// the encoder blocks on pathological inputs. ``None`` (the default) means
+    /// unlimited. Output is identical either way.
+    ///
+    /// Independently of this setting, long calls are split internally so that
+    /// Ctrl-C interrupts them promptly with ``KeyboardInterrupt``. An
+    /// interrupted encoder flushes nothing and raises ``EncodingError`` on any
+    /// further use.
+    ///
+    /// Raises:
+    ///     ValueError: If set to 0
+    #[getter]
+    fn get_max_samples_per_call(&self) -> PyResult<Option<usize>> {
+        let state = self.enter()?;
+        state.encoder()?;
+        Ok(state.max_samples_per_call)
+    }
+
+    #[setter]
+    fn set_max_samples_per_call(&mut self, max_samples: Option<usize>) -> PyResult<()> {
+        // Validates the value (0 is rejected) before applying the signal check limit
+        let encoder = self.encoder_mut()?;
+        encoder
+            .set_max_samples_per_call(max_samples)
+            .map_err(to_py_err)?;
+        let effective =
+            max_samples.map_or(SIGNAL_CHECK_SAMPLES, |max| max.min(SIGNAL_CHECK_SAMPLES));
+        encoder
+            .set_max_samples_per_call(Some(effective))
+            .map_err(to_py_err)?;
+        self.state.get_mut().max_samples_per_call = max_samples;
+        Ok(())
+    }
+
+    /// Sample rate of the input PCM in Hz
+    #[getter]
+    fn input_sample_rate(&self) -> PyResult<i32> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.input_sample_rate())
+    }
+
+    /// Sample rate of the MP3 output in Hz, including a rate chosen by LAME
+    #[getter]
+    fn output_sample_rate(&self) -> PyResult<i32> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.output_sample_rate())
+    }
+
+    /// Number of input channels
+    #[getter]
+    fn channels(&self) -> PyResult<i32> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.channels())
+    }
+
+    /// CBR bitrate in kbps, after any adjustment by LAME
+    #[getter]
+    fn bitrate(&self) -> PyResult<i32> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.bitrate())
+    }
+
+    /// Channel mode in effect, including a mode chosen by LAME
+    #[getter]
+    fn mode(&self) -> PyResult<ChannelMode> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.mode().into())
+    }
+
+    /// LAME quality level in effect (0-9, 0 = best)
+    #[getter]
+    fn quality_level(&self) -> PyResult<i32> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.quality_level())
+    }
+
+    /// VBR mode in effect
+    #[getter]
+    fn vbr_mode(&self) -> PyResult<VbrMode> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.vbr_mode().into())
+    }
+
+    /// Samples per channel in one MP3 frame: 1152 for MPEG-1, 576 for MPEG-2/2.5
+    #[getter]
+    fn frame_size(&self) -> PyResult<usize> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.frame_size())
+    }
+
+    /// Samples per channel that a decoder must drop from the start of the output
+    #[getter]
+    fn encoder_delay(&self) -> PyResult<usize> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.encoder_delay())
+    }
+
+    /// Samples per channel appended to fill the last frame; 0 until `flush()`
+    #[getter]
+    fn end_padding(&self) -> PyResult<usize> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.end_padding())
+    }
+
+    /// Playable samples per channel once delay and padding are removed
+    ///
+    /// After `flush()` this equals the number of input samples (counted at
+    /// the output sample rate when resampling). Useful for gapless playback
+    /// metadata such as CMAF/HLS edit lists.
+    #[getter]
+    fn gapless_samples(&self) -> PyResult<usize> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.gapless_samples())
+    }
+
+    /// Whether every frame is forced to M/S stereo (`EncoderBuilder.force_ms()`)
+    #[getter]
+    fn forces_ms(&self) -> PyResult<bool> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.forces_ms())
+    }
+
+    /// M/S masking adjustment in effect (`EncoderBuilder.ms_fix()`)
+    #[getter]
+    fn ms_fix(&self) -> PyResult<f32> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.ms_fix())
+    }
+
+    /// ReplayGain in dB after `flush()`, or None (`EncoderBuilder.find_replay_gain()`)
+    #[getter]
+    fn replay_gain(&self) -> PyResult<Option<f32>> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.replay_gain())
+    }
+
+    /// Peak decoded sample after `flush()`, or None (`EncoderBuilder.decode_on_the_fly()`)
+    #[getter]
+    fn peak_sample(&self) -> PyResult<Option<f32>> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.peak_sample())
+    }
+
+    /// Inter-channel masking ratio in effect (`EncoderBuilder.inter_channel_ratio()`)
+    #[getter]
+    fn inter_channel_ratio(&self) -> PyResult<f32> {
+        let state = self.enter()?;
+        Ok(state.encoder()?.inter_channel_ratio())
+    }
+
+    /// Read a LAME parameter, see `EncoderBuilder.set_raw()`
+    ///
+    /// Args:
+    ///     name (str): LAME parameter name, e.g. `"quant_comp"`
+    ///
+    /// Returns:
+    ///     int | float: Value in effect, of the parameter's type
+    ///
+    /// Raises:
+    ///     ValueError: If the name is unknown
+    #[pyo3(signature = (name))]
+    fn get_raw(&self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        let param = RawParam::from_name(name).map_err(to_py_err)?;
+        let state = self.enter()?;
+        Ok(match state.encoder()?.get_raw(param) {
+            RawValue::Int(value) => value.into_py(py),
+            RawValue::Float(value) => value.into_py(py),
+        })
+    }
+
     /// Create an ID3 tag builder for this encoder
     ///
-    /// Returns an Id3Tag builder for setting metadata.
-    fn id3_tag(&mut self) -> Id3Tag {
-        Id3Tag::new(self)
+    /// Returns:
+    ///     Id3Tag: A tag builder for setting metadata
+    ///
+    /// Raises:
+    ///     RuntimeError: If the encoder was returned to its pool
+    fn id3_tag(slf: &Bound<'_, Self>) -> PyResult<Id3Tag> {
+        slf.try_borrow_mut()?.encoder_mut()?;
+        Ok(Id3Tag::new(slf.clone().unbind()))
+    }
+
+    /// Size in bytes of the ID3v2 tag written at the start of the stream
+    ///
+    /// Includes the 10-byte header, all frames and the padding reserved with
+    /// `Id3Tag.pad_v2()`, so streams can check the space left for updating
+    /// metadata in place before sending audio. 0 when no ID3v2 tag is written.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the linked LAME cannot render ID3v2 tags, or the
+    ///         encoder was returned to its pool
+    fn id3v2_tag_size(&self) -> PyResult<usize> {
+        let state = self.enter()?;
+        state.encoder()?.id3v2_tag_size().map_err(to_py_err)
+    }
+
+    /// Set all ID3 tags at once from keyword arguments
+    ///
+    /// A shorthand for `id3_tag()` and its setters. Accepted keys are `title`,
+    /// `artist`, `album`, `album_artist`, `year`, `comment`, `genre` (str),
+    /// `track`, `track_total`, `disc`, `disc_total` (int) and `album_art`
+    /// (bytes); keys set to None are skipped. Replaces any earlier tag and must
+    /// be called before encoding starts.
+    ///
+    /// Example:
+    ///     ```python
+    ///     encoder.set_id3(title="Song", artist="Band", track=3, genre="Rock")
+    ///     ```
+    ///
+    /// Raises:
+    ///     InvalidParameterError: If any key is unknown; all unknown keys are listed
+    ///     TypeError: If a value has the wrong type
+    ///     ValueError: If values are invalid; every bad field is listed and no
+    ///         tag is written
+    ///     RuntimeError: If encoding has already started or the encoder was
+    ///         returned to its pool
+    #[pyo3(signature = (**kwargs))]
+    fn set_id3(&self, kwargs: Option<&Bound<'_, pyo3::types::PyDict>>) -> PyResult<()> {
+        let meta = id3::metadata_from_kwargs(kwargs)?;
+        let mut state = self.enter()?;
+        state.encoder_mut()?.set_id3(&meta).map_err(to_py_err)
+    }
+
+    /// Return a pooled encoder to its pool
+    ///
+    /// The encoder is reset and can no longer be used from this object.
+    /// Has no effect on encoders created by `EncoderBuilder.build()`.
+    fn release(&mut self) {
+        let state = self.state.get_mut();
+        if let EncoderSlot::Pooled(_) = state.inner {
+            state.inner = EncoderSlot::Released;
+        }
+    }
+
+    /// Enter a `with` block, returning the encoder itself
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Release pooled encoders when leaving a `with` block
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&mut self, _args: &Bound<'_, pyo3::types::PyTuple>) -> bool {
+        self.release();
+        false
     }
 
     fn __repr__(&self) -> String {
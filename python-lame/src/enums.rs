@@ -62,9 +62,160 @@ impl From<VbrMode> for lame_sys::VbrMode {
     }
 }
 
+impl From<lame_sys::VbrMode> for VbrMode {
+    fn from(v: lame_sys::VbrMode) -> Self {
+        match v {
+            lame_sys::VbrMode::Off => VbrMode::Off,
+            lame_sys::VbrMode::Vbr => VbrMode::Vbr,
+            lame_sys::VbrMode::Abr => VbrMode::Abr,
+        }
+    }
+}
+
 #[pymethods]
 impl VbrMode {
     fn __repr__(&self) -> String {
         format!("VbrMode.{:?}", self)
     }
 }
+
+/// MPEG channel mode
+///
+/// Independent of the number of input channels: stereo input with
+/// `ChannelMode.Mono` is downmixed to a mono MP3.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelMode {
+    /// Left and right channels encoded independently
+    Stereo = 0,
+    /// Joint stereo, LAME picks L/R or M/S per frame (default for stereo)
+    JointStereo = 1,
+    /// Two independent mono programs (LAME encodes it as Stereo)
+    DualChannel = 2,
+    /// Mono
+    Mono = 3,
+}
+
+impl From<ChannelMode> for lame_sys::ChannelMode {
+    fn from(m: ChannelMode) -> Self {
+        match m {
+            ChannelMode::Stereo => lame_sys::ChannelMode::Stereo,
+            ChannelMode::JointStereo => lame_sys::ChannelMode::JointStereo,
+            ChannelMode::DualChannel => lame_sys::ChannelMode::DualChannel,
+            ChannelMode::Mono => lame_sys::ChannelMode::Mono,
+        }
+    }
+}
+
+impl From<lame_sys::ChannelMode> for ChannelMode {
+    fn from(m: lame_sys::ChannelMode) -> Self {
+        match m {
+            lame_sys::ChannelMode::Stereo => ChannelMode::Stereo,
+            lame_sys::ChannelMode::JointStereo => ChannelMode::JointStereo,
+            lame_sys::ChannelMode::DualChannel => ChannelMode::DualChannel,
+            lame_sys::ChannelMode::Mono => ChannelMode::Mono,
+        }
+    }
+}
+
+#[pymethods]
+impl ChannelMode {
+    fn __repr__(&self) -> String {
+        format!("ChannelMode.{:?}", self)
+    }
+}
+
+/// How LAME uses short blocks
+///
+/// Short blocks split a frame into shorter transform windows. They reduce
+/// pre-echo before transients but cost bits.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortBlockMode {
+    /// Chosen per frame by the psychoacoustic model (default)
+    Allowed = 0,
+    /// Long blocks only, as the CLI's `--no-short-blocks`
+    Dispensed = 1,
+    /// Short blocks only, as the CLI's `--allshort`
+    Forced = 2,
+}
+
+impl From<ShortBlockMode> for lame_sys::ShortBlockMode {
+    fn from(m: ShortBlockMode) -> Self {
+        match m {
+            ShortBlockMode::Allowed => lame_sys::ShortBlockMode::Allowed,
+            ShortBlockMode::Dispensed => lame_sys::ShortBlockMode::Dispensed,
+            ShortBlockMode::Forced => lame_sys::ShortBlockMode::Forced,
+        }
+    }
+}
+
+#[pymethods]
+impl ShortBlockMode {
+    fn __repr__(&self) -> String {
+        format!("ShortBlockMode.{:?}", self)
+    }
+}
+
+/// LAME preset, as in the command-line tool's `-V n` and `--preset`
+///
+/// Apply it with `EncoderBuilder.preset()` before other settings; options set
+/// afterwards override the preset. For ABR presets pass the bitrate in kbps
+/// to `preset()` instead.
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// VBR quality 0 (best, ~245 kbps)
+    V0 = 0,
+    /// VBR quality 1 (~225 kbps)
+    V1 = 1,
+    /// VBR quality 2 (~190 kbps)
+    V2 = 2,
+    /// VBR quality 3 (~175 kbps)
+    V3 = 3,
+    /// VBR quality 4 (~165 kbps)
+    V4 = 4,
+    /// VBR quality 5 (~130 kbps)
+    V5 = 5,
+    /// VBR quality 6 (~115 kbps)
+    V6 = 6,
+    /// VBR quality 7 (~100 kbps)
+    V7 = 7,
+    /// VBR quality 8 (~85 kbps)
+    V8 = 8,
+    /// VBR quality 9 (lowest, ~65 kbps)
+    V9 = 9,
+    /// `--preset standard`, same as V2
+    Standard = 10,
+    /// `--preset extreme`, same as V0
+    Extreme = 11,
+    /// `--preset insane`, 320 kbps CBR
+    Insane = 12,
+}
+
+impl From<Preset> for lame_sys::Preset {
+    fn from(p: Preset) -> Self {
+        match p {
+            Preset::V0 => lame_sys::Preset::V0,
+            Preset::V1 => lame_sys::Preset::V1,
+            Preset::V2 => lame_sys::Preset::V2,
+            Preset::V3 => lame_sys::Preset::V3,
+            Preset::V4 => lame_sys::Preset::V4,
+            Preset::V5 => lame_sys::Preset::V5,
+            Preset::V6 => lame_sys::Preset::V6,
+            Preset::V7 => lame_sys::Preset::V7,
+            Preset::V8 => lame_sys::Preset::V8,
+            Preset::V9 => lame_sys::Preset::V9,
+            Preset::Standard => lame_sys::Preset::Standard,
+            Preset::Extreme => lame_sys::Preset::Extreme,
+            Preset::Insane => lame_sys::Preset::Insane,
+        }
+    }
+}
+
+#[pymethods]
+impl Preset {
+    fn __repr__(&self) -> String {
+        format!("Preset.{:?}", self)
+    }
+}
@@ -1,4 +1,4 @@
-use pyo3::exceptions::{PyException, PyRuntimeError, PyValueError};
+use pyo3::exceptions::{PyBlockingIOError, PyException, PyOSError, PyRuntimeError, PyValueError};
 use pyo3::{create_exception, prelude::*};
 
 // Create custom exception types
@@ -11,15 +11,15 @@ create_exception!(lame, BufferTooSmallError, LameError);
 /// Convert Rust LameError to Python exception
 pub fn to_py_err(err: lame_sys::LameError) -> PyErr {
     match err {
-        lame_sys::LameError::InitializationFailed => {
-            PyErr::new::<PyRuntimeError, _>("LAME initialization failed")
+        lame_sys::LameError::InitializationFailed(msg) => {
+            PyErr::new::<PyRuntimeError, _>(format!("LAME initialization failed: {}", msg))
         }
         lame_sys::LameError::InvalidParameter(msg) => {
             PyErr::new::<PyValueError, _>(format!("Invalid parameter: {}", msg))
         }
-        lame_sys::LameError::EncodingFailed(code) => {
-            PyErr::new::<PyRuntimeError, _>(format!("Encoding failed with code: {}", code))
-        }
+        lame_sys::LameError::EncodingFailed { code, message } => PyErr::new::<PyRuntimeError, _>(
+            format!("Encoding failed with code {}: {}", code, message),
+        ),
         lame_sys::LameError::BufferTooSmall { required, provided } => {
             PyErr::new::<PyRuntimeError, _>(format!(
                 "Buffer too small: required {} bytes, provided {} bytes",
@@ -35,12 +35,25 @@ pub fn to_py_err(err: lame_sys::LameError) -> PyErr {
         lame_sys::LameError::InternalError(msg) => {
             PyErr::new::<PyRuntimeError, _>(format!("Internal error: {}", msg))
         }
+        lame_sys::LameError::InvalidState(msg) => {
+            PyErr::new::<PyRuntimeError, _>(format!("Invalid encoder state: {}", msg))
+        }
         lame_sys::LameError::Utf8Error(e) => {
             PyErr::new::<PyValueError, _>(format!("UTF-8 error: {}", e))
         }
         lame_sys::LameError::NullPointer => {
             PyErr::new::<PyRuntimeError, _>("Null pointer error")
         }
+        lame_sys::LameError::Io { message, .. } => {
+            PyErr::new::<PyOSError, _>(format!("I/O error: {}", message))
+        }
+        lame_sys::LameError::Cancelled { bytes_written } => PyErr::new::<PyRuntimeError, _>(
+            format!("Encoding cancelled after {} bytes", bytes_written),
+        ),
+        lame_sys::LameError::Pending { buffered } => PyErr::new::<PyBlockingIOError, _>(format!(
+            "Output pending: {} bytes buffered until the sink is writable",
+            buffered
+        )),
     }
 }
 
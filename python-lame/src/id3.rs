@@ -1,7 +1,7 @@
 use crate::encoder::LameEncoder;
-use crate::error::to_py_err;
+use crate::error::{to_py_err, EncodingError, InvalidParameterError};
 use pyo3::prelude::*;
-use std::marker::PhantomData;
+use pyo3::types::PyDict;
 
 /// ID3 tag builder for MP3 metadata
 ///
@@ -13,130 +13,507 @@ use std::marker::PhantomData;
 /// encoder.channels(2)
 /// encoder = encoder.build()
 ///
-/// tag = encoder.id3_tag()
-/// tag.title("My Song")
-/// tag.artist("My Artist")
-/// tag.album("My Album")
-/// tag.year("2024")
-/// tag.apply()
+/// encoder.id3_tag().title("My Song").artist("My Artist").album("My Album").year("2024").apply()
 ///
 /// # Now encode...
 /// ```
-#[pyclass(unsendable)]
+///
+/// The setters only record values and return the tag, so calls can be
+/// chained; `apply()` writes them to the encoder.
+/// A setter that rejects its value leaves the tag unchanged, so it can be
+/// called again with a corrected value. After `apply()` the tag is done and
+/// further calls raise RuntimeError.
+///
+/// Text values containing a NUL character raise ValueError naming
+/// the field and byte offset. Other C0 control characters (newline, tab, ...)
+/// are replaced with spaces.
+#[pyclass]
 pub struct Id3Tag {
-    inner: Option<lame_sys::Id3Tag<'static>>,
-    _phantom: PhantomData<*mut LameEncoder>,
+    /// Encoder the tag is written to; the reference keeps it alive
+    encoder: Py<LameEncoder>,
+    state: TagState,
+}
+
+/// Lifecycle of a Python tag
+enum TagState {
+    /// Values are being recorded
+    Editing(PendingTag),
+    /// `apply()` wrote the tag to the encoder
+    Applied,
+    /// Writing the tag failed part way, so the encoder's tag is unknown
+    Invalid { reason: String },
+}
+
+/// Tag values recorded from Python until `apply()`
+#[derive(Default)]
+struct PendingTag {
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    year: Option<String>,
+    release_date: Option<(u16, u8, u8)>,
+    comment: Option<String>,
+    track: Option<(u32, Option<u32>)>,
+    disc: Option<(u32, Option<u32>)>,
+    genre: Option<String>,
+    album_artist: Option<String>,
+    album_art: Option<Vec<u8>>,
+    /// Frames set with `field()`, in call order
+    fields: Vec<(String, String)>,
+    version: Option<lame_sys::Id3Version>,
+    pad_v2: Option<usize>,
+}
+
+impl PendingTag {
+    /// Write the recorded values to `encoder`, replacing any earlier tag
+    fn write(&self, encoder: &mut lame_sys::LameEncoder) -> lame_sys::Result<()> {
+        let mut tag = lame_sys::Id3Tag::new(encoder);
+        if let Some(title) = &self.title {
+            tag = tag.title(title)?;
+        }
+        if let Some(artist) = &self.artist {
+            tag = tag.artist(artist)?;
+        }
+        if let Some(album) = &self.album {
+            tag = tag.album(album)?;
+        }
+        if let Some(year) = &self.year {
+            tag = tag.year(year)?;
+        }
+        if let Some((year, month, day)) = self.release_date {
+            tag = tag.release_date(year, month, day)?;
+        }
+        if let Some(comment) = &self.comment {
+            tag = tag.comment(comment)?;
+        }
+        if let Some((number, total)) = self.track {
+            tag = tag.track(number, total);
+        }
+        if let Some((number, total)) = self.disc {
+            tag = tag.disc(number, total)?;
+        }
+        if let Some(genre) = &self.genre {
+            tag = tag.genre(genre)?;
+        }
+        if let Some(album_artist) = &self.album_artist {
+            tag = tag.album_artist(album_artist)?;
+        }
+        if let Some(album_art) = &self.album_art {
+            tag = tag.album_art(album_art)?;
+        }
+        for (frame_id, value) in &self.fields {
+            tag = tag.field(frame_id, value)?;
+        }
+        if let Some(version) = self.version {
+            tag = tag.version(version);
+        }
+        if let Some(bytes) = self.pad_v2 {
+            tag = tag.pad_v2(bytes);
+        }
+        tag.apply().map(|_| ())
+    }
 }
 
 impl Id3Tag {
-    pub(crate) fn new(encoder: &mut LameEncoder) -> Self {
-        // SAFETY: We're using PhantomData to ensure the lifetime is managed correctly
-        // The Python borrow checker will ensure encoder lives long enough
-        let inner = unsafe {
-            std::mem::transmute::<lame_sys::Id3Tag<'_>, lame_sys::Id3Tag<'static>>(
-                lame_sys::Id3Tag::new(&mut encoder.inner),
-            )
-        };
+    pub(crate) fn new(encoder: Py<LameEncoder>) -> Self {
         Self {
-            inner: Some(inner),
-            _phantom: PhantomData,
+            encoder,
+            state: TagState::Editing(PendingTag::default()),
         }
     }
+
+    /// Values recorded so far, failing once the tag was applied or invalidated
+    fn pending_mut(&mut self) -> PyResult<&mut PendingTag> {
+        match &mut self.state {
+            TagState::Editing(pending) => Ok(pending),
+            TagState::Applied => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                "tag already applied",
+            )),
+            TagState::Invalid { reason } => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                format!("tag invalidated by earlier error: {}", reason),
+            )),
+        }
+    }
+
+    /// Validate a text field and return it for recording
+    fn text(field: &str, value: &str) -> PyResult<String> {
+        lame_sys::id3::sanitize_text(field, value).map_err(to_py_err)?;
+        Ok(value.to_owned())
+    }
+
+    /// Validate a text field and record it in the slot `slot` selects
+    fn set_text(
+        &mut self,
+        field: &str,
+        value: &str,
+        slot: fn(&mut PendingTag) -> &mut Option<String>,
+    ) -> PyResult<()> {
+        let value = Self::text(field, value)?;
+        *slot(self.pending_mut()?) = Some(value);
+        Ok(())
+    }
+}
+
+/// Validate a year given as int or str and return its text
+fn year_text(year: &Bound<'_, PyAny>) -> PyResult<String> {
+    let year = match year.extract::<i64>() {
+        Ok(year) => year.to_string(),
+        Err(_) => year.extract::<String>().map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyTypeError, _>("year must be an int or str")
+        })?,
+    };
+    lame_sys::id3::check_year(&year).map_err(to_py_err)?;
+    Ok(year)
+}
+
+/// Parse the `version()` argument
+fn parse_version(version: &str) -> PyResult<lame_sys::Id3Version> {
+    match version.to_ascii_lowercase().as_str() {
+        "v1" => Ok(lame_sys::Id3Version::V1Only),
+        "v2" => Ok(lame_sys::Id3Version::V2Only),
+        "both" => Ok(lame_sys::Id3Version::Both),
+        _ => Err(to_py_err(lame_sys::LameError::InvalidParameter(format!(
+            "version: expected \"v1\", \"v2\" or \"both\", got {:?}",
+            version
+        )))),
+    }
 }
 
 #[pymethods]
 impl Id3Tag {
     /// Set the song title
-    fn title(&mut self, title: &str) -> PyResult<()> {
-        let tag = self.inner.take().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Tag already consumed")
-        })?;
-        let tag = tag.title(title).map_err(to_py_err)?;
-        self.inner = Some(tag);
-        Ok(())
+    ///
+    /// Args:
+    ///     title (str): Song title
+    #[pyo3(signature = (title))]
+    fn title<'py>(mut slf: PyRefMut<'py, Self>, title: &str) -> PyResult<PyRefMut<'py, Self>> {
+        slf.set_text("title", title, |tag| &mut tag.title)?;
+        Ok(slf)
     }
 
     /// Set the artist name
-    fn artist(&mut self, artist: &str) -> PyResult<()> {
-        let tag = self.inner.take().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Tag already consumed")
-        })?;
-        let tag = tag.artist(artist).map_err(to_py_err)?;
-        self.inner = Some(tag);
-        Ok(())
+    ///
+    /// Args:
+    ///     artist (str): Artist name
+    #[pyo3(signature = (artist))]
+    fn artist<'py>(mut slf: PyRefMut<'py, Self>, artist: &str) -> PyResult<PyRefMut<'py, Self>> {
+        slf.set_text("artist", artist, |tag| &mut tag.artist)?;
+        Ok(slf)
     }
 
     /// Set the album name
-    fn album(&mut self, album: &str) -> PyResult<()> {
-        let tag = self.inner.take().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Tag already consumed")
-        })?;
-        let tag = tag.album(album).map_err(to_py_err)?;
-        self.inner = Some(tag);
-        Ok(())
+    ///
+    /// Args:
+    ///     album (str): Album name
+    #[pyo3(signature = (album))]
+    fn album<'py>(mut slf: PyRefMut<'py, Self>, album: &str) -> PyResult<PyRefMut<'py, Self>> {
+        slf.set_text("album", album, |tag| &mut tag.album)?;
+        Ok(slf)
     }
 
     /// Set the year
-    fn year(&mut self, year: &str) -> PyResult<()> {
-        let tag = self.inner.take().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Tag already consumed")
-        })?;
-        let tag = tag.year(year).map_err(to_py_err)?;
-        self.inner = Some(tag);
-        Ok(())
+    ///
+    /// Args:
+    ///     year (int | str): Release year between 1000 and 9999, e.g. 2024 or "2024"
+    ///
+    /// Raises:
+    ///     ValueError: If `year` is not a year between 1000 and 9999
+    ///     TypeError: If `year` is neither int nor str
+    #[pyo3(signature = (year))]
+    fn year<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        year: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let year = year_text(year)?;
+        slf.pending_mut()?.year = Some(year);
+        Ok(slf)
+    }
+
+    /// Set the full release date
+    ///
+    /// The year is written like `year()`; day and month go into the ID3v2
+    /// `TDAT` frame as "DDMM" (LAME writes ID3v2.3, which has no `TDRC`).
+    ///
+    /// Args:
+    ///     year (int): Release year between 1000 and 9999
+    ///     month (int): Month, 1-12
+    ///     day (int): Day of the month
+    ///
+    /// Raises:
+    ///     ValueError: If the date does not exist
+    #[pyo3(signature = (year, month, day))]
+    fn release_date<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        year: u16,
+        month: u8,
+        day: u8,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        lame_sys::id3::check_release_date(year, month, day).map_err(to_py_err)?;
+        slf.pending_mut()?.release_date = Some((year, month, day));
+        Ok(slf)
     }
 
     /// Set a comment
-    fn comment(&mut self, comment: &str) -> PyResult<()> {
-        let tag = self.inner.take().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Tag already consumed")
-        })?;
-        let tag = tag.comment(comment).map_err(to_py_err)?;
-        self.inner = Some(tag);
-        Ok(())
+    ///
+    /// Args:
+    ///     comment (str): Comment text
+    #[pyo3(signature = (comment))]
+    fn comment<'py>(mut slf: PyRefMut<'py, Self>, comment: &str) -> PyResult<PyRefMut<'py, Self>> {
+        slf.set_text("comment", comment, |tag| &mut tag.comment)?;
+        Ok(slf)
     }
 
     /// Set the track number
-    fn track(&mut self, track: u32) -> PyResult<()> {
-        let tag = self.inner.take().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Tag already consumed")
-        })?;
-        let tag = tag.track(track);
-        self.inner = Some(tag);
-        Ok(())
+    ///
+    /// Args:
+    ///     track (int): Track number
+    ///     total (int, optional): Number of tracks on the album, written as "3/12"
+    #[pyo3(signature = (track, total=None))]
+    fn track<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        track: u32,
+        total: Option<u32>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.pending_mut()?.track = Some((track, total));
+        Ok(slf)
+    }
+
+    /// Set the disc number (ID3v2 only)
+    ///
+    /// Args:
+    ///     number (int): Disc number
+    ///     total (int, optional): Number of discs in the set, written as "1/2"
+    #[pyo3(signature = (number, total=None))]
+    fn disc<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        number: u32,
+        total: Option<u32>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.pending_mut()?.disc = Some((number, total));
+        Ok(slf)
     }
 
     /// Set the genre
-    fn genre(&mut self, genre: &str) -> PyResult<()> {
-        let tag = self.inner.take().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Tag already consumed")
-        })?;
-        let tag = tag.genre(genre).map_err(to_py_err)?;
-        self.inner = Some(tag);
-        Ok(())
+    ///
+    /// Args:
+    ///     genre (str): Genre name from `genre_list()` or ID3v1 genre number (0-147).
+    ///         Names are matched ignoring case and punctuation
+    ///
+    /// Raises:
+    ///     ValueError: If `genre` is not a known ID3v1 genre name or number
+    #[pyo3(signature = (genre))]
+    fn genre<'py>(mut slf: PyRefMut<'py, Self>, genre: &str) -> PyResult<PyRefMut<'py, Self>> {
+        lame_sys::id3::check_genre(genre).map_err(to_py_err)?;
+        slf.set_text("genre", genre, |tag| &mut tag.genre)?;
+        Ok(slf)
     }
 
     /// Set the album artist
-    fn album_artist(&mut self, album_artist: &str) -> PyResult<()> {
-        let tag = self.inner.take().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Tag already consumed")
+    ///
+    /// Args:
+    ///     album_artist (str): Album artist
+    #[pyo3(signature = (album_artist))]
+    fn album_artist<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        album_artist: &str,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        slf.set_text("album_artist", album_artist, |tag| &mut tag.album_artist)?;
+        Ok(slf)
+    }
+
+    /// Set the album art (cover image)
+    ///
+    /// Args:
+    ///     image (bytes): Contents of a JPEG, PNG or GIF file, at most 128 KB
+    ///
+    /// Raises:
+    ///     ValueError: If `image` is not JPEG, PNG or GIF data, or is too large
+    #[pyo3(signature = (image))]
+    fn album_art<'py>(mut slf: PyRefMut<'py, Self>, image: &[u8]) -> PyResult<PyRefMut<'py, Self>> {
+        lame_sys::id3::check_album_art(image).map_err(|err| match err {
+            lame_sys::LameError::InvalidInput(message) => {
+                to_py_err(lame_sys::LameError::InvalidInput(format!(
+                    "{}; resize the image below 128 KB, e.g. to 600x600 pixels",
+                    message
+                )))
+            }
+            err => to_py_err(err),
         })?;
-        let tag = tag.album_artist(album_artist).map_err(to_py_err)?;
-        self.inner = Some(tag);
-        Ok(())
+        if lame_sys::id3::album_art_mime_type(image).is_none() {
+            return Err(to_py_err(lame_sys::LameError::InvalidInput(
+                "album art must be JPEG, PNG or GIF data".to_string(),
+            )));
+        }
+        slf.pending_mut()?.album_art = Some(image.to_vec());
+        Ok(slf)
+    }
+
+    /// Set an ID3v2 frame directly
+    ///
+    /// For frames without a dedicated method, e.g. the composer:
+    /// `tag.field("TCOM", "Bach")`. LAME supports text frames (`T***`), link
+    /// frames (`W***`) and `COMM`; `TXXX`, `WXXX` and `COMM` values are written
+    /// as `"description=text"`.
+    ///
+    /// Args:
+    ///     frame_id (str): Four uppercase letters or digits, e.g. "TCOM"
+    ///     value (str): Frame text
+    ///
+    /// Raises:
+    ///     ValueError: If `frame_id` is malformed; frames LAME does not support
+    ///         raise ValueError from `apply()`
+    #[pyo3(signature = (frame_id, value))]
+    fn field<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        frame_id: &str,
+        value: &str,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        lame_sys::id3::check_frame_id(frame_id).map_err(to_py_err)?;
+        let value = Self::text(frame_id, value)?;
+        slf.pending_mut()?.fields.push((frame_id.to_owned(), value));
+        Ok(slf)
+    }
+
+    /// Choose which tag versions are written
+    ///
+    /// By default LAME always writes ID3v1 and adds ID3v2 only when a field
+    /// needs it.
+    ///
+    /// Args:
+    ///     version (str): "v1" (ID3v1 only), "v2" (ID3v2 only) or "both"
+    ///
+    /// Raises:
+    ///     ValueError: If `version` is not one of the values above
+    #[pyo3(signature = (version))]
+    fn version<'py>(mut slf: PyRefMut<'py, Self>, version: &str) -> PyResult<PyRefMut<'py, Self>> {
+        let version = parse_version(version)?;
+        slf.pending_mut()?.version = Some(version);
+        Ok(slf)
+    }
+
+    /// Reserve padding at the end of the ID3v2 tag
+    ///
+    /// Lets other tools edit the tag in place later. Also turns on ID3v2
+    /// unless `version("v1")` is set.
+    ///
+    /// Args:
+    ///     bytes (int): Number of zero bytes to append
+    #[pyo3(signature = (bytes))]
+    fn pad_v2<'py>(mut slf: PyRefMut<'py, Self>, bytes: usize) -> PyResult<PyRefMut<'py, Self>> {
+        slf.pending_mut()?.pad_v2 = Some(bytes);
+        Ok(slf)
     }
 
     /// Apply the ID3 tags to the encoder
     ///
     /// Must be called before encoding starts.
-    fn apply(&mut self) -> PyResult<()> {
-        let tag = self.inner.take().ok_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Tag already consumed")
+    ///
+    /// Raises:
+    ///     ValueError: If the ID3v2 tag would be larger than LAME can write
+    ///         (about 140 KB); nothing is written to the encoder then
+    ///     RuntimeError: If encoding has already started, the tag was already
+    ///         applied, an earlier `apply()` failed part way, or the encoder was
+    ///         returned to its pool
+    ///     EncodingError: If another thread is using the encoder
+    fn apply(&mut self, py: Python<'_>) -> PyResult<()> {
+        let handle = self.encoder.clone_ref(py);
+        let pending = self.pending_mut()?;
+        // Failing to reach the encoder leaves the tag editable for a retry
+        let mut encoder = handle.bind(py).try_borrow_mut().map_err(|_| {
+            EncodingError::new_err(
+                "encoder is busy in another thread; apply the tag before encoding starts",
+            )
         })?;
-        tag.apply().map_err(to_py_err)
+        let result = pending.write(encoder.encoder_mut()?);
+        self.state = match &result {
+            Ok(()) => TagState::Applied,
+            Err(err) => TagState::Invalid {
+                reason: err.to_string(),
+            },
+        };
+        result.map_err(to_py_err)
     }
 
     fn __repr__(&self) -> String {
         "Id3Tag()".to_string()
     }
 }
+
+/// List the ID3v1 genres LAME knows, sorted by name
+///
+/// Returns:
+///     list[tuple[int, str]]: Genre number and name pairs, e.g. for a dropdown
+#[pyfunction]
+pub fn genre_list() -> Vec<(u8, String)> {
+    lame_sys::id3::genre_list()
+}
+
+/// Keys `LameEncoder.set_id3()` accepts, in the order they are applied
+const METADATA_KEYS: [&str; 12] = [
+    "title",
+    "artist",
+    "album",
+    "album_artist",
+    "year",
+    "comment",
+    "track",
+    "track_total",
+    "disc",
+    "disc_total",
+    "genre",
+    "album_art",
+];
+
+/// Build the metadata for `LameEncoder.set_id3()` from its keyword arguments
+///
+/// Keys set to None are skipped. Unknown keys raise InvalidParameterError
+/// listing all of them; a value of the wrong type raises TypeError naming the key.
+pub(crate) fn metadata_from_kwargs(
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<lame_sys::Id3Metadata> {
+    let mut meta = lame_sys::Id3Metadata::default();
+    let Some(kwargs) = kwargs else {
+        return Ok(meta);
+    };
+
+    let mut unknown = Vec::new();
+    for (key, value) in kwargs.iter() {
+        let key: String = key.extract()?;
+        if value.is_none() {
+            if !METADATA_KEYS.contains(&key.as_str()) {
+                unknown.push(key);
+            }
+            continue;
+        }
+        let typed = |err: PyErr| {
+            PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!("{}: {}", key, err))
+        };
+        match key.as_str() {
+            "title" => meta.title = Some(value.extract().map_err(typed)?),
+            "artist" => meta.artist = Some(value.extract().map_err(typed)?),
+            "album" => meta.album = Some(value.extract().map_err(typed)?),
+            "album_artist" => meta.album_artist = Some(value.extract().map_err(typed)?),
+            "year" => meta.year = Some(year_text(&value)?),
+            "comment" => meta.comment = Some(value.extract().map_err(typed)?),
+            "track" => meta.track = Some(value.extract().map_err(typed)?),
+            "track_total" => meta.track_total = Some(value.extract().map_err(typed)?),
+            "disc" => meta.disc = Some(value.extract().map_err(typed)?),
+            "disc_total" => meta.disc_total = Some(value.extract().map_err(typed)?),
+            "genre" => meta.genre = Some(value.extract().map_err(typed)?),
+            "album_art" => {
+                let image: &[u8] = value.extract().map_err(typed)?;
+                meta.album_art = Some(image.to_vec());
+            }
+            _ => unknown.push(key),
+        }
+    }
+    if !unknown.is_empty() {
+        unknown.sort();
+        return Err(InvalidParameterError::new_err(format!(
+            "unknown ID3 keys: {}; expected any of {}",
+            unknown.join(", "),
+            METADATA_KEYS.join(", ")
+        )));
+    }
+    Ok(meta)
+}
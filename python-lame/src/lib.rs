@@ -16,6 +16,7 @@
 //!
 //! ```python
 //! import lame
+//! import numpy as np
 //!
 //! # Create encoder
 //! encoder = (
@@ -28,25 +29,29 @@
 //! )
 //!
 //! # Set ID3 tags
-//! encoder.id3_tag()
-//!     .title("My Song")
-//!     .artist("My Artist")
-//!     .apply()
+//! encoder.id3_tag().title("My Song").artist("My Artist").apply()
 //!
 //! # Encode PCM data
-//! pcm_data = [0] * 1152
-//! mp3_data = encoder.encode_mono(pcm_data)
+//! pcm_data = np.zeros(1152, dtype=np.int16)
+//! mp3_data = encoder.encode_mono_numpy(pcm_data)
 //!
 //! # Flush encoder
 //! final_data = encoder.flush()
 //! ```
 
+#![deny(unsafe_op_in_unsafe_fn)]
+
+mod buffer;
 mod builder;
+mod config;
+mod decoder;
 mod encoder;
 mod enums;
 mod error;
 mod id3;
+mod pool;
 mod utils;
+mod wave;
 
 use pyo3::prelude::*;
 
@@ -60,17 +65,31 @@ fn lame(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<builder::EncoderBuilder>()?;
     m.add_class::<enums::Quality>()?;
     m.add_class::<enums::VbrMode>()?;
+    m.add_class::<enums::ChannelMode>()?;
+    m.add_class::<enums::ShortBlockMode>()?;
+    m.add_class::<enums::Preset>()?;
     m.add_class::<id3::Id3Tag>()?;
+    m.add_class::<pool::EncoderPool>()?;
+    m.add_class::<buffer::Mp3Buffer>()?;
+    m.add_class::<decoder::Mp3Decoder>()?;
 
     // Add exceptions
     error::register_exceptions(m)?;
 
     // Add utility functions
     m.add_function(wrap_pyfunction!(utils::get_version, m)?)?;
+    m.add_function(wrap_pyfunction!(utils::version_info, m)?)?;
     m.add_function(wrap_pyfunction!(utils::get_url, m)?)?;
+    m.add_function(wrap_pyfunction!(id3::genre_list, m)?)?;
+    m.add_function(wrap_pyfunction!(utils::shutdown, m)?)?;
+    m.add_function(wrap_pyfunction!(wave::encode_wave, m)?)?;
+    m.add_function(wrap_pyfunction!(config::diff_configs, m)?)?;
+    m.add_function(wrap_pyfunction!(decoder::decode_file, m)?)?;
+    m.add_function(wrap_pyfunction!(decoder::transcode, m)?)?;
 
     // Add module metadata
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("HAS_DECODER", decoder::HAS_DECODER)?;
     m.add(
         "__doc__",
         "Python bindings for LAME MP3 encoder with high-level API",
@@ -0,0 +1,105 @@
+use crate::encoder::{EncoderSlot, LameEncoder};
+use crate::enums::{Quality, VbrMode};
+use crate::error::to_py_err;
+use pyo3::prelude::*;
+
+/// Thread-safe pool of reusable encoders
+///
+/// Encoders are created on demand up to `max_size`. When every encoder is in
+/// use, `acquire()` blocks (with the GIL released) until one is returned.
+/// Returned encoders are reset, so no ID3 tags or stream state leak between
+/// users.
+///
+/// Args:
+///     sample_rate (int): Input sample rate in Hz
+///     channels (int): Number of input channels (1 or 2)
+///     bitrate (int | None): Bitrate in kbps
+///     quality (Quality | None): Quality preset
+///     vbr_mode (VbrMode | None): VBR mode
+///     vbr_quality (int | None): VBR quality from 0 to 9
+///     max_size (int): Maximum number of encoders
+///
+/// # Example
+///
+/// ```python
+/// pool = lame.EncoderPool(sample_rate=44100, channels=2, bitrate=128, max_size=4)
+///
+/// with pool.acquire() as encoder:
+///     mp3_data = encoder.encode_interleaved(pcm) + encoder.flush()
+/// ```
+#[pyclass]
+pub struct EncoderPool {
+    inner: lame_sys::pool::EncoderPool,
+}
+
+#[pymethods]
+impl EncoderPool {
+    /// Create a new encoder pool
+    #[new]
+    #[pyo3(
+        signature = (
+            sample_rate = 44100,
+            channels = 2,
+            bitrate = None,
+            quality = None,
+            vbr_mode = None,
+            vbr_quality = None,
+            max_size = 4
+        ),
+        text_signature = "(sample_rate=44100, channels=2, bitrate=None, quality=None, vbr_mode=None, vbr_quality=None, max_size=4)"
+    )]
+    fn new(
+        sample_rate: i32,
+        channels: i32,
+        bitrate: Option<i32>,
+        quality: Option<Quality>,
+        vbr_mode: Option<VbrMode>,
+        vbr_quality: Option<i32>,
+        max_size: usize,
+    ) -> Self {
+        let config = lame_sys::EncoderConfig {
+            sample_rate,
+            channels,
+            bitrate,
+            quality: quality.map(Into::into),
+            vbr_mode: vbr_mode.map(Into::into),
+            vbr_quality,
+            ..Default::default()
+        };
+        Self {
+            inner: lame_sys::pool::EncoderPool::new(config, max_size),
+        }
+    }
+
+    /// Borrow an encoder from the pool
+    ///
+    /// The returned encoder is a context manager; it is returned to the pool
+    /// when the `with` block exits or `release()` is called.
+    ///
+    /// Returns:
+    ///     LameEncoder: A pooled encoder
+    ///
+    /// Note: Releases the GIL while waiting for an encoder.
+    fn acquire(&self, py: Python<'_>) -> PyResult<LameEncoder> {
+        let pooled = py
+            .allow_threads(|| self.inner.acquire())
+            .map_err(to_py_err)?;
+        Ok(LameEncoder::new(EncoderSlot::Pooled(pooled)))
+    }
+
+    /// Maximum number of encoders held by the pool
+    #[getter]
+    fn max_size(&self) -> usize {
+        self.inner.max_size()
+    }
+
+    /// Number of encoders currently idle in the pool
+    #[getter]
+    fn idle_count(&self) -> usize {
+        self.inner.idle_count()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("EncoderPool(max_size={})", self.inner.max_size())
+    }
+}
@@ -1,9 +1,10 @@
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 
 /// Get the LAME version string
 ///
 /// Returns:
-///     Version string of the LAME library
+///     str: Version string of the LAME library
 ///
 /// # Example
 ///
@@ -16,11 +17,57 @@ pub fn get_version() -> String {
     lame_sys::get_lame_version()
 }
 
+/// Get the LAME version as numbers
+///
+/// Returns:
+///     dict: Keys `major`, `minor`, `alpha`, `beta`, `psy_major`,
+///     `psy_minor` (ints, `alpha`/`beta` are 0 for non-alpha/beta builds)
+///     and `features` (str, usually empty)
+///
+/// # Example
+///
+/// ```python
+/// import lame
+/// info = lame.version_info()
+/// if (info["major"], info["minor"]) >= (3, 100):
+///     ...
+/// ```
+#[pyfunction]
+pub fn version_info(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    let version = lame_sys::version();
+    let dict = PyDict::new_bound(py);
+    dict.set_item("major", version.major)?;
+    dict.set_item("minor", version.minor)?;
+    dict.set_item("alpha", version.alpha)?;
+    dict.set_item("beta", version.beta)?;
+    dict.set_item("psy_major", version.psy_major)?;
+    dict.set_item("psy_minor", version.psy_minor)?;
+    dict.set_item("features", version.features)?;
+    Ok(dict)
+}
+
 /// Get the LAME project URL
 ///
 /// Returns:
-///     URL of the LAME project website
+///     str: URL of the LAME project website
 #[pyfunction]
 pub fn get_url() -> String {
     lame_sys::get_lame_url()
 }
+
+/// Check that every LAME handle has been released before unloading the module
+///
+/// Clears module-level caches. The module stays usable afterwards, and
+/// importing it again or calling `importlib.reload()` is safe.
+///
+/// Raises:
+///     RuntimeError: If encoders, builders or pooled encoders are still alive
+#[pyfunction]
+pub fn shutdown() -> PyResult<()> {
+    lame_sys::shutdown().map_err(|err| match err {
+        lame_sys::LameError::InvalidInput(msg) => {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(msg)
+        }
+        err => crate::error::to_py_err(err),
+    })
+}
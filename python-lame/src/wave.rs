@@ -0,0 +1,192 @@
+use crate::enums::{Quality, VbrMode};
+use crate::error::to_py_err;
+use lame_sys::dsp::FadeShape;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyString};
+
+/// Number of PCM frames read from the wave file per chunk
+const CHUNK_FRAMES: usize = 1152 * 16;
+
+/// Encode a stdlib `wave` file to MP3
+///
+/// Args:
+///     source (wave.Wave_read | str): An open `wave.Wave_read` object, or a path to a WAV file
+///     **encoder_kwargs: Optional encoder settings: `bitrate`, `quality`,
+///         `vbr_mode`, `vbr_quality`, and `fade_ms`: an `(in_ms, out_ms)`
+///         tuple of linear fade-in/fade-out lengths applied before encoding
+///         to avoid clicks at the clip boundaries
+///
+/// Returns:
+///     bytes: Complete MP3 data (including the flushed final frames)
+///
+/// Raises:
+///     ValueError: If the sample width is not 16-bit or the channel count
+///         is not 1 or 2
+///     TypeError: If an unknown encoder keyword is given
+///
+/// Example:
+///     ```python
+///     import wave
+///     import lame
+///
+///     with wave.open("input.wav", "rb") as w:
+///         mp3_data = lame.encode_wave(w, bitrate=192)
+///
+///     mp3_data = lame.encode_wave("input.wav", quality=lame.Quality.High)
+///     mp3_data = lame.encode_wave("clip.wav", fade_ms=(10, 50))
+///     ```
+#[pyfunction]
+#[pyo3(signature = (source, **encoder_kwargs))]
+pub fn encode_wave<'py>(
+    py: Python<'py>,
+    source: &Bound<'py, PyAny>,
+    encoder_kwargs: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    // Accept a path for symmetry with `wave.open()`; the file is closed afterwards
+    if source.is_instance_of::<PyString>() {
+        let wave_read = py
+            .import_bound("wave")?
+            .call_method1("open", (source, "rb"))?;
+        let result = encode_wave_read(py, &wave_read, encoder_kwargs);
+        wave_read.call_method0("close")?;
+        return result;
+    }
+
+    encode_wave_read(py, source, encoder_kwargs)
+}
+
+fn encode_wave_read<'py>(
+    py: Python<'py>,
+    wave_read: &Bound<'py, PyAny>,
+    encoder_kwargs: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let sample_rate: i32 = wave_read.call_method0("getframerate")?.extract()?;
+    let channels: i32 = wave_read.call_method0("getnchannels")?.extract()?;
+    let sample_width: usize = wave_read.call_method0("getsampwidth")?.extract()?;
+
+    match sample_width {
+        2 => {}
+        1 | 3 | 4 => {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "Unsupported sample width: {} bytes ({}-bit). Only 16-bit PCM is supported; \
+                 convert the file first, e.g. `sox input.wav -b 16 output.wav`",
+                sample_width,
+                sample_width * 8
+            )))
+        }
+        _ => {
+            return Err(PyErr::new::<PyValueError, _>(format!(
+                "Invalid sample width: {} bytes",
+                sample_width
+            )))
+        }
+    }
+
+    if channels != 1 && channels != 2 {
+        return Err(PyErr::new::<PyValueError, _>(format!(
+            "Unsupported channel count: {} (expected 1 or 2)",
+            channels
+        )));
+    }
+
+    let mut builder = lame_sys::LameEncoder::builder()
+        .and_then(|b| b.sample_rate(sample_rate))
+        .and_then(|b| b.channels(channels))
+        .map_err(to_py_err)?;
+
+    let mut fade_ms = (0u32, 0u32);
+    if let Some(kwargs) = encoder_kwargs {
+        for (key, value) in kwargs.iter() {
+            let key: String = key.extract()?;
+            builder = match key.as_str() {
+                "fade_ms" => {
+                    fade_ms = value.extract()?;
+                    continue;
+                }
+                "bitrate" => builder.bitrate(value.extract()?),
+                "quality" => builder.quality(value.extract::<Quality>()?.into()),
+                "vbr_mode" => builder.vbr_mode(value.extract::<VbrMode>()?.into()),
+                "vbr_quality" => builder.vbr_quality(value.extract()?),
+                _ => {
+                    return Err(PyErr::new::<PyTypeError, _>(format!(
+                        "encode_wave() got an unexpected keyword argument '{}'",
+                        key
+                    )))
+                }
+            }
+            .map_err(to_py_err)?;
+        }
+    }
+
+    let mut encoder = builder.build().map_err(to_py_err)?;
+
+    // The fade-out is positioned from the frame count in the WAV header
+    let total_frames: usize = wave_read.call_method0("getnframes")?.extract()?;
+    // Like `lame_sys::dsp`, fades longer than the clip cover the whole clip
+    let fade = Fade {
+        in_frames: (fade_ms.0 as usize * sample_rate as usize / 1000).min(total_frames),
+        out_frames: (fade_ms.1 as usize * sample_rate as usize / 1000).min(total_frames),
+        total_frames,
+    };
+    let mut position = 0;
+
+    let mut mp3_data = Vec::new();
+    let mut mp3_buffer = vec![0u8; lame_sys::LameEncoder::mp3_buffer_size(CHUNK_FRAMES)];
+    let mut pcm = Vec::with_capacity(CHUNK_FRAMES * channels as usize);
+
+    loop {
+        let chunk = wave_read.call_method1("readframes", (CHUNK_FRAMES,))?;
+        let bytes = chunk.downcast::<PyBytes>()?.as_bytes();
+        if bytes.is_empty() {
+            break;
+        }
+
+        // WAV PCM data is always little-endian
+        pcm.clear();
+        pcm.extend(
+            bytes
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]])),
+        );
+        fade.apply(&mut pcm, channels as usize, position);
+        position += pcm.len() / channels as usize;
+
+        let bytes_written = if channels == 2 {
+            encoder.encode_interleaved(&pcm, &mut mp3_buffer)
+        } else {
+            encoder.encode_mono(&pcm, &mut mp3_buffer)
+        }
+        .map_err(to_py_err)?;
+        mp3_data.extend_from_slice(&mp3_buffer[..bytes_written]);
+    }
+
+    let bytes_written = encoder.flush(&mut mp3_buffer).map_err(to_py_err)?;
+    mp3_data.extend_from_slice(&mp3_buffer[..bytes_written]);
+
+    Ok(PyBytes::new_bound(py, &mp3_data))
+}
+
+/// Fade-in/fade-out applied chunk by chunk while streaming a wave file
+struct Fade {
+    in_frames: usize,
+    out_frames: usize,
+    total_frames: usize,
+}
+
+impl Fade {
+    /// Scale the interleaved `chunk` that starts at frame `start`
+    fn apply(&self, chunk: &mut [i16], channels: usize, start: usize) {
+        let shape = FadeShape::Linear;
+        for (offset, frame) in chunk.chunks_exact_mut(channels).enumerate() {
+            let position = start + offset;
+            let from_end = self.total_frames.saturating_sub(position + 1);
+            let gain = shape.gain(position, self.in_frames) * shape.gain(from_end, self.out_frames);
+            if gain < 1.0 {
+                for sample in frame {
+                    *sample = (f32::from(*sample) * gain).round() as i16;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+"""
+Long-running memory growth check for long-lived encoders
+
+Not collected by default (the file name has no ``test_`` prefix). Run it with
+
+    LAME_SOAK=1 pytest python-lame/tests/soak.py
+
+or directly as ``python python-lame/tests/soak.py``. ``LAME_SOAK_FRAMES``
+overrides the number of frames. RSS is read from /proc/self/statm, so it only
+runs on Linux.
+"""
+
+import math
+import os
+import struct
+import sys
+
+import pytest
+
+# About 14 hours of 44.1 kHz audio
+SOAK_FRAMES = 2_000_000
+
+# Retag the encoder every this many frames
+RETAG_INTERVAL = 10_000
+
+# Return the encoder to the pool (resetting it) every this many frames
+RESET_INTERVAL = 100_000
+
+# Allowed RSS growth after the first reset, in pages
+MAX_GROWTH_PAGES = 1024
+
+
+def resident_pages():
+    """Resident set size of this process in pages"""
+    with open("/proc/self/statm") as f:
+        return int(f.read().split()[1])
+
+
+def sine_frame():
+    """One frame of interleaved stereo 440 Hz sine wave as bytes"""
+    samples = []
+    for i in range(1152):
+        value = int(16384 * math.sin(2 * math.pi * 440 * i / 44100))
+        samples.extend([value, value])
+    return struct.pack("<%dh" % len(samples), *samples)
+
+
+def retag(encoder, cycle):
+    """Build a full tag mid-stream; apply() rejects it once encoding has started"""
+    tag = encoder.id3_tag()
+    tag.title("Call %d" % cycle)
+    tag.artist("Soak Test")
+    tag.album("Gateway")
+    tag.comment("x" * (cycle % 64))
+    tag.track(cycle % 256)
+    with pytest.raises(RuntimeError, match="before encoding starts"):
+        tag.apply()
+
+
+def run_soak(total_frames):
+    """Encode `total_frames` frames and return the peak RSS growth in pages"""
+    import lame
+
+    pool = lame.EncoderPool(sample_rate=44100, channels=2, bitrate=128, max_size=1)
+    encoder = pool.acquire()
+    frame = sine_frame()
+    # An occasional oversized call must not pin its output buffer forever
+    burst = frame * 1024
+
+    baseline = None
+    peak_growth = 0
+
+    for index in range(1, total_frames + 1):
+        encoder.encode_interleaved(frame)
+
+        if index % RETAG_INTERVAL == 0:
+            encoder.encode_interleaved(burst)
+            retag(encoder, index // RETAG_INTERVAL)
+
+        if index % RESET_INTERVAL == 0:
+            encoder.flush()
+            encoder.release()
+            encoder = pool.acquire()
+
+            pages = resident_pages()
+            if baseline is None:
+                baseline = pages
+            else:
+                growth = max(0, pages - baseline)
+                peak_growth = max(peak_growth, growth)
+                assert growth <= MAX_GROWTH_PAGES, (
+                    "RSS grew by %d pages after %d frames" % (growth, index)
+                )
+
+    encoder.release()
+    return peak_growth
+
+
+@pytest.mark.skipif(not sys.platform.startswith("linux"), reason="needs /proc/self/statm")
+@pytest.mark.skipif(not os.environ.get("LAME_SOAK"), reason="set LAME_SOAK=1 to run")
+def test_soak_long_lived_encoder():
+    run_soak(int(os.environ.get("LAME_SOAK_FRAMES", SOAK_FRAMES)))
+
+
+if __name__ == "__main__":
+    frames = int(os.environ.get("LAME_SOAK_FRAMES", SOAK_FRAMES))
+    growth = run_soak(frames)
+    print("soak: %d frames, peak RSS growth %d pages" % (frames, growth))
@@ -2,6 +2,8 @@
 Basic tests for the LAME Python bindings
 """
 
+import os
+
 import pytest
 
 
@@ -23,6 +25,18 @@ def test_version():
     assert "lame" in url.lower() or "mp3" in url.lower()
 
 
+def test_version_info():
+    """Test numeric version info"""
+    import lame
+    info = lame.version_info()
+    assert set(info) == {
+        "major", "minor", "alpha", "beta", "psy_major", "psy_minor", "features",
+    }
+    assert (info["major"], info["minor"]) >= (3, 100)
+    assert lame.get_version().startswith(f"{info['major']}.{info['minor']}")
+    assert isinstance(info["features"], str)
+
+
 def test_quality_enum():
     """Test Quality enum"""
     import lame
@@ -45,6 +59,32 @@ def test_vbr_mode_enum():
     assert hasattr(lame.VbrMode, 'Abr')
 
 
+def test_channel_mode_enum():
+    """Test ChannelMode enum"""
+    import lame
+
+    assert lame.ChannelMode.Stereo == 0
+    assert lame.ChannelMode.JointStereo == 1
+    assert lame.ChannelMode.Mono == 3
+
+
+def test_mono_downmix():
+    """Stereo input with ChannelMode.Mono encodes a mono stream"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(64)
+        .mode(lame.ChannelMode.Mono)
+        .build()
+    )
+
+    mp3_data = encoder.encode_interleaved(bytes(1152 * 4 * 4)) + encoder.flush()
+    assert len(mp3_data) > 0
+
+
 def test_encoder_builder():
     """Test encoder builder pattern"""
     import lame
@@ -134,6 +174,58 @@ def test_interleaved_encoding():
     assert isinstance(final_data, bytes)
 
 
+def test_interleaved_odd_length_rejected():
+    """Odd-length interleaved input raises instead of dropping a sample"""
+    import lame
+    import numpy as np
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .build()
+    )
+
+    odd = np.zeros(1153, dtype=np.int16)
+    with pytest.raises(ValueError, match="even"):
+        encoder.encode_interleaved(odd.tobytes())
+    with pytest.raises(ValueError, match="even"):
+        encoder.encode_interleaved_numpy(odd)
+
+
+def test_max_samples_per_call():
+    """Splitting large inputs across LAME calls does not change the output"""
+    import lame
+    import numpy as np
+
+    def make_encoder():
+        return (
+            lame.LameEncoder.builder()
+            .sample_rate(48000)
+            .channels(1)
+            .bitrate(128)
+            .build()
+        )
+
+    pcm = (np.sin(np.arange(48000 * 3) * 0.05) * 10000).astype(np.int16)
+
+    guarded = make_encoder()
+    assert guarded.max_samples_per_call is None
+    guarded.max_samples_per_call = 48000
+    assert guarded.max_samples_per_call == 48000
+
+    plain = make_encoder()
+    expected = plain.encode_mono_numpy(pcm) + plain.flush()
+    actual = guarded.encode_mono_numpy(pcm) + guarded.flush()
+    assert actual == expected
+
+    with pytest.raises(ValueError):
+        guarded.max_samples_per_call = 0
+    guarded.max_samples_per_call = None
+    assert guarded.max_samples_per_call is None
+
+
 def test_id3_tags():
     """Test ID3 tag functionality"""
     import lame
@@ -202,5 +294,832 @@ def test_different_sample_rates():
         assert isinstance(mp3_data, bytes)
 
 
+def test_resample_48k_to_24k():
+    """48 kHz input encoded to a 24 kHz MP3"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .input_sample_rate(48000)
+        .output_sample_rate(24000)
+        .channels(2)
+        .bitrate(64)
+        .build()
+    )
+    assert encoder.input_sample_rate == 48000
+    assert encoder.output_sample_rate == 24000
+
+    pcm = bytes(48000 * 2 * 2)
+    mp3_data = encoder.encode_interleaved(pcm) + encoder.flush()
+    assert len(mp3_data) > 0
+
+
+def test_output_sample_rate_chosen_by_lame():
+    """Only setting the input rate lets LAME pick the output rate"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .input_sample_rate(48000)
+        .channels(1)
+        .bitrate(32)
+        .build()
+    )
+    assert encoder.output_sample_rate in (8000, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000)
+
+    with pytest.raises(ValueError):
+        lame.LameEncoder.builder().output_sample_rate(23000)
+
+
+def test_build_reports_invalid_bitrate():
+    """An illegal bitrate for the output sample rate names the allowed values"""
+    import lame
+
+    builder = lame.LameEncoder.builder().sample_rate(48000).channels(2).bitrate(24)
+    with pytest.raises(ValueError, match=r"24 kbps is not valid for MPEG-1 .* allowed: 32, 40"):
+        builder.build()
+
+
+@pytest.mark.parametrize(
+    "configure",
+    [
+        lambda b: b.lowpass(8000),
+        lambda b: b.lowpass(8000).lowpass_width(1000),
+        lambda b: b.highpass(100),
+        lambda b: b.highpass(100).highpass_width(50),
+        lambda b: b.lowpass(-1).highpass(-1),
+    ],
+)
+def test_filters(configure):
+    """Encoders build and encode with each filter setting"""
+    import lame
+
+    builder = lame.LameEncoder.builder().sample_rate(44100).channels(1).bitrate(64)
+    encoder = configure(builder).build()
+    mp3_data = encoder.encode_mono(bytes(1152 * 2)) + encoder.flush()
+    assert len(mp3_data) > 0
+
+
+def test_filter_rejects_below_disabled():
+    import lame
+
+    with pytest.raises(ValueError):
+        lame.LameEncoder.builder().lowpass(-2)
+
+
+def test_presets():
+    """Every preset builds and encodes; an int selects an ABR preset"""
+    import lame
+
+    presets = [getattr(lame.Preset, "V%d" % n) for n in range(10)]
+    presets += [lame.Preset.Standard, lame.Preset.Extreme, lame.Preset.Insane, 128]
+    for preset in presets:
+        encoder = (
+            lame.LameEncoder.builder()
+            .preset(preset)
+            .sample_rate(44100)
+            .channels(2)
+            .build()
+        )
+        mp3_data = encoder.encode_interleaved(bytes(1152 * 4 * 4)) + encoder.flush()
+        assert len(mp3_data) > 0, preset
+
+    with pytest.raises(ValueError):
+        lame.LameEncoder.builder().preset(400)
+    with pytest.raises(TypeError):
+        lame.LameEncoder.builder().preset("V2")
+
+
+def test_abr_bitrate():
+    """ABR mean bitrate controls the output size"""
+    import lame
+
+    pcm = os.urandom(44100 * 4)
+    sizes = []
+    for kbps in (96, 256):
+        encoder = (
+            lame.LameEncoder.builder()
+            .sample_rate(44100)
+            .channels(2)
+            .vbr_mode(lame.VbrMode.Abr)
+            .abr_bitrate(kbps)
+            .build()
+        )
+        sizes.append(len(encoder.encode_interleaved(pcm) + encoder.flush()))
+    assert sizes[1] > sizes[0] * 2
+
+    with pytest.raises(ValueError):
+        lame.LameEncoder.builder().abr_bitrate(400)
+    with pytest.raises(ValueError):
+        lame.LameEncoder.builder().abr_bitrate(160).build()
+
+
+def test_vbr_bitrate_range():
+    """A VBR encoder pinned to min=max=128 kbps still encodes"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .vbr_mode(lame.VbrMode.Vbr)
+        .vbr_min_bitrate(128)
+        .vbr_max_bitrate(128)
+        .vbr_enforce_min(True)
+        .build()
+    )
+    mp3_data = encoder.encode_interleaved(os.urandom(44100 * 4)) + encoder.flush()
+    assert len(mp3_data) > 0
+
+    with pytest.raises(ValueError):
+        lame.LameEncoder.builder().vbr_min_bitrate(4)
+    with pytest.raises(ValueError):
+        lame.LameEncoder.builder().vbr_min_bitrate(256).vbr_max_bitrate(96).build()
+
+def test_total_samples_hint():
+    """Encoding exactly the announced number of samples flushes normally"""
+    import lame
+
+    samples = 1152 * 20 + 300
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .vbr_mode(lame.VbrMode.Vbr)
+        .total_samples(samples)
+        .build()
+    )
+    encoder.encode_interleaved(os.urandom(samples * 4))
+    assert len(encoder.flush()) > 0
+
+    with pytest.raises(OverflowError):
+        lame.LameEncoder.builder().total_samples(-1)
+
+def test_write_vbr_tag_toggle():
+    """Disabling the VBR tag drops the info frame from the first encode call"""
+    import lame
+
+    pcm = os.urandom(1152 * 10 * 4)
+
+    def first_call(write_vbr_tag):
+        encoder = (
+            lame.LameEncoder.builder()
+            .sample_rate(44100)
+            .channels(2)
+            .bitrate(128)
+            .write_vbr_tag(write_vbr_tag)
+            .build()
+        )
+        return encoder.encode_interleaved(pcm)
+
+    with_tag = first_call(True)
+    without_tag = first_call(False)
+    info_len = 144 * 128000 // 44100 + ((with_tag[2] >> 1) & 1)
+    # The info frame is a placeholder: zeros after the 4-byte header
+    assert with_tag[4:info_len] == bytes(info_len - 4)
+    assert without_tag[4:info_len] != bytes(info_len - 4)
+    assert with_tag[info_len:] == without_tag
+
+def test_quality_level_and_scale():
+    """quality() takes a plain LAME level; scale() applies gain before encoding"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .quality(3)
+        .scale(0.5)
+        .scale_left(1.5)
+        .scale_right(0.25)
+        .build()
+    )
+    mp3_data = encoder.encode_interleaved(os.urandom(44100 * 4)) + encoder.flush()
+    assert len(mp3_data) > 0
+
+    pcm = os.urandom(1152 * 20 * 4)
+
+    def encode(quality):
+        encoder = (
+            lame.LameEncoder.builder()
+            .sample_rate(44100)
+            .channels(2)
+            .bitrate(128)
+            .quality(quality)
+            .build()
+        )
+        return encoder.encode_interleaved(pcm) + encoder.flush()
+
+    assert encode(5) == encode(lame.Quality.Standard)
+
+    for level in (-1, 10):
+        with pytest.raises(ValueError):
+            lame.LameEncoder.builder().quality(level)
+    with pytest.raises(ValueError):
+        lame.LameEncoder.builder().scale(float("nan"))
+    with pytest.raises(TypeError):
+        lame.LameEncoder.builder().quality("high")
+
+
+@pytest.mark.parametrize("temporal", [True, False])
+def test_short_blocks_and_temporal_masking(temporal):
+    """Every short block mode encodes a transient-heavy signal"""
+    import lame
+
+    # Silence with a 5 ms noise burst every 100 ms
+    samples = bytearray(44100 * 4)
+    for start in range(0, 44100, 4410):
+        samples[start * 4:(start + 220) * 4] = os.urandom(220 * 4)
+    pcm = bytes(samples)
+
+    outputs = []
+    for mode in (lame.ShortBlockMode.Allowed, lame.ShortBlockMode.Dispensed,
+                 lame.ShortBlockMode.Forced):
+        encoder = (
+            lame.LameEncoder.builder()
+            .sample_rate(44100)
+            .channels(2)
+            .bitrate(128)
+            .short_blocks(mode)
+            .use_temporal_masking(temporal)
+            .build()
+        )
+        outputs.append(encoder.encode_interleaved(pcm) + encoder.flush())
+        assert len(outputs[-1]) > 0
+
+    # Forcing and dispensing short blocks differ on transients
+    assert outputs[2] != outputs[1]
+    assert repr(lame.ShortBlockMode.Forced) == "ShortBlockMode.Forced"
+
+
+def test_force_ms_and_ms_fix():
+    """force_ms() and ms_fix() are readable back from the built encoder"""
+    import lame
+
+    def builder():
+        return (
+            lame.LameEncoder.builder()
+            .sample_rate(44100)
+            .channels(2)
+            .bitrate(64)
+            .mode(lame.ChannelMode.JointStereo)
+        )
+
+    assert not builder().build().forces_ms
+    encoder = builder().force_ms(True).ms_fix(1.5).build()
+    assert encoder.forces_ms
+    assert encoder.ms_fix == 1.5
+    assert len(encoder.encode_interleaved(os.urandom(1152 * 10 * 4)) + encoder.flush()) > 0
+
+    for msfix in (-1.0, float("nan")):
+        with pytest.raises(ValueError):
+            builder().ms_fix(msfix)
+
+
+def test_effective_settings():
+    """Encoder properties report the settings LAME actually uses"""
+    import lame
+
+    encoder = lame.LameEncoder.builder().sample_rate(44100).channels(2).bitrate(128).build()
+    assert encoder.output_sample_rate == 44100
+    assert encoder.channels == 2
+    assert encoder.bitrate == 128
+    assert encoder.mode == lame.ChannelMode.JointStereo
+    assert encoder.vbr_mode == lame.VbrMode.Off
+
+    # LAME resamples low-bitrate stereo and forces mono for one input channel
+    low = lame.LameEncoder.builder().input_sample_rate(44100).channels(2).bitrate(32).build()
+    assert low.output_sample_rate < 44100
+    mono = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(1)
+        .bitrate(64)
+        .mode(lame.ChannelMode.Stereo)
+        .build()
+    )
+    assert mono.mode == lame.ChannelMode.Mono
+
+
+def test_replay_gain():
+    """find_replay_gain() reports a negative gain for a loud tone after flush()"""
+    import lame
+    import math
+    import struct
+
+    tone = [int(16384 * math.sin(2 * math.pi * 440 * i / 44100)) for i in range(44100 * 3)]
+    pcm = struct.pack(f"<{len(tone) * 2}h", *(s for s in tone for _ in range(2)))
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .find_replay_gain(True)
+        .build()
+    )
+    encoder.encode_interleaved(pcm)
+    assert encoder.replay_gain is None
+    encoder.flush()
+    assert -20.0 < encoder.replay_gain < -5.0
+    assert encoder.peak_sample is None
+
+
+def test_inter_channel_ratio():
+    """inter_channel_ratio() is readable back and mono encoders reject interleaved input"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .inter_channel_ratio(0.25)
+        .build()
+    )
+    assert encoder.inter_channel_ratio == 0.25
+
+    for ratio in (1.5, float("nan")):
+        with pytest.raises(ValueError):
+            lame.LameEncoder.builder().inter_channel_ratio(ratio)
+
+    mono = lame.LameEncoder.builder().sample_rate(44100).channels(1).bitrate(128).build()
+    with pytest.raises(ValueError, match="2-channel"):
+        mono.encode_interleaved(bytes(1152 * 4))
+
+def test_raw_params():
+    """set_raw() reaches parameters the builder has no method for"""
+    import lame
+
+    def builder():
+        return lame.LameEncoder.builder().sample_rate(44100).channels(2).bitrate(128)
+
+    encoder = (
+        builder()
+        .set_raw("quant_comp", 3)
+        .set_raw("interChRatio", 0.25)
+        .set_raw("ATHlower", 2)
+        .build()
+    )
+    assert encoder.get_raw("quant_comp") == 3
+    assert isinstance(encoder.get_raw("quant_comp"), int)
+    assert encoder.get_raw("interChRatio") == 0.25
+    assert encoder.get_raw("ATHlower") == 2.0
+    assert isinstance(encoder.get_raw("ATHlower"), float)
+
+    for name, value in [
+        ("no_such_param", 1),
+        ("error_protection", 2),
+        ("interChRatio", 1.5),
+        ("quant_comp", 1.0),
+    ]:
+        with pytest.raises(ValueError, match=name):
+            builder().set_raw(name, value)
+    with pytest.raises(ValueError, match="no_such_param"):
+        encoder.get_raw("no_such_param")
+
+
+    """Delay, padding and the gapless length account for every input sample"""
+    import lame
+
+    samples = 1152 * 20 + 300
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .total_samples(samples)
+        .build()
+    )
+    encoder.encode_interleaved(os.urandom(samples * 4))
+    assert encoder.end_padding == 0
+
+    encoder.flush()
+    assert encoder.encoder_delay > 0
+    assert encoder.end_padding > 0
+    assert encoder.gapless_samples == samples
+
+def test_id3_text_validation():
+    """NUL is rejected with the field name, control chars become spaces, emoji pass"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .build()
+    )
+
+    tag = encoder.id3_tag()
+    with pytest.raises(ValueError, match="title contains NUL at byte 5"):
+        tag.title("Hello\0World")
+    with pytest.raises(ValueError, match="comment contains NUL at byte 0"):
+        tag.comment("\0")
+
+    # A rejected value leaves the tag usable
+    tag.title("Night \U0001F3B5")
+    tag.comment("Line1\nLine2")
+    tag.apply()
+
+    mp3_data = encoder.encode_interleaved(bytes(1152 * 4 * 2)) + encoder.flush()
+    v1 = mp3_data[-128:]
+    assert v1[:3] == b"TAG"
+    assert b"Line1 Line2" in v1
+    assert "\U0001F3B5".encode("utf-8") in v1
+
+
+def test_id3_tag_keeps_encoder_alive():
+    """A tag holds a reference to its encoder and fails cleanly once it is released"""
+    import gc
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .build()
+    )
+    tag = encoder.id3_tag()
+    tag.title("Orphan")
+    del encoder
+    gc.collect()
+    # The encoder is still alive through the tag
+    tag.apply()
+
+    pool = lame.EncoderPool(sample_rate=44100, channels=2, bitrate=128, max_size=1)
+    pooled = pool.acquire()
+    tag = pooled.id3_tag()
+    tag.title("Returned")
+    pooled.release()
+    with pytest.raises(RuntimeError):
+        tag.apply()
+
+
+
+def test_id3_tag_state():
+    """A tag can be fixed after a rejected value but not reused after apply()"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .build()
+    )
+    tag = encoder.id3_tag()
+    with pytest.raises(ValueError, match="genre"):
+        tag.genre("999")
+    with pytest.raises(ValueError, match="genre"):
+        tag.genre("Chiptune")
+    tag.genre("Rock")
+    tag.apply()
+
+    with pytest.raises(RuntimeError, match="tag already applied"):
+        tag.apply()
+    with pytest.raises(RuntimeError, match="tag already applied"):
+        tag.title("Too late")
+
+    mp3_data = encoder.encode_interleaved(bytes(1152 * 4 * 2)) + encoder.flush()
+    assert mp3_data[-128:][:3] == b"TAG"
+
+
+def test_id3_oversized_tag_rejected():
+    """An ID3v2 tag too large for LAME is dropped instead of corrupting the stream"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .build()
+    )
+    tag = encoder.id3_tag()
+    tag.title("Dropped with the rest of the tag")
+    tag.comment("x" * 150_000)
+    with pytest.raises(ValueError, match="143360 byte limit"):
+        tag.apply()
+
+    mp3_data = encoder.encode_interleaved(bytes(1152 * 4 * 2)) + encoder.flush()
+    assert mp3_data[:3] != b"ID3"
+    assert mp3_data[-128:][:3] != b"TAG"
+
+
+TINY_PNG = bytes.fromhex(
+    "89504e470d0a1a0a0000000d4948445200000001000000010806000000"
+    "1f15c4890000000a49444154789c63000100000500010d0a2db4000000"
+    "0049454e44ae426082"
+)
+
+
+def test_id3_album_art():
+    """album_art() embeds the image bytes in an APIC frame"""
+    import lame
+
+    def encode(image):
+        encoder = (
+            lame.LameEncoder.builder()
+            .sample_rate(44100)
+            .channels(2)
+            .bitrate(128)
+            .build()
+        )
+        tag = encoder.id3_tag()
+        tag.album_artist("Various Artists")
+        if image is not None:
+            tag.album_art(image)
+        tag.apply()
+        return encoder.encode_interleaved(bytes(1152 * 4 * 2)) + encoder.flush()
+
+    def id3v2_len(data):
+        assert data[:3] == b"ID3"
+        size = 0
+        for b in data[6:10]:
+            size = (size << 7) | (b & 0x7F)
+        return 10 + size
+
+    plain = encode(None)
+    with_art = encode(TINY_PNG)
+    assert id3v2_len(with_art) - id3v2_len(plain) > len(TINY_PNG)
+    assert b"APIC" in with_art[: id3v2_len(with_art)]
+    assert TINY_PNG in with_art[: id3v2_len(with_art)]
+
+    encoder = lame.LameEncoder.builder().sample_rate(44100).channels(2).build()
+    tag = encoder.id3_tag()
+    with pytest.raises(ValueError, match="JPEG, PNG or GIF"):
+        tag.album_art(b"not an image")
+    with pytest.raises(ValueError, match="byte limit.*resize the image"):
+        tag.album_art(TINY_PNG + bytes(200_000))
+
+
+def test_genre_list():
+    """genre_list() pairs every ID3v1 genre number with its name"""
+    import lame
+
+    genres = lame.genre_list()
+    assert len(genres) == 148
+    assert sorted(number for number, _ in genres) == list(range(148))
+    assert (17, "Rock") in genres
+    assert genres[0] == (123, "A Cappella")
+    assert genres[-1] == (28, "Vocal")
+
+
+def test_id3_track_and_disc():
+    """track() and disc() write "n/total" into TRCK and TPOS"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .build()
+    )
+    tag = encoder.id3_tag()
+    tag.title("Track")
+    tag.track(3, total=12)
+    tag.disc(1, 2)
+    tag.apply()
+    mp3 = encoder.encode_interleaved(bytes(1152 * 4 * 2)) + encoder.flush()
+
+    assert mp3[:3] == b"ID3"
+    assert b"TRCK" in mp3 and b"3/12" in mp3
+    assert b"TPOS" in mp3 and b"1/2" in mp3
+    # ID3v1 keeps only the track number
+    assert mp3[-128:-125] == b"TAG"
+    assert mp3[-2] == 3
+
+
+def test_id3_field_version_and_padding():
+    """field(), version() and pad_v2() shape the written tags"""
+    import lame
+
+    def encode(setup):
+        encoder = (
+            lame.LameEncoder.builder()
+            .sample_rate(44100)
+            .channels(2)
+            .bitrate(128)
+            .build()
+        )
+        tag = encoder.id3_tag()
+        tag.title("Fields")
+        setup(tag)
+        tag.apply()
+        return encoder.encode_interleaved(bytes(1152 * 4 * 2)) + encoder.flush()
+
+    mp3 = encode(lambda tag: tag.field("TCOM", "J. S. Bach"))
+    assert mp3[:3] == b"ID3"
+    assert b"TCOM" in mp3 and b"J. S. Bach" in mp3
+
+    # Only ID3v1 by default for short fields, only ID3v2 with "v2"
+    mp3 = encode(lambda tag: None)
+    assert mp3[:3] != b"ID3" and mp3[-128:-125] == b"TAG"
+    mp3 = encode(lambda tag: tag.version("v2"))
+    assert mp3[:3] == b"ID3" and mp3[-128:-125] != b"TAG"
+    mp3 = encode(lambda tag: tag.version("both"))
+    assert mp3[:3] == b"ID3" and mp3[-128:-125] == b"TAG"
+
+    def padded(tag):
+        tag.version("v2")
+        tag.pad_v2(1000)
+
+    assert b"\x00" * 1000 in encode(padded)
+
+    encoder = lame.LameEncoder.builder().sample_rate(44100).channels(2).build()
+    tag = encoder.id3_tag()
+    with pytest.raises(ValueError, match="frame ID"):
+        tag.field("tcom", "lowercase")
+    with pytest.raises(ValueError, match="v1"):
+        tag.version("v3")
+    tag.field("APIC", "not a text frame")
+    with pytest.raises(ValueError, match="APIC"):
+        tag.apply()
+
+
+def test_id3_setters_chain():
+    """Every setter returns the tag, so the documented fluent style works"""
+    import lame
+
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .build()
+    )
+    tag = encoder.id3_tag()
+    chained = (
+        tag.title("My Song")
+        .artist("My Artist")
+        .album("My Album")
+        .year("2024")
+        .comment("Chained")
+        .track(3, total=12)
+        .disc(1, 2)
+        .genre("Rock")
+        .album_artist("Various Artists")
+        .album_art(TINY_PNG)
+        .field("TCOM", "Composer")
+        .version("both")
+        .pad_v2(16)
+    )
+    assert chained is tag
+    assert chained.apply() is None
+
+    mp3 = encoder.encode_interleaved(bytes(1152 * 4 * 2)) + encoder.flush()
+    assert mp3[:3] == b"ID3"
+    for text in (b"My Song", b"My Artist", b"3/12", b"Various Artists", b"Composer"):
+        assert text in mp3
+    assert mp3[-128:-125] == b"TAG"
+
+    # The short form from the module docstring
+    encoder = lame.LameEncoder.builder().sample_rate(44100).channels(2).build()
+    encoder.id3_tag().title("My Song").artist("My Artist").apply()
+
+    with pytest.raises(RuntimeError, match="tag already applied"):
+        tag.title("Again")
+
+
+def test_id3_year_and_release_date():
+    """year() takes int or str; release_date() adds a TDAT frame"""
+    import lame
+
+    def encode(setup):
+        encoder = (
+            lame.LameEncoder.builder()
+            .sample_rate(44100)
+            .channels(2)
+            .bitrate(128)
+            .build()
+        )
+        setup(encoder.id3_tag().version("both")).apply()
+        return encoder.encode_interleaved(bytes(1152 * 4 * 2)) + encoder.flush()
+
+    for year in (2024, "2024"):
+        mp3 = encode(lambda tag: tag.year(year))
+        assert b"TYER" in mp3
+        assert mp3[-128 + 93 : -128 + 97] == b"2024"
+
+    mp3 = encode(lambda tag: tag.release_date(2024, 2, 29))
+    assert b"TDAT" in mp3 and b"2902" in mp3
+
+    encoder = lame.LameEncoder.builder().sample_rate(44100).channels(2).build()
+    tag = encoder.id3_tag()
+    for year in (999, 10000, "24", "2024-01-01", "abcd"):
+        with pytest.raises(ValueError, match="year"):
+            tag.year(year)
+    with pytest.raises(TypeError, match="int or str"):
+        tag.year(2024.0)
+    with pytest.raises(ValueError, match="not a valid date"):
+        tag.release_date(2023, 2, 29)
+
+    encoder.set_id3(year=2021)
+    with pytest.raises(ValueError, match="year"):
+        encoder.set_id3(year="twenty")
+
+
+def test_id3v2_tag_size_with_padding():
+    """id3v2_tag_size() reports the reserved space before any audio is sent"""
+    import lame
+
+    def tag_size(padding):
+        encoder = (
+            lame.LameEncoder.builder()
+            .sample_rate(44100)
+            .channels(2)
+            .bitrate(128)
+            .build()
+        )
+        assert encoder.id3v2_tag_size() == 0
+        encoder.id3_tag().title("Live stream").version("v2").pad_v2(padding).apply()
+        size = encoder.id3v2_tag_size()
+        mp3 = encoder.encode_interleaved(bytes(1152 * 4 * 2)) + encoder.flush()
+        assert mp3[:3] == b"ID3"
+        # The header stores the size after the 10 header bytes, 7 bits per byte
+        body = 0
+        for b in mp3[6:10]:
+            body = (body << 7) | (b & 0x7F)
+        assert 10 + body == size
+        return size
+
+    unpadded = tag_size(0)
+    assert tag_size(4096) == unpadded + 4096
+
+
+def test_set_id3_keywords():
+    """set_id3(**kwargs) maps keys to the tag setters"""
+    import lame
+
+    def encoder():
+        return (
+            lame.LameEncoder.builder()
+            .sample_rate(44100)
+            .channels(2)
+            .bitrate(128)
+            .build()
+        )
+
+    enc = encoder()
+    enc.set_id3(
+        title="夜に駆ける",
+        artist="Café Tacvba",
+        track=3,
+        track_total=12,
+        genre="Rock",
+        album_art=TINY_PNG,
+        comment=None,
+    )
+    mp3 = enc.encode_interleaved(bytes(1152 * 4 * 2)) + enc.flush()
+    assert mp3[:3] == b"ID3"
+    # Non-ASCII text is written to ID3v2 as UTF-16 and survives unchanged
+    assert "夜に駆ける".encode("utf-16-le") in mp3
+    assert "Café Tacvba".encode("utf-16-le") in mp3
+    assert b"3/12" in mp3
+    assert TINY_PNG in mp3
+    assert b"APIC" in mp3
+
+    enc = encoder()
+    with pytest.raises(lame.InvalidParameterError, match="colour, titel"):
+        enc.set_id3(title="Song", titel="Typo", colour="red")
+    with pytest.raises(TypeError, match="track"):
+        enc.set_id3(track="three")
+    with pytest.raises(ValueError, match="track_total.*genre"):
+        enc.set_id3(genre="Not A Genre", track_total=12)
+    with pytest.raises(ValueError, match="JPEG, PNG or GIF"):
+        enc.set_id3(album_art=b"not an image")
+
+    # Nothing was written by the failed calls
+    mp3 = enc.encode_interleaved(bytes(1152 * 4 * 2)) + enc.flush()
+    assert mp3[:3] != b"ID3"
+    assert mp3[-128:-125] != b"TAG"
+
+
+def test_id3_apply_after_encoding_fails():
+    """Tags applied after the first encode call raise RuntimeError"""
+    import lame
+
+    encoder = lame.LameEncoder.builder().sample_rate(44100).channels(2).build()
+    encoder.encode_interleaved(bytes(1152 * 4))
+    tag = encoder.id3_tag()
+    tag.title("Too late")
+    with pytest.raises(RuntimeError, match="before encoding starts"):
+        tag.apply()
+
 if __name__ == "__main__":
     pytest.main([__file__, "-v"])
+
+
+def test_frame_size():
+    """frame_size is 1152 for MPEG-1 sample rates and 576 for MPEG-2"""
+    import lame
+
+    def frame_size(rate):
+        return lame.LameEncoder.builder().sample_rate(rate).channels(2).bitrate(64).build().frame_size
+
+    assert frame_size(44100) == 1152
+    assert frame_size(16000) == 576
@@ -0,0 +1,26 @@
+"""
+Tests for the decoder availability flag and the placeholders used when the
+decoder is not compiled in
+"""
+
+import pytest
+
+
+def test_has_decoder_is_bool():
+    import lame
+    assert isinstance(lame.HAS_DECODER, bool)
+
+
+def test_decoder_api_without_decoder():
+    """Without the decoder every entry point raises a clear NotImplementedError"""
+    import lame
+
+    if lame.HAS_DECODER:
+        pytest.skip("this build includes the decoder")
+
+    with pytest.raises(NotImplementedError, match="compiled without the decoder"):
+        lame.Mp3Decoder()
+    with pytest.raises(NotImplementedError, match="compiled without the decoder"):
+        lame.decode_file("input.mp3")
+    with pytest.raises(NotImplementedError, match="compiled without the decoder"):
+        lame.transcode("input.mp3", "output.mp3", bitrate=128)
@@ -0,0 +1,73 @@
+"""
+Tests for comparing encoder settings
+"""
+
+import math
+import struct
+
+import pytest
+
+
+def sine_pcm(frames=40):
+    """Interleaved stereo 440 Hz sine wave as bytes"""
+    samples = []
+    for i in range(1152 * frames):
+        value = int(16384 * math.sin(2 * math.pi * 440 * i / 44100))
+        samples.extend([value, value])
+    return struct.pack("<%dh" % len(samples), *samples)
+
+
+def make_encoder(vbr=False):
+    import lame
+    builder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .quality(lame.Quality.High)
+    )
+    if vbr:
+        builder = builder.vbr_mode(lame.VbrMode.Vbr).vbr_quality(2)
+    else:
+        builder = builder.bitrate(128)
+    return builder.build()
+
+
+def encode_file(vbr=False):
+    import lame
+    with lame.Mp3Buffer(make_encoder(vbr)) as buffer:
+        buffer.write(sine_pcm())
+    return buffer.getvalue()
+
+
+def test_diff_cbr_vs_vbr_encoders():
+    import lame
+
+    diffs = lame.diff_configs(make_encoder(), make_encoder(vbr=True))
+    assert [d["field"] for d in diffs] == ["bitrate", "vbr_mode", "vbr_quality"]
+    assert diffs[0] == {"field": "bitrate", "a": "128", "b": "default"}
+    assert diffs[1]["b"] == "Vbr"
+    assert diffs[2]["b"] == "2"
+
+    encoder = make_encoder()
+    assert lame.diff_configs(encoder, encoder) == []
+
+
+@pytest.mark.parametrize("vbr", [False, True])
+def test_diff_encoder_against_file(vbr):
+    """A file's LAME tag reports the settings it was encoded with"""
+    import lame
+
+    mp3 = encode_file(vbr)
+    assert lame.diff_configs(make_encoder(vbr), mp3) == []
+
+    fields = [d["field"] for d in lame.diff_configs(make_encoder(not vbr), mp3)]
+    assert fields == ["bitrate", "vbr_mode", "vbr_quality"]
+
+
+def test_diff_rejects_other_inputs():
+    import lame
+
+    with pytest.raises(ValueError, match="LAME tag"):
+        lame.diff_configs(make_encoder(), b"not an mp3 file")
+    with pytest.raises(TypeError, match="LameEncoder or MP3 bytes"):
+        lame.diff_configs(make_encoder(), "a.mp3")
@@ -0,0 +1,140 @@
+"""
+Run every script in examples/ so they stay in sync with the API
+
+Each example exposes `main(argv)`; the tests call it with small inputs and
+check that valid MP3 data was written.
+"""
+
+import importlib.util
+import math
+import shutil
+import struct
+import subprocess
+import wave
+from pathlib import Path
+
+import pytest
+
+EXAMPLES_DIR = Path(__file__).resolve().parent.parent / "examples"
+
+# 1x1 transparent PNG
+COVER_PNG = bytes.fromhex(
+    "89504e470d0a1a0a0000000d4948445200000001000000010806000000"
+    "1f15c4890000000a49444154789c63000100000500010d0a2db4000000"
+    "0049454e44ae426082"
+)
+
+
+def load_example(name):
+    """Import examples/<name>.py as a module"""
+    path = EXAMPLES_DIR / (name + ".py")
+    spec = importlib.util.spec_from_file_location("example_" + name, path)
+    module = importlib.util.module_from_spec(spec)
+    spec.loader.exec_module(module)
+    return module
+
+
+def write_wav(path, channels=2, framerate=44100, seconds=0.5):
+    """Write a 16-bit 440 Hz sine wave WAV file"""
+    num_frames = int(framerate * seconds)
+    samples = []
+    for i in range(num_frames):
+        value = int(16384 * math.sin(2 * math.pi * 440 * i / framerate))
+        samples.extend([value] * channels)
+    with wave.open(str(path), "wb") as w:
+        w.setnchannels(channels)
+        w.setsampwidth(2)
+        w.setframerate(framerate)
+        w.writeframes(struct.pack("<%dh" % len(samples), *samples))
+    return path
+
+
+def assert_mp3(data):
+    """MP3 data starts with an ID3v2 tag or an MPEG frame sync"""
+    assert len(data) > 0
+    assert data[:3] == b"ID3" or (data[0] == 0xFF and data[1] & 0xE0 == 0xE0)
+
+
+def test_sine_to_mp3(tmp_path):
+    pytest.importorskip("numpy")
+    output = tmp_path / "sine.mp3"
+    assert load_example("sine_to_mp3").main([str(output), "--seconds", "0.5"]) == 0
+    assert_mp3(output.read_bytes())
+
+
+@pytest.mark.parametrize("extra", [[], ["--vbr"]])
+def test_wav_to_mp3(tmp_path, extra):
+    source = write_wav(tmp_path / "input.wav")
+    output = tmp_path / "output.mp3"
+    assert load_example("wav_to_mp3").main([str(source), str(output)] + extra) == 0
+    assert_mp3(output.read_bytes())
+
+
+def test_stream_to_bytesio():
+    module = load_example("stream_to_bytesio")
+    assert module.main(["--seconds", "0.5"]) == 0
+
+    chunks = list(module.mp3_stream(module.pcm_chunks(0.5)))
+    assert len(chunks) > 1
+    assert_mp3(b"".join(chunks))
+
+
+def test_podcast_episode(tmp_path):
+    source = write_wav(tmp_path / "recording.wav", channels=1)
+    output = tmp_path / "episode.mp3"
+    argv = [str(source), str(output), "--title", "Episode 12", "--number", "12"]
+    assert load_example("podcast_episode").main(argv) == 0
+
+    # Short text fields fit ID3v1, so LAME only writes the tag at the end
+    data = output.read_bytes()
+    assert_mp3(data)
+    assert data[-128:-125] == b"TAG"
+    assert b"Episode 12" in data[-128:]
+
+
+def test_podcast_episode_cover(tmp_path):
+    source = write_wav(tmp_path / "recording.wav", channels=1)
+    cover = tmp_path / "cover.png"
+    cover.write_bytes(COVER_PNG)
+    output = tmp_path / "episode.mp3"
+    argv = [str(source), str(output), "--title", "Episode 12", "--cover", str(cover)]
+    assert load_example("podcast_episode").main(argv) == 0
+
+    # Cover art only fits ID3v2, which LAME writes at the start
+    data = output.read_bytes()
+    assert data[:3] == b"ID3"
+    assert COVER_PNG in data
+
+
+def test_batch_convert(tmp_path):
+    source_dir = tmp_path / "wavs"
+    source_dir.mkdir()
+    for index in range(3):
+        write_wav(source_dir / ("track%d.wav" % index), seconds=0.2)
+
+    output_dir = tmp_path / "mp3s"
+    argv = [str(source_dir), str(output_dir), "--workers", "2"]
+    assert load_example("batch_convert").main(argv) == 0
+
+    outputs = sorted(output_dir.glob("*.mp3"))
+    assert [p.name for p in outputs] == ["track0.mp3", "track1.mp3", "track2.mp3"]
+    for path in outputs:
+        assert_mp3(path.read_bytes())
+
+
+def test_simple_encode(tmp_path):
+    output = tmp_path / "output.mp3"
+    assert load_example("simple_encode").main([str(output)]) == 0
+    assert output.read_bytes()[:3] == b"ID3"
+
+
+def test_quality_comparison(tmp_path):
+    output = tmp_path / "standard.mp3"
+    argv = [str(output), "--seconds", "0.2"]
+    assert load_example("quality_comparison").main(argv) == 0
+    assert_mp3(output.read_bytes())
+
+
+@pytest.mark.skipif(shutil.which("ruff") is None, reason="ruff not installed")
+def test_examples_are_ruff_clean():
+    subprocess.run(["ruff", "check", str(EXAMPLES_DIR)], check=True)
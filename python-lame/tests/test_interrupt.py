@@ -0,0 +1,75 @@
+"""
+Tests for Ctrl-C (SIGINT) during long encode calls
+"""
+
+import os
+import signal
+import threading
+import time
+
+import pytest
+
+# Generous bound on the time between SIGINT and KeyboardInterrupt
+MAX_ABORT_LATENCY = 1.0
+
+
+def make_encoder():
+    import lame
+    return (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .build()
+    )
+
+
+@pytest.mark.skipif(not hasattr(signal, "raise_signal"), reason="needs signal.raise_signal")
+def test_sigint_aborts_long_encode():
+    """SIGINT raised mid-call interrupts the encode promptly and poisons the encoder"""
+    import lame
+
+    encoder = make_encoder()
+    # About three minutes of noise, far longer than the abort latency
+    long_pcm = os.urandom(1152 * 4 * 8000)
+
+    raised_at = []
+
+    def interrupt():
+        time.sleep(0.2)
+        raised_at.append(time.monotonic())
+        signal.raise_signal(signal.SIGINT)
+
+    worker = threading.Thread(target=interrupt)
+    worker.start()
+    try:
+        with pytest.raises(KeyboardInterrupt):
+            encoder.encode_interleaved(long_pcm)
+        aborted_at = time.monotonic()
+    finally:
+        worker.join()
+
+    assert raised_at, "the encode finished before SIGINT was raised"
+    latency = aborted_at - raised_at[0]
+    assert latency < MAX_ABORT_LATENCY, "KeyboardInterrupt took %.3fs" % latency
+
+    # The stream is incomplete, so the encoder refuses further work
+    with pytest.raises(lame.EncodingError, match="interrupted"):
+        encoder.encode_interleaved(bytes(1152 * 4))
+    with pytest.raises(lame.EncodingError, match="interrupted"):
+        encoder.flush()
+
+
+def test_max_samples_per_call_keeps_user_value():
+    """The internal signal check split does not leak into the Python property"""
+    encoder = make_encoder()
+    assert encoder.max_samples_per_call is None
+
+    encoder.max_samples_per_call = 1152 * 1000
+    assert encoder.max_samples_per_call == 1152 * 1000
+
+    encoder.max_samples_per_call = 1152
+    assert encoder.max_samples_per_call == 1152
+
+    encoder.max_samples_per_call = None
+    assert encoder.max_samples_per_call is None
@@ -0,0 +1,82 @@
+"""
+Tests for shutting down and re-importing the module
+"""
+
+import subprocess
+import sys
+import textwrap
+
+
+def run_script(source):
+    """Run source in a fresh interpreter so the module is imported from scratch"""
+    result = subprocess.run(
+        [sys.executable, "-c", textwrap.dedent(source)],
+        capture_output=True,
+        text=True,
+        timeout=60,
+    )
+    assert result.returncode == 0, result.stderr
+    return result.stdout
+
+
+def test_shutdown_and_reimport():
+    """The module keeps working after shutdown, re-import and reload"""
+    output = run_script(
+        """
+        import gc
+        import importlib
+        import sys
+
+        def encode_once(lame):
+            encoder = (
+                lame.LameEncoder.builder()
+                .sample_rate(44100)
+                .channels(2)
+                .bitrate(128)
+                .build()
+            )
+            data = encoder.encode_interleaved(bytes(1152 * 4 * 10)) + encoder.flush()
+            assert data
+            del encoder
+            gc.collect()
+
+        import lame
+        encode_once(lame)
+        lame.shutdown()
+
+        del sys.modules["lame"]
+        import lame
+        encode_once(lame)
+
+        lame = importlib.reload(lame)
+        encode_once(lame)
+        lame.shutdown()
+        print("ok")
+        """
+    )
+    assert output.strip() == "ok"
+
+
+def test_shutdown_with_live_encoder():
+    """shutdown() refuses while an encoder still holds a LAME handle"""
+    # Other tests in this process may still hold encoders
+    output = run_script(
+        """
+        import gc
+        import lame
+
+        encoder = lame.LameEncoder.builder().sample_rate(44100).channels(1).build()
+        try:
+            lame.shutdown()
+        except RuntimeError as err:
+            assert "still open" in str(err), err
+        else:
+            raise AssertionError("shutdown() succeeded with a live encoder")
+
+        del encoder
+        gc.collect()
+        lame.shutdown()
+        print("ok")
+        """
+    )
+    assert output.strip() == "ok"
@@ -0,0 +1,127 @@
+"""
+Tests for the in-memory MP3 file assembler
+"""
+
+import math
+import struct
+
+import pytest
+
+BITRATES = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320]
+
+
+def sine_pcm(frames=40):
+    """Interleaved stereo 440 Hz sine wave as bytes"""
+    samples = []
+    for i in range(1152 * frames + 17):
+        value = int(16384 * math.sin(2 * math.pi * 440 * i / 44100))
+        samples.extend([value, value])
+    return struct.pack("<%dh" % len(samples), *samples)
+
+
+def make_encoder(vbr=False, title=None):
+    import lame
+    builder = lame.LameEncoder.builder().sample_rate(44100).channels(2)
+    if vbr:
+        builder = builder.vbr_mode(lame.VbrMode.Vbr).vbr_quality(2)
+    else:
+        builder = builder.bitrate(128)
+    encoder = builder.build()
+    if title is not None:
+        tag = encoder.id3_tag()
+        tag.title(title)
+        tag.apply()
+    return encoder
+
+
+def info_frame_span(mp3):
+    """Offset and length of the first MPEG-1 Layer III frame after any ID3v2 tag"""
+    offset = 0
+    if mp3[:3] == b"ID3":
+        size = 0
+        for b in mp3[6:10]:
+            size = (size << 7) | (b & 0x7F)
+        offset = 10 + size
+    header = mp3[offset:offset + 4]
+    assert header[0] == 0xFF and header[1] & 0xFE == 0xFA
+    length = 144 * BITRATES[header[2] >> 4] * 1000 // 44100 + ((header[2] >> 1) & 1)
+    return offset, length
+
+
+@pytest.mark.parametrize("vbr", [False, True])
+def test_buffer_fills_in_info_frame(vbr):
+    """Only the info frame differs from the plain encode/flush output"""
+    import lame
+
+    pcm = sine_pcm()
+    title = "A title long enough to need an ID3v2 tag"
+
+    plain = make_encoder(vbr, title)
+    expected = plain.encode_interleaved(pcm) + plain.flush()
+
+    buffer = lame.Mp3Buffer(make_encoder(vbr, title))
+    for start in range(0, len(pcm), 6002 * 2):
+        buffer.write(pcm[start:start + 6002 * 2])
+    assert not buffer.closed
+    buffer.close()
+    assert buffer.closed
+    mp3 = buffer.getvalue()
+
+    assert len(mp3) == len(expected) == buffer.bytes_written
+    assert mp3[:3] == b"ID3"
+    assert mp3[-128:-125] == b"TAG"
+
+    offset, length = info_frame_span(mp3)
+    assert expected[offset + 4:offset + length] == bytes(length - 4)
+    assert (b"Xing" if vbr else b"Info") in mp3[offset:offset + length]
+    assert mp3[:offset] == expected[:offset]
+    assert mp3[offset + length:] == expected[offset + length:]
+
+
+def test_buffer_context_manager_and_closed_state():
+    """Leaving a with block closes the buffer; it can't be written afterwards"""
+    import lame
+
+    with lame.Mp3Buffer(make_encoder()) as buffer:
+        buffer.write(sine_pcm(10))
+        with pytest.raises(ValueError, match="after close"):
+            buffer.getvalue()
+
+    data = buffer.getvalue()
+    assert len(data) > 0
+    buffer.close()
+    assert buffer.getvalue() == data
+
+    with pytest.raises(ValueError, match="closed"):
+        buffer.write(sine_pcm(1))
+
+
+def test_buffer_takes_over_encoder():
+    """The encoder moves into the buffer; pooled encoders are refused"""
+    import lame
+
+    encoder = make_encoder()
+    lame.Mp3Buffer(encoder)
+    with pytest.raises(RuntimeError, match="moved into an Mp3Buffer"):
+        encoder.encode_interleaved(sine_pcm(1))
+    with pytest.raises(RuntimeError):
+        lame.Mp3Buffer(encoder)
+
+    pool = lame.EncoderPool(sample_rate=44100, channels=2, bitrate=128, max_size=1)
+    with pool.acquire() as pooled:
+        with pytest.raises(ValueError, match="pooled"):
+            lame.Mp3Buffer(pooled)
+        # A refused encoder stays usable
+        assert len(pooled.encode_interleaved(sine_pcm(10))) > 0
+
+
+def test_buffer_rejects_odd_stereo_input():
+    import lame
+
+    buffer = lame.Mp3Buffer(make_encoder())
+    with pytest.raises(ValueError):
+        buffer.write(b"\x00" * 6)
+    with pytest.raises(ValueError):
+        buffer.write(b"\x00" * 3)
+    buffer.write(sine_pcm(2))
+    buffer.close()
@@ -0,0 +1,112 @@
+"""
+Tests for the encoder pool
+"""
+
+import math
+import struct
+import threading
+
+import pytest
+
+
+def sine_pcm(frames=10):
+    """Interleaved stereo 440 Hz sine wave as bytes"""
+    samples = []
+    for i in range(1152 * frames):
+        value = int(16384 * math.sin(2 * math.pi * 440 * i / 44100))
+        samples.extend([value, value])
+    return struct.pack("<%dh" % len(samples), *samples)
+
+
+def make_pool(max_size=2):
+    import lame
+    return lame.EncoderPool(sample_rate=44100, channels=2, bitrate=128, max_size=max_size)
+
+
+def reference_output(pcm):
+    import lame
+    encoder = (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .build()
+    )
+    return encoder.encode_interleaved(pcm) + encoder.flush()
+
+
+def test_pool_context_manager():
+    """acquire() works as a context manager and returns the encoder"""
+    pool = make_pool()
+    pcm = sine_pcm(2)
+
+    with pool.acquire() as encoder:
+        mp3_data = encoder.encode_interleaved(pcm) + encoder.flush()
+        assert len(mp3_data) > 0
+        assert pool.idle_count == 0
+
+    assert pool.idle_count == 1
+
+    # The released encoder can no longer be used
+    with pytest.raises(RuntimeError):
+        encoder.encode_interleaved(pcm)
+
+
+def test_pool_resets_between_uses():
+    """Tags and stream state do not leak into the next user"""
+    pool = make_pool(max_size=1)
+    pcm = sine_pcm()
+    expected = reference_output(pcm)
+
+    with pool.acquire() as encoder:
+        encoder.id3_tag().title("Leaked Title").apply()
+        encoder.encode_interleaved(pcm)
+
+    with pool.acquire() as encoder:
+        assert encoder.encode_interleaved(pcm) + encoder.flush() == expected
+
+
+def test_pool_multithreaded():
+    """Hammer the pool from many threads"""
+    pool = make_pool(max_size=3)
+    pcm = sine_pcm()
+    expected = reference_output(pcm)
+
+    lock = threading.Lock()
+    active = [0]
+    peak = [0]
+    errors = []
+
+    def worker(index):
+        try:
+            for round_ in range(10):
+                with pool.acquire() as encoder:
+                    with lock:
+                        active[0] += 1
+                        peak[0] = max(peak[0], active[0])
+
+                    if (index + round_) % 3 == 0:
+                        encoder.id3_tag().title("Per-request title").apply()
+                        encoder.encode_interleaved(pcm)
+                    else:
+                        output = encoder.encode_interleaved(pcm) + encoder.flush()
+                        assert output == expected, "state leaked between requests"
+
+                    with lock:
+                        active[0] -= 1
+        except Exception as exc:  # pragma: no cover - reported below
+            errors.append(exc)
+
+    threads = [threading.Thread(target=worker, args=(i,)) for i in range(8)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+
+    assert not errors, errors
+    assert peak[0] <= pool.max_size
+    assert pool.idle_count <= pool.max_size
+
+
+if __name__ == "__main__":
+    pytest.main([__file__, "-v"])
@@ -0,0 +1,98 @@
+"""
+Introspection tests: every public callable has a signature and a docstring
+"""
+
+import inspect
+
+import pytest
+
+
+def public_names(obj):
+    return [name for name in dir(obj) if not name.startswith("_")]
+
+
+def public_classes():
+    import lame
+    return [
+        getattr(lame, name)
+        for name in public_names(lame)
+        if inspect.isclass(getattr(lame, name))
+        and not issubclass(getattr(lame, name), BaseException)
+    ]
+
+
+def public_callables():
+    """(qualified name, callable) for module functions and class methods"""
+    import lame
+
+    found = []
+    for name in public_names(lame):
+        obj = getattr(lame, name)
+        if inspect.isroutine(obj):
+            found.append((name, obj))
+
+    for cls in public_classes():
+        for name in public_names(cls):
+            member = inspect.getattr_static(cls, name)
+            if isinstance(member, (staticmethod, classmethod)):
+                member = getattr(cls, name)
+            if inspect.isroutine(member):
+                found.append(("%s.%s" % (cls.__name__, name), member))
+    return found
+
+
+def test_module_has_docstring():
+    import lame
+    assert lame.__doc__
+
+
+@pytest.mark.parametrize("cls", public_classes(), ids=lambda c: c.__name__)
+def test_class_has_docstring(cls):
+    assert cls.__doc__ and cls.__doc__.strip()
+
+
+@pytest.mark.parametrize("name,func", public_callables(), ids=lambda v: v if isinstance(v, str) else None)
+def test_callable_has_signature_and_docstring(name, func):
+    inspect.signature(func)
+    assert func.__doc__ and func.__doc__.strip(), "%s has no docstring" % name
+
+
+def test_callables_were_found():
+    names = {name for name, _ in public_callables()}
+    assert "encode_wave" in names
+    assert "LameEncoder.encode_numpy" in names
+    assert "EncoderBuilder.sample_rate" in names
+    assert "Id3Tag.title" in names
+
+
+def test_encode_signatures():
+    import lame
+
+    params = list(inspect.signature(lame.LameEncoder.encode).parameters)
+    assert params[-2:] == ["pcm_left", "pcm_right"]
+
+    params = list(inspect.signature(lame.LameEncoder.encode_mono_numpy).parameters)
+    assert params[-1] == "pcm"
+
+
+def test_constructor_signatures():
+    import lame
+
+    assert list(inspect.signature(lame.EncoderBuilder).parameters) == []
+
+    params = inspect.signature(lame.EncoderPool).parameters
+    assert params["sample_rate"].default == 44100
+    assert params["max_size"].default == 4
+
+
+def test_builder_setters_chain():
+    import lame
+
+    builder = lame.LameEncoder.builder()
+    assert builder.sample_rate(44100) is builder
+    assert builder.channels(2).bitrate(128) is builder
+    assert isinstance(builder.build(), lame.LameEncoder)
+
+
+if __name__ == "__main__":
+    pytest.main([__file__, "-v"])
@@ -0,0 +1,80 @@
+"""
+Tests for concurrent use of a single encoder from several threads
+"""
+
+import os
+import threading
+
+
+def make_encoder():
+    import lame
+    return (
+        lame.LameEncoder.builder()
+        .sample_rate(44100)
+        .channels(2)
+        .bitrate(128)
+        .build()
+    )
+
+
+def test_concurrent_calls_raise_busy_error():
+    """A second thread entering encode() while another is inside it gets EncodingError"""
+    import lame
+
+    encoder = make_encoder()
+    # About a minute of noise keeps the first call busy with the GIL released
+    long_pcm = os.urandom(1152 * 4 * 2000)
+    short_pcm = bytes(1152 * 4)
+
+    barrier = threading.Barrier(2)
+    done = threading.Event()
+    errors = []
+
+    def long_call():
+        barrier.wait()
+        try:
+            encoder.encode_interleaved(long_pcm)
+        except lame.EncodingError as e:
+            errors.append(e)
+        finally:
+            done.set()
+
+    def probe():
+        barrier.wait()
+        while not done.is_set():
+            try:
+                encoder.encode_interleaved(short_pcm)
+            except lame.EncodingError as e:
+                errors.append(e)
+                return
+
+    threads = [threading.Thread(target=long_call), threading.Thread(target=probe)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+
+    assert errors, "expected one of the overlapping calls to be rejected"
+    assert "busy in another thread" in str(errors[0])
+
+    # The rejected call did not disturb the encoder
+    assert len(encoder.encode_interleaved(short_pcm)) >= 0
+    assert len(encoder.flush()) > 0
+
+
+def test_sequential_calls_from_different_threads():
+    """Handing an encoder from one thread to another is allowed"""
+    encoder = make_encoder()
+    pcm = bytes(1152 * 4 * 10)
+    output = []
+
+    def encode():
+        output.append(encoder.encode_interleaved(pcm))
+
+    for _ in range(3):
+        t = threading.Thread(target=encode)
+        t.start()
+        t.join()
+
+    output.append(encoder.flush())
+    assert len(b"".join(output)) > 0
@@ -0,0 +1,101 @@
+"""
+Tests for the stdlib wave module integration
+"""
+
+import math
+import struct
+import wave
+
+import pytest
+
+
+def write_fixture(path, channels=2, sampwidth=2, framerate=44100, seconds=0.5):
+    """Write a 440 Hz sine wave WAV fixture"""
+    num_frames = int(framerate * seconds)
+    with wave.open(str(path), "wb") as w:
+        w.setnchannels(channels)
+        w.setsampwidth(sampwidth)
+        w.setframerate(framerate)
+        if sampwidth == 2:
+            samples = []
+            for i in range(num_frames):
+                value = int(16384 * math.sin(2 * math.pi * 440 * i / framerate))
+                samples.extend([value] * channels)
+            w.writeframes(struct.pack("<%dh" % len(samples), *samples))
+        else:
+            w.writeframes(b"\x00" * num_frames * channels * sampwidth)
+    return path
+
+
+def test_encode_wave_object(tmp_path):
+    """Encode from an open wave.Wave_read object"""
+    import lame
+
+    path = write_fixture(tmp_path / "stereo.wav", channels=2)
+    with wave.open(str(path), "rb") as w:
+        mp3_data = lame.encode_wave(w)
+
+    assert isinstance(mp3_data, bytes)
+    assert len(mp3_data) > 0
+
+
+def test_encode_wave_path(tmp_path):
+    """Encode from a path string"""
+    import lame
+
+    path = write_fixture(tmp_path / "mono.wav", channels=1)
+    mp3_data = lame.encode_wave(str(path), bitrate=64)
+
+    assert len(mp3_data) > 0
+
+
+def test_encode_wave_kwargs(tmp_path):
+    """Encoder keyword arguments are applied"""
+    import lame
+
+    path = write_fixture(tmp_path / "stereo.wav", seconds=2.0)
+    low = lame.encode_wave(str(path), bitrate=64)
+    high = lame.encode_wave(str(path), bitrate=256, quality=lame.Quality.High)
+
+    assert len(high) > len(low)
+
+
+def test_encode_wave_fade(tmp_path):
+    """fade_ms applies a fade-in and fade-out before encoding"""
+    import lame
+
+    path = write_fixture(tmp_path / "stereo.wav", seconds=1.0)
+    plain = lame.encode_wave(str(path), bitrate=128)
+    faded = lame.encode_wave(str(path), bitrate=128, fade_ms=(10, 50))
+    # Fades longer than the clip are clamped
+    whole = lame.encode_wave(str(path), bitrate=128, fade_ms=(5000, 5000))
+
+    assert len(faded) > 0 and len(whole) > 0
+    assert faded != plain
+    assert whole != faded
+
+    with pytest.raises(TypeError):
+        lame.encode_wave(str(path), fade_ms=10)
+
+
+def test_encode_wave_unknown_kwarg(tmp_path):
+    """Unknown encoder keywords raise TypeError"""
+    import lame
+
+    path = write_fixture(tmp_path / "stereo.wav")
+    with pytest.raises(TypeError):
+        lame.encode_wave(str(path), sample_rate=48000)
+
+
+@pytest.mark.parametrize("sampwidth", [1, 3, 4])
+def test_encode_wave_unsupported_width(tmp_path, sampwidth):
+    """Non 16-bit sample widths raise ValueError with guidance"""
+    import lame
+
+    path = write_fixture(tmp_path / "wide.wav", sampwidth=sampwidth)
+    with pytest.raises(ValueError, match="16-bit"):
+        lame.encode_wave(str(path))
+
+
+if __name__ == "__main__":
+    pytest.main([__file__, "-v"])